@@ -563,6 +563,10 @@ pub enum ChatResponseStream {
         conversation_id: Option<String>,
         utterance_id: Option<String>,
     },
+    /// Metadata about the whole response, sent once it has been generated.
+    MetadataEvent {
+        token_usage: Option<TokenUsage>,
+    },
     SupplementaryWebLinksEvent(()),
     ToolUseEvent {
         tool_use_id: String,
@@ -623,6 +627,11 @@ impl From<amzn_codewhisperer_streaming_client::types::ChatResponseStream> for Ch
                 input,
                 stop,
             },
+            amzn_codewhisperer_streaming_client::types::ChatResponseStream::MetadataEvent(
+                amzn_codewhisperer_streaming_client::types::MetadataEvent { token_usage, .. },
+            ) => ChatResponseStream::MetadataEvent {
+                token_usage: token_usage.map(Into::into),
+            },
             amzn_codewhisperer_streaming_client::types::ChatResponseStream::SupplementaryWebLinksEvent(_) => {
                 ChatResponseStream::SupplementaryWebLinksEvent(())
             },
@@ -679,6 +688,11 @@ impl From<amzn_qdeveloper_streaming_client::types::ChatResponseStream> for ChatR
                 input,
                 stop,
             },
+            amzn_qdeveloper_streaming_client::types::ChatResponseStream::MetadataEvent(
+                amzn_qdeveloper_streaming_client::types::MetadataEvent { token_usage, .. },
+            ) => ChatResponseStream::MetadataEvent {
+                token_usage: token_usage.map(Into::into),
+            },
             amzn_qdeveloper_streaming_client::types::ChatResponseStream::SupplementaryWebLinksEvent(_) => {
                 ChatResponseStream::SupplementaryWebLinksEvent(())
             },
@@ -687,6 +701,51 @@ impl From<amzn_qdeveloper_streaming_client::types::ChatResponseStream> for ChatR
     }
 }
 
+/// Number of tokens a request and its response took up, as counted by the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Input tokens that were not read from or written to the prompt cache.
+    pub uncached_input_tokens: usize,
+    pub cache_read_input_tokens: usize,
+    pub cache_write_input_tokens: usize,
+    pub output_tokens: usize,
+}
+
+impl TokenUsage {
+    /// All input tokens, whether or not they were cached.
+    pub fn input_tokens(&self) -> usize {
+        self.uncached_input_tokens + self.cache_read_input_tokens + self.cache_write_input_tokens
+    }
+
+    /// The tokens the conversation takes up in the context window once the response is added to
+    /// it.
+    pub fn context_tokens(&self) -> usize {
+        self.input_tokens() + self.output_tokens
+    }
+}
+
+impl From<amzn_codewhisperer_streaming_client::types::TokenUsage> for TokenUsage {
+    fn from(value: amzn_codewhisperer_streaming_client::types::TokenUsage) -> Self {
+        Self {
+            uncached_input_tokens: value.uncached_input_tokens.max(0) as usize,
+            cache_read_input_tokens: value.cache_read_input_tokens.unwrap_or_default().max(0) as usize,
+            cache_write_input_tokens: value.cache_write_input_tokens.unwrap_or_default().max(0) as usize,
+            output_tokens: value.output_tokens.max(0) as usize,
+        }
+    }
+}
+
+impl From<amzn_qdeveloper_streaming_client::types::TokenUsage> for TokenUsage {
+    fn from(value: amzn_qdeveloper_streaming_client::types::TokenUsage) -> Self {
+        Self {
+            uncached_input_tokens: value.uncached_input_tokens.max(0) as usize,
+            cache_read_input_tokens: value.cache_read_input_tokens.unwrap_or_default().max(0) as usize,
+            cache_write_input_tokens: value.cache_write_input_tokens.unwrap_or_default().max(0) as usize,
+            output_tokens: value.output_tokens.max(0) as usize,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EnvState {
     pub operating_system: Option<String>,
@@ -1173,6 +1232,41 @@ mod tests {
             }
         );
 
+        let user_input_event = amzn_codewhisperer_streaming_client::types::ChatResponseStream::MetadataEvent(
+            amzn_codewhisperer_streaming_client::types::MetadataEvent::builder()
+                .token_usage(
+                    amzn_codewhisperer_streaming_client::types::TokenUsage::builder()
+                        .uncached_input_tokens(100)
+                        .cache_read_input_tokens(1000)
+                        .output_tokens(10)
+                        .total_tokens(1110)
+                        .build()
+                        .unwrap(),
+                )
+                .build(),
+        );
+        let expected_usage = TokenUsage {
+            uncached_input_tokens: 100,
+            cache_read_input_tokens: 1000,
+            cache_write_input_tokens: 0,
+            output_tokens: 10,
+        };
+        assert_eq!(
+            ChatResponseStream::from(user_input_event),
+            ChatResponseStream::MetadataEvent {
+                token_usage: Some(expected_usage)
+            }
+        );
+        assert_eq!(expected_usage.context_tokens(), 1110);
+
+        let user_input_event = amzn_qdeveloper_streaming_client::types::ChatResponseStream::MetadataEvent(
+            amzn_qdeveloper_streaming_client::types::MetadataEvent::builder().build(),
+        );
+        assert_eq!(
+            ChatResponseStream::from(user_input_event),
+            ChatResponseStream::MetadataEvent { token_usage: None }
+        );
+
         let user_input_event =
             amzn_codewhisperer_streaming_client::types::ChatResponseStream::SupplementaryWebLinksEvent(
                 amzn_codewhisperer_streaming_client::types::SupplementaryWebLinksEvent::builder().build(),
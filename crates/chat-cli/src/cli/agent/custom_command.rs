@@ -0,0 +1,102 @@
+use schemars::JsonSchema;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Placeholder in [CustomCommand::prompt] that is replaced with the text typed after the command
+pub const ARGUMENTS_PLACEHOLDER: &str = "$ARGUMENTS";
+
+/// A slash command declared by an agent. Typing `/name some text` sends [CustomCommand::prompt]
+/// to the model as the user message, with `$ARGUMENTS` replaced by `some text`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CustomCommand {
+    /// Short summary of the command, shown in /help
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The message sent to the model when the command is invoked. "$ARGUMENTS" is replaced with
+    /// the text typed after the command
+    pub prompt: String,
+    /// Tools that are trusted while the model responds to this command, in addition to the
+    /// agent's allowedTools
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Model id to use while the model responds to this command
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl CustomCommand {
+    /// Builds the user message for an invocation of this command. If the prompt does not reference
+    /// `$ARGUMENTS`, any arguments supplied are appended to the end of the prompt instead.
+    pub fn expand(&self, arguments: &str) -> String {
+        let arguments = arguments.trim();
+        if self.prompt.contains(ARGUMENTS_PLACEHOLDER) {
+            self.prompt.replace(ARGUMENTS_PLACEHOLDER, arguments)
+        } else if arguments.is_empty() {
+            self.prompt.clone()
+        } else {
+            format!("{}\n\n{}", self.prompt.trim_end(), arguments)
+        }
+    }
+}
+
+/// Command names are typed after `/`, so they are restricted to characters that won't be
+/// confused with arguments.
+pub fn is_valid_command_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(prompt: &str) -> CustomCommand {
+        CustomCommand {
+            description: None,
+            prompt: prompt.to_string(),
+            allowed_tools: Vec::new(),
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let review = command("Review the changes in $ARGUMENTS for bugs.");
+        assert_eq!(
+            review.expand(" src/lib.rs "),
+            "Review the changes in src/lib.rs for bugs."
+        );
+        assert_eq!(review.expand(""), "Review the changes in  for bugs.");
+
+        let notes = command("Write release notes for the latest commits.\n");
+        assert_eq!(notes.expand(""), "Write release notes for the latest commits.\n");
+        assert_eq!(
+            notes.expand("since v1.2"),
+            "Write release notes for the latest commits.\n\nsince v1.2"
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let command: CustomCommand = serde_json::from_value(serde_json::json!({
+            "description": "Review a file",
+            "prompt": "Review $ARGUMENTS",
+            "allowedTools": ["fs_read"],
+            "model": "claude-sonnet-4"
+        }))
+        .unwrap();
+        assert_eq!(command.allowed_tools, vec!["fs_read".to_string()]);
+        assert_eq!(command.model.as_deref(), Some("claude-sonnet-4"));
+
+        assert!(serde_json::from_value::<CustomCommand>(serde_json::json!({ "description": "no prompt" })).is_err());
+    }
+
+    #[test]
+    fn test_is_valid_command_name() {
+        assert!(is_valid_command_name("release-notes"));
+        assert!(!is_valid_command_name("release notes"));
+        assert!(!is_valid_command_name(""));
+    }
+}
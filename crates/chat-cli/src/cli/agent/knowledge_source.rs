@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::os::Os;
+use crate::util::knowledge_store::{
+    AddOptions,
+    KnowledgeScope,
+};
+
+/// A file or directory indexed into the workspace knowledge base when a chat session with the
+/// agent starts.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KnowledgeSource {
+    /// Path to index. Relative paths are resolved against the directory q chat is launched in
+    pub path: String,
+    /// Name of the knowledge base entry. Defaults to the last component of the path
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Glob patterns of the files to index
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Glob patterns of the files to skip
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Index type to use (Fast, Best, Hybrid or Http). Defaults to the knowledge.indexType setting
+    #[serde(default)]
+    pub index_type: Option<String>,
+    /// Whether to re-index changed files automatically while the session is running
+    #[serde(default)]
+    pub auto_update: bool,
+}
+
+impl KnowledgeSource {
+    /// The name of the knowledge base entry created for this source
+    pub fn entry_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            Path::new(&self.path)
+                .file_name()
+                .map_or_else(|| self.path.clone(), |name| name.to_string_lossy().to_string())
+        })
+    }
+
+    /// Options for adding this source to the workspace knowledge base, falling back to the
+    /// knowledge settings for anything the source doesn't set
+    pub fn add_options(&self, os: &Os) -> AddOptions {
+        let mut options = AddOptions::with_db_defaults(os)
+            .with_auto_update(self.auto_update)
+            .with_scope(Some(KnowledgeScope::Workspace));
+        if !self.include.is_empty() {
+            options = options.with_include_patterns(self.include.clone());
+        }
+        if !self.exclude.is_empty() {
+            options = options.with_exclude_patterns(self.exclude.clone());
+        }
+        if self.index_type.is_some() {
+            options = options.with_embedding_type(self.index_type.clone());
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_name() {
+        let mut source: KnowledgeSource = serde_json::from_value(serde_json::json!({
+            "path": "./docs/api",
+            "include": ["**/*.md"],
+            "autoUpdate": true
+        }))
+        .unwrap();
+        assert_eq!(source.entry_name(), "api");
+        assert!(source.auto_update);
+
+        source.name = Some("api-docs".to_string());
+        assert_eq!(source.entry_name(), "api-docs");
+
+        source.name = None;
+        source.path = ".".to_string();
+        assert_eq!(source.entry_name(), ".");
+    }
+}
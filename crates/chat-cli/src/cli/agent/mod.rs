@@ -1,4 +1,6 @@
+pub mod custom_command;
 pub mod hook;
+pub mod knowledge_source;
 mod legacy;
 mod mcp_config;
 mod root_command_args;
@@ -59,10 +61,15 @@ use super::chat::tools::{
     NATIVE_TOOLS,
     ToolOrigin,
 };
+use crate::cli::agent::custom_command::{
+    CustomCommand,
+    is_valid_command_name,
+};
 use crate::cli::agent::hook::{
     Hook,
     HookTrigger,
 };
+use crate::cli::agent::knowledge_source::KnowledgeSource;
 use crate::database::settings::Setting;
 use crate::os::Os;
 use crate::util::{
@@ -161,6 +168,14 @@ pub struct Agent {
     /// you configure in the mcpServers field in this config
     #[serde(default)]
     pub use_legacy_mcp_json: bool,
+    /// Custom slash commands available while this agent is active, keyed by the name they are
+    /// invoked with. Built-in commands take precedence over custom commands of the same name
+    #[serde(default)]
+    pub commands: HashMap<String, CustomCommand>,
+    /// Files and directories indexed into the workspace knowledge base when a chat session with
+    /// this agent starts
+    #[serde(default)]
+    pub knowledge: Vec<KnowledgeSource>,
     #[serde(skip)]
     pub path: Option<PathBuf>,
 }
@@ -188,6 +203,8 @@ impl Default for Agent {
             hooks: Default::default(),
             tools_settings: Default::default(),
             use_legacy_mcp_json: true,
+            commands: Default::default(),
+            knowledge: Default::default(),
             path: None,
         }
     }
@@ -213,6 +230,24 @@ impl Agent {
 
         self.path = Some(path.to_path_buf());
 
+        self.commands.retain(|name, _| {
+            let valid = is_valid_command_name(name);
+            if !valid {
+                let _ = queue!(
+                    std::io::stderr(),
+                    style::SetForegroundColor(Color::Yellow),
+                    style::Print("WARNING: "),
+                    style::ResetColor,
+                    style::Print("Custom command '"),
+                    style::SetForegroundColor(Color::Green),
+                    style::Print(name),
+                    style::ResetColor,
+                    style::Print("' may only contain letters, numbers, '-' and '_'. Skipping.\n")
+                );
+            }
+            valid
+        });
+
         if let (true, Some(legacy_mcp_config)) = (self.use_legacy_mcp_json, legacy_mcp_config) {
             let mut stderr = std::io::stderr();
             for (name, legacy_server) in &legacy_mcp_config.mcp_servers {
@@ -332,6 +367,65 @@ impl Agent {
         self.tools_settings
             .retain(|target, _| !is_mcp_tool_ref(&target.to_string()));
     }
+
+    /// Adds the tool in `rule` to [Agent::allowed_tools], narrowing it with the rule's settings
+    /// unless the tool is already allowed without restrictions.
+    pub fn add_trust_rule(&mut self, rule: &TrustRule) {
+        let unrestricted =
+            self.allowed_tools.contains(&rule.tool_name) && !self.tools_settings.contains_key(rule.tool_name.as_str());
+        self.allowed_tools.insert(rule.tool_name.clone());
+        let Some((key, values)) = &rule.settings else {
+            return;
+        };
+        if unrestricted {
+            return;
+        }
+
+        let settings = self
+            .tools_settings
+            .entry(ToolSettingTarget(rule.tool_name.clone()))
+            .or_insert_with(|| serde_json::json!({}));
+        let Some(settings) = settings.as_object_mut() else {
+            warn!("Settings for {} are not an object, not saving {key}", rule.tool_name);
+            return;
+        };
+        let list = settings.entry(*key).or_insert_with(|| serde_json::json!([]));
+        if let Some(list) = list.as_array_mut() {
+            for value in values {
+                if !list.iter().any(|v| v.as_str() == Some(value)) {
+                    list.push(value.clone().into());
+                }
+            }
+        }
+    }
+
+    /// Applies `rule` to the config file this agent was loaded from, leaving any permissions
+    /// granted only for the current session out of the file. Returns the path written to.
+    pub async fn save_trust_rule(&self, os: &Os, rule: &TrustRule) -> eyre::Result<PathBuf> {
+        let Some(path) = &self.path else {
+            bail!(
+                "Agent {} does not have a config file. Use /agent create to create one",
+                self.name
+            );
+        };
+
+        let content = os.fs.read(path).await?;
+        let mut agent = serde_json::from_slice::<Agent>(&content)?;
+        agent.add_trust_rule(rule);
+        os.fs.write(path, agent.to_str_pretty()?).await?;
+
+        Ok(path.clone())
+    }
+}
+
+/// A permission granted from within a chat session that can be written back to an agent config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustRule {
+    /// The tool to trust, as it would appear in allowedTools
+    pub tool_name: String,
+    /// A list in the tool's toolsSettings to extend, e.g. `allowedCommands` for execute_bash,
+    /// restricting the rule to the given values
+    pub settings: Option<(&'static str, Vec<String>)>,
 }
 
 /// Result of evaluating tool permissions, indicating whether a tool should be allowed,
@@ -1009,6 +1103,76 @@ mod tests {
         assert!(validate_agent_name("invalid space").is_err());
     }
 
+    #[test]
+    fn test_add_trust_rule() {
+        let mut agent = Agent {
+            allowed_tools: HashSet::new(),
+            ..Default::default()
+        };
+        let rule = TrustRule {
+            tool_name: "execute_bash".to_string(),
+            settings: Some(("allowedCommands", vec!["git status".to_string()])),
+        };
+        agent.add_trust_rule(&rule);
+        agent.add_trust_rule(&rule);
+        agent.add_trust_rule(&TrustRule {
+            tool_name: "execute_bash".to_string(),
+            settings: Some(("allowedCommands", vec!["cargo test".to_string()])),
+        });
+        assert!(agent.allowed_tools.contains("execute_bash"));
+        assert_eq!(
+            agent.tools_settings.get("execute_bash"),
+            Some(&json!({ "allowedCommands": ["git status", "cargo test"] }))
+        );
+
+        // A tool that is already allowed without restrictions is not narrowed down
+        agent.allowed_tools.insert("fs_write".to_string());
+        agent.add_trust_rule(&TrustRule {
+            tool_name: "fs_write".to_string(),
+            settings: Some(("allowedPaths", vec!["src/lib.rs".to_string()])),
+        });
+        assert!(!agent.tools_settings.contains_key("fs_write"));
+    }
+
+    #[tokio::test]
+    async fn test_save_trust_rule() {
+        let os = Os::new().await.unwrap();
+        let path = PathBuf::from("/agents/some_agent.json");
+        os.fs.create_dir_all("/agents").await.unwrap();
+        os.fs.write(&path, INPUT).await.unwrap();
+
+        let mut agent = Agent::load(&os, &path, &mut None, true).await.unwrap();
+        // Trust granted only for the session should not end up in the config file
+        agent.allowed_tools.insert("use_aws".to_string());
+        let saved_path = agent
+            .save_trust_rule(&os, &TrustRule {
+                tool_name: "fs_write".to_string(),
+                settings: Some(("allowedPaths", vec!["/tmp/out.txt".to_string()])),
+            })
+            .await
+            .unwrap();
+        assert_eq!(saved_path, path);
+
+        let saved = serde_json::from_str::<Agent>(&os.fs.read_to_string(&path).await.unwrap()).unwrap();
+        assert!(saved.allowed_tools.contains("fs_write"));
+        assert!(saved.allowed_tools.contains("fs_read"));
+        assert!(!saved.allowed_tools.contains("use_aws"));
+        assert_eq!(
+            saved.tools_settings.get("fs_write"),
+            Some(&json!({ "allowedPaths": ["~/**", "/tmp/out.txt"] }))
+        );
+
+        assert!(
+            Agent::default()
+                .save_trust_rule(&os, &TrustRule {
+                    tool_name: "fs_read".to_string(),
+                    settings: None,
+                })
+                .await
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_clear_mcp_configs_with_builtin_variants() {
         let mut agent: Agent = serde_json::from_value(json!({
@@ -1157,6 +1321,8 @@ mod tests {
             resources: Vec::new(),
             hooks: Default::default(),
             use_legacy_mcp_json: false,
+            commands: Default::default(),
+            knowledge: Default::default(),
             path: None,
         };
 
@@ -25,10 +25,15 @@ When to use
 How it works
 • Creates an AI-generated summary of your conversation
 • Retains key information, code, and tool executions in the summary
-• Clears the conversation history to free up space
+• Updates the previous summary, if any, rather than summarizing from scratch
+• Clears the conversation history to free up space, except for turns pinned with /pin
 • The assistant will reference the summary context in future responses
 
-Compaction will be automatically performed whenever the context window overflows.
+With --elide-tool-results, large results of old tool uses are replaced with a note
+naming the tool use instead, and the conversation is only summarized if there are none.
+
+Compaction will be automatically performed whenever the context window overflows,
+eliding old tool results first and only summarizing if that is not enough.
 To disable this behavior, run: `q settings chat.disableAutoCompaction true`"
 )]
 pub struct CompactArgs {
@@ -46,6 +51,9 @@ pub struct CompactArgs {
     /// truncate_large_messages to be set.
     #[arg(long, requires = "truncate_large_messages")]
     max_message_length: Option<usize>,
+    /// Elide large results of old tool uses, and only summarize if there are none.
+    #[arg(long)]
+    elide_tool_results: bool,
 }
 
 impl CompactArgs {
@@ -68,6 +76,7 @@ impl CompactArgs {
                 max_message_length: self.max_message_length.map_or(default.max_message_length, |v| {
                     v.clamp(UserMessageContent::TRUNCATED_SUFFIX.len(), MAX_USER_MESSAGE_SIZE)
                 }),
+                elide_tool_results: self.elide_tool_results,
             })
             .await
     }
@@ -82,6 +91,9 @@ pub struct CompactStrategy {
     pub truncate_large_messages: bool,
     /// Maximum allowed size of messages in the conversation history.
     pub max_message_length: usize,
+    /// Whether to first elide large results of old tool uses, only summarizing the history if
+    /// there are none.
+    pub elide_tool_results: bool,
 }
 
 impl Default for CompactStrategy {
@@ -90,6 +102,7 @@ impl Default for CompactStrategy {
             messages_to_exclude: Default::default(),
             truncate_large_messages: Default::default(),
             max_message_length: MAX_USER_MESSAGE_SIZE,
+            elide_tool_results: Default::default(),
         }
     }
 }
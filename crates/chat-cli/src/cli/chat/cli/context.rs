@@ -15,7 +15,10 @@ use crate::cli::chat::context::{
     ContextFilePath,
     calc_max_context_files_size,
 };
-use crate::cli::chat::token_counter::TokenCounter;
+use crate::cli::chat::token_counter::{
+    TokenCounter,
+    Tokenizer,
+};
 use crate::cli::chat::util::drop_matched_context_files;
 use crate::cli::chat::{
     ChatError,
@@ -181,9 +184,10 @@ impl ContextSubcommand {
                     )?;
                 } else {
                     let total = profile_context_files.len();
+                    let tokenizer = Tokenizer::for_model(session.conversation.model_info.as_ref());
                     let total_tokens = profile_context_files
                         .iter()
-                        .map(|(_, content, _)| TokenCounter::count_tokens(content))
+                        .map(|(_, content, _)| TokenCounter::count_tokens_with(content, tokenizer))
                         .sum::<usize>();
                     execute!(
                         session.stderr,
@@ -199,7 +203,7 @@ impl ContextSubcommand {
                     )?;
 
                     for (filename, content, is_temporary) in &profile_context_files {
-                        let est_tokens = TokenCounter::count_tokens(content);
+                        let est_tokens = TokenCounter::count_tokens_with(content, tokenizer);
                         let icon = if *is_temporary { "💬" } else { "👤" };
                         execute!(
                             session.stderr,
@@ -227,7 +231,8 @@ impl ContextSubcommand {
                         .iter()
                         .map(|(path, content, _)| (path.clone(), content.clone()))
                         .collect::<Vec<_>>();
-                    let dropped_files = drop_matched_context_files(&mut files_as_vec, context_files_max_size).ok();
+                    let dropped_files =
+                        drop_matched_context_files(&mut files_as_vec, context_files_max_size, tokenizer).ok();
 
                     execute!(
                         session.stderr,
@@ -250,7 +255,7 @@ impl ContextSubcommand {
                             let truncated_dropped_files = &dropped_files[..10];
 
                             for (filename, content) in truncated_dropped_files {
-                                let est_tokens = TokenCounter::count_tokens(content);
+                                let est_tokens = TokenCounter::count_tokens_with(content, tokenizer);
                                 execute!(
                                     session.stderr,
                                     style::Print(format!("{} ", filename)),
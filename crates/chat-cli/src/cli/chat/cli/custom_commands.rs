@@ -0,0 +1,158 @@
+use std::io::Write;
+
+use crossterm::style::{
+    self,
+    Attribute,
+    Color,
+};
+use crossterm::{
+    execute,
+    queue,
+};
+
+use crate::cli::agent::Agent;
+use crate::cli::agent::custom_command::CustomCommand;
+use crate::cli::chat::cli::model::{
+    ModelInfo,
+    get_model_info,
+};
+use crate::cli::chat::conversation::ConversationState;
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::mcp_client::{
+    MessageContent,
+    Prompt,
+    Role,
+};
+use crate::os::Os;
+
+/// Session changes made for the duration of a custom command's turn, reverted once the user is
+/// prompted for their next message.
+#[derive(Debug, Default)]
+pub struct CommandOverrides {
+    /// Tools trusted by the command that were not already trusted by the agent
+    trusted_tools: Vec<String>,
+    /// Whether the command switched models
+    model_overridden: bool,
+    /// The model in use before the command switched models
+    previous_model: Option<ModelInfo>,
+}
+
+impl CommandOverrides {
+    pub fn restore(self, conversation: &mut ConversationState) {
+        conversation.agents.untrust_tools(&self.trusted_tools);
+        if self.model_overridden {
+            conversation.model_info = self.previous_model;
+        }
+    }
+}
+
+/// Looks up a custom command declared by the active agent.
+pub fn find_custom_command(session: &ChatSession, name: &str) -> Option<CustomCommand> {
+    session
+        .conversation
+        .agents
+        .get_active()
+        .and_then(|agent| agent.commands.get(name))
+        .cloned()
+}
+
+/// Expands the custom command into a pending prompt, applying its tool and model overrides until
+/// the model has finished responding.
+pub async fn execute_custom_command(
+    os: &Os,
+    session: &mut ChatSession,
+    command: CustomCommand,
+    orig_input: &str,
+) -> Result<ChatState, ChatError> {
+    let arguments = orig_input
+        .split_once(char::is_whitespace)
+        .map(|(_, arguments)| arguments)
+        .unwrap_or_default();
+
+    if let Some(overrides) = session.custom_command_overrides.take() {
+        overrides.restore(&mut session.conversation);
+    }
+
+    let mut overrides = CommandOverrides::default();
+    if let Some(agent) = session.conversation.agents.get_active() {
+        overrides.trusted_tools = command
+            .allowed_tools
+            .iter()
+            .filter(|tool| !agent.allowed_tools.contains(*tool))
+            .cloned()
+            .collect();
+    }
+    session.conversation.agents.trust_tools(overrides.trusted_tools.clone());
+
+    if let Some(model_id) = &command.model {
+        match get_model_info(model_id, os).await {
+            Ok(model_info) => {
+                overrides.model_overridden = true;
+                overrides.previous_model = session.conversation.model_info.replace(model_info);
+            },
+            Err(err) => {
+                execute!(
+                    session.stderr,
+                    style::SetForegroundColor(Color::Yellow),
+                    style::Print(format!("{err}. Continuing with the current model.\n")),
+                    style::SetForegroundColor(Color::Reset),
+                )?;
+            },
+        }
+    }
+    session.custom_command_overrides = Some(overrides);
+
+    session.pending_prompts.clear();
+    session.pending_prompts.push_back(Prompt {
+        role: Role::User,
+        content: MessageContent::Text {
+            text: command.expand(arguments),
+        },
+    });
+
+    Ok(ChatState::HandleInput {
+        input: orig_input.to_string(),
+    })
+}
+
+/// Appends the active agent's custom commands to the output of `/help`.
+pub fn queue_custom_commands_help(output: &mut impl Write, agent: &Agent) -> Result<(), ChatError> {
+    if agent.commands.is_empty() {
+        return Ok(());
+    }
+
+    let mut commands = agent.commands.iter().collect::<Vec<_>>();
+    commands.sort_by_key(|(name, _)| name.as_str());
+    let name_width = commands
+        .iter()
+        .map(|(name, _)| name.len() + 1)
+        .max()
+        .unwrap_or_default();
+
+    queue!(
+        output,
+        style::SetAttribute(Attribute::Bold),
+        style::SetAttribute(Attribute::Underlined),
+        style::Print(format!("Custom commands ({}):", agent.name)),
+        style::SetAttribute(Attribute::Reset),
+        style::Print("\n"),
+    )?;
+    for (name, command) in commands {
+        queue!(
+            output,
+            style::SetAttribute(Attribute::Bold),
+            style::Print(format!("  {:<name_width$}", format!("/{name}"))),
+            style::SetAttribute(Attribute::Reset),
+            style::Print("  "),
+            style::Print(command.description.as_deref().unwrap_or_default()),
+            style::Print("\n"),
+        )?;
+    }
+    queue!(output, style::Print("\n"))?;
+
+    Ok(())
+}
@@ -84,9 +84,16 @@ impl EditorArgs {
 
 /// Opens the user's preferred editor to compose a prompt
 fn open_editor(initial_text: Option<String>) -> Result<String, ChatError> {
+    let content = open_editor_with_content(&initial_text.unwrap_or_default(), "md")?;
+    Ok(content.trim().to_string())
+}
+
+/// Opens the user's preferred editor on a temporary file with the given `extension` containing
+/// `initial_content`, returning the file's content once the editor exits.
+pub fn open_editor_with_content(initial_content: &str, extension: &str) -> Result<String, ChatError> {
     // Create a temporary file with a unique name
     let temp_dir = std::env::temp_dir();
-    let file_name = format!("q_prompt_{}.md", Uuid::new_v4());
+    let file_name = format!("q_prompt_{}.{}", Uuid::new_v4(), extension);
     let temp_file_path = temp_dir.join(file_name);
 
     // Get the editor from environment variable or use a default
@@ -102,9 +109,8 @@ fn open_editor(initial_text: Option<String>) -> Result<String, ChatError> {
 
     let editor_bin = parts.remove(0);
 
-    // Write initial content to the file if provided
-    let initial_content = initial_text.unwrap_or_default();
-    std::fs::write(&temp_file_path, &initial_content)
+    // Write initial content to the file
+    std::fs::write(&temp_file_path, initial_content)
         .map_err(|e| ChatError::Custom(format!("Failed to create temporary file: {}", e).into()))?;
 
     // Open the editor with the parsed command and arguments
@@ -130,5 +136,5 @@ fn open_editor(initial_text: Option<String>) -> Result<String, ChatError> {
     // Clean up the temporary file
     let _ = std::fs::remove_file(&temp_file_path);
 
-    Ok(content.trim().to_string())
+    Ok(content)
 }
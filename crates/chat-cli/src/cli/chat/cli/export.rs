@@ -0,0 +1,79 @@
+use clap::Args;
+use crossterm::execute;
+use crossterm::style::{
+    self,
+    Attribute,
+    Color,
+};
+
+use crate::cli::chat::transcript::{
+    TranscriptFormat,
+    render_transcript,
+};
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::os::Os;
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+#[command(
+    before_long_help = "/export writes the conversation as a readable transcript, with the prompts, responses,
+tool uses with their inputs and collapsed outputs, and the changes made to files as diffs.
+Use /save instead to save the conversation so that it can be loaded again with /load.
+
+The format is chosen by the extension of the file unless --format is given.
+Transcripts of previous conversations can be exported with: q export"
+)]
+pub struct ExportArgs {
+    /// File to write the transcript to
+    path: String,
+    /// Format of the transcript
+    #[arg(long, value_enum)]
+    format: Option<TranscriptFormat>,
+    /// Overwrite the file if it exists
+    #[arg(short, long)]
+    force: bool,
+}
+
+impl ExportArgs {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if os.fs.exists(&self.path) && !self.force {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!(
+                    "\nFile at {} already exists. To overwrite, use -f or --force\n\n",
+                    &self.path
+                )),
+                style::SetAttribute(Attribute::Reset)
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        }
+
+        let format = self.format.unwrap_or_else(|| TranscriptFormat::from_path(&self.path));
+        let transcript = render_transcript(&session.conversation, format);
+        match os.fs.write(&self.path, transcript).await {
+            Ok(()) => execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Green),
+                style::Print(format!("\n✔ Exported conversation transcript to {}\n\n", &self.path)),
+                style::SetAttribute(Attribute::Reset)
+            )?,
+            Err(err) => execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!("\nFailed to export to {}: {}\n\n", &self.path, err)),
+                style::SetAttribute(Attribute::Reset)
+            )?,
+        }
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
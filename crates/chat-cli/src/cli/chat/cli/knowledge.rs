@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::io::Write;
+use std::path::Path;
 
 use clap::Subcommand;
 use crossterm::queue;
@@ -8,9 +10,12 @@ use crossterm::style::{
 };
 use eyre::Result;
 use semantic_search_client::{
+    Citation,
     KnowledgeContext,
     OperationStatus,
+    SearchFilter,
     SystemStatus,
+    WatcherStatus,
 };
 
 use crate::cli::chat::tools::sanitize_path_tool_arg;
@@ -21,7 +26,10 @@ use crate::cli::chat::{
 };
 use crate::database::settings::Setting;
 use crate::os::Os;
-use crate::util::knowledge_store::KnowledgeStore;
+use crate::util::knowledge_store::{
+    KnowledgeScope,
+    KnowledgeStore,
+};
 
 /// Knowledge base management commands
 #[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
@@ -37,17 +45,64 @@ pub enum KnowledgeSubcommand {
         /// Exclude patterns (e.g., `node_modules/**`, `target/**`)
         #[arg(long, action = clap::ArgAction::Append)]
         exclude: Vec<String>,
-        /// Index type to use (Fast, Best)
+        /// Index type to use (Fast, Best, Hybrid, Http)
         #[arg(long)]
         index_type: Option<String>,
+        /// Re-index changed files automatically while Q is running
+        #[arg(long)]
+        auto_update: bool,
+        /// Knowledge base to add to (defaults to the workspace one if it exists)
+        #[arg(long, value_parser = ["workspace", "global"])]
+        scope: Option<String>,
     },
     /// Remove specified knowledge base entry by path
     #[command(alias = "rm")]
     Remove { path: String },
     /// Update a file or directory in knowledge base
     Update { path: String },
+    /// Turn automatic re-indexing of a knowledge base entry on or off
+    AutoUpdate {
+        /// Path or name of the entry
+        path: String,
+        #[arg(value_parser = ["on", "off"])]
+        state: String,
+    },
+    /// Search the knowledge base and show where the matches are
+    Search {
+        /// Text to search for
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+        /// Knowledge base to search (defaults to the workspace one if it exists)
+        #[arg(long, value_parser = ["workspace", "global", "all"])]
+        scope: Option<String>,
+        /// Only search matching chunks, e.g. "path:src/** type:markdown lang:rs context:docs"
+        #[arg(long)]
+        filter: Option<SearchFilter>,
+    },
     /// Remove all knowledge base entries
-    Clear,
+    Clear {
+        /// Knowledge base to clear (defaults to the workspace one if it exists)
+        #[arg(long, value_parser = ["workspace", "global"])]
+        scope: Option<String>,
+    },
+    /// Export a knowledge base entry as a bundle that can be imported on another machine
+    Export {
+        /// Name or path of the entry
+        name: String,
+        /// File to write the bundle to
+        file: String,
+    },
+    /// Import a knowledge base entry from a bundle
+    Import {
+        /// Bundle file to import
+        file: String,
+        /// Local directory the entry was indexed from, so results point at your copy of the files
+        #[arg(long)]
+        path: Option<String>,
+        /// Knowledge base to import into (defaults to the workspace one if it exists)
+        #[arg(long, value_parser = ["workspace", "global"])]
+        scope: Option<String>,
+    },
     /// Show background operation status
     Status,
     /// Cancel a background operation
@@ -116,10 +171,33 @@ impl KnowledgeSubcommand {
                 include,
                 exclude,
                 index_type,
-            } => Self::handle_add(os, path, include, exclude, index_type).await,
+                auto_update,
+                scope,
+            } => {
+                let scope = scope.as_deref().and_then(|scope| scope.parse().ok());
+                Self::handle_add(os, path, include, exclude, index_type, *auto_update, scope).await
+            },
             KnowledgeSubcommand::Remove { path } => Self::handle_remove(os, path).await,
             KnowledgeSubcommand::Update { path } => Self::handle_update(os, path).await,
-            KnowledgeSubcommand::Clear => Self::handle_clear(os, session).await,
+            KnowledgeSubcommand::AutoUpdate { path, state } => Self::handle_auto_update(os, path, state == "on").await,
+            KnowledgeSubcommand::Search { query, scope, filter } => {
+                Self::handle_search(
+                    os,
+                    &query.join(" "),
+                    scope.as_deref(),
+                    &filter.clone().unwrap_or_default(),
+                )
+                .await
+            },
+            KnowledgeSubcommand::Clear { scope } => {
+                let scope = scope.as_deref().and_then(|scope| scope.parse().ok());
+                Self::handle_clear(os, session, scope).await
+            },
+            KnowledgeSubcommand::Export { name, file } => Self::handle_export(os, name, file).await,
+            KnowledgeSubcommand::Import { file, path, scope } => {
+                let scope = scope.as_deref().and_then(|scope| scope.parse().ok());
+                Self::handle_import(os, file, path.as_deref(), scope).await
+            },
             KnowledgeSubcommand::Status => Self::handle_status(os).await,
             KnowledgeSubcommand::Cancel { operation_id } => Self::handle_cancel(os, operation_id.as_deref()).await,
         }
@@ -138,7 +216,8 @@ impl KnowledgeSubcommand {
                     );
                     Vec::new()
                 });
-                let _ = Self::format_knowledge_entries(session, &entries);
+                let load_errors = store.get_load_errors().await;
+                let _ = Self::format_knowledge_entries(session, &entries, &load_errors);
             },
             Err(e) => {
                 queue!(
@@ -154,7 +233,8 @@ impl KnowledgeSubcommand {
 
     fn format_knowledge_entries(
         session: &mut ChatSession,
-        knowledge_entries: &[KnowledgeContext],
+        knowledge_entries: &[(KnowledgeScope, KnowledgeContext)],
+        load_errors: &HashMap<String, String>,
     ) -> Result<(), std::io::Error> {
         if knowledge_entries.is_empty() {
             queue!(
@@ -170,8 +250,8 @@ impl KnowledgeSubcommand {
                 style::Print(format!("{}\n", "━".repeat(80)))
             )?;
 
-            for entry in knowledge_entries {
-                Self::format_single_entry(session, &entry)?;
+            for (scope, entry) in knowledge_entries {
+                Self::format_single_entry(session, *scope, entry, load_errors.get(&entry.id))?;
                 queue!(session.stderr, style::Print(format!("{}\n", "━".repeat(80))))?;
             }
             // Add final newline to match original formatting exactly
@@ -180,7 +260,12 @@ impl KnowledgeSubcommand {
         Ok(())
     }
 
-    fn format_single_entry(session: &mut ChatSession, entry: &&KnowledgeContext) -> Result<(), std::io::Error> {
+    fn format_single_entry(
+        session: &mut ChatSession,
+        scope: KnowledgeScope,
+        entry: &KnowledgeContext,
+        load_error: Option<&String>,
+    ) -> Result<(), std::io::Error> {
         queue!(
             session.stderr,
             style::SetAttribute(style::Attribute::Bold),
@@ -189,6 +274,9 @@ impl KnowledgeSubcommand {
             style::SetForegroundColor(Color::Green),
             style::Print(&entry.name),
             style::SetAttribute(style::Attribute::Reset),
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print(format!(" ({})", scope)),
+            style::SetForegroundColor(Color::Reset),
             style::Print("\n")
         )?;
 
@@ -239,6 +327,15 @@ impl KnowledgeSubcommand {
                 style::Print("\n")
             )?;
         }
+
+        if let Some(error) = load_error {
+            queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!("   ⚠️ Not loaded, excluded from search: {}\n", error)),
+                style::SetForegroundColor(Color::Reset)
+            )?;
+        }
         Ok(())
     }
 
@@ -258,6 +355,8 @@ impl KnowledgeSubcommand {
         include_patterns: &[String],
         exclude_patterns: &[String],
         index_type: &Option<String>,
+        auto_update: bool,
+        scope: Option<KnowledgeScope>,
     ) -> OperationResult {
         match Self::validate_and_sanitize_path(os, path) {
             Ok(sanitized_path) => {
@@ -289,7 +388,9 @@ impl KnowledgeSubcommand {
                 let options = crate::util::knowledge_store::AddOptions::new()
                     .with_include_patterns(include)
                     .with_exclude_patterns(exclude)
-                    .with_embedding_type(embedding_type_resolved);
+                    .with_embedding_type(embedding_type_resolved)
+                    .with_auto_update(auto_update)
+                    .with_scope(scope);
 
                 match store.add(path, &sanitized_path.clone(), options).await {
                     Ok(message) => OperationResult::Info(message),
@@ -347,12 +448,153 @@ impl KnowledgeSubcommand {
         }
     }
 
+    /// Handle auto-update toggle
+    async fn handle_auto_update(os: &Os, path: &str, enabled: bool) -> OperationResult {
+        let sanitized_path = sanitize_path_tool_arg(os, path);
+
+        let async_knowledge_store = match KnowledgeStore::get_async_instance_with_os(os).await {
+            Ok(store) => store,
+            Err(e) => return OperationResult::Error(format!("Error accessing knowledge base: {}", e)),
+        };
+        let mut store = async_knowledge_store.lock().await;
+
+        // Try path first, then name
+        let result = match store.set_auto_update(&sanitized_path.to_string_lossy(), enabled).await {
+            Ok(message) => Ok(message),
+            Err(_) => store.set_auto_update(path, enabled).await,
+        };
+
+        match result {
+            Ok(message) => OperationResult::Success(message),
+            Err(e) => OperationResult::Error(format!("Failed to set auto-update: {}", e)),
+        }
+    }
+
+    /// Handle export operation
+    async fn handle_export(os: &Os, name: &str, file: &str) -> OperationResult {
+        let file = sanitize_path_tool_arg(os, file);
+
+        let async_knowledge_store = match KnowledgeStore::get_async_instance_with_os(os).await {
+            Ok(store) => store,
+            Err(e) => return OperationResult::Error(format!("Error accessing knowledge base: {}", e)),
+        };
+        let mut store = async_knowledge_store.lock().await;
+
+        // Try name first, then path. Only a missing context is retried, so that other failures
+        // are reported as they are.
+        let sanitized_path = sanitize_path_tool_arg(os, name);
+        let result = match store.export(name, &file).await {
+            Err(e) if e.starts_with("No context found") => store.export(&sanitized_path.to_string_lossy(), &file).await,
+            result => result,
+        };
+
+        match result {
+            Ok(message) => OperationResult::Success(message),
+            Err(e) => OperationResult::Error(format!("Failed to export: {}", e)),
+        }
+    }
+
+    /// Handle import operation
+    async fn handle_import(
+        os: &Os,
+        file: &str,
+        source_path: Option<&str>,
+        scope: Option<KnowledgeScope>,
+    ) -> OperationResult {
+        let file = match Self::validate_and_sanitize_path(os, file) {
+            Ok(file) => file,
+            Err(e) => return OperationResult::Error(e),
+        };
+        let source_path = match source_path.map(|path| Self::validate_and_sanitize_path(os, path)) {
+            Some(Ok(path)) => Some(path),
+            Some(Err(e)) => return OperationResult::Error(e),
+            None => None,
+        };
+
+        let async_knowledge_store = match KnowledgeStore::get_async_instance_with_os(os).await {
+            Ok(store) => store,
+            Err(e) => return OperationResult::Error(format!("Error accessing knowledge base: {}", e)),
+        };
+        let mut store = async_knowledge_store.lock().await;
+
+        match store
+            .import(Path::new(&file), source_path.as_deref().map(Path::new), scope)
+            .await
+        {
+            Ok(message) => OperationResult::Success(message),
+            Err(e) => OperationResult::Error(format!("Failed to import: {}", e)),
+        }
+    }
+
+    /// Handle search operation
+    async fn handle_search(os: &Os, query: &str, scope: Option<&str>, filter: &SearchFilter) -> OperationResult {
+        let async_knowledge_store = match KnowledgeStore::get_async_instance_with_os(os).await {
+            Ok(store) => store,
+            Err(e) => return OperationResult::Error(format!("Error accessing knowledge base: {}", e)),
+        };
+        let store = async_knowledge_store.lock().await;
+        let scopes = match store.search_scopes(scope) {
+            Ok(scopes) => scopes,
+            Err(e) => return OperationResult::Error(e),
+        };
+
+        match store.search(query, filter, &scopes).await {
+            Ok(citations) if citations.is_empty() => {
+                OperationResult::Warning(format!("No matching entries found for \"{}\"", query))
+            },
+            Ok(citations) => {
+                let mut lines = vec![format!("🔍 Results for \"{}\":", query)];
+                for citation in &citations {
+                    lines.push(Self::format_citation_display(citation));
+                }
+                OperationResult::Info(lines.join("\n\n"))
+            },
+            Err(e) => OperationResult::Error(format!("Search failed: {}", e)),
+        }
+    }
+
+    /// Format a search result as its `path:line` location followed by the start of its text
+    fn format_citation_display(citation: &Citation) -> String {
+        const SNIPPET_LINES: usize = 3;
+        const SNIPPET_WIDTH: usize = 100;
+
+        let location = citation.location().unwrap_or_else(|| citation.context_name.clone());
+        let mut header = format!("  {} ({}", location, citation.context_name);
+        if let Some(section) = &citation.section {
+            header.push_str(&format!(" | {}", section));
+        }
+        header.push_str(&format!(" | score {:.3})", citation.score));
+
+        let snippet = citation
+            .text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim().is_empty())
+            .take(SNIPPET_LINES)
+            .map(|line| match line.char_indices().nth(SNIPPET_WIDTH) {
+                Some((end, _)) => format!("    {}…", &line[..end]),
+                None => format!("    {}", line),
+            })
+            .collect::<Vec<_>>();
+
+        std::iter::once(header).chain(snippet).collect::<Vec<_>>().join("\n")
+    }
+
     /// Handle clear operation
-    async fn handle_clear(os: &Os, session: &mut ChatSession) -> OperationResult {
+    async fn handle_clear(os: &Os, session: &mut ChatSession, scope: Option<KnowledgeScope>) -> OperationResult {
+        let async_knowledge_store = match KnowledgeStore::get_async_instance_with_os(os).await {
+            Ok(store) => store,
+            Err(e) => return OperationResult::Error(format!("Error accessing knowledge base directory: {}", e)),
+        };
+        let scope = scope.unwrap_or(async_knowledge_store.lock().await.default_scope());
+
         // Require confirmation
         queue!(
             session.stderr,
-            style::Print("⚠️  This action will remove all knowledge base entries.\n"),
+            style::Print(format!(
+                "⚠️  This action will remove all {} knowledge base entries.\n",
+                scope
+            )),
             style::Print("Clear the knowledge base? (y/N): ")
         )
         .unwrap();
@@ -368,10 +610,6 @@ impl KnowledgeSubcommand {
             return OperationResult::Info("Clear operation cancelled".to_string());
         }
 
-        let async_knowledge_store = match KnowledgeStore::get_async_instance_with_os(os).await {
-            Ok(store) => store,
-            Err(e) => return OperationResult::Error(format!("Error accessing knowledge base directory: {}", e)),
-        };
         let mut store = async_knowledge_store.lock().await;
 
         // First, cancel any pending operations
@@ -394,7 +632,7 @@ impl KnowledgeSubcommand {
             style::Print("🗑️  Clearing all knowledge base entries...\n")
         )
         .unwrap();
-        match store.clear_immediate().await {
+        match store.clear_immediate(Some(scope)).await {
             Ok(message) => OperationResult::Success(message),
             Err(e) => OperationResult::Error(format!("Failed to clear: {}", e)),
         }
@@ -427,6 +665,13 @@ impl KnowledgeSubcommand {
             status.total_contexts, status.persistent_contexts, status.volatile_contexts
         ));
 
+        if !status.watchers.is_empty() {
+            status_lines.push("👀 Auto-update:".to_string());
+            for watcher in &status.watchers {
+                status_lines.push(Self::format_watcher_display(watcher));
+            }
+        }
+
         if status.operations.is_empty() {
             status_lines.push("✅ No active operations".to_string());
             return status_lines.join("\n");
@@ -446,6 +691,21 @@ impl KnowledgeSubcommand {
         status_lines.join("\n")
     }
 
+    /// Format the watcher of an auto-updated entry for display
+    fn format_watcher_display(watcher: &WatcherStatus) -> String {
+        let state = match (watcher.pending, &watcher.error) {
+            (Some(count), _) => format!("Changes pending: {} path(s)", count),
+            (None, Some(error)) => format!("Not watching: {}", error),
+            (None, None) if watcher.watching_since.is_some() => "Up to date".to_string(),
+            (None, None) => "Starting".to_string(),
+        };
+        let mut line = format!("  {} | {} | {}", watcher.name, watcher.path, state);
+        if let Some(elapsed) = watcher.last_updated.and_then(|updated| updated.elapsed().ok()) {
+            line.push_str(&format!(" | Last auto-update: {}s ago", elapsed.as_secs()));
+        }
+        line
+    }
+
     /// Format a single operation for display
     fn format_operation_display(op: &OperationStatus) -> String {
         let elapsed = op.started_at.elapsed().unwrap_or_default();
@@ -454,6 +714,8 @@ impl KnowledgeSubcommand {
             ("🛑", "Cancelled".to_string())
         } else if op.is_failed {
             ("❌", op.message.clone())
+        } else if op.is_completed {
+            ("✅", op.message.clone())
         } else if op.is_waiting {
             ("⏳", op.message.clone())
         } else if Self::should_show_progress_bar(op.current, op.total) {
@@ -465,11 +727,22 @@ impl KnowledgeSubcommand {
         let operation_desc = op.operation_type.display_name();
 
         // Format with conditional elapsed time and ETA
-        if op.is_cancelled || op.is_failed {
+        if op.is_cancelled || op.is_failed || op.is_completed {
             format!(
                 "  {} {} | {}\n    {}",
                 status_icon, op.short_id, operation_desc, status_info
             )
+        } else if let Some(changes) = op.changes {
+            let mut time_info = format!("Elapsed: {}s", elapsed.as_secs());
+
+            if let Some(eta) = op.eta {
+                time_info.push_str(&format!(" | ETA: {}s", eta.as_secs()));
+            }
+
+            format!(
+                "  {} {} | {}\n    {} | {}\n    Changes: {}",
+                status_icon, op.short_id, operation_desc, status_info, time_info, changes
+            )
         } else {
             let mut time_info = format!("Elapsed: {}s", elapsed.as_secs());
 
@@ -584,7 +857,11 @@ impl KnowledgeSubcommand {
             KnowledgeSubcommand::Add { .. } => "add",
             KnowledgeSubcommand::Remove { .. } => "remove",
             KnowledgeSubcommand::Update { .. } => "update",
-            KnowledgeSubcommand::Clear => "clear",
+            KnowledgeSubcommand::AutoUpdate { .. } => "auto-update",
+            KnowledgeSubcommand::Search { .. } => "search",
+            KnowledgeSubcommand::Clear { .. } => "clear",
+            KnowledgeSubcommand::Export { .. } => "export",
+            KnowledgeSubcommand::Import { .. } => "import",
             KnowledgeSubcommand::Status => "status",
             KnowledgeSubcommand::Cancel { .. } => "cancel",
         }
@@ -636,6 +913,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_auto_update_parsing() {
+        let cli = TestCli::try_parse_from(["test", "add", "/some/path", "--auto-update"]).unwrap();
+        assert!(matches!(cli.knowledge, KnowledgeSubcommand::Add {
+            auto_update: true,
+            ..
+        }));
+
+        let cli = TestCli::try_parse_from(["test", "auto-update", "docs", "off"]).unwrap();
+        assert_eq!(cli.knowledge, KnowledgeSubcommand::AutoUpdate {
+            path: "docs".to_string(),
+            state: "off".to_string(),
+        });
+
+        assert!(TestCli::try_parse_from(["test", "auto-update", "docs", "maybe"]).is_err());
+    }
+
+    #[test]
+    fn test_scope_parsing() {
+        let cli = TestCli::try_parse_from(["test", "add", "/some/path", "--scope", "global"]).unwrap();
+        assert!(matches!(cli.knowledge, KnowledgeSubcommand::Add { scope: Some(ref scope), .. } if scope == "global"));
+
+        let cli = TestCli::try_parse_from(["test", "search", "retries", "--scope", "all"]).unwrap();
+        assert_eq!(cli.knowledge, KnowledgeSubcommand::Search {
+            query: vec!["retries".to_string()],
+            scope: Some("all".to_string()),
+            filter: None,
+        });
+
+        let cli = TestCli::try_parse_from(["test", "clear"]).unwrap();
+        assert_eq!(cli.knowledge, KnowledgeSubcommand::Clear { scope: None });
+
+        assert!(TestCli::try_parse_from(["test", "add", "/some/path", "--scope", "all"]).is_err());
+        assert!(TestCli::try_parse_from(["test", "clear", "--scope", "team"]).is_err());
+    }
+
+    #[test]
+    fn test_export_import_parsing() {
+        let cli = TestCli::try_parse_from(["test", "export", "monorepo", "monorepo.qkb"]).unwrap();
+        assert_eq!(cli.knowledge, KnowledgeSubcommand::Export {
+            name: "monorepo".to_string(),
+            file: "monorepo.qkb".to_string(),
+        });
+
+        let cli =
+            TestCli::try_parse_from(["test", "import", "monorepo.qkb", "--path", ".", "--scope", "global"]).unwrap();
+        assert_eq!(cli.knowledge, KnowledgeSubcommand::Import {
+            file: "monorepo.qkb".to_string(),
+            path: Some(".".to_string()),
+            scope: Some("global".to_string()),
+        });
+
+        assert!(TestCli::try_parse_from(["test", "export", "monorepo"]).is_err());
+    }
+
+    #[test]
+    fn test_search_parsing_and_display() {
+        let cli = TestCli::try_parse_from(["test", "search", "error", "handling"]).unwrap();
+        assert_eq!(cli.knowledge, KnowledgeSubcommand::Search {
+            query: vec!["error".to_string(), "handling".to_string()],
+            scope: None,
+            filter: None,
+        });
+        assert!(TestCli::try_parse_from(["test", "search"]).is_err());
+
+        let cli = TestCli::try_parse_from(["test", "search", "retry", "--filter", "path:src/** lang:rs"]).unwrap();
+        assert_eq!(cli.knowledge, KnowledgeSubcommand::Search {
+            query: vec!["retry".to_string()],
+            scope: None,
+            filter: Some(SearchFilter {
+                path: Some("src/**".to_string()),
+                language: Some("rs".to_string()),
+                ..Default::default()
+            }),
+        });
+        assert!(TestCli::try_parse_from(["test", "search", "retry", "--filter", "owner:me"]).is_err());
+
+        let citation = Citation {
+            context_id: "ctx".to_string(),
+            context_name: "repo".to_string(),
+            path: Some("/repo/src/lib.rs".to_string()),
+            start_line: Some(12),
+            end_line: Some(30),
+            page: None,
+            section: None,
+            score: 0.125,
+            text: "fn handle_error() {\n\n    todo!()\n}\n// more\n".to_string(),
+        };
+        assert_eq!(
+            KnowledgeSubcommand::format_citation_display(&citation),
+            "  /repo/src/lib.rs:12-30 (repo | score 0.125)\n    fn handle_error() {\n        todo!()\n    }"
+        );
+    }
+
     #[test]
     fn test_clap_markdown_parsing_issue() {
         let help_result = TestCli::try_parse_from(&["test", "add", "--help"]);
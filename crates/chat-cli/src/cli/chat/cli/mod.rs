@@ -1,12 +1,17 @@
 pub mod clear;
 pub mod compact;
 pub mod context;
+pub mod custom_commands;
 pub mod editor;
+pub mod export;
 pub mod hooks;
 pub mod knowledge;
 pub mod mcp;
 pub mod model;
+pub mod paste;
 pub mod persist;
+pub mod pin;
+pub mod plan;
 pub mod profile;
 pub mod prompts;
 pub mod subscribe;
@@ -18,11 +23,15 @@ use clear::ClearArgs;
 use compact::CompactArgs;
 use context::ContextSubcommand;
 use editor::EditorArgs;
+use export::ExportArgs;
 use hooks::HooksArgs;
 use knowledge::KnowledgeSubcommand;
 use mcp::McpArgs;
 use model::ModelArgs;
+use paste::PasteArgs;
 use persist::PersistSubcommand;
+use pin::PinArgs;
+use plan::PlanArgs;
 use profile::AgentSubcommand;
 use prompts::PromptsArgs;
 use tools::ToolsArgs;
@@ -63,8 +72,15 @@ pub enum SlashCommand {
     /// Open $EDITOR (defaults to vi) to compose a prompt
     #[command(name = "editor")]
     PromptEditor(EditorArgs),
+    /// Attach the image on the clipboard to your next prompt
+    Paste(PasteArgs),
     /// Summarize the conversation to free up context space
     Compact(CompactArgs),
+    /// Pin the latest turn so that it is kept rather than summarized when compacting
+    Pin(PinArgs),
+    /// Toggle plan mode, where the model investigates with read-only tools and proposes a plan
+    /// for you to approve
+    Plan(PlanArgs),
     /// View tools and permissions
     Tools(ToolsArgs),
     /// Create a new Github issue or make a feature request
@@ -83,6 +99,8 @@ pub enum SlashCommand {
     Subscribe(SubscribeArgs),
     #[command(flatten)]
     Persist(PersistSubcommand),
+    /// Export the conversation to a Markdown or HTML transcript
+    Export(ExportArgs),
     // #[command(flatten)]
     // Root(RootSubcommand),
 }
@@ -119,8 +137,11 @@ impl SlashCommand {
             Self::Context(args) => args.execute(os, session).await,
             Self::Knowledge(subcommand) => subcommand.execute(os, session).await,
             Self::PromptEditor(args) => args.execute(session).await,
+            Self::Paste(args) => args.execute(os, session).await,
             Self::Compact(args) => args.execute(os, session).await,
-            Self::Tools(args) => args.execute(session).await,
+            Self::Pin(args) => args.execute(session).await,
+            Self::Plan(args) => args.execute(session).await,
+            Self::Tools(args) => args.execute(os, session).await,
             Self::Issue(args) => {
                 if let Err(err) = args.execute(os).await {
                     return Err(ChatError::Custom(err.to_string().into()));
@@ -130,13 +151,14 @@ impl SlashCommand {
                     skip_printing_tools: true,
                 })
             },
-            Self::Prompts(args) => args.execute(session).await,
+            Self::Prompts(args) => args.execute(os, session).await,
             Self::Hooks(args) => args.execute(session).await,
             Self::Usage(args) => args.execute(os, session).await,
             Self::Mcp(args) => args.execute(session).await,
             Self::Model(args) => args.execute(os, session).await,
             Self::Subscribe(args) => args.execute(os, session).await,
             Self::Persist(subcommand) => subcommand.execute(os, session).await,
+            Self::Export(args) => args.execute(os, session).await,
             // Self::Root(subcommand) => {
             //     if let Err(err) = subcommand.execute(os, database, telemetry).await {
             //         return Err(ChatError::Custom(err.to_string().into()));
@@ -158,7 +180,10 @@ impl SlashCommand {
             Self::Context(_) => "context",
             Self::Knowledge(_) => "knowledge",
             Self::PromptEditor(_) => "editor",
+            Self::Paste(_) => "paste",
             Self::Compact(_) => "compact",
+            Self::Pin(_) => "pin",
+            Self::Plan(_) => "plan",
             Self::Tools(_) => "tools",
             Self::Issue(_) => "issue",
             Self::Prompts(_) => "prompts",
@@ -171,6 +196,7 @@ impl SlashCommand {
                 PersistSubcommand::Save { .. } => "save",
                 PersistSubcommand::Load { .. } => "load",
             },
+            Self::Export(_) => "export",
         }
     }
 
@@ -181,6 +207,7 @@ impl SlashCommand {
             SlashCommand::Knowledge(sub) => Some(sub.name()),
             SlashCommand::Tools(arg) => arg.subcommand_name(),
             SlashCommand::Prompts(arg) => arg.subcommand_name(),
+            SlashCommand::Pin(arg) => arg.subcommand_name(),
             _ => None,
         }
     }
@@ -0,0 +1,83 @@
+use clap::Args;
+use crossterm::execute;
+use crossterm::style::{
+    self,
+    Color,
+};
+
+use crate::cli::chat::consts::MAX_NUMBER_OF_IMAGES_PER_REQUEST;
+use crate::cli::chat::util::images::{
+    format_size,
+    get_image_from_clipboard,
+};
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::database::settings::Setting;
+use crate::os::Os;
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+#[command(
+    before_long_help = format!("/paste attaches the image on the clipboard to your next prompt. The clipboard is read with
+wl-paste on Wayland and xclip on X11. To use another tool, set {} to a command
+that prints the clipboard's image, e.g. q settings {0} \"pngpaste -\"", Setting::ChatClipboardImageCommand)
+)]
+pub struct PasteArgs {
+    /// Discard the images pasted since the last prompt
+    #[arg(long)]
+    clear: bool,
+}
+
+impl PasteArgs {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if self.clear {
+            let count = std::mem::take(&mut session.pasted_images).len();
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Green),
+                style::Print(format!("\nDiscarded {count} pasted image(s).\n\n")),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        } else if session.pasted_images.len() >= MAX_NUMBER_OF_IMAGES_PER_REQUEST {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!(
+                    "\nNo more than {MAX_NUMBER_OF_IMAGES_PER_REQUEST} images can be sent with a prompt.\n\n"
+                )),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        } else {
+            match get_image_from_clipboard(os).await {
+                Ok(image) => {
+                    let size = format_size(image.1.size);
+                    session.pasted_images.push(image);
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(format!(
+                            "\nPasted image #{} ({size}). It will be sent with your next prompt.\n\n",
+                            session.pasted_images.len()
+                        )),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                },
+                Err(err) => {
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Red),
+                        style::Print(format!("\nFailed to paste an image: {err}\n\n")),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                },
+            }
+        }
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
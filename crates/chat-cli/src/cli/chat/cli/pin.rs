@@ -0,0 +1,153 @@
+use clap::{
+    Args,
+    Subcommand,
+};
+use crossterm::style::{
+    self,
+    Color,
+};
+use crossterm::{
+    execute,
+    queue,
+};
+
+use crate::cli::chat::util::truncate_safe;
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+
+/// Longest prompt excerpt shown for a pinned turn
+const MAX_PROMPT_EXCERPT_LEN: usize = 80;
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+#[command(
+    before_long_help = "/pin keeps the latest turn - your last prompt and every response and tool use after it - out
+of compaction. Pinned turns stay in the conversation history as they are rather than being
+summarized, whether the history is compacted with /compact or automatically."
+)]
+pub struct PinArgs {
+    #[command(subcommand)]
+    subcommand: Option<PinSubcommand>,
+}
+
+impl PinArgs {
+    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if let Some(subcommand) = self.subcommand {
+            return subcommand.execute(session).await;
+        }
+
+        match session.conversation.pin_latest_turn() {
+            Some(prompt) => {
+                let prompt = excerpt(prompt);
+                execute!(
+                    session.stderr,
+                    style::SetForegroundColor(Color::Green),
+                    style::Print(format!("\nPinned the latest turn: {prompt}\n\n")),
+                    style::SetForegroundColor(Color::Reset),
+                )?;
+            },
+            None => {
+                execute!(
+                    session.stderr,
+                    style::SetForegroundColor(Color::Yellow),
+                    style::Print("\nThere is nothing to pin yet.\n\n"),
+                    style::SetForegroundColor(Color::Reset),
+                )?;
+            },
+        }
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+
+    pub fn subcommand_name(&self) -> Option<&'static str> {
+        self.subcommand.as_ref().map(|s| s.name())
+    }
+}
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum PinSubcommand {
+    /// List the pinned turns
+    List,
+    /// Unpin a turn so that it can be summarized
+    Remove {
+        /// The number of the turn as shown by /pin list
+        index: usize,
+    },
+}
+
+impl PinSubcommand {
+    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        match self {
+            Self::List => {
+                let prompts = session.conversation.pinned_prompts();
+                if prompts.is_empty() {
+                    queue!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print("\nNo turns are pinned. Run /pin to pin the latest turn.\n"),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                } else {
+                    queue!(session.stderr, style::Print("\nPinned turns:\n"))?;
+                }
+                for (i, prompt) in prompts.into_iter().enumerate() {
+                    queue!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(format!("  {}. ", i + 1)),
+                        style::SetForegroundColor(Color::Reset),
+                        style::Print(format!("{}\n", excerpt(prompt))),
+                    )?;
+                }
+                execute!(session.stderr, style::Print("\n"))?;
+            },
+            Self::Remove { index } => {
+                if index > 0 && session.conversation.unpin_turn(index - 1) {
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(format!("\nUnpinned turn {index}.\n\n")),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                } else {
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Red),
+                        style::Print(format!(
+                            "\nNo pinned turn {index}. Run /pin list to see the pinned turns.\n\n"
+                        )),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                }
+            },
+        }
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Remove { .. } => "remove",
+        }
+    }
+}
+
+/// Returns the first line of `prompt`, truncated for display.
+fn excerpt(prompt: &str) -> String {
+    let line = prompt.lines().next().unwrap_or_default();
+    let truncated = truncate_safe(line, MAX_PROMPT_EXCERPT_LEN);
+    if truncated.len() < prompt.len() {
+        format!("{truncated}...")
+    } else {
+        truncated.to_string()
+    }
+}
@@ -0,0 +1,45 @@
+use clap::Args;
+use crossterm::execute;
+use crossterm::style::{
+    self,
+    Color,
+};
+
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+#[command(
+    before_long_help = "/plan toggles plan mode. In plan mode the model can only use tools that read state: fs_read,
+read-only shell commands, read-only AWS operations and knowledge base searches. Once it has
+investigated your request, it proposes a plan for you to approve. Approving the plan ends plan
+mode so that the model can carry it out with all of its tools. Start a session in plan mode
+with q chat --plan."
+)]
+pub struct PlanArgs;
+
+impl PlanArgs {
+    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        let enabled = !session.conversation.plan_mode();
+        session.conversation.set_plan_mode(enabled);
+
+        let message = match enabled {
+            true => "\nPlan mode is on. The model can only use tools that read state until you approve its plan.\n\n",
+            false => "\nPlan mode is off. All tools are available again.\n\n",
+        };
+        execute!(
+            session.stderr,
+            style::SetForegroundColor(Color::Green),
+            style::Print(message),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
@@ -2,6 +2,8 @@ use std::collections::{
     HashMap,
     VecDeque,
 };
+use std::io::Write;
+use std::path::PathBuf;
 
 use clap::{
     Args,
@@ -19,6 +21,7 @@ use crossterm::{
 use thiserror::Error;
 use unicode_width::UnicodeWidthStr;
 
+use crate::cli::chat::cli::editor::open_editor_with_content;
 use crate::cli::chat::error_formatter::format_mcp_error;
 use crate::cli::chat::tool_manager::PromptBundle;
 use crate::cli::chat::{
@@ -26,7 +29,26 @@ use crate::cli::chat::{
     ChatSession,
     ChatState,
 };
-use crate::mcp_client::PromptGetResult;
+use crate::mcp_client::{
+    MessageContent,
+    Prompt,
+    PromptGetArg,
+    PromptGetResult,
+    Role,
+};
+use crate::os::Os;
+use crate::util::directories::{
+    chat_global_prompts_dir,
+    chat_local_prompts_dir,
+};
+
+/// Template used to seed the editor when creating a new local prompt
+const NEW_PROMPT_TEMPLATE: &str = "---
+description: Describe what this prompt does
+arguments: target*, focus
+---
+Review {{target}} and pay particular attention to {{focus}}.
+";
 
 #[derive(Debug, Error)]
 pub enum GetPromptError {
@@ -46,18 +68,253 @@ pub enum GetPromptError {
     IncorrectResponseType,
     #[error("Missing channel")]
     MissingChannel,
+    #[error("Prompt {0} is missing required arguments: {1}")]
+    MissingArguments(String, String),
+}
+
+/// Where a local prompt file was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalPromptScope {
+    Workspace,
+    Global,
+}
+
+impl LocalPromptScope {
+    fn label(&self) -> &'static str {
+        match self {
+            LocalPromptScope::Workspace => "Workspace",
+            LocalPromptScope::Global => "Global",
+        }
+    }
+}
+
+/// A prompt stored as a markdown file in `.amazonq/prompts` or `~/.aws/amazonq/prompts`.
+///
+/// The file may begin with a front matter block declaring a `description` and a comma separated
+/// list of `arguments`, where a trailing `*` marks an argument as required. Arguments are
+/// referenced from the body as `{{name}}`:
+///
+/// ```markdown
+/// ---
+/// description: Review a pull request
+/// arguments: pr*, focus
+/// ---
+/// Review pull request {{pr}}, focusing on {{focus}}.
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalPrompt {
+    pub name: String,
+    pub scope: LocalPromptScope,
+    pub path: PathBuf,
+    pub description: Option<String>,
+    pub arguments: Vec<PromptGetArg>,
+    pub body: String,
+}
+
+impl LocalPrompt {
+    pub fn parse(name: String, scope: LocalPromptScope, path: PathBuf, content: &str) -> Self {
+        let mut description = None;
+        let mut arguments = Vec::new();
+        let mut body = content;
+
+        if let Some(rest) = content.strip_prefix("---\n").or(content.strip_prefix("---\r\n")) {
+            let mut offset = 0;
+            for line in rest.split_inclusive('\n') {
+                offset += line.len();
+                let line = line.trim();
+                if line == "---" {
+                    body = &rest[offset..];
+                    break;
+                }
+                match line.split_once(':') {
+                    Some(("description", value)) => description = Some(value.trim().to_string()),
+                    Some(("arguments", value)) => {
+                        arguments = value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|arg| !arg.is_empty())
+                            .map(|arg| match arg.strip_suffix('*') {
+                                Some(arg) => PromptGetArg {
+                                    name: arg.trim().to_string(),
+                                    description: None,
+                                    required: Some(true),
+                                },
+                                None => PromptGetArg {
+                                    name: arg.to_string(),
+                                    description: None,
+                                    required: None,
+                                },
+                            })
+                            .collect();
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        Self {
+            name,
+            scope,
+            path,
+            description,
+            arguments,
+            body: body.trim_start_matches(['\r', '\n']).to_string(),
+        }
+    }
+
+    /// Substitutes the positional `values` into the prompt body in a single pass, so placeholders
+    /// inside the values are kept as is. Placeholders for optional arguments that were not
+    /// supplied are replaced with an empty string.
+    pub fn render(&self, values: &[String]) -> Result<String, GetPromptError> {
+        let missing = self
+            .arguments
+            .iter()
+            .enumerate()
+            .filter(|(i, arg)| arg.required == Some(true) && *i >= values.len())
+            .map(|(_, arg)| arg.name.as_str())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(GetPromptError::MissingArguments(self.name.clone(), missing.join(", ")));
+        }
+
+        let mut rendered = String::with_capacity(self.body.len());
+        let mut rest = self.body.as_str();
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start + 2..].find("}}").map(|end| start + 2 + end) else {
+                break;
+            };
+            rendered.push_str(&rest[..start]);
+            match self.arguments.iter().position(|arg| arg.name == rest[start + 2..end]) {
+                Some(i) => rendered.push_str(values.get(i).map(String::as_str).unwrap_or_default()),
+                None => rendered.push_str(&rest[start..end + 2]),
+            }
+            rest = &rest[end + 2..];
+        }
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+}
+
+/// Returns the `@server/name` references of the MCP prompts that a local prompt named `name`
+/// shadows, sorted by server.
+fn shadowed_mcp_prompts(mcp_prompts: &HashMap<String, Vec<PromptBundle>>, name: &str) -> Vec<String> {
+    let mut shadowed = mcp_prompts
+        .get(name)
+        .into_iter()
+        .flatten()
+        .map(|bundle| format!("@{}/{name}", bundle.server_name))
+        .collect::<Vec<_>>();
+    shadowed.sort();
+    shadowed
+}
+
+/// Returns the local prompt directories in order of precedence.
+pub fn local_prompt_dirs(os: &Os) -> Vec<(LocalPromptScope, PathBuf)> {
+    let mut dirs = Vec::new();
+    if let Ok(dir) = chat_local_prompts_dir(os) {
+        dirs.push((LocalPromptScope::Workspace, dir));
+    }
+    if let Ok(dir) = chat_global_prompts_dir(os) {
+        dirs.push((LocalPromptScope::Global, dir));
+    }
+    dirs
+}
+
+/// Loads every `.md` prompt from the local prompt directories, sorted by name. A workspace prompt
+/// shadows a global prompt of the same name.
+pub async fn load_local_prompts(os: &Os) -> Vec<LocalPrompt> {
+    let mut prompts = HashMap::<String, LocalPrompt>::new();
+    for (scope, dir) in local_prompt_dirs(os) {
+        let Ok(mut entries) = os.fs.read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = dir.join(entry.file_name());
+            if path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else {
+                continue;
+            };
+            if prompts.contains_key(&name) {
+                continue;
+            }
+            match os.fs.read_to_string(&path).await {
+                Ok(content) => {
+                    let prompt = LocalPrompt::parse(name.clone(), scope, path, &content);
+                    prompts.insert(name, prompt);
+                },
+                Err(e) => tracing::warn!("Failed to read prompt file {}: {:?}", path.display(), e),
+            }
+        }
+    }
+
+    let mut prompts = prompts.into_values().collect::<Vec<_>>();
+    prompts.sort_by(|a, b| a.name.cmp(&b.name));
+    prompts
+}
+
+/// Prompt names become file names and are typed after `@`, so they are restricted to characters
+/// that are safe in both places.
+fn is_valid_prompt_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn queue_prompt_entry(
+    output: &mut impl Write,
+    name: &str,
+    arguments: Option<&[PromptGetArg]>,
+    arg_pos: usize,
+) -> Result<(), ChatError> {
+    queue!(
+        output,
+        style::Print("- "),
+        style::Print(name),
+        style::Print({
+            if arguments.is_some_and(|args| !args.is_empty()) {
+                let name_width = UnicodeWidthStr::width(name);
+                let padding = arg_pos
+                    .saturating_sub(name_width)
+                    .saturating_sub(UnicodeWidthStr::width("- "));
+                " ".repeat(padding.max(1))
+            } else {
+                "\n".to_owned()
+            }
+        })
+    )?;
+    if let Some(args) = arguments {
+        for (i, arg) in args.iter().enumerate() {
+            queue!(
+                output,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print(match arg.required {
+                    Some(true) => format!("{}*", arg.name),
+                    _ => arg.name.clone(),
+                }),
+                style::SetForegroundColor(Color::Reset),
+                style::Print(if i < args.len() - 1 { ", " } else { "\n" }),
+            )?;
+        }
+    }
+    Ok(())
 }
 
 #[deny(missing_docs)]
 #[derive(Debug, PartialEq, Args)]
 #[command(color = clap::ColorChoice::Always,
     before_long_help = color_print::cstr!{"Prompts are reusable templates that help you quickly access common workflows and tasks. 
-These templates are provided by the mcp servers you have installed and configured.
+These templates are provided by the mcp servers you have installed and configured, or stored as
+markdown files in <em>.amazonq/prompts</em> (workspace) and <em>~/.aws/amazonq/prompts</em> (global).
 
 To actually retrieve a prompt, directly start with the following command (without prepending /prompt get):
   <em>@<<prompt name>> [arg]</em>                             <black!>Retrieve prompt specified</black!>
 Or if you prefer the long way:
-  <em>/prompts get <<prompt name>> [arg]</em>                 <black!>Retrieve prompt specified</black!>"
+  <em>/prompts get <<prompt name>> [arg]</em>                 <black!>Retrieve prompt specified</black!>
+
+Local prompts can be managed with:
+  <em>/prompts create <<prompt name>> [--global]</em>         <black!>Create a new prompt file</black!>
+  <em>/prompts edit <<prompt name>></em>                      <black!>Edit an existing prompt file</black!>"
 })]
 pub struct PromptsArgs {
     #[command(subcommand)]
@@ -65,20 +322,29 @@ pub struct PromptsArgs {
 }
 
 impl PromptsArgs {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
         let search_word = match &self.subcommand {
             Some(PromptsSubcommand::List { search_word }) => search_word.clone(),
             _ => None,
         };
 
         if let Some(subcommand) = self.subcommand {
-            if matches!(subcommand, PromptsSubcommand::Get { .. }) {
-                return subcommand.execute(session).await;
+            if !matches!(subcommand, PromptsSubcommand::List { .. }) {
+                return subcommand.execute(os, session).await;
             }
         }
 
         let terminal_width = session.terminal_width();
-        let prompts = session.conversation.tool_manager.list_prompts().await?;
+        let prompts = match session.conversation.tool_manager.list_prompts().await {
+            Ok(prompts) => prompts,
+            Err(GetPromptError::MissingChannel) => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let local_prompts = load_local_prompts(os)
+            .await
+            .into_iter()
+            .filter(|prompt| prompt.name.contains(search_word.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>();
         let mut longest_name = "";
         let arg_pos = {
             let optimal_case = UnicodeWidthStr::width(longest_name) + terminal_width / 4;
@@ -157,44 +423,62 @@ impl PromptsArgs {
                 style::Print("\n"),
             )?;
             for bundle in bundles {
-                queue!(
-                    session.stderr,
-                    style::Print("- "),
-                    style::Print(&bundle.prompt_get.name),
-                    style::Print({
-                        if bundle
-                            .prompt_get
-                            .arguments
-                            .as_ref()
-                            .is_some_and(|args| !args.is_empty())
-                        {
-                            let name_width = UnicodeWidthStr::width(bundle.prompt_get.name.as_str());
-                            let padding = arg_pos
-                                .saturating_sub(name_width)
-                                .saturating_sub(UnicodeWidthStr::width("- "));
-                            " ".repeat(padding.max(1))
-                        } else {
-                            "\n".to_owned()
-                        }
-                    })
+                queue_prompt_entry(
+                    &mut session.stderr,
+                    &bundle.prompt_get.name,
+                    bundle.prompt_get.arguments.as_deref(),
+                    arg_pos,
                 )?;
-                if let Some(args) = bundle.prompt_get.arguments.as_ref() {
-                    for (i, arg) in args.iter().enumerate() {
-                        queue!(
-                            session.stderr,
-                            style::SetForegroundColor(Color::DarkGrey),
-                            style::Print(match arg.required {
-                                Some(true) => format!("{}*", arg.name),
-                                _ => arg.name.clone(),
-                            }),
-                            style::SetForegroundColor(Color::Reset),
-                            style::Print(if i < args.len() - 1 { ", " } else { "\n" }),
-                        )?;
-                    }
+            }
+        }
+
+        let mut has_groups = !prompts_by_server.is_empty();
+        for scope in [LocalPromptScope::Workspace, LocalPromptScope::Global] {
+            let mut in_scope = local_prompts.iter().filter(|prompt| prompt.scope == scope).peekable();
+            if in_scope.peek().is_none() {
+                continue;
+            }
+            if has_groups {
+                queue!(session.stderr, style::Print("\n"))?;
+            }
+            has_groups = true;
+            queue!(
+                session.stderr,
+                style::SetAttribute(Attribute::Bold),
+                style::Print(scope.label()),
+                style::Print(" (Local):"),
+                style::SetAttribute(Attribute::Reset),
+                style::Print("\n"),
+            )?;
+            for prompt in in_scope {
+                queue_prompt_entry(&mut session.stderr, &prompt.name, Some(&prompt.arguments), arg_pos)?;
+                if let Some(description) = &prompt.description {
+                    queue!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print(format!("  {description}\n")),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
                 }
             }
         }
 
+        let shadowed = local_prompts
+            .iter()
+            .flat_map(|prompt| shadowed_mcp_prompts(&prompts, &prompt.name))
+            .collect::<Vec<_>>();
+        if !shadowed.is_empty() {
+            queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(format!(
+                    "\nLocal prompts take precedence over MCP prompts of the same name. Use {} to run the MCP prompts instead.\n",
+                    shadowed.join(", ")
+                )),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+
         Ok(ChatState::PromptUser {
             skip_printing_tools: true,
         })
@@ -216,19 +500,87 @@ pub enum PromptsSubcommand {
         name: String,
         arguments: Option<Vec<String>>,
     },
+    /// Create a local prompt file and open it in $EDITOR
+    Create {
+        /// Name of the prompt, used as the file name and invoked as @name
+        name: String,
+        /// Save the prompt in ~/.aws/amazonq/prompts instead of the workspace
+        #[arg(long)]
+        global: bool,
+    },
+    /// Open an existing local prompt file in $EDITOR
+    Edit {
+        /// Name of the prompt to edit
+        name: String,
+    },
 }
 
 impl PromptsSubcommand {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
-        let PromptsSubcommand::Get {
-            orig_input,
-            name,
-            arguments,
-        } = self
-        else {
-            unreachable!("List has already been parsed out at this point");
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        let (orig_input, name, arguments) = match self {
+            PromptsSubcommand::Get {
+                orig_input,
+                name,
+                arguments,
+            } => (orig_input, name, arguments),
+            PromptsSubcommand::Create { name, global } => return Self::create(os, session, name, global).await,
+            PromptsSubcommand::Edit { name } => return Self::edit(os, session, name).await,
+            PromptsSubcommand::List { .. } => unreachable!("List has already been parsed out at this point"),
         };
 
+        // Local prompts take precedence over MCP prompts unless a server is named explicitly
+        if !name.contains('/') {
+            let local_prompt = load_local_prompts(os)
+                .await
+                .into_iter()
+                .find(|prompt| prompt.name == name);
+            if let Some(local_prompt) = local_prompt {
+                match local_prompt.render(&arguments.unwrap_or_default()) {
+                    Ok(text) => {
+                        let mcp_prompts = session
+                            .conversation
+                            .tool_manager
+                            .list_prompts()
+                            .await
+                            .unwrap_or_default();
+                        let shadowed = shadowed_mcp_prompts(&mcp_prompts, &name);
+                        if !shadowed.is_empty() {
+                            execute!(
+                                session.stderr,
+                                style::SetForegroundColor(Color::Yellow),
+                                style::Print(format!(
+                                    "\nUsing the local prompt {name}, which shadows an MCP prompt of the same name. Use {} to run the MCP prompt instead.\n",
+                                    shadowed.join(" or ")
+                                )),
+                                style::SetForegroundColor(Color::Reset),
+                            )?;
+                        }
+                        session.pending_prompts.clear();
+                        session.pending_prompts.push_back(Prompt {
+                            role: Role::User,
+                            content: MessageContent::Text { text },
+                        });
+                        return Ok(ChatState::HandleInput {
+                            input: orig_input.unwrap_or_default(),
+                        });
+                    },
+                    Err(e) => {
+                        execute!(
+                            session.stderr,
+                            style::Print("\n"),
+                            style::SetForegroundColor(Color::Yellow),
+                            style::Print(e.to_string()),
+                            style::SetForegroundColor(Color::Reset),
+                            style::Print("\n\n"),
+                        )?;
+                        return Ok(ChatState::PromptUser {
+                            skip_printing_tools: true,
+                        });
+                    },
+                }
+            }
+        }
+
         let prompts = match session.conversation.tool_manager.get_prompt(name, arguments).await {
             Ok(resp) => resp,
             Err(e) => {
@@ -309,10 +661,257 @@ impl PromptsSubcommand {
         })
     }
 
+    async fn create(os: &Os, session: &mut ChatSession, name: String, global: bool) -> Result<ChatState, ChatError> {
+        if !is_valid_prompt_name(&name) {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!(
+                    "\nInvalid prompt name '{name}'. Use only letters, numbers, '-' and '_'.\n\n"
+                )),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        }
+
+        let dir = if global {
+            chat_global_prompts_dir(os)
+        } else {
+            chat_local_prompts_dir(os)
+        }
+        .map_err(|e| ChatError::Custom(e.to_string().into()))?;
+        let path = dir.join(format!("{name}.md"));
+        if os.fs.exists(&path) {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(format!("\nPrompt '{name}' already exists. Use ")),
+                style::SetForegroundColor(Color::Cyan),
+                style::Print(format!("/prompts edit {name}")),
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(" to change it.\n\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        }
+
+        let content = open_editor_with_content(NEW_PROMPT_TEMPLATE, "md")?;
+        Self::save(os, session, &name, &path, &content).await
+    }
+
+    async fn edit(os: &Os, session: &mut ChatSession, name: String) -> Result<ChatState, ChatError> {
+        let Some(prompt) = load_local_prompts(os)
+            .await
+            .into_iter()
+            .find(|prompt| prompt.name == name)
+        else {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(format!("\nNo local prompt named '{name}' was found. Use ")),
+                style::SetForegroundColor(Color::Cyan),
+                style::Print(format!("/prompts create {name}")),
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(" to create it.\n\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        };
+
+        let current = os
+            .fs
+            .read_to_string(&prompt.path)
+            .await
+            .map_err(|e| ChatError::Custom(format!("Failed to read {}: {}", prompt.path.display(), e).into()))?;
+        let content = open_editor_with_content(&current, "md")?;
+        Self::save(os, session, &name, &prompt.path, &content).await
+    }
+
+    async fn save(
+        os: &Os,
+        session: &mut ChatSession,
+        name: &str,
+        path: &std::path::Path,
+        content: &str,
+    ) -> Result<ChatState, ChatError> {
+        if content.trim().is_empty() {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Yellow),
+                style::Print("\nEmpty prompt, nothing was saved.\n\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        }
+
+        if let Some(parent) = path.parent() {
+            os.fs.create_dir_all(parent).await?;
+        }
+        os.fs.write(path, content).await?;
+
+        execute!(
+            session.stderr,
+            style::SetForegroundColor(Color::Green),
+            style::Print(format!("\nSaved prompt @{name} to {}\n\n", path.display())),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             PromptsSubcommand::List { .. } => "list",
             PromptsSubcommand::Get { .. } => "get",
+            PromptsSubcommand::Create { .. } => "create",
+            PromptsSubcommand::Edit { .. } => "edit",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REVIEW_PROMPT: &str = "---
+description: Review a pull request
+arguments: pr*, focus
+---
+
+Review pull request {{pr}}, focusing on {{focus}}.
+";
+
+    fn review_prompt() -> LocalPrompt {
+        LocalPrompt::parse(
+            "review".to_string(),
+            LocalPromptScope::Workspace,
+            PathBuf::from("review.md"),
+            REVIEW_PROMPT,
+        )
+    }
+
+    #[test]
+    fn test_parse_local_prompt() {
+        let prompt = review_prompt();
+        assert_eq!(prompt.description.as_deref(), Some("Review a pull request"));
+        assert_eq!(
+            prompt
+                .arguments
+                .iter()
+                .map(|arg| (arg.name.as_str(), arg.required))
+                .collect::<Vec<_>>(),
+            vec![("pr", Some(true)), ("focus", None)]
+        );
+        assert_eq!(prompt.body, "Review pull request {{pr}}, focusing on {{focus}}.\n");
+
+        let plain = LocalPrompt::parse(
+            "plain".to_string(),
+            LocalPromptScope::Global,
+            PathBuf::from("plain.md"),
+            "Just a prompt\n",
+        );
+        assert!(plain.description.is_none());
+        assert!(plain.arguments.is_empty());
+        assert_eq!(plain.body, "Just a prompt\n");
+    }
+
+    #[test]
+    fn test_render_local_prompt() {
+        let prompt = review_prompt();
+        assert_eq!(
+            prompt
+                .render(&["42".to_string(), "error handling".to_string()])
+                .unwrap(),
+            "Review pull request 42, focusing on error handling.\n"
+        );
+        assert_eq!(
+            prompt.render(&["42".to_string()]).unwrap(),
+            "Review pull request 42, focusing on .\n"
+        );
+        assert_eq!(
+            prompt
+                .render(&["{{focus}}".to_string(), "{{pr}} and {{other}}".to_string()])
+                .unwrap(),
+            "Review pull request {{focus}}, focusing on {{pr}} and {{other}}.\n"
+        );
+        assert!(matches!(
+            prompt.render(&[]),
+            Err(GetPromptError::MissingArguments(name, missing)) if name == "review" && missing == "pr"
+        ));
+    }
+
+    #[test]
+    fn test_shadowed_mcp_prompts() {
+        let bundle = |server_name: &str| PromptBundle {
+            server_name: server_name.to_string(),
+            prompt_get: crate::mcp_client::PromptGet {
+                name: "review".to_string(),
+                description: None,
+                arguments: None,
+            },
+        };
+        let mcp_prompts = HashMap::from([("review".to_string(), vec![bundle("github"), bundle("gitlab")])]);
+
+        assert_eq!(shadowed_mcp_prompts(&mcp_prompts, "review"), vec![
+            "@github/review".to_string(),
+            "@gitlab/review".to_string()
+        ]);
+        assert!(shadowed_mcp_prompts(&mcp_prompts, "explain").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_local_prompts() {
+        let os = Os::new().await.unwrap();
+        let workspace_dir = chat_local_prompts_dir(&os).unwrap();
+        let global_dir = chat_global_prompts_dir(&os).unwrap();
+        os.fs.create_dir_all(&workspace_dir).await.unwrap();
+        os.fs.create_dir_all(&global_dir).await.unwrap();
+        os.fs
+            .write(workspace_dir.join("review.md"), REVIEW_PROMPT)
+            .await
+            .unwrap();
+        os.fs
+            .write(workspace_dir.join("notes.txt"), "not a prompt")
+            .await
+            .unwrap();
+        os.fs
+            .write(global_dir.join("review.md"), "global review")
+            .await
+            .unwrap();
+        os.fs
+            .write(global_dir.join("explain.md"), "Explain this")
+            .await
+            .unwrap();
+
+        let prompts = load_local_prompts(&os).await;
+        assert_eq!(
+            prompts
+                .iter()
+                .map(|prompt| (prompt.name.as_str(), prompt.scope))
+                .collect::<Vec<_>>(),
+            vec![
+                ("explain", LocalPromptScope::Global),
+                ("review", LocalPromptScope::Workspace)
+            ]
+        );
+        assert_eq!(prompts[1].path, workspace_dir.join("review.md"));
+    }
+
+    #[test]
+    fn test_is_valid_prompt_name() {
+        assert!(is_valid_prompt_name("code-review_2"));
+        assert!(!is_valid_prompt_name(""));
+        assert!(!is_valid_prompt_name("../escape"));
+        assert!(!is_valid_prompt_name("server/name"));
+    }
+}
@@ -21,6 +21,7 @@ use crate::api_client::model::Tool as FigTool;
 use crate::cli::agent::{
     Agent,
     DEFAULT_AGENT_NAME,
+    TrustRule,
 };
 use crate::cli::chat::consts::{
     AGENT_FORMAT_TOOLS_DOC_URL,
@@ -33,6 +34,7 @@ use crate::cli::chat::{
     ChatState,
     TRUST_ALL_TEXT,
 };
+use crate::os::Os;
 use crate::util::consts::MCP_SERVER_TOOL_DELIMITER;
 
 #[deny(missing_docs)]
@@ -43,9 +45,9 @@ pub struct ToolsArgs {
 }
 
 impl ToolsArgs {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
         if let Some(subcommand) = self.subcommand {
-            return subcommand.execute(session).await;
+            return subcommand.execute(os, session).await;
         }
 
         // No subcommand - print the current tools and their permissions.
@@ -204,6 +206,9 @@ pub enum ToolsSubcommand {
     Trust {
         #[arg(required = true)]
         tool_names: Vec<String>,
+        /// Also add the tools to allowedTools in the active agent's config file
+        #[arg(long)]
+        save: bool,
     },
     /// Revert a tool or tools to per-request confirmation
     Untrust {
@@ -217,7 +222,7 @@ pub enum ToolsSubcommand {
 }
 
 impl ToolsSubcommand {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
         // Here we need to obtain the list of host tool names
         let existing_custom_tools = session
             .conversation
@@ -250,7 +255,7 @@ impl ToolsSubcommand {
                     .map_err(|e| ChatError::Custom(format!("Error converting tool schema to string: {e}").into()))?;
                 queue!(session.stderr, style::Print(schema_json), style::Print("\n"))?;
             },
-            Self::Trust { tool_names } => {
+            Self::Trust { tool_names, save } => {
                 let (valid_tools, invalid_tools): (Vec<String>, Vec<String>) =
                     tool_names.into_iter().partition(|tool_name| {
                         existing_custom_tools.contains(tool_name) || native_tool_names.contains(tool_name)
@@ -308,6 +313,16 @@ impl ToolsSubcommand {
                         style::SetForegroundColor(Color::Reset),
                     )?;
 
+                    if save {
+                        for tool_name in &tools_to_trust {
+                            let rule = TrustRule {
+                                tool_name: tool_name.clone(),
+                                settings: None,
+                            };
+                            save_trust_rule(os, session, &rule).await?;
+                        }
+                    }
+
                     session.conversation.agents.trust_tools(tools_to_trust);
                 }
             },
@@ -419,3 +434,33 @@ impl ToolsSubcommand {
         }
     }
 }
+
+/// Writes `rule` to the active agent's config file so that it applies to future sessions,
+/// reporting the outcome to the user.
+pub async fn save_trust_rule(os: &Os, session: &mut ChatSession, rule: &TrustRule) -> Result<(), ChatError> {
+    let Some(agent) = session.conversation.agents.get_active() else {
+        return Ok(());
+    };
+
+    match agent.save_trust_rule(os, rule).await {
+        Ok(path) => queue!(
+            session.stderr,
+            style::SetForegroundColor(Color::Green),
+            style::Print(format!("\nSaved '{}' to {}", rule.tool_name, path.display())),
+            style::Print(match &rule.settings {
+                Some((key, values)) if !values.is_empty() => format!(" ({key}: {})", values.join(", ")),
+                _ => String::new(),
+            }),
+            style::Print("\n"),
+            style::SetForegroundColor(Color::Reset),
+        )?,
+        Err(err) => queue!(
+            session.stderr,
+            style::SetForegroundColor(Color::Yellow),
+            style::Print(format!("\nCould not save '{}': {err}\n", rule.tool_name)),
+            style::SetForegroundColor(Color::Reset),
+        )?,
+    }
+
+    Ok(())
+}
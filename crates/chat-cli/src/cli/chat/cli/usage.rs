@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use clap::Args;
 use crossterm::style::{
     Attribute,
@@ -11,21 +13,42 @@ use crossterm::{
 
 use super::model::context_window_tokens;
 use crate::cli::chat::token_counter::{
-    CharCount,
     TokenCount,
+    Tokenizer,
 };
 use crate::cli::chat::{
     ChatError,
     ChatSession,
     ChatState,
 };
+use crate::cli::usage::{
+    format_usage_table,
+    unix_ms_before,
+};
+use crate::database::{
+    DatabaseError,
+    UsageGrouping,
+};
 use crate::os::Os;
+use crate::util::CLI_BINARY_NAME;
+
+/// How far back /usage --history reports
+const HISTORY_DAYS: u64 = 7;
+
 #[deny(missing_docs)]
 #[derive(Debug, PartialEq, Args)]
-pub struct UsageArgs;
+pub struct UsageArgs {
+    /// Show the requests, tokens and tool calls of this session and of the past week
+    #[arg(long)]
+    history: bool,
+}
 
 impl UsageArgs {
     pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if self.history {
+            return Self::execute_history(os, session);
+        }
+
         let state = session
             .conversation
             .backend_conversation_state(os, true, &mut session.stderr)
@@ -51,13 +74,26 @@ impl UsageArgs {
             .filter_map(|s| serde_json::to_string(s).ok())
             .collect::<Vec<String>>()
             .join("");
-        let context_token_count: TokenCount = data.context_messages.into();
-        let assistant_token_count: TokenCount = data.assistant_messages.into();
-        let user_token_count: TokenCount = data.user_messages.into();
-        let tools_char_count: CharCount = tool_specs_json.len().into(); // usize → CharCount
-        let tools_token_count: TokenCount = tools_char_count.into(); // CharCount → TokenCount
-        let total_token_used: TokenCount =
-            (data.context_messages + data.user_messages + data.assistant_messages + tools_char_count).into();
+        let tokenizer = Tokenizer::for_model(session.conversation.model_info.as_ref());
+        let tools_token_count = tokenizer.count_tokens(&tool_specs_json);
+        let estimated_token_used = data.total() + tools_token_count;
+
+        // The backend reports the total for the latest response, but not how it breaks down, so
+        // the estimated breakdown is scaled to add up to the reported total.
+        let reported_token_used = session.conversation.reported_token_count();
+        let total_token_used = reported_token_used.unwrap_or(estimated_token_used);
+        let breakdown = |estimate: TokenCount| -> TokenCount {
+            match reported_token_used {
+                Some(reported) if estimated_token_used.value() > 0 => {
+                    (estimate.value() * reported.value() / estimated_token_used.value()).into()
+                },
+                _ => estimate,
+            }
+        };
+        let context_token_count = breakdown(data.context_messages);
+        let assistant_token_count = breakdown(data.assistant_messages);
+        let user_token_count = breakdown(data.user_messages);
+        let tools_token_count = breakdown(tools_token_count);
         let window_width = session.terminal_width();
         // set a max width for the progress bar for better aesthetic
         let progress_bar_width = std::cmp::min(window_width, 80);
@@ -204,7 +240,84 @@ impl UsageArgs {
             style::SetForegroundColor(Color::DarkGreen),
             style::Print("/context show"),
             style::SetForegroundColor(Color::DarkGrey),
-            style::Print(" to see tokens per context file\n\n"),
+            style::Print(" to see tokens per context file\n"),
+            style::Print("Run "),
+            style::SetForegroundColor(Color::DarkGreen),
+            style::Print("/usage --history"),
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print(" to see the requests and tokens used over time\n\n"),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+
+    fn execute_history(os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        let since_ms = unix_ms_before(Duration::from_secs(HISTORY_DAYS * 24 * 60 * 60));
+        let usage_error =
+            |err: DatabaseError| ChatError::Custom(format!("Failed to load the usage history: {err}").into());
+        let conversation_id = session.conversation.conversation_id();
+        let this_session = os
+            .database
+            .get_usage(0, Some(conversation_id), UsageGrouping::Session)
+            .map_err(usage_error)?
+            .pop();
+        let days = os
+            .database
+            .get_usage(since_ms, None, UsageGrouping::Day)
+            .map_err(usage_error)?;
+        let total = os
+            .database
+            .get_usage(since_ms, None, UsageGrouping::Total)
+            .map_err(usage_error)?
+            .pop();
+
+        match this_session {
+            Some(mut summary) => {
+                summary.key = "This session".to_string();
+                queue!(
+                    session.stderr,
+                    style::Print("\n"),
+                    style::Print(format_usage_table("", &[summary], None)),
+                    style::Print("\n"),
+                )?;
+            },
+            None => queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print("\nNo requests in this session yet\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?,
+        }
+
+        queue!(
+            session.stderr,
+            style::SetAttribute(Attribute::Bold),
+            style::Print(format!("\nThe past {HISTORY_DAYS} days\n")),
+            style::SetAttribute(Attribute::Reset),
+        )?;
+        match days.is_empty() {
+            true => queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print("No requests in this period\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?,
+            false => queue!(
+                session.stderr,
+                style::Print(format_usage_table("Day", &days, total.as_ref())),
+                style::Print("\n"),
+            )?,
+        }
+
+        execute!(
+            session.stderr,
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print(format!(
+                "\nToken counts are estimated where the backend did not report them. Run {CLI_BINARY_NAME} usage report for more.\n\n"
+            )),
             style::SetForegroundColor(Color::Reset),
         )?;
 
@@ -13,6 +13,15 @@ pub const MAX_USER_MESSAGE_SIZE: usize = 400_000;
 
 pub const DUMMY_TOOL_NAME: &str = "dummy";
 
+/// Tool results in this many of the most recent history entries are never elided.
+pub const TOOL_RESULTS_TO_KEEP: usize = 4;
+
+/// Tool results of at least this many bytes are elided when the context window overflows.
+pub const MIN_ELIDED_TOOL_RESULT_SIZE: usize = 4_000;
+
+/// Tool use arguments are truncated to this many bytes in the stub of an elided tool result.
+pub const MAX_ELIDED_TOOL_ARGS_LEN: usize = 200;
+
 pub const MAX_NUMBER_OF_IMAGES_PER_REQUEST: usize = 10;
 
 /// In bytes - 10 MB
@@ -15,6 +15,7 @@ use serde::{
 };
 
 use super::cli::model::context_window_tokens;
+use super::token_counter::Tokenizer;
 use super::util::drop_matched_context_files;
 use crate::cli::agent::Agent;
 use crate::cli::agent::hook::{
@@ -93,6 +94,9 @@ impl PartialEq<ContextFilePath> for String {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContextManager {
     max_context_files_size: usize,
+    /// Tokenizer of the model, for counting the size of the context files
+    #[serde(default)]
+    tokenizer: Tokenizer,
     /// Name of the current active profile.
     pub current_profile: String,
     /// List of file paths or glob patterns to include in the context.
@@ -104,7 +108,7 @@ pub struct ContextManager {
 }
 
 impl ContextManager {
-    pub fn from_agent(agent: &Agent, max_context_files_size: usize) -> Result<Self> {
+    pub fn from_agent(agent: &Agent, max_context_files_size: usize, tokenizer: Tokenizer) -> Result<Self> {
         let paths = agent
             .resources
             .iter()
@@ -114,6 +118,7 @@ impl ContextManager {
 
         Ok(Self {
             max_context_files_size,
+            tokenizer,
             current_profile: agent.name.clone(),
             paths,
             hooks: agent.hooks.clone(),
@@ -219,7 +224,8 @@ impl ContextManager {
     ) -> Result<(Vec<(String, String)>, Vec<(String, String)>)> {
         let mut files = self.get_context_files(os).await?;
 
-        let dropped_files = drop_matched_context_files(&mut files, self.max_context_files_size).unwrap_or_default();
+        let dropped_files =
+            drop_matched_context_files(&mut files, self.max_context_files_size, self.tokenizer).unwrap_or_default();
 
         // remove dropped files from files
         files.retain(|file| !dropped_files.iter().any(|dropped| dropped.0 == file.0));
@@ -4,6 +4,7 @@ use std::collections::{
     VecDeque,
 };
 use std::io::Write;
+use std::ops::Range;
 use std::sync::atomic::Ordering;
 
 use chrono::Utc;
@@ -26,6 +27,9 @@ use super::cli::model::context_window_tokens;
 use super::consts::{
     DUMMY_TOOL_NAME,
     MAX_CONVERSATION_STATE_HISTORY_LEN,
+    MAX_ELIDED_TOOL_ARGS_LEN,
+    MIN_ELIDED_TOOL_RESULT_SIZE,
+    TOOL_RESULTS_TO_KEEP,
 };
 use super::context::{
     ContextManager,
@@ -34,23 +38,33 @@ use super::context::{
 use super::line_tracker::FileLineTracker;
 use super::message::{
     AssistantMessage,
+    AssistantToolUse,
     ToolUseResult,
+    ToolUseResultBlock,
     UserMessage,
 };
 use super::parser::RequestMetadata;
 use super::token_counter::{
-    CharCount,
-    CharCounter,
-    TokenCounter,
+    TokenCount,
+    TokenCountable,
+    Tokenizer,
 };
 use super::tool_manager::ToolManager;
+use super::tools::propose_plan::{
+    PLAN_MODE_INSTRUCTIONS,
+    PLAN_MODE_TOOLS,
+    ProposePlan,
+};
 use super::tools::{
     InputSchema,
     QueuedTool,
     ToolOrigin,
     ToolSpec,
 };
-use super::util::serde_value_to_document;
+use super::util::{
+    serde_value_to_document,
+    truncate_safe,
+};
 use crate::api_client::model::{
     ChatMessage,
     ConversationState as FigConversationState,
@@ -82,6 +96,19 @@ pub struct HistoryEntry {
     assistant: AssistantMessage,
     #[serde(default)]
     request_metadata: Option<RequestMetadata>,
+    /// Pinned entries are kept in the history rather than summarized by /compact.
+    #[serde(default)]
+    pinned: bool,
+}
+
+impl HistoryEntry {
+    pub fn user(&self) -> &UserMessage {
+        &self.user
+    }
+
+    pub fn assistant(&self) -> &AssistantMessage {
+        &self.assistant
+    }
 }
 
 /// Tracks state related to an ongoing conversation.
@@ -101,6 +128,10 @@ pub struct ConversationState {
     /// chat.
     pub transcript: VecDeque<String>,
     pub tools: HashMap<ToolOrigin, Vec<Tool>>,
+    /// The subset of [Self::tools] sent to the model while in plan mode, see
+    /// [Self::set_plan_mode].
+    #[serde(skip)]
+    plan_mode_tools: Option<HashMap<ToolOrigin, Vec<Tool>>>,
     /// Context manager for handling sticky context files
     pub context_manager: Option<ContextManager>,
     /// Tool manager for handling tool and mcp related activities
@@ -151,7 +182,12 @@ impl ConversationState {
         };
 
         let context_manager = if let Some(agent) = agents.get_active() {
-            ContextManager::from_agent(agent, calc_max_context_files_size(model.as_ref())).ok()
+            ContextManager::from_agent(
+                agent,
+                calc_max_context_files_size(model.as_ref()),
+                Tokenizer::for_model(model.as_ref()),
+            )
+            .ok()
         } else {
             None
         };
@@ -175,6 +211,7 @@ impl ConversationState {
                         .or_insert(vec![tool]);
                     acc
                 }),
+            plan_mode_tools: None,
             context_manager,
             tool_manager,
             context_message_length: None,
@@ -204,6 +241,94 @@ impl ConversationState {
         }
     }
 
+    /// Pins the latest turn, i.e. the last prompt and every message after it, so that it is kept in
+    /// the history rather than summarized when compacting. Returns the pinned prompt, or [None] if
+    /// the history is empty.
+    pub fn pin_latest_turn(&mut self) -> Option<&str> {
+        let turn = self.turns().pop()?;
+        for entry in self.history.range_mut(turn.clone()) {
+            entry.pinned = true;
+        }
+        Some(self.history[turn.start].user.prompt().unwrap_or_default())
+    }
+
+    /// Returns the prompts of the pinned turns, oldest first.
+    pub fn pinned_prompts(&self) -> Vec<&str> {
+        self.pinned_turns()
+            .into_iter()
+            .map(|turn| self.history[turn.start].user.prompt().unwrap_or_default())
+            .collect()
+    }
+
+    /// Unpins the pinned turn at `index` in [Self::pinned_prompts]. Returns false if there is no
+    /// such turn.
+    pub fn unpin_turn(&mut self, index: usize) -> bool {
+        let Some(turn) = self.pinned_turns().into_iter().nth(index) else {
+            return false;
+        };
+        for entry in self.history.range_mut(turn) {
+            entry.pinned = false;
+        }
+        true
+    }
+
+    /// Splits the history into turns, each starting with a message from the user that does not
+    /// contain tool results.
+    fn turns(&self) -> Vec<Range<usize>> {
+        let starts = self
+            .history
+            .iter()
+            .enumerate()
+            .filter(|(i, entry)| *i == 0 || !entry.user.has_tool_use_results())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        starts
+            .iter()
+            .zip(starts.iter().skip(1).chain([&self.history.len()]))
+            .map(|(start, end)| *start..*end)
+            .collect()
+    }
+
+    fn pinned_turns(&self) -> Vec<Range<usize>> {
+        let mut turns = self.turns();
+        turns.retain(|turn| self.history[turn.start].pinned);
+        turns
+    }
+
+    /// Replaces tool results of at least [MIN_ELIDED_TOOL_RESULT_SIZE] bytes with a stub naming
+    /// the tool use they belong to, leaving pinned entries and the last [TOOL_RESULTS_TO_KEEP]
+    /// entries untouched. Returns the number of elided tool results.
+    pub fn elide_tool_results(&mut self) -> usize {
+        let end = self.history.len().saturating_sub(TOOL_RESULTS_TO_KEEP);
+        let mut elided = 0;
+        let mut tool_uses = Vec::new();
+        for entry in self.history.range_mut(..end) {
+            if let (false, Some(tool_use_results)) = (entry.pinned, entry.user.tool_use_results_mut()) {
+                for result in tool_use_results {
+                    let size = result.content_len();
+                    if size < MIN_ELIDED_TOOL_RESULT_SIZE {
+                        continue;
+                    }
+                    let tool_use = tool_uses
+                        .iter()
+                        .find(|tool_use: &&AssistantToolUse| tool_use.id == result.tool_use_id);
+                    let stub = match tool_use {
+                        Some(tool_use) => format!(
+                            "[Elided the {size} byte result of {} with arguments {} to free up context space]",
+                            tool_use.name,
+                            truncate_safe(&tool_use.args.to_string(), MAX_ELIDED_TOOL_ARGS_LEN)
+                        ),
+                        None => format!("[Elided a {size} byte tool result to free up context space]"),
+                    };
+                    result.content = vec![ToolUseResultBlock::Text(stub)];
+                    elided += 1;
+                }
+            }
+            tool_uses = entry.assistant.tool_uses().map(<[_]>::to_vec).unwrap_or_default();
+        }
+        elided
+    }
+
     /// Appends a collection prompts into history and returns the last message in the collection.
     /// It asserts that the collection ends with a prompt that assumes the role of user.
     pub fn append_prompts(&mut self, mut prompts: VecDeque<Prompt>) -> Option<String> {
@@ -231,6 +356,7 @@ impl ConversationState {
                     user,
                     assistant,
                     request_metadata: None,
+                    pinned: false,
                 });
             }
         }
@@ -262,6 +388,31 @@ impl ConversationState {
         self.next_message = Some(msg);
     }
 
+    /// Adds images to the message that is sent next, e.g. those pasted from the clipboard.
+    pub fn add_images_to_next_message(&mut self, images: Vec<ImageBlock>) {
+        if let Some(next_message) = self.next_message.as_mut() {
+            next_message.images.get_or_insert_default().extend(images);
+        }
+    }
+
+    /// Adds messages the user sent while tools were running to the tool results that are sent
+    /// next, so that the model can change course before continuing.
+    pub fn add_steering_messages(&mut self, messages: &[String]) {
+        let Some(tool_use_result) = self
+            .next_message
+            .as_mut()
+            .and_then(|message| message.tool_use_results_mut())
+            .and_then(|results| results.last_mut())
+        else {
+            return;
+        };
+        tool_use_result.content.push(ToolUseResultBlock::Text(format!(
+            "The user sent the following while the tools were running. Take it into account before \
+             continuing:\n{}",
+            messages.join("\n")
+        )));
+    }
+
     /// Sets the response message according to the currently set [Self::next_message].
     pub fn push_assistant_message(
         &mut self,
@@ -269,6 +420,17 @@ impl ConversationState {
         message: AssistantMessage,
         request_metadata: Option<RequestMetadata>,
     ) {
+        self.record_assistant_message(message, request_metadata);
+
+        if let Ok(cwd) = std::env::current_dir() {
+            os.database.set_conversation_by_path(cwd, self).ok();
+        }
+    }
+
+    /// Like [Self::push_assistant_message], but without saving the conversation as the one to
+    /// resume in the current directory. Used by conversations that are not the user's own, e.g.
+    /// those of sub-agents.
+    pub fn record_assistant_message(&mut self, message: AssistantMessage, request_metadata: Option<RequestMetadata>) {
         debug_assert!(self.next_message.is_some(), "next_message should exist");
         let next_user_message = self.next_message.take().expect("next user message should exist");
 
@@ -277,11 +439,8 @@ impl ConversationState {
             user: next_user_message,
             assistant: message,
             request_metadata,
+            pinned: false,
         });
-
-        if let Ok(cwd) = std::env::current_dir() {
-            os.database.set_conversation_by_path(cwd, self).ok();
-        }
     }
 
     /// Returns the conversation id.
@@ -320,8 +479,8 @@ impl ConversationState {
     /// 3. If the last message from the assistant contains tool results, and a next user message is
     ///    set without tool results, then the user message will have "cancelled" tool results.
     pub fn enforce_conversation_invariants(&mut self) {
-        self.valid_history_range =
-            enforce_conversation_invariants(&mut self.history, &mut self.next_message, &self.tools);
+        let tools = self.plan_mode_tools.as_ref().unwrap_or(&self.tools);
+        self.valid_history_range = enforce_conversation_invariants(&mut self.history, &mut self.next_message, tools);
     }
 
     /// Here we also need to make sure that the tool result corresponds to one of the tools
@@ -337,7 +496,18 @@ impl ConversationState {
     /// 3. The model had decided to call a tool that does not exist. The intervention here is to
     ///    substitute the non-existent tool name with a dummy.
     pub fn enforce_tool_use_history_invariants(&mut self) {
-        enforce_tool_use_history_invariants(&mut self.history, &self.tools);
+        let tools = self.plan_mode_tools.as_ref().unwrap_or(&self.tools);
+        enforce_tool_use_history_invariants(&mut self.history, tools);
+    }
+
+    /// Whether plan mode is on, in which the model can only use tools that read state until the
+    /// user approves the plan it proposes.
+    pub fn plan_mode(&self) -> bool {
+        self.plan_mode_tools.is_some()
+    }
+
+    pub fn set_plan_mode(&mut self, enabled: bool) {
+        self.plan_mode_tools = enabled.then(|| plan_mode_tools(&self.tools));
     }
 
     pub fn add_tool_results(&mut self, tool_results: Vec<ToolUseResult>) {
@@ -421,6 +591,9 @@ impl ConversationState {
                 acc
             });
         self.tool_manager.has_new_stuff.store(false, Ordering::Release);
+        if self.plan_mode() {
+            self.set_plan_mode(true);
+        }
         // We call this in [Self::enforce_conversation_invariants] as well. But we need to call it
         // here as well because when it's being called in [Self::enforce_conversation_invariants]
         // it is only checking the last entry.
@@ -465,7 +638,7 @@ impl ConversationState {
                 .range(self.valid_history_range.0..self.valid_history_range.1),
             context_messages,
             dropped_context_files,
-            tools: &self.tools,
+            tools: self.plan_mode_tools.as_ref().unwrap_or(&self.tools),
             model_id: self.model_info.as_ref().map(|m| m.model_id.as_str()),
         })
     }
@@ -524,9 +697,11 @@ impl ConversationState {
             },
         };
         if let Some((summary, _)) = &self.latest_summary {
+            // Only the messages since the last compaction are sent, so the previous summary is
+            // updated with them rather than the conversation summarized from scratch.
             summary_content.push_str("\n\n");
             summary_content.push_str(CONTEXT_ENTRY_START_HEADER);
-            summary_content.push_str("This summary contains ALL relevant information from our previous conversation including tool uses, results, code analysis, and file operations. YOU MUST be sure to include this information when creating your summarization document.\n\n");
+            summary_content.push_str("This summary contains ALL relevant information from our conversation before the messages above, including tool uses, results, code analysis, and file operations. DO NOT summarize from scratch: update this summary with the messages above, keeping everything that is still relevant, revising anything the messages above have changed, and merging in what is new.\n\n");
             summary_content.push_str("SUMMARY CONTENT:\n");
             summary_content.push_str(summary);
             summary_content.push('\n');
@@ -539,6 +714,8 @@ impl ConversationState {
         // Create the history according to the passed compact strategy.
         let mut history = conv_state.history.cloned().collect::<VecDeque<_>>();
         history.drain((history.len().saturating_sub(strategy.messages_to_exclude))..);
+        // Pinned turns stay in the history as they are.
+        history.retain(|entry| !entry.pinned);
         if strategy.truncate_large_messages {
             for HistoryEntry { user, .. } in &mut history {
                 user.truncate_safe(strategy.max_message_length);
@@ -569,6 +746,9 @@ impl ConversationState {
         })
     }
 
+    /// Replaces the history with `summary`, keeping pinned turns and the messages excluded from
+    /// the summary.
+    ///
     /// `strategy` - The [CompactStrategy] used for the corresponding
     /// [ConversationState::create_summary_request].
     pub fn replace_history_with_summary(
@@ -577,8 +757,21 @@ impl ConversationState {
         strategy: CompactStrategy,
         request_metadata: RequestMetadata,
     ) {
-        self.history
-            .drain(..(self.history.len().saturating_sub(strategy.messages_to_exclude)));
+        let end = self.history.len().saturating_sub(strategy.messages_to_exclude);
+        // The first excluded message may contain the results of tool uses that were summarized.
+        if let Some(HistoryEntry {
+            user, pinned: false, ..
+        }) = self.history.get_mut(end)
+        {
+            if user.has_tool_use_results() {
+                user.replace_content_with_tool_use_results();
+            }
+        }
+        let mut i = 0;
+        self.history.retain(|entry| {
+            i += 1;
+            entry.pinned || i > end
+        });
         self.latest_summary = Some((summary, request_metadata));
     }
 
@@ -640,6 +833,10 @@ impl ConversationState {
             context_content.push_str(&context);
         }
 
+        if self.plan_mode() {
+            context_content.push_str(PLAN_MODE_INSTRUCTIONS);
+        }
+
         if let Some(agent_prompt) = self.agents.get_active().and_then(|a| a.prompt.as_ref()) {
             context_content.push_str(&format!("Follow this instruction: {}", agent_prompt));
         }
@@ -653,6 +850,7 @@ impl ConversationState {
                     user,
                     assistant,
                     request_metadata: None,
+                    pinned: false,
                 }]),
                 dropped_context_files,
             )
@@ -666,20 +864,57 @@ impl ConversationState {
         self.context_message_length
     }
 
-    /// Calculate the total character count in the conversation
-    pub async fn calculate_char_count(&mut self, os: &Os) -> Result<CharCount, ChatError> {
-        Ok(self
-            .backend_conversation_state(os, false, &mut vec![])
-            .await?
-            .char_count())
+    /// Estimate the total token count in the conversation, including the next user message, with
+    /// the tokenizer of the model
+    pub async fn estimate_token_count(&mut self, os: &Os) -> Result<TokenCount, ChatError> {
+        let tokenizer = Tokenizer::for_model(self.model_info.as_ref());
+        let state = self.backend_conversation_state(os, false, &mut vec![]).await?;
+        let next_message = state
+            .next_user_message
+            .map_or(0.into(), |message| message.token_count(tokenizer));
+        Ok(state.calculate_conversation_size().total() + next_message)
+    }
+
+    /// The tokens the conversation takes up in the context window, based on the latest response
+    /// the backend reported usage for. The history appended after that response (e.g. tool
+    /// results and the next user message) isn't part of the reported usage yet, so it's added as
+    /// an estimate.
+    pub fn reported_token_count(&self) -> Option<TokenCount> {
+        let (index, usage) = self.history.iter().enumerate().rev().find_map(|(i, entry)| {
+            entry
+                .request_metadata
+                .as_ref()
+                .and_then(|metadata| metadata.token_usage)
+                .map(|usage| (i, usage))
+        })?;
+
+        let tokenizer = Tokenizer::for_model(self.model_info.as_ref());
+        let appended = self
+            .history
+            .range(index + 1..)
+            .map(|entry| entry.user.token_count(tokenizer) + entry.assistant.token_count(tokenizer))
+            .sum::<TokenCount>()
+            + self
+                .next_message
+                .as_ref()
+                .map_or(0.into(), |message| message.token_count(tokenizer));
+        Some(TokenCount::from(usage.context_tokens()) + appended)
+    }
+
+    /// Calculate the total token count in the conversation, based on the usage reported by the
+    /// backend if available and estimated otherwise
+    pub async fn calculate_token_count(&mut self, os: &Os) -> Result<TokenCount, ChatError> {
+        match self.reported_token_count() {
+            Some(count) => Ok(count),
+            None => self.estimate_token_count(os).await,
+        }
     }
 
     /// Get the current token warning level
     pub async fn get_token_warning_level(&mut self, os: &Os) -> Result<TokenWarningLevel, ChatError> {
-        let total_chars = self.calculate_char_count(os).await?;
-        let max_chars = TokenCounter::token_to_chars(context_window_tokens(self.model_info.as_ref()));
+        let total_tokens = self.calculate_token_count(os).await?;
 
-        Ok(if *total_chars >= max_chars {
+        Ok(if *total_tokens >= context_window_tokens(self.model_info.as_ref()) {
             TokenWarningLevel::Critical
         } else {
             TokenWarningLevel::None
@@ -715,8 +950,12 @@ impl ConversationState {
     ) -> Result<(), ChatError> {
         let agent = self.agents.switch(agent_name).map_err(ChatError::AgentSwapError)?;
         self.context_manager.replace({
-            ContextManager::from_agent(agent, calc_max_context_files_size(self.model_info.as_ref()))
-                .map_err(|e| ChatError::Custom(format!("Context manager has failed to instantiate: {e}").into()))?
+            ContextManager::from_agent(
+                agent,
+                calc_max_context_files_size(self.model_info.as_ref()),
+                Tokenizer::for_model(self.model_info.as_ref()),
+            )
+            .map_err(|e| ChatError::Custom(format!("Context manager has failed to instantiate: {e}").into()))?
         });
 
         self.tool_manager
@@ -768,33 +1007,30 @@ impl BackendConversationStateImpl<'_, std::collections::vec_deque::Iter<'_, Hist
     }
 
     pub fn calculate_conversation_size(&self) -> ConversationSize {
-        let mut user_chars = 0;
-        let mut assistant_chars = 0;
-        let mut context_chars = 0;
+        let tokenizer = self.model_id.map_or_else(Tokenizer::default, Tokenizer::for_model_id);
+        let mut user_tokens = TokenCount::from(0);
+        let mut assistant_tokens = TokenCount::from(0);
+        let mut context_tokens = TokenCount::from(0);
 
-        // Count the chars used by the messages in the history.
+        // Count the tokens used by the messages in the history.
         // this clone is cheap
         let history = self.history.clone();
         for HistoryEntry { user, assistant, .. } in history {
-            user_chars += *user.char_count();
-            assistant_chars += *assistant.char_count();
+            user_tokens = user_tokens + user.token_count(tokenizer);
+            assistant_tokens = assistant_tokens + assistant.token_count(tokenizer);
         }
 
-        // Add any chars from context messages, if available.
-        context_chars += self
-            .context_messages
-            .as_ref()
-            .map(|v| {
-                v.iter().fold(0, |acc, HistoryEntry { user, assistant, .. }| {
-                    acc + *user.char_count() + *assistant.char_count()
-                })
-            })
-            .unwrap_or_default();
+        // Add any tokens from context messages, if available.
+        if let Some(context_messages) = self.context_messages.as_ref() {
+            for HistoryEntry { user, assistant, .. } in context_messages {
+                context_tokens = context_tokens + user.token_count(tokenizer) + assistant.token_count(tokenizer);
+            }
+        }
 
         ConversationSize {
-            context_messages: context_chars.into(),
-            user_messages: user_chars.into(),
-            assistant_messages: assistant_chars.into(),
+            context_messages: context_tokens,
+            user_messages: user_tokens,
+            assistant_messages: assistant_tokens,
         }
     }
 }
@@ -802,9 +1038,15 @@ impl BackendConversationStateImpl<'_, std::collections::vec_deque::Iter<'_, Hist
 /// Reflects a detailed accounting of the context window utilization for a given conversation.
 #[derive(Debug, Clone, Copy)]
 pub struct ConversationSize {
-    pub context_messages: CharCount,
-    pub user_messages: CharCount,
-    pub assistant_messages: CharCount,
+    pub context_messages: TokenCount,
+    pub user_messages: TokenCount,
+    pub assistant_messages: TokenCount,
+}
+
+impl ConversationSize {
+    pub fn total(&self) -> TokenCount {
+        self.context_messages + self.user_messages + self.assistant_messages
+    }
 }
 
 /// Converts a list of user/assistant message pairs into a flattened list of ChatMessage.
@@ -961,6 +1203,29 @@ fn enforce_conversation_invariants(
     valid_history_range
 }
 
+/// Returns the native tools of `tools` that are exposed in plan mode, along with the tool to
+/// propose a plan.
+fn plan_mode_tools(tools: &HashMap<ToolOrigin, Vec<Tool>>) -> HashMap<ToolOrigin, Vec<Tool>> {
+    let mut native_tools = tools
+        .get(&ToolOrigin::Native)
+        .into_iter()
+        .flatten()
+        .filter(|tool| match tool {
+            Tool::ToolSpecification(tool_spec) => {
+                tool_spec.name == DUMMY_TOOL_NAME || PLAN_MODE_TOOLS.contains(&tool_spec.name.as_str())
+            },
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let propose_plan = ProposePlan::tool_spec();
+    native_tools.push(Tool::ToolSpecification(ToolSpecification {
+        name: propose_plan.name,
+        description: propose_plan.description,
+        input_schema: propose_plan.input_schema.into(),
+    }));
+    HashMap::from([(ToolOrigin::Native, native_tools)])
+}
+
 fn enforce_tool_use_history_invariants(history: &mut VecDeque<HistoryEntry>, tools: &HashMap<ToolOrigin, Vec<Tool>>) {
     let tool_names: HashSet<_> = tools
         .values()
@@ -1019,6 +1284,7 @@ mod tests {
     use super::*;
     use crate::api_client::model::{
         AssistantResponseMessage,
+        TokenUsage,
         ToolResultStatus,
     };
     use crate::cli::agent::{
@@ -1289,4 +1555,154 @@ mod tests {
             conversation.set_next_user_message(i.to_string()).await;
         }
     }
+
+    #[tokio::test]
+    async fn test_token_count_prefers_reported_usage() {
+        let mut os = Os::new().await.unwrap();
+        let mut tool_manager = ToolManager::default();
+        let tool_config = tool_manager.load_tools(&mut os, &mut vec![]).await.unwrap();
+        let mut conversation = ConversationState::new(
+            "fake_conv_id",
+            Agents::default(),
+            tool_config,
+            tool_manager,
+            None,
+            &os,
+            false,
+        )
+        .await;
+
+        conversation.set_next_user_message("hello".to_string()).await;
+        conversation.push_assistant_message(&mut os, AssistantMessage::new_response(None, "hi".to_string()), None);
+        assert!(conversation.reported_token_count().is_none());
+        let estimate = conversation.calculate_token_count(&os).await.unwrap();
+        assert_eq!(estimate, conversation.estimate_token_count(&os).await.unwrap());
+
+        conversation.set_next_user_message("hello again".to_string()).await;
+        conversation.push_assistant_message(
+            &mut os,
+            AssistantMessage::new_response(None, "hi again".to_string()),
+            Some(RequestMetadata {
+                token_usage: Some(TokenUsage {
+                    uncached_input_tokens: 3000,
+                    cache_read_input_tokens: 9000,
+                    cache_write_input_tokens: 0,
+                    output_tokens: 4,
+                }),
+                ..Default::default()
+            }),
+        );
+        assert_eq!(conversation.calculate_token_count(&os).await.unwrap(), 12_004.into());
+
+        // Messages appended after the reported response are estimated
+        let tokenizer = Tokenizer::for_model(conversation.model_info.as_ref());
+        conversation
+            .set_next_user_message("what about the tests?".to_string())
+            .await;
+        assert_eq!(
+            conversation.calculate_token_count(&os).await.unwrap(),
+            TokenCount::from(12_004) + tokenizer.count_tokens("what about the tests?")
+        );
+    }
+
+    async fn test_conversation(os: &mut Os) -> ConversationState {
+        let mut tool_manager = ToolManager::default();
+        let tool_config = tool_manager.load_tools(os, &mut vec![]).await.unwrap();
+        ConversationState::new(
+            "fake_conv_id",
+            Agents::default(),
+            tool_config,
+            tool_manager,
+            None,
+            os,
+            false,
+        )
+        .await
+    }
+
+    /// Records a turn of `prompt`, a tool use with the given result and the final `response`.
+    async fn record_tool_use_turn(conversation: &mut ConversationState, prompt: &str, result: &str, response: &str) {
+        conversation.set_next_user_message(prompt.to_string()).await;
+        conversation.record_assistant_message(
+            AssistantMessage::new_tool_use(None, String::new(), vec![AssistantToolUse {
+                id: prompt.to_string(),
+                name: "fs_read".to_string(),
+                args: serde_json::json!({ "path": "/big.txt" }),
+                ..Default::default()
+            }]),
+            None,
+        );
+        conversation.add_tool_results(vec![ToolUseResult {
+            tool_use_id: prompt.to_string(),
+            content: vec![ToolUseResultBlock::Text(result.to_string())],
+            status: ToolResultStatus::Success,
+        }]);
+        conversation.record_assistant_message(AssistantMessage::new_response(None, response.to_string()), None);
+    }
+
+    #[tokio::test]
+    async fn test_elide_tool_results() {
+        let mut os = Os::new().await.unwrap();
+        let mut conversation = test_conversation(&mut os).await;
+        record_tool_use_turn(&mut conversation, "old", &"x".repeat(10_000), "done").await;
+        record_tool_use_turn(&mut conversation, "small", "y", "done").await;
+        record_tool_use_turn(&mut conversation, "recent", &"z".repeat(10_000), "done").await;
+
+        assert_eq!(conversation.elide_tool_results(), 1);
+        let results = conversation
+            .history()
+            .iter()
+            .filter_map(|entry| entry.user.tool_use_results())
+            .map(|results| results[0].content_len())
+            .collect::<Vec<_>>();
+        assert!(results[0] < MIN_ELIDED_TOOL_RESULT_SIZE);
+        assert_eq!(results[1..], [1, 10_000]);
+        let Some([ToolUseResult { content, .. }]) = conversation.history()[1].user.tool_use_results() else {
+            panic!("expected a tool result");
+        };
+        assert!(
+            matches!(&content[..], [ToolUseResultBlock::Text(stub)] if stub.contains("fs_read") && stub.contains("/big.txt"))
+        );
+
+        // Elided results are not elided again
+        assert_eq!(conversation.elide_tool_results(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_compaction_keeps_pinned_turns() {
+        let mut os = Os::new().await.unwrap();
+        let mut conversation = test_conversation(&mut os).await;
+        conversation.set_next_user_message("first".to_string()).await;
+        conversation.record_assistant_message(AssistantMessage::new_response(None, "hi".to_string()), None);
+        record_tool_use_turn(&mut conversation, "pinned", "contents", "done").await;
+        assert_eq!(conversation.pin_latest_turn(), Some("pinned"));
+        conversation.set_next_user_message("last".to_string()).await;
+        conversation.record_assistant_message(AssistantMessage::new_response(None, "bye".to_string()), None);
+
+        let summary_request = conversation
+            .create_summary_request(&os, None::<&str>, CompactStrategy::default())
+            .await
+            .unwrap();
+        let summarized = format!("{:?}", summary_request.history);
+        assert!(summarized.contains("first") && summarized.contains("last"));
+        assert!(!summarized.contains("pinned"));
+
+        conversation.replace_history_with_summary(
+            "summary".to_string(),
+            CompactStrategy::default(),
+            RequestMetadata::default(),
+        );
+        assert_eq!(conversation.history().len(), 2);
+        assert_eq!(conversation.pinned_prompts(), ["pinned"]);
+        conversation.set_next_user_message("next".to_string()).await;
+        let state = conversation
+            .as_sendable_conversation_state(&os, &mut vec![], false)
+            .await
+            .unwrap();
+        assert_conversation_state_invariants(state, 0);
+
+        assert!(!conversation.unpin_turn(1));
+        assert!(conversation.unpin_turn(0));
+        assert!(conversation.pinned_prompts().is_empty());
+    }
 }
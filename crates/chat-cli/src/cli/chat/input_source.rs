@@ -66,6 +66,15 @@ impl InputSource {
         }
     }
 
+    /// Updates the custom slash commands offered for tab completion
+    pub fn set_custom_commands(&mut self, commands: Vec<String>) {
+        if let inner::Inner::Readline(rl) = &mut self.0 {
+            if let Some(helper) = rl.helper_mut() {
+                helper.set_custom_commands(commands);
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub fn new_mock(lines: Vec<String>) -> Self {
         Self(inner::Inner::Mock { index: 0, lines })
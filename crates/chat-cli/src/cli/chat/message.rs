@@ -241,6 +241,14 @@ impl UserMessage {
         }
     }
 
+    pub fn tool_use_results_mut(&mut self) -> Option<&mut [ToolUseResult]> {
+        match &mut self.content {
+            UserMessageContent::Prompt { .. } => None,
+            UserMessageContent::CancelledToolUses { tool_use_results, .. } => Some(tool_use_results.as_mut_slice()),
+            UserMessageContent::ToolUseResults { tool_use_results } => Some(tool_use_results.as_mut_slice()),
+        }
+    }
+
     pub fn additional_context(&self) -> &str {
         &self.additional_context
     }
@@ -330,6 +338,19 @@ pub struct ToolUseResult {
     pub status: ToolResultStatus,
 }
 
+impl ToolUseResult {
+    /// Returns the size of the content of this result in bytes.
+    pub fn content_len(&self) -> usize {
+        self.content
+            .iter()
+            .map(|block| match block {
+                ToolUseResultBlock::Json(value) => serde_json::to_string(value).map_or(0, |s| s.len()),
+                ToolUseResultBlock::Text(text) => text.len(),
+            })
+            .sum()
+    }
+}
+
 impl From<ToolResult> for ToolUseResult {
     fn from(value: ToolResult) -> Self {
         Self {
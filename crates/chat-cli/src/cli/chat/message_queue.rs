@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+use std::time::Duration;
+
+use crossterm::queue;
+use crossterm::style::{
+    self,
+    Color,
+};
+
+/// How often a streaming response checks for newly queued messages to show.
+pub const SHOW_RECEIVED_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Collects the messages the user types while the assistant is responding or running tools, so
+/// that they can be sent once the current tool batch or response ends.
+///
+/// Reading only happens between [Self::start] and [Self::stop], so that the queue never competes
+/// with the prompt or with tools for input.
+#[derive(Debug)]
+pub struct MessageQueue {
+    /// Lines read from the terminal that have not been moved to [Self::queued] yet.
+    received: Arc<Mutex<Vec<String>>>,
+    /// Messages waiting to be sent, oldest first.
+    queued: VecDeque<String>,
+    kind: Kind,
+}
+
+#[derive(Debug)]
+enum Kind {
+    /// Input is not a terminal, so nothing is queued.
+    Disabled,
+    #[cfg(unix)]
+    Terminal(Option<unix::Reader>),
+    /// Lines that are "typed" as soon as the queue is first started.
+    #[cfg(test)]
+    Mock(Vec<String>),
+}
+
+impl MessageQueue {
+    /// Creates a queue that reads from the terminal, if `enabled` and stdin is a terminal.
+    pub fn new(enabled: bool) -> Self {
+        use std::io::IsTerminal;
+
+        let kind = match enabled && std::io::stdin().is_terminal() {
+            #[cfg(unix)]
+            true => Kind::Terminal(None),
+            _ => Kind::Disabled,
+        };
+        Self {
+            received: Arc::default(),
+            queued: VecDeque::new(),
+            kind,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn new_mock(lines: Vec<String>) -> Self {
+        Self {
+            received: Arc::default(),
+            queued: VecDeque::new(),
+            kind: Kind::Mock(lines),
+        }
+    }
+
+    /// Starts reading the lines the user types, if not already reading.
+    pub fn start(&mut self) {
+        match &mut self.kind {
+            Kind::Disabled => (),
+            #[cfg(unix)]
+            Kind::Terminal(reader) => {
+                if reader.is_none() {
+                    *reader = unix::Reader::spawn(Arc::clone(&self.received));
+                }
+            },
+            #[cfg(test)]
+            Kind::Mock(lines) => {
+                let lines = std::mem::take(lines);
+                self.received.lock().unwrap().extend(lines);
+            },
+        }
+    }
+
+    /// Stops reading, restoring the terminal for the prompt.
+    pub fn stop(&mut self) {
+        #[cfg(unix)]
+        if let Kind::Terminal(reader) = &mut self.kind {
+            reader.take();
+        }
+    }
+
+    /// Returns the messages received since the last call, which are queued to be sent.
+    pub fn receive(&mut self) -> Vec<String> {
+        let received = std::mem::take(&mut *self.received.lock().unwrap());
+        self.queued.extend(received.iter().cloned());
+        received
+    }
+
+    /// Whether messages were received that haven't been shown or taken yet.
+    pub fn has_received(&self) -> bool {
+        !self.received.lock().unwrap().is_empty()
+    }
+
+    /// Shows the messages received since the last call, dimmed.
+    pub fn show_received(&mut self, output: &mut impl Write) -> std::io::Result<()> {
+        for message in self.receive() {
+            queue!(
+                output,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print(format!("↳ Queued: {message}\n")),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+        output.flush()
+    }
+
+    /// Takes the oldest queued message.
+    pub fn pop(&mut self) -> Option<String> {
+        self.receive();
+        self.queued.pop_front()
+    }
+
+    /// Takes all the queued messages.
+    pub fn take_all(&mut self) -> Vec<String> {
+        self.receive();
+        self.queued.drain(..).collect()
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::io::stdin;
+    use std::os::fd::{
+        AsRawFd,
+        RawFd,
+    };
+    use std::sync::atomic::{
+        AtomicBool,
+        Ordering,
+    };
+    use std::sync::{
+        Arc,
+        Mutex,
+    };
+    use std::thread::JoinHandle;
+
+    use nix::sys::termios::{
+        LocalFlags,
+        SetArg,
+        Termios,
+        tcgetattr,
+        tcsetattr,
+    };
+    use tracing::warn;
+
+    /// How long the reader waits for input before checking whether it should stop
+    const POLL_INTERVAL_MS: i32 = 100;
+
+    /// Reads lines from stdin on a thread until dropped.
+    ///
+    /// The terminal stays in canonical mode, so the kernel handles line editing and only complete
+    /// lines are read, but echo is turned off so that typing does not garble the response being
+    /// printed. Queued lines are shown by the chat session instead. Input typed without pressing
+    /// enter is left for the prompt once the reader stops.
+    #[derive(Debug)]
+    pub struct Reader {
+        stop: Arc<AtomicBool>,
+        handle: Option<JoinHandle<()>>,
+        original_termios: Termios,
+    }
+
+    impl Reader {
+        pub fn spawn(received: Arc<Mutex<Vec<String>>>) -> Option<Self> {
+            let original_termios = match tcgetattr(stdin()) {
+                Ok(termios) => termios,
+                Err(err) => {
+                    warn!(?err, "Failed to get the terminal attributes, not queueing messages");
+                    return None;
+                },
+            };
+            let mut termios = original_termios.clone();
+            termios.local_flags.insert(LocalFlags::ICANON);
+            termios.local_flags.remove(LocalFlags::ECHO);
+            if let Err(err) = tcsetattr(stdin(), SetArg::TCSANOW, &termios) {
+                warn!(?err, "Failed to set the terminal attributes, not queueing messages");
+                return None;
+            }
+
+            let stop = Arc::new(AtomicBool::new(false));
+            let stop_clone = Arc::clone(&stop);
+            let handle = std::thread::spawn(move || read_lines(stdin().as_raw_fd(), &stop_clone, &received));
+
+            Some(Self {
+                stop,
+                handle: Some(handle),
+                original_termios,
+            })
+        }
+    }
+
+    impl Drop for Reader {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+            if let Some(handle) = self.handle.take() {
+                let _ = handle.join();
+            }
+            if let Err(err) = tcsetattr(stdin(), SetArg::TCSANOW, &self.original_termios) {
+                warn!(?err, "Failed to restore the terminal attributes");
+            }
+        }
+    }
+
+    /// Collect the lines read from `fd` into `received` until `stop` is set or the input ends
+    pub(super) fn read_lines(fd: RawFd, stop: &AtomicBool, received: &Mutex<Vec<String>>) {
+        let mut pending = Vec::new();
+        let mut buf = [0u8; 4096];
+
+        while !stop.load(Ordering::Relaxed) {
+            let mut poll_fd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: poll_fd is a valid pollfd for the duration of the call.
+            let ready = unsafe { libc::poll(&mut poll_fd, 1, POLL_INTERVAL_MS) };
+            if ready < 0 && !is_retryable(&std::io::Error::last_os_error()) {
+                break;
+            }
+            if ready <= 0 {
+                continue;
+            }
+            if poll_fd.revents & libc::POLLIN == 0 {
+                // The terminal went away
+                break;
+            }
+
+            // Reading directly from the file descriptor rather than through std's buffered stdin
+            // leaves nothing behind in a buffer that the prompt would not see. In canonical mode
+            // this returns at most one line.
+            // SAFETY: buf is valid for writes of buf.len() bytes.
+            let read = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+            match read {
+                // End of input, e.g. Ctrl-D or a closed pipe, after which POLLIN stays set
+                0 => break,
+                read if read < 0 => {
+                    if is_retryable(&std::io::Error::last_os_error()) {
+                        continue;
+                    }
+                    break;
+                },
+                _ => (),
+            }
+            pending.extend_from_slice(&buf[..read as usize]);
+
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line = String::from_utf8_lossy(&pending[..end]).trim().to_string();
+                pending.drain(..=end);
+                if !line.is_empty() {
+                    received.lock().unwrap().push(line);
+                }
+            }
+        }
+    }
+
+    fn is_retryable(error: &std::io::Error) -> bool {
+        matches!(
+            error.kind(),
+            std::io::ErrorKind::Interrupted | std::io::ErrorKind::WouldBlock
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_queue() {
+        let mut queue = MessageQueue::new_mock(vec!["first".to_string(), "second".to_string()]);
+        assert!(queue.receive().is_empty());
+        assert!(queue.pop().is_none());
+
+        queue.start();
+        assert_eq!(queue.receive(), vec!["first".to_string(), "second".to_string()]);
+        assert!(queue.receive().is_empty());
+        assert_eq!(queue.pop(), Some("first".to_string()));
+        assert_eq!(queue.take_all(), vec!["second".to_string()]);
+        assert!(queue.pop().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_lines_stops_at_end_of_input() {
+        use std::io::Write;
+        use std::os::fd::{
+            AsRawFd,
+            FromRawFd,
+        };
+        use std::sync::Mutex;
+        use std::sync::atomic::AtomicBool;
+
+        let mut fds = [0; 2];
+        // SAFETY: fds has room for the two file descriptors of the pipe.
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        // SAFETY: both ends are open and owned by nothing else.
+        let (read_end, mut write_end) =
+            unsafe { (std::fs::File::from_raw_fd(fds[0]), std::fs::File::from_raw_fd(fds[1])) };
+        write_end.write_all(b"first\n\nsecond\npartial").unwrap();
+        drop(write_end);
+
+        // Returns, rather than polling the closed pipe forever
+        let received = Mutex::new(Vec::new());
+        unix::read_lines(read_end.as_raw_fd(), &AtomicBool::new(false), &received);
+        assert_eq!(received.into_inner().unwrap(), vec![
+            "first".to_string(),
+            "second".to_string()
+        ]);
+    }
+}
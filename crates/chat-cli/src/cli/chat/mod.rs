@@ -5,6 +5,7 @@ mod conversation;
 mod error_formatter;
 mod input_source;
 mod message;
+mod message_queue;
 mod parse;
 use std::path::MAIN_SEPARATOR;
 mod line_tracker;
@@ -17,6 +18,7 @@ mod skim_integration;
 mod token_counter;
 pub mod tool_manager;
 pub mod tools;
+mod transcript;
 pub mod util;
 use std::borrow::Cow;
 use std::collections::{
@@ -42,10 +44,18 @@ use clap::{
     Parser,
 };
 use cli::compact::CompactStrategy;
+use cli::custom_commands::{
+    CommandOverrides,
+    execute_custom_command,
+    find_custom_command,
+    queue_custom_commands_help,
+};
+use cli::editor::open_editor_with_content;
 use cli::model::{
     get_available_models,
     select_model,
 };
+use cli::tools::save_trust_rule;
 pub use conversation::ConversationState;
 use conversation::TokenWarningLevel;
 use crossterm::style::{
@@ -63,9 +73,14 @@ use crossterm::{
 use eyre::{
     Report,
     Result,
+    WrapErr,
     bail,
     eyre,
 };
+use futures::{
+    StreamExt,
+    stream,
+};
 use input_source::InputSource;
 use message::{
     AssistantMessage,
@@ -73,6 +88,7 @@ use message::{
     ToolUseResult,
     ToolUseResultBlock,
 };
+use message_queue::MessageQueue;
 use parse::{
     ParseState,
     interpret_markdown,
@@ -89,7 +105,11 @@ use spinners::{
 };
 use thiserror::Error;
 use time::OffsetDateTime;
-use token_counter::TokenCounter;
+use token_counter::{
+    CharCount,
+    TokenCount,
+    TokenCounter,
+};
 use tokio::signal::ctrl_c;
 use tokio::sync::{
     Mutex,
@@ -102,7 +122,9 @@ use tool_manager::{
     ToolManagerBuilder,
 };
 use tools::gh_issue::GhIssueContext;
+use tools::use_subagent::SubagentContext;
 use tools::{
+    InvokeOutput,
     NATIVE_TOOLS,
     OutputKind,
     QueuedTool,
@@ -116,6 +138,10 @@ use tracing::{
     trace,
     warn,
 };
+use transcript::{
+    TranscriptFormat,
+    render_transcript,
+};
 use util::images::RichImageBlock;
 use util::ui::draw_box;
 use util::{
@@ -123,7 +149,10 @@ use util::{
     play_notification_bell,
 };
 use winnow::Partial;
-use winnow::stream::Offset;
+use winnow::stream::{
+    Offset,
+    StreamIsPartial,
+};
 
 use super::agent::PermissionEvalResult;
 use crate::api_client::model::ToolResultStatus;
@@ -141,6 +170,7 @@ use crate::cli::chat::cli::prompts::{
     PromptsSubcommand,
 };
 use crate::cli::chat::util::sanitize_unicode_tags;
+use crate::database::UsageRecord;
 use crate::database::settings::Setting;
 use crate::mcp_client::Prompt;
 use crate::os::Os;
@@ -158,6 +188,7 @@ use crate::telemetry::{
     get_error_reason,
 };
 use crate::util::MCP_SERVER_TOOL_DELIMITER;
+use crate::util::knowledge_store::KnowledgeStore;
 
 const LIMIT_REACHED_TEXT: &str = color_print::cstr! { "You've used all your free requests for this month. You have two options:
 1. Upgrade to a paid subscription for increased limits. See our Pricing page for what's included> <blue!>https://aws.amazon.com/q/developer/pricing/</blue!>
@@ -199,6 +230,10 @@ pub struct ChatArgs {
     /// Whether the command should run without expecting user input
     #[arg(long, alias = "non-interactive")]
     pub no_interactive: bool,
+    /// Start in plan mode, where the model can only use tools that read state until you approve
+    /// the plan it proposes
+    #[arg(long)]
+    pub plan: bool,
     /// The first question to ask
     pub input: Option<String>,
 }
@@ -362,7 +397,7 @@ impl ChatArgs {
             .await?;
         let tool_config = tool_manager.load_tools(os, &mut stderr).await?;
 
-        ChatSession::new(
+        let mut session = ChatSession::new(
             os,
             stdout,
             stderr,
@@ -378,13 +413,82 @@ impl ChatArgs {
             !self.no_interactive,
             mcp_enabled,
         )
-        .await?
-        .spawn(os)
-        .await
-        .map(|_| ExitCode::SUCCESS)
+        .await?;
+        if self.plan {
+            session.conversation.set_plan_mode(true);
+        }
+        session.spawn(os).await.map(|_| ExitCode::SUCCESS)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct ChatExportArgs {
+    /// A conversation saved with /save, or a directory to export the last conversation of.
+    /// Defaults to the current directory.
+    pub session: Option<String>,
+    /// File to write the transcript to. Printed if not given.
+    #[arg(short, long)]
+    pub output: Option<String>,
+    /// Format of the transcript. Defaults to the one matching the extension of the output file,
+    /// or Markdown.
+    #[arg(long, value_enum)]
+    pub format: Option<TranscriptFormat>,
+    /// Overwrite the output file if it exists
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+impl ChatExportArgs {
+    pub async fn execute(self, os: &mut Os) -> Result<ExitCode> {
+        let conversation = load_conversation(os, self.session.as_deref()).await?;
+        let format = self.format.unwrap_or_else(|| {
+            self.output
+                .as_ref()
+                .map_or(TranscriptFormat::Md, TranscriptFormat::from_path)
+        });
+        let transcript = render_transcript(&conversation, format);
+        match self.output {
+            Some(path) => {
+                if os.fs.exists(&path) && !self.force {
+                    bail!("File at {path} already exists. To overwrite, use -f or --force");
+                }
+                os.fs.write(&path, transcript).await?;
+                eprintln!("✔ Exported conversation to {path}");
+            },
+            None => print!("{transcript}"),
+        }
+        Ok(ExitCode::SUCCESS)
     }
 }
 
+/// Loads a conversation saved with /save from `session`, or otherwise the last conversation in the
+/// directory `session` or the current directory.
+async fn load_conversation(os: &mut Os, session: Option<&str>) -> Result<ConversationState> {
+    let dir = match session {
+        Some(session) => {
+            let path = os
+                .fs
+                .canonicalize(session)
+                .await
+                .wrap_err_with(|| format!("No conversation found at {session}"))?;
+            if !path.is_dir() {
+                let contents = os
+                    .fs
+                    .read_to_string(session)
+                    .await
+                    .wrap_err_with(|| format!("Failed to read {session}"))?;
+                return serde_json::from_str(&contents)
+                    .wrap_err_with(|| format!("{session} is not a conversation saved with /save"));
+            }
+            path
+        },
+        None => os.env.current_dir()?,
+    };
+    os.database
+        .get_conversation_by_path(&dir)?
+        .ok_or_else(|| eyre!("No conversation found for {}", dir.display()))
+}
+
 const WELCOME_TEXT: &str = color_print::cstr! {"<cyan!>
     ⢠⣶⣶⣦⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⢀⣤⣶⣿⣿⣿⣶⣦⡀⠀
  ⠀⠀⠀⣾⡿⢻⣿⡆⠀⠀⠀⢀⣄⡄⢀⣠⣤⣤⡀⢀⣠⣤⣤⡀⠀⠀⢀⣠⣤⣤⣤⣄⠀⠀⢀⣤⣤⣤⣤⣤⣤⡀⠀⠀⣀⣤⣤⣤⣀⠀⠀⠀⢠⣤⡀⣀⣤⣤⣄⡀⠀⠀⠀⠀⠀⠀⢠⣿⣿⠋⠀⠀⠀⠙⣿⣿⡆
@@ -445,6 +549,9 @@ const TRUST_ALL_TEXT: &str = color_print::cstr! {"<green!>All tools are now trus
 \nAgents can sometimes do unexpected things so understand the risks.</green!>
 \nLearn more at https://docs.aws.amazon.com/amazonq/latest/qdeveloper-ug/command-line-chat-security.html#command-line-chat-trustall-safety"};
 
+/// How many read-only tool uses of a single turn can run at the same time
+const MAX_CONCURRENT_TOOL_USES: usize = 8;
+
 const TOOL_BULLET: &str = " ● ";
 const CONTINUATION_LINE: &str = " ⋮ ";
 const PURPOSE_ARROW: &str = " ↳ ";
@@ -581,6 +688,13 @@ pub struct ChatSession {
     failed_request_ids: Vec<String>,
     /// Pending prompts to be sent
     pending_prompts: VecDeque<Prompt>,
+    /// Images pasted from the clipboard with /paste, sent with the next prompt
+    pasted_images: Vec<RichImageBlock>,
+    /// Messages typed while the assistant is busy, sent once the current tool batch or response
+    /// ends
+    message_queue: MessageQueue,
+    /// Tool and model overrides applied by the custom command currently being responded to
+    custom_command_overrides: Option<CommandOverrides>,
     interactive: bool,
     inner: Option<ChatState>,
     ctrlc_rx: broadcast::Receiver<()>,
@@ -691,6 +805,9 @@ impl ChatSession {
             tool_use_status: ToolUseStatus::Idle,
             failed_request_ids: Vec::new(),
             pending_prompts: VecDeque::new(),
+            pasted_images: Vec::new(),
+            message_queue: MessageQueue::new(interactive),
+            custom_command_overrides: None,
             interactive,
             inner: Some(ChatState::default()),
             ctrlc_rx,
@@ -701,6 +818,18 @@ impl ChatSession {
         // Update conversation state with new tool information
         self.conversation.update_state(false).await;
 
+        // Messages are only queued while the assistant is busy. Tools running other programs are
+        // given the terminal since the programs may read from it.
+        match &self.inner {
+            Some(ChatState::HandleResponseStream(_) | ChatState::ValidateTools { .. }) => self.message_queue.start(),
+            Some(ChatState::ExecuteTools)
+                if !self.tool_uses.iter().any(|tool_use| tool_use.tool.spawns_processes()) =>
+            {
+                self.message_queue.start();
+            },
+            _ => self.message_queue.stop(),
+        }
+
         let mut ctrl_c_stream = self.ctrlc_rx.resubscribe();
         let result = match self.inner.take().expect("state must always be Some") {
             ChatState::PromptUser { skip_printing_tools } => {
@@ -755,7 +884,7 @@ impl ChatSession {
                         // Wait for handle_response to finish handling the ctrlc.
                         tokio::time::sleep(Duration::from_millis(5)).await;
                         if let Some(request_metadata) = request_metadata.lock().await.take() {
-                            self.user_turn_request_metadata.push(request_metadata);
+                            self.push_request_metadata(os, request_metadata).await;
                         }
                         self.send_chat_telemetry(os, TelemetryResult::Cancelled, None, None, None, true).await;
                         Err(ChatError::Interrupted { tool_uses: None })
@@ -798,6 +927,16 @@ impl ChatSession {
             ChatError::Interrupted { tool_uses: ref inter } => {
                 execute!(self.stderr, style::Print("\n\n"))?;
 
+                let discarded = self.message_queue.take_all().len();
+                if discarded > 0 {
+                    execute!(
+                        self.stderr,
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print(format!("Discarded {discarded} queued message(s)\n\n")),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                }
+
                 // If there was an interrupt during tool execution, then we add fake
                 // messages to "reset" the chat state.
                 match inter {
@@ -873,6 +1012,7 @@ impl ChatSession {
                                 } else {
                                     Default::default()
                                 },
+                                elide_tool_results: true,
                                 ..Default::default()
                             },
                         });
@@ -880,7 +1020,7 @@ impl ChatSession {
                         execute!(
                             self.stdout,
                             style::SetForegroundColor(Color::Yellow),
-                            style::Print("The context window has overflowed, summarizing the history..."),
+                            style::Print("The context window has overflowed, compacting the history..."),
                             style::SetAttribute(Attribute::Reset),
                             style::Print("\n\n"),
                         )?;
@@ -1223,6 +1363,8 @@ impl ChatSession {
             }
         }
 
+        self.load_agent_knowledge(os).await?;
+
         if let Some(user_input) = self.initial_input.take() {
             self.inner = Some(ChatState::HandleInput { input: user_input });
         }
@@ -1234,6 +1376,77 @@ impl ChatSession {
         Ok(())
     }
 
+    /// Indexes the knowledge sources declared by the active agent into the workspace knowledge
+    /// base. Indexing runs in the background, so this only reports how many sources started
+    /// indexing and which ones failed.
+    async fn load_agent_knowledge(&mut self, os: &Os) -> Result<()> {
+        let sources = self
+            .conversation
+            .agents
+            .get_active()
+            .map(|agent| agent.knowledge.clone())
+            .unwrap_or_default();
+        if sources.is_empty()
+            || !os
+                .database
+                .settings
+                .get_bool(Setting::EnabledKnowledge)
+                .unwrap_or(false)
+        {
+            return Ok(());
+        }
+
+        let store = match KnowledgeStore::get_async_instance_with_os(os).await {
+            Ok(store) => store,
+            Err(e) => {
+                warn!(?e, "failed to open the knowledge base");
+                return Ok(());
+            },
+        };
+        let cwd = os.env.current_dir()?;
+        let mut store = store.lock().await;
+        let mut results = Vec::new();
+        for source in &sources {
+            // Sources that are already indexed are kept current by auto-update or /knowledge update
+            let result = match cwd.join(&source.path).canonicalize() {
+                Ok(path) => {
+                    store
+                        .add_if_missing(&source.entry_name(), &path.to_string_lossy(), source.add_options(os))
+                        .await
+                },
+                Err(_) => Some(Err(format!("Path does not exist: {}", source.path))),
+            };
+            results.extend(result);
+        }
+        drop(store);
+
+        let started = results.iter().filter(|result| result.is_ok()).count();
+        if started > 0 {
+            queue!(
+                self.stderr,
+                style::SetForegroundColor(Color::Cyan),
+                style::Print(format!(
+                    "📚 Indexing {} knowledge source{} from the agent config into the workspace knowledge base. Use /knowledge status to check progress.\n",
+                    started,
+                    if started == 1 { "" } else { "s" }
+                )),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+        for error in results.into_iter().filter_map(Result::err) {
+            queue!(
+                self.stderr,
+                style::SetForegroundColor(Color::Yellow),
+                style::Print("WARNING: "),
+                style::SetForegroundColor(Color::Reset),
+                style::Print(format!("Failed to index knowledge source: {}\n", error)),
+            )?;
+        }
+        self.stderr.flush()?;
+
+        Ok(())
+    }
+
     /// Compacts the conversation history using the strategy specified by [CompactStrategy],
     /// replacing the history with a summary generated by the model.
     ///
@@ -1261,7 +1474,7 @@ impl ChatSession {
                 // Wait for handle_response to finish handling the ctrlc.
                 tokio::time::sleep(Duration::from_millis(5)).await;
                 if let Some(request_metadata) = request_metadata.lock().await.take() {
-                    self.user_turn_request_metadata.push(request_metadata);
+                    self.push_request_metadata(os, request_metadata).await;
                 }
                 self.send_chat_telemetry(
                     os,
@@ -1301,6 +1514,38 @@ impl ChatSession {
             });
         }
 
+        // Eliding old tool results loses less than summarizing, so only summarize if there were
+        // none to elide.
+        if strategy.elide_tool_results {
+            let elided = self.conversation.elide_tool_results();
+            if elided > 0 {
+                info!(elided, "elided tool results");
+                execute!(
+                    self.stderr,
+                    terminal::Clear(terminal::ClearType::CurrentLine),
+                    cursor::MoveToColumn(0),
+                    style::SetForegroundColor(Color::Green),
+                    style::Print(format!(
+                        "✔ Elided {elided} old tool result{} to free up context space\n\n",
+                        if elided == 1 { "" } else { "s" }
+                    )),
+                    style::SetForegroundColor(Color::Reset),
+                )?;
+
+                return if self.conversation.next_user_message().is_some() {
+                    Ok(ChatState::HandleResponseStream(
+                        self.conversation
+                            .as_sendable_conversation_state(os, &mut self.stderr, false)
+                            .await?,
+                    ))
+                } else {
+                    Ok(ChatState::PromptUser {
+                        skip_printing_tools: true,
+                    })
+                };
+            }
+        }
+
         if strategy.truncate_large_messages {
             info!("truncating large messages");
             execute!(
@@ -1365,6 +1610,7 @@ impl ChatSession {
                                     truncate_large_messages: true,
                                     max_message_length: 25_000,
                                     messages_to_exclude: 0,
+                                    ..strategy
                                 },
                             });
                         }
@@ -1406,7 +1652,7 @@ impl ChatSession {
                         message,
                         request_metadata,
                     })) => {
-                        self.user_turn_request_metadata.push(request_metadata.clone());
+                        self.push_request_metadata(os, request_metadata.clone()).await;
                         break (message.content().to_string(), request_metadata);
                     },
                     Some(Ok(_)) => (),
@@ -1415,7 +1661,7 @@ impl ChatSession {
                             self.failed_request_ids.push(request_id.clone());
                         };
 
-                        self.user_turn_request_metadata.push(err.request_metadata.clone());
+                        self.push_request_metadata(os, err.request_metadata.clone()).await;
 
                         let (reason, reason_desc) = get_error_reason(&err);
                         self.send_chat_telemetry(
@@ -1534,6 +1780,18 @@ impl ChatSession {
 
         // Check token usage and display warnings if needed
         if self.pending_tool_index.is_none() {
+            if let Some(overrides) = self.custom_command_overrides.take() {
+                overrides.restore(&mut self.conversation);
+            }
+
+            let custom_commands = self
+                .conversation
+                .agents
+                .get_active()
+                .map(|agent| agent.commands.keys().map(|name| format!("/{name}")).collect())
+                .unwrap_or_default();
+            self.input_source.set_custom_commands(custom_commands);
+
             // Only display warnings when not waiting for tool approval
             if let Err(err) = self.display_char_warnings(os).await {
                 warn!("Failed to display character limit warnings: {}", err);
@@ -1541,15 +1799,62 @@ impl ChatSession {
         }
 
         let show_tool_use_confirmation_dialog = !skip_printing_tools && self.pending_tool_index.is_some();
-        if show_tool_use_confirmation_dialog {
+        let proposes_plan = self
+            .pending_tool_index
+            .and_then(|i| self.tool_uses.get(i))
+            .is_some_and(|t| matches!(t.tool, Tool::ProposePlan(_)));
+        if show_tool_use_confirmation_dialog && proposes_plan {
             execute!(
+                self.stderr,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print(
+                    "\nApprove this plan? Approving it ends plan mode so that it can be carried out with all tools. Use '"
+                ),
+                style::SetForegroundColor(Color::Green),
+                style::Print("n"),
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print("' or describe what to change to have it revised. ["),
+                style::SetForegroundColor(Color::Green),
+                style::Print("y"),
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print("/"),
+                style::SetForegroundColor(Color::Green),
+                style::Print("n"),
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print("]:\n\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        } else if show_tool_use_confirmation_dialog {
+            let editable = self
+                .pending_tool_index
+                .and_then(|i| self.tool_uses.get(i))
+                .is_some_and(|t| matches!(&t.tool, Tool::FsWrite(w) if w.is_editable()));
+            queue!(
                 self.stderr,
                 style::SetForegroundColor(Color::DarkGrey),
                 style::Print("\nAllow this action? Use '"),
                 style::SetForegroundColor(Color::Green),
                 style::Print("t"),
                 style::SetForegroundColor(Color::DarkGrey),
-                style::Print("' to trust (always allow) this tool for the session. ["),
+                style::Print("' to trust (always allow) this tool for the session, '"),
+                style::SetForegroundColor(Color::Green),
+                style::Print("a"),
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print("' to always allow this action by saving it to the agent config"),
+            )?;
+            if editable {
+                queue!(
+                    self.stderr,
+                    style::Print(", or '"),
+                    style::SetForegroundColor(Color::Green),
+                    style::Print("e"),
+                    style::SetForegroundColor(Color::DarkGrey),
+                    style::Print("' to edit the changes before accepting them"),
+                )?;
+            }
+            queue!(
+                self.stderr,
+                style::Print(". ["),
                 style::SetForegroundColor(Color::Green),
                 style::Print("y"),
                 style::SetForegroundColor(Color::DarkGrey),
@@ -1561,6 +1866,22 @@ impl ChatSession {
                 style::SetForegroundColor(Color::Green),
                 style::Print("t"),
                 style::SetForegroundColor(Color::DarkGrey),
+                style::Print("/"),
+                style::SetForegroundColor(Color::Green),
+                style::Print("a"),
+            )?;
+            if editable {
+                queue!(
+                    self.stderr,
+                    style::SetForegroundColor(Color::DarkGrey),
+                    style::Print("/"),
+                    style::SetForegroundColor(Color::Green),
+                    style::Print("e"),
+                )?;
+            }
+            execute!(
+                self.stderr,
+                style::SetForegroundColor(Color::DarkGrey),
                 style::Print("]:\n\n"),
                 style::SetForegroundColor(Color::Reset),
             )?;
@@ -1593,9 +1914,26 @@ impl ChatSession {
             style::SetAttribute(Attribute::Reset)
         )?;
         let prompt = self.generate_tool_trust_prompt();
-        let user_input = match self.read_user_input(&prompt, false) {
-            Some(input) => input,
-            None => return Ok(ChatState::Exit),
+        // Messages queued while the assistant was busy are sent as if typed at the prompt, but
+        // not as the answer to a tool use confirmation.
+        let queued_input = match self.pending_tool_index {
+            Some(_) => None,
+            None => self.message_queue.pop(),
+        };
+        let user_input = match queued_input {
+            Some(input) => {
+                execute!(
+                    self.stderr,
+                    style::Print(&prompt),
+                    style::Print(&input),
+                    style::Print("\n")
+                )?;
+                input
+            },
+            None => match self.read_user_input(&prompt, false) {
+                Some(input) => input,
+                None => return Ok(ChatState::Exit),
+            },
         };
 
         self.conversation.append_user_transcript(&user_input);
@@ -1612,6 +1950,17 @@ impl ChatSession {
             return Ok(chat_state);
         }
         if let Some(mut args) = input.strip_prefix("/").and_then(shlex::split) {
+            // Built-in commands take precedence over those declared by the agent.
+            if let Some(name) = args
+                .first()
+                .filter(|name| SlashCommand::command().find_subcommand(name).is_none())
+            {
+                if let Some(command) = find_custom_command(self, name) {
+                    let orig_input = input.strip_prefix("/").unwrap_or_default().to_string();
+                    return execute_custom_command(os, self, command, &orig_input).await;
+                }
+            }
+
             // Required for printing errors correctly.
             let orig_args = args.clone();
 
@@ -1680,6 +2029,12 @@ impl ChatSession {
 
                     writeln!(self.stderr, "{}", ansi_output)?;
 
+                    if err.kind() == clap::error::ErrorKind::DisplayHelp && orig_args.len() <= 1 {
+                        if let Some(agent) = self.conversation.agents.get_active() {
+                            queue_custom_commands_help(&mut self.stderr, agent)?;
+                        }
+                    }
+
                     // Print the subcommand help, if available. Required since by default we won't
                     // show what the actual arguments are, requiring an unnecessary --help call.
                     if let clap::error::ErrorKind::InvalidValue
@@ -1720,7 +2075,7 @@ impl ChatSession {
                 name: prompt_name,
                 arguments,
             };
-            return subcommand.execute(self).await;
+            return subcommand.execute(os, self).await;
         } else if let Some(command) = input.strip_prefix("!") {
             // Use platform-appropriate shell
             let result = if cfg!(target_os = "windows") {
@@ -1757,26 +2112,53 @@ impl ChatSession {
         } else {
             // Check for a pending tool approval
             if let Some(index) = self.pending_tool_index {
-                let is_trust = ["t", "T"].contains(&input);
-                let tool_use = &mut self.tool_uses[index];
-                if ["y", "Y"].contains(&input) || is_trust {
+                if ["e", "E"].contains(&input)
+                    && matches!(&self.tool_uses[index].tool, Tool::FsWrite(w) if w.is_editable())
+                {
+                    return self.edit_tool_use(os, index).await;
+                }
+                // Approving a plan ends plan mode. Plans are approved one by one, never trusted.
+                let is_plan = matches!(self.tool_uses[index].tool, Tool::ProposePlan(_));
+                if is_plan && ["y", "Y"].contains(&input) {
+                    self.conversation.set_plan_mode(false);
+                    execute!(
+                        self.stderr,
+                        style::SetForegroundColor(Color::Green),
+                        style::Print("Plan approved. Plan mode is off.\n"),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                    self.tool_uses[index].accepted = true;
+
+                    return Ok(ChatState::ExecuteTools);
+                }
+                let is_trust = !is_plan && ["t", "T"].contains(&input);
+                let is_always_allow = !is_plan && ["a", "A"].contains(&input);
+                if ["y", "Y"].contains(&input) || is_trust || is_always_allow {
+                    let tool_use = &self.tool_uses[index];
+                    let formatted_tool_name = self
+                        .conversation
+                        .tool_manager
+                        .tn_map
+                        .get(&tool_use.name)
+                        .map(|info| {
+                            format!(
+                                "@{}{MCP_SERVER_TOOL_DELIMITER}{}",
+                                info.server_name, info.host_tool_name
+                            )
+                        })
+                        .clone()
+                        .unwrap_or(tool_use.name.clone());
                     if is_trust {
-                        let formatted_tool_name = self
-                            .conversation
-                            .tool_manager
-                            .tn_map
-                            .get(&tool_use.name)
-                            .map(|info| {
-                                format!(
-                                    "@{}{MCP_SERVER_TOOL_DELIMITER}{}",
-                                    info.server_name, info.host_tool_name
-                                )
-                            })
-                            .clone()
-                            .unwrap_or(tool_use.name.clone());
                         self.conversation.agents.trust_tools(vec![formatted_tool_name]);
+                    } else if is_always_allow {
+                        let rule = tool_use.tool.trust_rule(formatted_tool_name);
+                        if let Some(agent) = self.conversation.agents.get_active_mut() {
+                            agent.add_trust_rule(&rule);
+                        }
+                        save_trust_rule(os, self, &rule).await?;
+                        queue!(self.stderr, style::Print("\n"))?;
                     }
-                    tool_use.accepted = true;
+                    self.tool_uses[index].accepted = true;
 
                     return Ok(ChatState::ExecuteTools);
                 }
@@ -1797,7 +2179,12 @@ impl ChatSession {
                 // TODO: Update this flow to something that does *not* require two requests just to
                 // get a meaningful response from the user - this is a short term solution before
                 // we decide on a better flow.
-                let user_input = if ["n", "N"].contains(&user_input.trim()) {
+                let proposes_plan = self
+                    .pending_tool_index
+                    .is_some_and(|i| matches!(self.tool_uses[i].tool, Tool::ProposePlan(_)));
+                let user_input = if ["n", "N"].contains(&user_input.trim()) && proposes_plan {
+                    "I reject this plan. Ask me what should change about it".to_string()
+                } else if ["n", "N"].contains(&user_input.trim()) {
                     "I deny this tool request. Ask a follow up question clarifying the expected action".to_string()
                 } else {
                     user_input
@@ -1806,6 +2193,11 @@ impl ChatSession {
             } else {
                 self.conversation.set_next_user_message(user_input).await;
             }
+            if !self.pasted_images.is_empty() {
+                let images = std::mem::take(&mut self.pasted_images);
+                self.conversation
+                    .add_images_to_next_message(images.into_iter().map(|(block, _)| block).collect());
+            }
 
             self.reset_user_turn();
 
@@ -1850,7 +2242,7 @@ impl ChatSession {
                             false
                         },
                     })
-                    || self.conversation.agents.trust_all_tools;
+                    || (self.conversation.agents.trust_all_tools && !matches!(tool.tool, Tool::ProposePlan(_)));
 
             if let Some(match_set) = denied_match_set {
                 let formatted_set = match_set.into_iter().fold(String::new(), |mut acc, rule| {
@@ -1908,163 +2300,63 @@ impl ChatSession {
             });
         }
 
-        // Execute the requested tools.
+        // Execute the requested tools. Consecutive tools that only read state run concurrently,
+        // with their output buffered and then rendered in the order they were requested.
         let mut tool_results = vec![];
         let mut image_blocks: Vec<RichImageBlock> = Vec::new();
 
-        for tool in &self.tool_uses {
-            let tool_start = std::time::Instant::now();
-            let mut tool_telemetry = self.tool_use_telemetry_events.entry(tool.id.clone());
-            tool_telemetry = tool_telemetry.and_modify(|ev| {
-                ev.is_accepted = true;
-            });
-
-            // Extract AWS service name and operation name if available
-            if let Some(additional_info) = tool.tool.get_additional_info() {
-                if let Some(aws_service_name) = additional_info.get("aws_service_name").and_then(|v| v.as_str()) {
-                    tool_telemetry =
-                        tool_telemetry.and_modify(|ev| ev.aws_service_name = Some(aws_service_name.to_string()));
-                }
-                if let Some(aws_operation_name) = additional_info.get("aws_operation_name").and_then(|v| v.as_str()) {
-                    tool_telemetry =
-                        tool_telemetry.and_modify(|ev| ev.aws_operation_name = Some(aws_operation_name.to_string()));
-                }
-            }
-
-            let invoke_result = tool
-                .tool
-                .invoke(os, &mut self.stdout, &mut self.conversation.file_line_tracker)
+        let mut start = 0;
+        while start < self.tool_uses.len() {
+            let concurrent = self.tool_uses[start..]
+                .iter()
+                .take_while(|tool| tool.tool.is_concurrency_safe())
+                .count();
+
+            if concurrent > 1 {
+                let end = start + concurrent;
+                let os_ref: &Os = os;
+                let outcomes = stream::iter(self.tool_uses[start..end].iter().map(|tool| async move {
+                    let tool_start = Instant::now();
+                    let mut output = Vec::new();
+                    // Read-only tools don't change any lines
+                    let mut line_tracker = HashMap::new();
+                    let invoke_result = tool.tool.invoke(os_ref, &mut output, &mut line_tracker).await;
+                    (output, tool_start.elapsed(), invoke_result)
+                }))
+                .buffered(MAX_CONCURRENT_TOOL_USES)
+                .collect::<Vec<_>>()
                 .await;
 
-            if self.spinner.is_some() {
-                queue!(
-                    self.stderr,
-                    terminal::Clear(terminal::ClearType::CurrentLine),
-                    cursor::MoveToColumn(0),
-                    cursor::Show
-                )?;
-            }
-            execute!(self.stdout, style::Print("\n"))?;
-
-            let tool_end_time = Instant::now();
-            let tool_time = tool_end_time.duration_since(tool_start);
-            tool_telemetry = tool_telemetry.and_modify(|ev| {
-                ev.execution_duration = Some(tool_time);
-                ev.turn_duration = self.tool_turn_start_time.map(|t| tool_end_time.duration_since(t));
-            });
-            if let Tool::Custom(ct) = &tool.tool {
-                tool_telemetry = tool_telemetry.and_modify(|ev| {
-                    ev.is_custom_tool = true;
-                    // legacy fields previously implemented for only MCP tools
-                    ev.custom_tool_call_latency = Some(tool_time.as_secs() as usize);
-                    ev.input_token_size = Some(ct.get_input_token_size());
-                });
-            }
-            let tool_time = format!("{}.{}", tool_time.as_secs(), tool_time.subsec_millis());
-            match invoke_result {
-                Ok(result) => {
-                    match result.output {
-                        OutputKind::Text(ref text) => {
-                            debug!("Output is Text: {}", text);
-                        },
-                        OutputKind::Json(ref json) => {
-                            debug!("Output is JSON: {}", json);
-                        },
-                        OutputKind::Images(ref image) => {
-                            image_blocks.extend(image.clone());
-                        },
-                        OutputKind::Mixed { ref text, ref images } => {
-                            debug!("Output is Mixed: text = {:?}, images = {}", text, images.len());
-                            image_blocks.extend(images.clone());
-                        },
-                    }
-
-                    debug!("tool result output: {:#?}", result);
-                    execute!(
-                        self.stdout,
-                        style::Print(CONTINUATION_LINE),
-                        style::Print("\n"),
-                        style::SetForegroundColor(Color::Green),
-                        style::SetAttribute(Attribute::Bold),
-                        style::Print(format!(" ● Completed in {}s", tool_time)),
-                        style::SetForegroundColor(Color::Reset),
-                        style::Print("\n\n"),
-                    )?;
-
-                    tool_telemetry = tool_telemetry.and_modify(|ev| ev.is_success = Some(true));
-                    if let Tool::Custom(_) = &tool.tool {
-                        tool_telemetry
-                            .and_modify(|ev| ev.output_token_size = Some(TokenCounter::count_tokens(&result.as_str())));
-                    }
-
-                    // Send telemetry for agent contribution
-                    if let Tool::FsWrite(w) = &tool.tool {
-                        let sanitized_path_str = w.path(os).to_string_lossy().to_string();
-                        let conversation_id = self.conversation.conversation_id().to_string();
-                        let message_id = self.conversation.message_id().map(|s| s.to_string());
-                        if let Some(tracker) = self.conversation.file_line_tracker.get_mut(&sanitized_path_str) {
-                            let lines_by_agent = tracker.lines_by_agent();
-                            let lines_by_user = tracker.lines_by_user();
-
-                            os.telemetry
-                                .send_agent_contribution_metric(
-                                    &os.database,
-                                    conversation_id,
-                                    message_id,
-                                    Some(tool.id.clone()),   // Already a String
-                                    Some(tool.name.clone()), // Already a String
-                                    Some(lines_by_agent),
-                                    Some(lines_by_user),
-                                )
-                                .await
-                                .ok();
-
-                            tracker.prev_fswrite_lines = tracker.after_fswrite_lines;
-                        }
-                    }
-
-                    tool_results.push(ToolUseResult {
-                        tool_use_id: tool.id.clone(),
-                        content: vec![result.into()],
-                        status: ToolResultStatus::Success,
-                    });
-                },
-                Err(err) => {
-                    error!(?err, "An error occurred processing the tool");
-                    execute!(
-                        self.stderr,
-                        style::Print(CONTINUATION_LINE),
-                        style::Print("\n"),
-                        style::SetAttribute(Attribute::Bold),
-                        style::SetForegroundColor(Color::Red),
-                        style::Print(format!(" ● Execution failed after {}s:\n", tool_time)),
-                        style::SetAttribute(Attribute::Reset),
-                        style::SetForegroundColor(Color::Red),
-                        style::Print(&err),
-                        style::SetAttribute(Attribute::Reset),
-                        style::Print("\n\n"),
-                    )?;
-
-                    tool_telemetry.and_modify(|ev| {
-                        ev.is_success = Some(false);
-                        ev.reason_desc = Some(err.to_string());
-                    });
-                    tool_results.push(ToolUseResult {
-                        tool_use_id: tool.id.clone(),
-                        content: vec![ToolUseResultBlock::Text(format!(
-                            "An error occurred processing the tool: \n{}",
-                            &err
-                        ))],
-                        status: ToolResultStatus::Error,
-                    });
-                    if let ToolUseStatus::Idle = self.tool_use_status {
-                        self.tool_use_status = ToolUseStatus::RetryInProgress(
-                            self.conversation
-                                .message_id()
-                                .map_or("No utterance id found".to_string(), |v| v.to_string()),
-                        );
-                    }
-                },
+                for (index, (output, tool_time, invoke_result)) in (start..end).zip(outcomes) {
+                    self.stdout.write_all(&output)?;
+                    self.handle_tool_result(
+                        os,
+                        index,
+                        tool_time,
+                        invoke_result,
+                        &mut tool_results,
+                        &mut image_blocks,
+                    )
+                    .await?;
+                }
+                start = end;
+            } else {
+                let tool = &self.tool_uses[start];
+                let tool_start = Instant::now();
+                let invoke_result = tool
+                    .tool
+                    .invoke(os, &mut self.stdout, &mut self.conversation.file_line_tracker)
+                    .await;
+                self.handle_tool_result(
+                    os,
+                    start,
+                    tool_start.elapsed(),
+                    invoke_result,
+                    &mut tool_results,
+                    &mut image_blocks,
+                )
+                .await?;
+                start += 1;
             }
         }
 
@@ -2081,6 +2373,20 @@ impl ChatSession {
             self.conversation.add_tool_results(tool_results);
         }
 
+        let steering = self.message_queue.take_all();
+        if !steering.is_empty() {
+            self.conversation.add_steering_messages(&steering);
+            execute!(
+                self.stderr,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print(format!(
+                    "Sending {} queued message(s) along with the tool results\n",
+                    steering.len()
+                )),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+
         execute!(self.stderr, cursor::Hide)?;
         execute!(self.stderr, style::Print("\n"), style::SetAttribute(Attribute::Reset))?;
         if self.interactive {
@@ -2097,9 +2403,178 @@ impl ChatSession {
         ));
     }
 
-    /// Sends a [crate::api_client::ApiClient::send_message] request to the backend and consumes
-    /// the response stream.
-    ///
+    /// Renders the outcome of the tool use at `index`, records its telemetry and collects its
+    /// result to send back to the model
+    async fn handle_tool_result(
+        &mut self,
+        os: &Os,
+        index: usize,
+        tool_time: Duration,
+        invoke_result: Result<InvokeOutput>,
+        tool_results: &mut Vec<ToolUseResult>,
+        image_blocks: &mut Vec<RichImageBlock>,
+    ) -> Result<(), ChatError> {
+        let tool = &self.tool_uses[index];
+        let mut tool_telemetry = self.tool_use_telemetry_events.entry(tool.id.clone());
+        tool_telemetry = tool_telemetry.and_modify(|ev| {
+            ev.is_accepted = true;
+        });
+
+        // Extract AWS service name and operation name if available
+        if let Some(additional_info) = tool.tool.get_additional_info() {
+            if let Some(aws_service_name) = additional_info.get("aws_service_name").and_then(|v| v.as_str()) {
+                tool_telemetry =
+                    tool_telemetry.and_modify(|ev| ev.aws_service_name = Some(aws_service_name.to_string()));
+            }
+            if let Some(aws_operation_name) = additional_info.get("aws_operation_name").and_then(|v| v.as_str()) {
+                tool_telemetry =
+                    tool_telemetry.and_modify(|ev| ev.aws_operation_name = Some(aws_operation_name.to_string()));
+            }
+        }
+
+        if self.spinner.is_some() {
+            queue!(
+                self.stderr,
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                cursor::MoveToColumn(0),
+                cursor::Show
+            )?;
+        }
+        execute!(self.stdout, style::Print("\n"))?;
+
+        let tool_end_time = Instant::now();
+        tool_telemetry = tool_telemetry.and_modify(|ev| {
+            ev.execution_duration = Some(tool_time);
+            ev.turn_duration = self.tool_turn_start_time.map(|t| tool_end_time.duration_since(t));
+        });
+        if let Tool::Custom(ct) = &tool.tool {
+            tool_telemetry = tool_telemetry.and_modify(|ev| {
+                ev.is_custom_tool = true;
+                // legacy fields previously implemented for only MCP tools
+                ev.custom_tool_call_latency = Some(tool_time.as_secs() as usize);
+                ev.input_token_size = Some(ct.get_input_token_size());
+            });
+        }
+        let tool_time = format!("{}.{}", tool_time.as_secs(), tool_time.subsec_millis());
+        match invoke_result {
+            Ok(result) => {
+                match result.output {
+                    OutputKind::Text(ref text) => {
+                        debug!("Output is Text: {}", text);
+                    },
+                    OutputKind::Json(ref json) => {
+                        debug!("Output is JSON: {}", json);
+                    },
+                    OutputKind::Images(ref image) => {
+                        image_blocks.extend(image.clone());
+                    },
+                    OutputKind::Mixed { ref text, ref images } => {
+                        debug!("Output is Mixed: text = {:?}, images = {}", text, images.len());
+                        image_blocks.extend(images.clone());
+                    },
+                }
+
+                debug!("tool result output: {:#?}", result);
+                execute!(
+                    self.stdout,
+                    style::Print(CONTINUATION_LINE),
+                    style::Print("\n"),
+                    style::SetForegroundColor(Color::Green),
+                    style::SetAttribute(Attribute::Bold),
+                    style::Print(format!(" ● Completed in {}s", tool_time)),
+                    style::SetForegroundColor(Color::Reset),
+                    style::Print("\n\n"),
+                )?;
+                self.message_queue.show_received(&mut self.stderr)?;
+
+                tool_telemetry = tool_telemetry.and_modify(|ev| ev.is_success = Some(true));
+                if let Tool::Custom(_) = &tool.tool {
+                    tool_telemetry
+                        .and_modify(|ev| ev.output_token_size = Some(TokenCounter::count_tokens(&result.as_str())));
+                }
+
+                // Send telemetry for agent contribution
+                if let Tool::FsWrite(w) = &tool.tool {
+                    for path in w.paths(os) {
+                        let sanitized_path_str = path.to_string_lossy().to_string();
+                        let conversation_id = self.conversation.conversation_id().to_string();
+                        let message_id = self.conversation.message_id().map(|s| s.to_string());
+                        if let Some(tracker) = self.conversation.file_line_tracker.get_mut(&sanitized_path_str) {
+                            let lines_by_agent = tracker.lines_by_agent();
+                            let lines_by_user = tracker.lines_by_user();
+
+                            os.telemetry
+                                .send_agent_contribution_metric(
+                                    &os.database,
+                                    conversation_id,
+                                    message_id,
+                                    Some(tool.id.clone()),   // Already a String
+                                    Some(tool.name.clone()), // Already a String
+                                    Some(lines_by_agent),
+                                    Some(lines_by_user),
+                                )
+                                .await
+                                .ok();
+
+                            tracker.prev_fswrite_lines = tracker.after_fswrite_lines;
+                        }
+                    }
+                }
+
+                let mut content = vec![result.into()];
+                if let Some(note) = &tool.user_edit_note {
+                    content.push(ToolUseResultBlock::Text(note.clone()));
+                }
+                tool_results.push(ToolUseResult {
+                    tool_use_id: tool.id.clone(),
+                    content,
+                    status: ToolResultStatus::Success,
+                });
+            },
+            Err(err) => {
+                error!(?err, "An error occurred processing the tool");
+                execute!(
+                    self.stderr,
+                    style::Print(CONTINUATION_LINE),
+                    style::Print("\n"),
+                    style::SetAttribute(Attribute::Bold),
+                    style::SetForegroundColor(Color::Red),
+                    style::Print(format!(" ● Execution failed after {}s:\n", tool_time)),
+                    style::SetAttribute(Attribute::Reset),
+                    style::SetForegroundColor(Color::Red),
+                    style::Print(&err),
+                    style::SetAttribute(Attribute::Reset),
+                    style::Print("\n\n"),
+                )?;
+
+                tool_telemetry.and_modify(|ev| {
+                    ev.is_success = Some(false);
+                    ev.reason_desc = Some(err.to_string());
+                });
+                tool_results.push(ToolUseResult {
+                    tool_use_id: tool.id.clone(),
+                    content: vec![ToolUseResultBlock::Text(format!(
+                        "An error occurred processing the tool: \n{}",
+                        &err
+                    ))],
+                    status: ToolResultStatus::Error,
+                });
+                if let ToolUseStatus::Idle = self.tool_use_status {
+                    self.tool_use_status = ToolUseStatus::RetryInProgress(
+                        self.conversation
+                            .message_id()
+                            .map_or("No utterance id found".to_string(), |v| v.to_string()),
+                    );
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Sends a [crate::api_client::ApiClient::send_message] request to the backend and consumes
+    /// the response stream.
+    ///
     /// In order to handle sigints while also keeping track of metadata about how the
     /// response stream was handled, we need an extra parameter:
     /// * `request_metadata_lock` - Updated with the [RequestMetadata] once it has been received
@@ -2137,8 +2612,18 @@ impl ChatSession {
             )?;
         }
 
+        let mut show_received = tokio::time::interval(message_queue::SHOW_RECEIVED_INTERVAL);
         loop {
-            match rx.recv().await {
+            let event = tokio::select! {
+                event = rx.recv() => event,
+                _ = show_received.tick() => {
+                    // Only interrupt the response at the start of a line, so the text stays intact
+                    let at_line_start = state.column == 0 && !state.in_codeblock;
+                    self.show_queued_messages(at_line_start)?;
+                    continue;
+                },
+            };
+            match event {
                 Some(Ok(msg_event)) => {
                     trace!("Consumed: {:?}", msg_event);
                     match msg_event {
@@ -2184,7 +2669,7 @@ impl ChatSession {
                                 error!(?request_id, ?message, "Encountered an unexpected model response");
                             }
                             self.conversation.push_assistant_message(os, message, Some(rm.clone()));
-                            self.user_turn_request_metadata.push(rm);
+                            self.push_request_metadata(os, rm).await;
                             ended = true;
                         },
                     }
@@ -2194,8 +2679,8 @@ impl ChatSession {
                         self.failed_request_ids.push(request_id.clone());
                     };
 
-                    self.user_turn_request_metadata
-                        .push(recv_error.request_metadata.clone());
+                    self.push_request_metadata(os, recv_error.request_metadata.clone())
+                        .await;
                     let (reason, reason_desc) = get_error_reason(&recv_error);
                     let status_code = recv_error.status_code();
 
@@ -2318,7 +2803,15 @@ impl ChatSession {
 
             // Print the response for normal cases
             loop {
-                let input = Partial::new(&buf[offset..]);
+                let mut input = Partial::new(&buf[offset..]);
+                if ended {
+                    // Nothing more is coming, so parsers waiting to see where e.g. a table ends
+                    // can finish.
+                    if input.is_empty() {
+                        break;
+                    }
+                    let _ = input.complete();
+                }
                 match interpret_markdown(input, &mut self.stdout, &mut state) {
                     Ok(parsed) => {
                         offset += parsed.offset_from(&input);
@@ -2370,6 +2863,7 @@ impl ChatSession {
                         style::SetForegroundColor(Color::Reset)
                     )?;
                 }
+                self.message_queue.show_received(&mut self.stderr)?;
 
                 break;
             }
@@ -2414,6 +2908,16 @@ impl ChatSession {
                     self.contextualize_tool(&mut tool);
 
                     match tool.validate(os).await {
+                        Ok(()) if self.conversation.plan_mode() && !tool.is_read_only() => {
+                            tool_telemetry.is_valid = Some(false);
+                            tool_results.push(ToolUseResult {
+                                tool_use_id: tool_use_id.clone(),
+                                content: vec![ToolUseResultBlock::Text(format!(
+                                    "{tool_use_name} is not allowed in plan mode, which only allows tools that read state. Propose a plan with propose_plan instead."
+                                ))],
+                                status: ToolResultStatus::Error,
+                            });
+                        },
                         Ok(()) => {
                             tool_telemetry.is_valid = Some(true);
                             queued_tools.push(QueuedTool {
@@ -2421,6 +2925,7 @@ impl ChatSession {
                                 name: tool_use_name,
                                 tool,
                                 accepted: false,
+                                user_edit_note: None,
                             });
                         },
                         Err(err) => {
@@ -2548,6 +3053,89 @@ impl ChatSession {
                 tool_permissions: allowed_tools,
             });
         }
+
+        if let Tool::UseSubagent(use_subagent) = tool {
+            let mut agents = self.conversation.agents.clone();
+            let agent_name = use_subagent.agent.clone().unwrap_or_else(|| agents.active_idx.clone());
+            if let Ok(agent) = agents.switch(&agent_name) {
+                let tool_manager = Arc::new(self.conversation.tool_manager.for_subagent(agent));
+                use_subagent.set_context(SubagentContext {
+                    agents,
+                    tool_manager,
+                    model_info: self.conversation.model_info.clone(),
+                });
+            }
+        }
+    }
+
+    /// Opens the changes proposed by a pending `fs_write` tool use in the user's editor, accepting
+    /// the tool use with the user's version of the file once the editor exits.
+    async fn edit_tool_use(&mut self, os: &Os, index: usize) -> Result<ChatState, ChatError> {
+        let Tool::FsWrite(fs_write) = &self.tool_uses[index].tool else {
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: false,
+            });
+        };
+
+        let edited = match fs_write.proposed_content(os).await {
+            Ok((path, proposed)) => {
+                let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("txt");
+                open_editor_with_content(&proposed, extension).map(|edited| (proposed, edited))
+            },
+            Err(err) => Err(ChatError::Custom(err.to_string().into())),
+        };
+        let (proposed, edited) = match edited {
+            Ok(res) => res,
+            Err(err) => {
+                execute!(
+                    self.stderr,
+                    style::SetForegroundColor(Color::Red),
+                    style::Print(format!("\nError editing the proposed changes: {}\n", err)),
+                    style::SetForegroundColor(Color::Reset)
+                )?;
+                return Ok(ChatState::PromptUser {
+                    skip_printing_tools: false,
+                });
+            },
+        };
+
+        if edited.trim().is_empty() {
+            execute!(
+                self.stderr,
+                style::SetForegroundColor(Color::Yellow),
+                style::Print("\nEmpty content from editor, not applying the changes.\n"),
+                style::SetForegroundColor(Color::Reset)
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: false,
+            });
+        }
+
+        let edits = fs_write.with_user_edits(&proposed, edited);
+        let tool_use = &mut self.tool_uses[index];
+        match edits {
+            Some((edited_tool, note)) => {
+                execute!(
+                    self.stderr,
+                    style::SetForegroundColor(Color::Green),
+                    style::Print("\nApplying your edited version of the changes.\n\n"),
+                    style::SetForegroundColor(Color::Reset)
+                )?;
+                tool_use.tool = Tool::FsWrite(edited_tool);
+                tool_use.user_edit_note = Some(note);
+            },
+            None => {
+                execute!(
+                    self.stderr,
+                    style::SetForegroundColor(Color::Green),
+                    style::Print("\nNo changes were made in the editor, applying the proposed changes.\n\n"),
+                    style::SetForegroundColor(Color::Reset)
+                )?;
+            },
+        }
+        tool_use.accepted = true;
+
+        Ok(ChatState::ExecuteTools)
     }
 
     async fn print_tool_description(&mut self, os: &Os, tool_index: usize, trusted: bool) -> Result<(), ChatError> {
@@ -2626,7 +3214,7 @@ impl ChatSession {
     fn generate_tool_trust_prompt(&mut self) -> String {
         let profile = self.conversation.current_profile().map(|s| s.to_string());
         let all_trusted = self.all_tools_trusted();
-        prompt::generate_prompt(profile.as_deref(), all_trusted)
+        prompt::generate_prompt(profile.as_deref(), self.conversation.plan_mode(), all_trusted)
     }
 
     async fn send_tool_use_telemetry(&mut self, os: &Os) {
@@ -2649,6 +3237,30 @@ impl ChatSession {
         self.conversation.agents.trust_all_tools
     }
 
+    /// Shows the messages queued while the response streams, under the spinner if it's running.
+    /// Without a spinner they're only shown if `at_line_start`, and otherwise wait for a later
+    /// call.
+    fn show_queued_messages(&mut self, at_line_start: bool) -> Result<(), ChatError> {
+        if !self.message_queue.has_received() {
+            return Ok(());
+        }
+
+        if self.spinner.is_some() {
+            drop(self.spinner.take());
+            queue!(
+                self.stderr,
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                cursor::MoveToColumn(0),
+            )?;
+            self.message_queue.show_received(&mut self.stderr)?;
+            self.spinner = Some(Spinner::new(Spinners::Dots, "Thinking...".to_string()));
+        } else if at_line_start {
+            self.message_queue.show_received(&mut self.stderr)?;
+        }
+
+        Ok(())
+    }
+
     /// Display character limit warnings based on current conversation size
     async fn display_char_warnings(&mut self, os: &Os) -> Result<(), ChatError> {
         let warning_level = self.conversation.get_token_warning_level(os).await?;
@@ -2685,6 +3297,42 @@ impl ChatSession {
         self.user_turn_request_metadata.clear();
     }
 
+    /// Adds the metadata of a request to the current user turn and records the request in the
+    /// usage ledger.
+    async fn push_request_metadata(&mut self, os: &Os, request_metadata: RequestMetadata) {
+        let (input_tokens, output_tokens) = match request_metadata.token_usage {
+            Some(usage) => (usage.input_tokens(), usage.output_tokens),
+            None => {
+                // The conversation already contains the response if the request succeeded. Only
+                // its size is known here, so its tokens are estimated from that.
+                let conversation_tokens = match self.conversation.estimate_token_count(os).await {
+                    Ok(count) => count.value(),
+                    Err(err) => {
+                        warn!(?err, "Failed to estimate the size of the conversation");
+                        0
+                    },
+                };
+                let output_tokens = TokenCount::from(CharCount::from(request_metadata.response_size)).value();
+                (conversation_tokens.saturating_sub(output_tokens), output_tokens)
+            },
+        };
+
+        let record = UsageRecord {
+            conversation_id: self.conversation.conversation_id().to_string(),
+            timestamp_ms: request_metadata.request_start_timestamp_ms,
+            model_id: request_metadata.model_id.clone(),
+            input_tokens: input_tokens as u64,
+            output_tokens: output_tokens as u64,
+            tool_calls: request_metadata.tool_use_ids_and_names.len() as u64,
+            time_to_first_chunk_ms: request_metadata.time_to_first_chunk.map(|d| d.as_millis() as u64),
+        };
+        if let Err(err) = os.database.record_usage(&record) {
+            warn!(?err, "Failed to record usage");
+        }
+
+        self.user_turn_request_metadata.push(request_metadata);
+    }
+
     /// Sends an "codewhispererterminal_addChatMessage" telemetry event.
     ///
     /// This *MUST* be called in the following cases:
@@ -3004,6 +3652,146 @@ mod tests {
         assert_eq!(os.fs.read_to_string("/file.txt").await.unwrap(), "Hello, world!\n");
     }
 
+    async fn mock_input_session(os: &mut Os, inputs: Vec<&str>, queued: Vec<&str>) -> ChatSession {
+        let agents = get_test_agents(os).await;
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        let mut session = ChatSession::new(
+            os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            agents,
+            None,
+            InputSource::new_mock(inputs.into_iter().map(String::from).collect()),
+            false,
+            || Some(80),
+            ToolManager::default(),
+            None,
+            tool_config,
+            true,
+            false,
+        )
+        .await
+        .unwrap();
+        session.message_queue = MessageQueue::new_mock(queued.into_iter().map(String::from).collect());
+        session
+    }
+
+    #[tokio::test]
+    async fn test_flow_queued_message_steers_tool_batch() {
+        let mut os = Os::new().await.unwrap();
+        os.fs.write("/notes.txt", "the build is broken").await.unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Let me read the notes",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_read",
+                    "args": { "operations": [{ "mode": "Line", "path": "/notes.txt" }] }
+                }
+            ],
+            [
+                "Done",
+            ],
+        ]));
+
+        let mut session = mock_input_session(&mut os, vec!["read the notes"], vec!["also check the tests"]).await;
+        session.spawn(&mut os).await.unwrap();
+
+        let history = session.conversation.history();
+        assert_eq!(history.len(), 2);
+        let tool_results = history[1].user().tool_use_results().unwrap();
+        let ToolUseResultBlock::Text(steering) = tool_results[0].content.last().unwrap() else {
+            panic!("expected the queued message to be added to the tool results");
+        };
+        assert!(steering.ends_with("also check the tests"));
+    }
+
+    #[tokio::test]
+    async fn test_flow_queued_message_is_next_prompt() {
+        let mut os = Os::new().await.unwrap();
+        os.client
+            .set_mock_output(serde_json::json!([["Hi there",], ["Sure",],]));
+
+        let mut session = mock_input_session(&mut os, vec!["hello"], vec!["and now a follow up"]).await;
+        session.spawn(&mut os).await.unwrap();
+
+        let history = session.conversation.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].user().prompt(), Some("hello"));
+        assert_eq!(history[1].user().prompt(), Some("and now a follow up"));
+        assert_eq!(history[1].assistant().content(), "Sure");
+    }
+
+    #[tokio::test]
+    async fn test_show_queued_messages_waits_for_line_start() {
+        let mut os = Os::new().await.unwrap();
+        let mut session = mock_input_session(&mut os, vec![], vec!["and the docs"]).await;
+        session.message_queue.start();
+
+        // Mid-line, the message is kept until the response reaches the start of a line
+        session.show_queued_messages(false).unwrap();
+        assert!(session.message_queue.has_received());
+
+        session.show_queued_messages(true).unwrap();
+        assert!(!session.message_queue.has_received());
+        // Showing a message keeps it queued
+        assert_eq!(session.message_queue.pop(), Some("and the docs".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_flow_plan_mode() {
+        let mut os = Os::new().await.unwrap();
+        let create_file = serde_json::json!({
+            "command": "create",
+            "file_text": "Hello, world!",
+            "path": "/file.txt",
+        });
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Ok",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": create_file,
+                }
+            ],
+            [
+                "Here is my plan",
+                {
+                    "tool_use_id": "2",
+                    "name": "propose_plan",
+                    "args": {
+                        "summary": "Create the file",
+                        "steps": [{ "description": "Write the greeting", "files": ["/file.txt"] }],
+                    }
+                }
+            ],
+            [
+                "Ok",
+                {
+                    "tool_use_id": "3",
+                    "name": "fs_write",
+                    "args": create_file,
+                }
+            ],
+            [
+                "Done",
+            ],
+        ]));
+
+        let mut session = mock_input_session(&mut os, vec!["create a file", "y", "y"], vec![]).await;
+        session.conversation.set_plan_mode(true);
+        session.spawn(&mut os).await.unwrap();
+
+        let history = session.conversation.history();
+        let rejected = history[1].user().tool_use_results().unwrap();
+        assert!(matches!(rejected[0].status, ToolResultStatus::Error));
+        assert!(!session.conversation.plan_mode());
+        assert_eq!(os.fs.read_to_string("/file.txt").await.unwrap(), "Hello, world!\n");
+    }
+
     #[tokio::test]
     async fn test_flow_tool_permissions() {
         let mut os = Os::new().await.unwrap();
@@ -3246,6 +4034,88 @@ mod tests {
         assert_eq!(os.fs.read_to_string("/file4.txt").await.unwrap(), "Hello, world!\n");
     }
 
+    #[tokio::test]
+    async fn test_flow_concurrent_read_only_tools() {
+        let mut os = Os::new().await.unwrap();
+        os.fs.write("/a.txt", "a").await.unwrap();
+        os.fs.write("/b.txt", "b").await.unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Reading both files, then writing a summary",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_read",
+                    "args": { "operations": [{ "mode": "Line", "path": "/a.txt" }] }
+                },
+                {
+                    "tool_use_id": "2",
+                    "name": "fs_read",
+                    "args": { "operations": [{ "mode": "Line", "path": "/b.txt" }] }
+                },
+                {
+                    "tool_use_id": "3",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "a and b",
+                        "path": "/summary.txt",
+                    }
+                },
+                {
+                    "tool_use_id": "4",
+                    "name": "fs_read",
+                    "args": { "operations": [{ "mode": "Line", "path": "/a.txt" }] }
+                }
+            ],
+            [
+                "Done",
+            ],
+        ]));
+
+        let agents = get_test_agents(&os).await;
+        let tool_manager = ToolManager::default();
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        let mut session = ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            agents,
+            None,
+            InputSource::new_mock(vec![
+                "summarize a and b".to_string(),
+                "t".to_string(),
+                "y".to_string(),
+                "exit".to_string(),
+            ]),
+            false,
+            || Some(80),
+            tool_manager,
+            None,
+            tool_config,
+            true,
+            false,
+        )
+        .await
+        .unwrap();
+        session.spawn(&mut os).await.unwrap();
+
+        assert_eq!(os.fs.read_to_string("/summary.txt").await.unwrap(), "a and b\n");
+
+        // The results are sent in the order of the tool uses, even though the reads ran concurrently
+        let history = session.conversation.history();
+        let tool_results = history[1].user().tool_use_results().unwrap();
+        let ids = tool_results.iter().map(|r| r.tool_use_id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1", "2", "3", "4"]);
+        for (i, expected) in [(0, "a"), (1, "b"), (3, "a")] {
+            let ToolUseResultBlock::Text(content) = &tool_results[i].content[0] else {
+                panic!("expected a text result for tool use {}", tool_results[i].tool_use_id);
+            };
+            assert_eq!(content, expected, "tool use {}", tool_results[i].tool_use_id);
+        }
+    }
+
     #[tokio::test]
     async fn test_flow_tools_trust_all() {
         // let _ = tracing_subscriber::fmt::try_init();
@@ -3334,6 +4204,160 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_flow_always_allow() {
+        let mut os = Os::new().await.unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Sure, I'll create a file for you",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file.txt",
+                    }
+                }
+            ],
+            [
+                "Done",
+            ],
+            [
+                "Sure, I'll update it",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Goodbye, world!",
+                        "path": "/file.txt",
+                    }
+                }
+            ],
+            [
+                "Done",
+            ],
+        ]));
+
+        let agents = get_test_agents(&os).await;
+        let tool_manager = ToolManager::default();
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            agents,
+            None,
+            InputSource::new_mock(vec![
+                "create a new file".to_string(),
+                "a".to_string(),
+                "update the file".to_string(),
+                "exit".to_string(),
+            ]),
+            false,
+            || Some(80),
+            tool_manager,
+            None,
+            tool_config,
+            true,
+            false,
+        )
+        .await
+        .unwrap()
+        .spawn(&mut os)
+        .await
+        .unwrap();
+
+        assert_eq!(os.fs.read_to_string("/file.txt").await.unwrap(), "Goodbye, world!\n");
+        let saved =
+            serde_json::from_str::<Agent>(&os.fs.read_to_string("/persona/TestAgent.json").await.unwrap()).unwrap();
+        assert!(saved.allowed_tools.contains("fs_write"));
+        assert_eq!(
+            saved.tools_settings.get("fs_write"),
+            Some(&serde_json::json!({ "allowedPaths": ["/file.txt"] }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flow_custom_command() {
+        let mut os = Os::new().await.unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Sure, I'll scaffold that for you",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file1.txt",
+                    }
+                }
+            ],
+            [
+                "Done",
+            ],
+            [
+                "Sure, I'll create a file for you",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file2.txt",
+                    }
+                }
+            ],
+        ]));
+
+        let mut agents = get_test_agents(&os).await;
+        agents.get_active_mut().unwrap().commands.insert(
+            "scaffold".to_string(),
+            crate::cli::agent::custom_command::CustomCommand {
+                description: None,
+                prompt: "Create $ARGUMENTS".to_string(),
+                allowed_tools: vec!["fs_write".to_string()],
+                model: None,
+            },
+        );
+        let tool_manager = ToolManager::default();
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            agents,
+            None,
+            InputSource::new_mock(vec![
+                "/scaffold file1.txt".to_string(),
+                "create a new file".to_string(),
+                "exit".to_string(),
+            ]),
+            false,
+            || Some(80),
+            tool_manager,
+            None,
+            tool_config,
+            true,
+            false,
+        )
+        .await
+        .unwrap()
+        .spawn(&mut os)
+        .await
+        .unwrap();
+
+        // fs_write is only trusted while responding to the custom command
+        assert_eq!(os.fs.read_to_string("/file1.txt").await.unwrap(), "Hello, world!\n");
+        assert!(!os.fs.exists("/file2.txt"));
+    }
+
     #[tokio::test]
     #[cfg(unix)]
     async fn test_subscribe_flow() {
@@ -3383,4 +4407,13 @@ mod tests {
             assert_eq!(actual, *expected, "expected {} for input {}", expected, input);
         }
     }
+
+    #[tokio::test]
+    async fn test_load_conversation_from_unreadable_file() {
+        let mut os = Os::new().await.unwrap();
+        os.fs.write("conversation.json", [0xff, 0xfe]).await.unwrap();
+
+        let err = load_conversation(&mut os, Some("conversation.json")).await.unwrap_err();
+        assert_eq!(err.to_string(), "Failed to read conversation.json");
+    }
 }
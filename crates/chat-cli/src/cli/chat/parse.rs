@@ -24,6 +24,8 @@ use winnow::ascii::{
 use winnow::combinator::{
     alt,
     delimited,
+    eof,
+    opt,
     preceded,
     repeat,
     terminated,
@@ -50,9 +52,14 @@ const HEADING_COLOR: Color = Color::Magenta;
 const BLOCKQUOTE_COLOR: Color = Color::DarkGrey;
 const URL_TEXT_COLOR: Color = Color::Blue;
 const URL_LINK_COLOR: Color = Color::DarkGrey;
+const TASK_DONE_COLOR: Color = Color::Green;
+const TABLE_BORDER_COLOR: Color = Color::DarkGrey;
 
 const DEFAULT_RULE_WIDTH: usize = 40;
 
+/// Bullets of list items by nesting level
+const BULLETS: [&str; 3] = ["•", "◦", "▪"];
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error<'a> {
     #[error(transparent)]
@@ -90,6 +97,8 @@ pub struct ParseState {
     pub strikethrough: bool,
     pub set_newline: bool,
     pub newline: bool,
+    /// Column that wrapped lines continue at, e.g. to align them with the text of a list item
+    pub indent: usize,
     pub citations: Vec<(String, String)>,
 }
 
@@ -105,6 +114,7 @@ impl ParseState {
             strikethrough: false,
             set_newline: false,
             newline: true,
+            indent: 0,
             citations: vec![],
         }
     }
@@ -149,6 +159,7 @@ pub fn interpret_markdown<'a, 'b>(
                 text,
                 // multiline patterns
                 blockquote,
+                table,
                 // linted_codeblock,
                 codeblock_begin,
                 // single line patterns
@@ -233,10 +244,27 @@ fn bulleted_item<'a, 'b>(
         }
 
         let ws = (space0, alt(("-", "*")), space1).parse_next(i)?.0;
-        let print = format!("{ws}• ");
+        let task = opt(terminated(delimited("[", alt((" ", "x", "X")), "]"), space1)).parse_next(i)?;
+        let indent = " ".repeat(indent_width(ws));
+
+        queue_newline_or_advance(&mut o, state, indent.len() + 2)?;
+        queue(&mut o, style::Print(indent))?;
+        match task {
+            Some(" ") => queue(&mut o, style::Print("☐ "))?,
+            Some(_) => {
+                queue(&mut o, style::SetForegroundColor(TASK_DONE_COLOR))?;
+                queue(&mut o, style::Print("☑"))?;
+                queue(&mut o, style::ResetColor)?;
+                queue(&mut o, style::Print(' '))?;
+            },
+            None => {
+                let bullet = BULLETS[(indent_width(ws) / 2) % BULLETS.len()];
+                queue(&mut o, style::Print(format!("{bullet} ")))?;
+            },
+        }
+        state.indent = state.column;
 
-        queue_newline_or_advance(&mut o, state, print.width())?;
-        queue(&mut o, style::Print(print))
+        Ok(())
     }
 }
 
@@ -249,14 +277,22 @@ fn numbered_item<'a, 'b>(
             return Err(ErrMode::from_error_kind(i, ErrorKind::Fail));
         }
 
-        let (ws, digits, _, _) = (space0, digit1, ".", space1).parse_next(i)?;
-        let print = format!("{ws}{digits}. ");
+        let (ws, digits, delimiter, _) = (space0, digit1, alt((".", ")")), space1).parse_next(i)?;
+        let print = format!("{}{digits}{delimiter} ", " ".repeat(indent_width(ws)));
 
         queue_newline_or_advance(&mut o, state, print.width())?;
-        queue(&mut o, style::Print(print))
+        queue(&mut o, style::Print(print))?;
+        state.indent = state.column;
+
+        Ok(())
     }
 }
 
+/// Returns the width of the indentation of a list item, counting tabs as four spaces.
+fn indent_width(ws: &str) -> usize {
+    ws.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
 fn horizontal_rule<'a, 'b>(
     mut o: impl Write + 'b,
     state: &'b mut ParseState,
@@ -273,6 +309,7 @@ fn horizontal_rule<'a, 'b>(
             .parse_next(i)?;
 
         state.column = 0;
+        state.indent = 0;
         state.set_newline = true;
 
         let rule_width = state.terminal_width.unwrap_or(DEFAULT_RULE_WIDTH);
@@ -316,6 +353,255 @@ fn blockquote<'a, 'b>(
     }
 }
 
+fn table<'a, 'b>(
+    mut o: impl Write + 'b,
+    state: &'b mut ParseState,
+) -> impl FnMut(&mut Partial<&'a str>) -> PResult<(), Error<'a>> + 'b {
+    move |i| {
+        if !state.newline {
+            return Err(ErrMode::from_error_kind(i, ErrorKind::Fail));
+        }
+
+        // The whole table is needed to lay out its columns, so wait for a line that is not a row.
+        let header = table_row.parse_next(i)?;
+        let delimiter_row = table_row.parse_next(i)?;
+        let alignments = delimiter_row
+            .iter()
+            .map(|cell| Alignment::from_delimiter(cell))
+            .collect::<Option<Vec<_>>>();
+        let alignments = match alignments {
+            Some(alignments) if alignments.len() == header.len() => alignments,
+            _ => return Err(ErrMode::from_error_kind(i, ErrorKind::Fail)),
+        };
+
+        let mut rows = vec![];
+        loop {
+            let start = i.checkpoint();
+            match table_row.parse_next(i) {
+                Ok(row) => rows.push(row),
+                Err(ErrMode::Backtrack(_)) => {
+                    i.reset(&start);
+                    break;
+                },
+                Err(err) => return Err(err),
+            }
+        }
+
+        let header = header.iter().map(|cell| plain_text(cell)).collect::<Vec<_>>();
+        let rows = rows
+            .iter()
+            .map(|row| {
+                (0..header.len())
+                    .map(|column| row.get(column).map(|cell| plain_text(cell)).unwrap_or_default())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut widths = header.iter().map(|cell| cell.width().max(1)).collect::<Vec<_>>();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.width());
+            }
+        }
+        if let Some(terminal_width) = state.terminal_width {
+            fit_column_widths(&mut widths, terminal_width);
+        }
+
+        queue_table_border(&mut o, &widths, ('┌', '┬', '┐'))?;
+        queue_table_row(&mut o, &header, &widths, &alignments, true)?;
+        queue_table_border(&mut o, &widths, ('├', '┼', '┤'))?;
+        for row in &rows {
+            queue_table_row(&mut o, row, &widths, &alignments, false)?;
+        }
+        queue_table_border(&mut o, &widths, ('└', '┴', '┘'))?;
+
+        state.column = 0;
+        state.indent = 0;
+        state.set_newline = true;
+
+        Ok(())
+    }
+}
+
+/// Parses a line of a table into its cells.
+fn table_row<'a>(i: &mut Partial<&'a str>) -> PResult<Vec<&'a str>, Error<'a>> {
+    let line = delimited((space0, "|"), till_line_ending, alt((ascii::line_ending, eof))).parse_next(i)?;
+    let line = line.trim_end();
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+
+    // Pipes are escaped, or part of a code span, when not separating cells
+    let mut cells = vec![];
+    let (mut start, mut in_code, mut escaped) = (0, false, false);
+    for (index, c) in line.char_indices() {
+        match c {
+            '|' if !escaped && !in_code => {
+                cells.push(line[start..index].trim());
+                start = index + 1;
+            },
+            '`' if !escaped => in_code = !in_code,
+            _ => (),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells.push(line[start..].trim());
+
+    Ok(cells)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl Alignment {
+    /// Parses a cell of the row separating the header of a table from its body, e.g. `:---:`.
+    fn from_delimiter(cell: &str) -> Option<Self> {
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+            return None;
+        }
+        match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => Some(Self::Center),
+            (false, true) => Some(Self::Right),
+            _ => Some(Self::Left),
+        }
+    }
+}
+
+/// Returns the text of a table cell without inline markdown, which table cells are printed
+/// without.
+fn plain_text(cell: &str) -> String {
+    let mut text = String::with_capacity(cell.len());
+    let mut rest = cell;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix('[') {
+            // Links are printed as their text
+            if let Some((display, after)) = after.split_once("](") {
+                if let Some((_, after)) = after.split_once(')') {
+                    text.push_str(&plain_text(display));
+                    rest = after;
+                    continue;
+                }
+            }
+        }
+        if let Some(marker) = ["**", "__", "~~", "\\|", "`"].iter().find(|m| rest.starts_with(**m)) {
+            if *marker == "\\|" {
+                text.push('|');
+            }
+            rest = &rest[marker.len()..];
+            continue;
+        }
+        text.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// Narrows the widest columns until the table fits within `max_width`, leaving every column at
+/// least one character wide.
+fn fit_column_widths(widths: &mut [usize], max_width: usize) {
+    // Each column is padded by a space on both sides and followed by a border
+    let available = max_width.saturating_sub(3 * widths.len() + 1).max(widths.len());
+    while widths.iter().sum::<usize>() > available {
+        if let Some(widest) = widths.iter_mut().max() {
+            *widest -= 1;
+        }
+    }
+}
+
+/// Wraps `text` at word boundaries into lines of at most `width` columns, breaking words that
+/// are longer than that.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.width() + 1 + word.width() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        for c in word.chars() {
+            if line.width() + c.width().unwrap_or(0) > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn queue_table_border<'a>(
+    mut o: impl Write,
+    widths: &[usize],
+    (left, middle, right): (char, char, char),
+) -> Result<(), ErrMode<Error<'a>>> {
+    let columns = widths.iter().map(|width| "─".repeat(width + 2)).collect::<Vec<_>>();
+    queue(&mut o, style::SetForegroundColor(TABLE_BORDER_COLOR))?;
+    queue(
+        &mut o,
+        style::Print(format!("{left}{}{right}", columns.join(&middle.to_string()))),
+    )?;
+    queue(&mut o, style::ResetColor)?;
+    queue(&mut o, style::Print('\n'))
+}
+
+fn queue_table_row<'a>(
+    mut o: impl Write,
+    cells: &[String],
+    widths: &[usize],
+    alignments: &[Alignment],
+    header: bool,
+) -> Result<(), ErrMode<Error<'a>>> {
+    let cells = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| wrap_text(cell, *width))
+        .collect::<Vec<_>>();
+    let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+    for line in 0..height {
+        queue(&mut o, style::SetForegroundColor(TABLE_BORDER_COLOR))?;
+        queue(&mut o, style::Print('│'))?;
+        for ((cell, width), alignment) in cells.iter().zip(widths).zip(alignments) {
+            let text = cell.get(line).map(String::as_str).unwrap_or_default();
+            let padding = width.saturating_sub(text.width());
+            let (before, after) = match alignment {
+                Alignment::Left => (0, padding),
+                Alignment::Center => (padding / 2, padding - padding / 2),
+                Alignment::Right => (padding, 0),
+            };
+            queue(&mut o, style::ResetColor)?;
+            queue(&mut o, style::Print(" ".repeat(before + 1)))?;
+            if header {
+                queue(&mut o, style::SetAttribute(Attribute::Bold))?;
+            }
+            queue(&mut o, style::Print(text))?;
+            if header {
+                queue(&mut o, style::SetAttribute(Attribute::NormalIntensity))?;
+            }
+            queue(&mut o, style::Print(" ".repeat(after + 1)))?;
+            queue(&mut o, style::SetForegroundColor(TABLE_BORDER_COLOR))?;
+            queue(&mut o, style::Print('│'))?;
+        }
+        queue(&mut o, style::ResetColor)?;
+        queue(&mut o, style::Print('\n'))?;
+    }
+
+    Ok(())
+}
+
 fn bold<'a, 'b>(
     mut o: impl Write + 'b,
     state: &'b mut ParseState,
@@ -433,10 +719,15 @@ fn url<'a, 'b>(
             },
         };
 
-        // Only generate output if the complete URL pattern matches
+        // Only generate output if the complete URL pattern matches. The text links to the URL in
+        // terminals that support hyperlinks, the URL is still printed for those that do not.
         queue_newline_or_advance(&mut o, state, display.width() + 1)?;
         queue(&mut o, style::SetForegroundColor(URL_TEXT_COLOR))?;
-        queue(&mut o, style::Print(format!("{display} ")))?;
+        queue(&mut o, Hyperlink {
+            url: link,
+            text: display,
+        })?;
+        queue(&mut o, style::Print(' '))?;
         queue(&mut o, style::SetForegroundColor(URL_LINK_COLOR))?;
         state.column += link.width();
         queue(&mut o, style::Print(link))?;
@@ -444,6 +735,28 @@ fn url<'a, 'b>(
     }
 }
 
+/// Prints `text` as an OSC 8 hyperlink to `url`.
+struct Hyperlink<'a> {
+    url: &'a str,
+    text: &'a str,
+}
+
+impl Command for Hyperlink<'_> {
+    fn write_ansi(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+        write!(f, "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", self.url, self.text)
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        panic!("tried to execute Hyperlink command using WinAPI, use ANSI instead");
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        true
+    }
+}
+
 fn less_than<'a, 'b>(
     mut o: impl Write + 'b,
     state: &'b mut ParseState,
@@ -496,6 +809,7 @@ fn line_ending<'a, 'b>(
         ascii::line_ending.parse_next(i)?;
 
         state.column = 0;
+        state.indent = 0;
         state.set_newline = true;
 
         queue(&mut o, style::ResetColor)?;
@@ -512,7 +826,7 @@ fn fallback<'a, 'b>(
         let fallback = any.parse_next(i)?;
         if let Some(width) = fallback.width() {
             queue_newline_or_advance(&mut o, state, width)?;
-            if fallback != ' ' || state.column != 1 {
+            if fallback != ' ' || state.column != state.indent + 1 {
                 queue(&mut o, style::Print(fallback))?;
             }
         }
@@ -527,9 +841,10 @@ fn queue_newline_or_advance<'a, 'b>(
     width: usize,
 ) -> Result<(), ErrMode<Error<'a>>> {
     if let Some(terminal_width) = state.terminal_width {
-        if state.column > 0 && state.column + width > terminal_width {
-            state.column = width;
+        if state.column > state.indent && state.column + width > terminal_width {
+            state.column = state.indent + width;
             queue(&mut o, style::Print('\n'))?;
+            queue(&mut o, style::Print(" ".repeat(state.indent)))?;
             return Ok(());
         }
     }
@@ -714,7 +1029,11 @@ mod tests {
     ]);
     validate!(url_1, "[google](google.com)", [
         style::SetForegroundColor(URL_TEXT_COLOR),
-        style::Print("google "),
+        Hyperlink {
+            url: "google.com",
+            text: "google"
+        },
+        style::Print(' '),
         style::SetForegroundColor(URL_LINK_COLOR),
         style::Print("google.com"),
         style::ResetColor,
@@ -751,8 +1070,16 @@ mod tests {
         style::Print("# Hello World"),
     ]);
     validate!(bulleted_item_1, "- bullet", [style::Print("• bullet")]);
+    validate!(task_item_1, "- [ ] task", [style::Print("☐ task")]);
+    validate!(task_item_2, "- [x] task", [
+        style::SetForegroundColor(TASK_DONE_COLOR),
+        style::Print("☑"),
+        style::ResetColor,
+        style::Print(" task"),
+    ]);
     validate!(bulleted_item_2, "* bullet", [style::Print("• bullet")]);
     validate!(numbered_item_1, "1. number", [style::Print("1. number")]);
+    validate!(numbered_item_2, "1) number", [style::Print("1) number")]);
     validate!(blockquote_1, "> hello", [
         style::SetForegroundColor(BLOCKQUOTE_COLOR),
         style::Print("│ hello"),
@@ -818,4 +1145,92 @@ mod tests {
         [style::Print("+ % @ . ?")],
         true
     );
+
+    /// Renders `input` as a complete response, without styling.
+    fn render(input: &str, terminal_width: usize) -> String {
+        let mut state = ParseState::new(Some(terminal_width), None);
+        let mut output = vec![];
+        let mut offset = 0;
+        while offset < input.len() {
+            let mut partial = Partial::new(&input[offset..]);
+            let _ = partial.complete();
+            let parsed = interpret_markdown(partial, &mut output, &mut state).unwrap();
+            offset += parsed.offset_from(&partial);
+            state.newline = state.set_newline;
+            state.set_newline = false;
+        }
+        let output = String::from_utf8(strip_ansi_escapes::strip(output)).unwrap();
+        output.lines().map(str::trim_end).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn table_snapshot() {
+        let input = "Fruit:\n| Name | Count | Notes |\n|:-----|------:|:-----:|\n| **apples** | 3 | [red](https://apples.com) |\n| `kiwi` | 12 | a \\| b |\nDone.\n";
+        insta::assert_snapshot!(render(input, 80), @r"
+        Fruit:
+        ┌────────┬───────┬───────┐
+        │ Name   │ Count │ Notes │
+        ├────────┼───────┼───────┤
+        │ apples │     3 │  red  │
+        │ kiwi   │    12 │ a | b │
+        └────────┴───────┴───────┘
+        Done.
+        ");
+    }
+
+    #[test]
+    fn table_wraps_to_terminal_width_snapshot() {
+        let input = "| Option | Description |\n|---|---|\n| `--verbose` | Prints every request and response to the terminal as they are sent |\n";
+        insta::assert_snapshot!(render(input, 36), @r"
+        ┌───────────┬──────────────────────┐
+        │ Option    │ Description          │
+        ├───────────┼──────────────────────┤
+        │ --verbose │ Prints every request │
+        │           │ and response to the  │
+        │           │ terminal as they are │
+        │           │ sent                 │
+        └───────────┴──────────────────────┘
+        ");
+    }
+
+    #[test]
+    fn table_waits_for_end_of_table() {
+        let mut state = ParseState::new(Some(80), None);
+        let input = Partial::new("| a | b |\n|---|---|\n| 1 | 2 |\n");
+        let err = interpret_markdown(input, vec![], &mut state).unwrap_err();
+        assert!(err.into_inner().is_none(), "expected incomplete input");
+    }
+
+    #[test]
+    fn not_a_table_snapshot() {
+        insta::assert_snapshot!(render("| not a table\nsome text\n", 80), @r"
+        | not a table
+        some text
+        ");
+    }
+
+    #[test]
+    fn nested_list_snapshot() {
+        let input = "- one\n  - two\n    - three\n1. first\n2) second\n   - [ ] todo\n   - [x] done\n";
+        insta::assert_snapshot!(render(input, 80), @r"
+        • one
+          ◦ two
+            ▪ three
+        1. first
+        2) second
+           ☐ todo
+           ☑ done
+        ");
+    }
+
+    #[test]
+    fn wrapped_list_item_snapshot() {
+        let input = "- top level item that is long enough to wrap\n  - nested item that is long enough to wrap too\n";
+        insta::assert_snapshot!(render(input, 30), @r"
+        • top level item that is long
+          enough to wrap
+          ◦ nested item that is long
+            enough to wrap too
+        ");
+    }
 }
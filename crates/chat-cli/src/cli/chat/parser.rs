@@ -32,6 +32,7 @@ use super::message::{
 use crate::api_client::model::{
     ChatResponseStream,
     ConversationState,
+    TokenUsage,
 };
 use crate::api_client::send_message_output::SendMessageOutput;
 use crate::api_client::{
@@ -312,6 +313,8 @@ struct ResponseParser {
     received_response_size: usize,
     time_to_first_chunk: Option<Duration>,
     time_between_chunks: Vec<Duration>,
+    /// Token usage reported by the backend, if any.
+    token_usage: Option<TokenUsage>,
 }
 
 impl ResponseParser {
@@ -345,6 +348,7 @@ impl ResponseParser {
             received_response_size: 0,
             time_to_first_chunk: None,
             time_between_chunks: Vec::new(),
+            token_usage: None,
             request_metadata,
             cancel_token,
         }
@@ -568,6 +572,11 @@ impl ResponseParser {
                         ChatResponseStream::ToolUseEvent { input, .. } => {
                             self.received_response_size += input.as_ref().map(String::len).unwrap_or_default();
                         },
+                        ChatResponseStream::MetadataEvent { token_usage } => {
+                            if token_usage.is_some() {
+                                self.token_usage = *token_usage;
+                            }
+                        },
                         _ => {
                             warn!(?r, "received unexpected event from the response stream");
                         },
@@ -615,6 +624,7 @@ impl ResponseParser {
                 .map(|t| (t.id.clone(), t.name.clone()))
                 .collect::<_>(),
             model_id: self.model_id.clone(),
+            token_usage: self.token_usage,
         }
     }
 }
@@ -666,6 +676,9 @@ pub struct RequestMetadata {
     pub model_id: Option<String>,
     /// Meta tags for the request.
     pub message_meta_tags: Vec<MessageMetaTag>,
+    /// Token usage of the request and its response, if reported by the backend.
+    #[serde(default)]
+    pub token_usage: Option<TokenUsage>,
 }
 
 fn system_time_to_unix_ms(time: SystemTime) -> u64 {
@@ -753,4 +766,43 @@ mod tests {
             "assistant text preceding a code reference should be ignored as this indicates licensed code is being returned"
         );
     }
+
+    #[tokio::test]
+    async fn test_response_parser_records_token_usage() {
+        let token_usage = TokenUsage {
+            uncached_input_tokens: 20,
+            cache_read_input_tokens: 1200,
+            cache_write_input_tokens: 0,
+            output_tokens: 5,
+        };
+        let mut events = vec![
+            ChatResponseStream::AssistantResponseEvent {
+                content: "hi".to_string(),
+            },
+            ChatResponseStream::MetadataEvent {
+                token_usage: Some(token_usage),
+            },
+        ];
+        events.reverse();
+        let mut parser = ResponseParser::new(
+            SendMessageOutput::Mock(events),
+            "".to_string(),
+            None,
+            1,
+            vec![],
+            mpsc::channel(32).0,
+            Instant::now(),
+            SystemTime::now(),
+            CancellationToken::new(),
+            Arc::new(Mutex::new(None)),
+        );
+
+        assert!(matches!(parser.recv().await.unwrap(), ResponseEvent::AssistantText(_)));
+        match parser.recv().await.unwrap() {
+            ResponseEvent::EndStream { request_metadata, .. } => {
+                assert_eq!(request_metadata.token_usage, Some(token_usage));
+            },
+            other => panic!("expected the end of the stream, got {:?}", other),
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::path::PathBuf;
 
 use eyre::Result;
 use rustyline::completion::{
@@ -36,6 +37,7 @@ use rustyline::{
 };
 use winnow::stream::AsChar;
 
+use super::cli::prompts::local_prompt_dirs;
 pub use super::prompt_parser::generate_prompt;
 use super::prompt_parser::parse_prompt_components;
 use super::tool_manager::{
@@ -43,12 +45,16 @@ use super::tool_manager::{
     PromptQueryResult,
 };
 use crate::database::settings::Setting;
-use crate::os::Os;
+use crate::os::{
+    Fs,
+    Os,
+};
 
 pub const COMMANDS: &[&str] = &[
     "/clear",
     "/help",
     "/editor",
+    "/paste",
     "/issue",
     "/quit",
     "/tools",
@@ -67,6 +73,9 @@ pub const COMMANDS: &[&str] = &[
     "/agent set",
     "/agent schema",
     "/prompts",
+    "/prompts list",
+    "/prompts create",
+    "/prompts edit",
     "/context",
     "/context help",
     "/context show",
@@ -84,9 +93,15 @@ pub const COMMANDS: &[&str] = &[
     "/hooks disable-all",
     "/compact",
     "/compact help",
+    "/pin",
+    "/pin list",
+    "/pin remove",
+    "/plan",
     "/usage",
+    "/usage --history",
     "/save",
     "/load",
+    "/export",
     "/subscribe",
 ];
 
@@ -144,6 +159,9 @@ impl PathCompleter {
 pub struct PromptCompleter {
     sender: PromptQuerySender,
     receiver: RefCell<PromptQueryResponseReceiver>,
+    /// The filesystem and directories containing local prompt files, see
+    /// [super::cli::prompts::LocalPrompt]
+    local_prompt_dirs: Option<(Fs, Vec<PathBuf>)>,
 }
 
 impl PromptCompleter {
@@ -151,9 +169,32 @@ impl PromptCompleter {
         PromptCompleter {
             sender,
             receiver: RefCell::new(receiver),
+            local_prompt_dirs: None,
         }
     }
 
+    fn complete_local_prompt(&self, word: &str) -> Vec<String> {
+        let Some((fs, dirs)) = &self.local_prompt_dirs else {
+            return Vec::new();
+        };
+        let mut matches = dirs
+            .iter()
+            .filter_map(|dir| fs.read_dir_sync(dir).ok())
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "md" {
+                    return None;
+                }
+                let name = path.file_stem()?.to_str()?;
+                name.contains(word).then(|| format!("@{name}"))
+            })
+            .collect::<Vec<_>>();
+        matches.sort();
+        matches.dedup();
+        matches
+    }
+
     fn complete_prompt(&self, word: &str) -> Result<Vec<String>, ReadlineError> {
         let sender = &self.sender;
         let receiver = self.receiver.borrow_mut();
@@ -205,6 +246,8 @@ impl PromptCompleter {
 pub struct ChatCompleter {
     path_completer: PathCompleter,
     prompt_completer: PromptCompleter,
+    /// Custom slash commands declared by the active agent, including the leading slash
+    custom_commands: Vec<String>,
 }
 
 impl ChatCompleter {
@@ -212,8 +255,14 @@ impl ChatCompleter {
         Self {
             path_completer: PathCompleter::new(),
             prompt_completer: PromptCompleter::new(sender, receiver),
+            custom_commands: Vec::new(),
         }
     }
+
+    fn with_local_prompt_dirs(mut self, fs: Fs, dirs: Vec<PathBuf>) -> Self {
+        self.prompt_completer.local_prompt_dirs = Some((fs, dirs));
+        self
+    }
 }
 
 impl Completer for ChatCompleter {
@@ -229,16 +278,30 @@ impl Completer for ChatCompleter {
 
         // Handle command completion
         if word.starts_with('/') {
-            return Ok(complete_command(word, start));
+            let (start, mut completions) = complete_command(word, start);
+            completions.extend(
+                self.custom_commands
+                    .iter()
+                    .filter(|c| c.starts_with(word) && !completions.contains(c))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+            return Ok((start, completions));
         }
 
         if line.starts_with('@') {
             let search_word = line.strip_prefix('@').unwrap_or("");
-            if let Ok(completions) = self.prompt_completer.complete_prompt(search_word) {
-                if !completions.is_empty() {
-                    return Ok((0, completions));
+            let mut completions = self.prompt_completer.complete_local_prompt(search_word);
+            if let Ok(mcp_completions) = self.prompt_completer.complete_prompt(search_word) {
+                for completion in mcp_completions {
+                    if !completions.contains(&completion) {
+                        completions.push(completion);
+                    }
                 }
             }
+            if !completions.is_empty() {
+                return Ok((0, completions));
+            }
         }
 
         // Handle file path completion as fallback
@@ -360,6 +423,11 @@ impl ChatHelper {
     pub fn update_hinter_history(&mut self, command: &str) {
         self.hinter.update_history(command);
     }
+
+    /// Replaces the custom slash commands offered for tab completion
+    pub fn set_custom_commands(&mut self, commands: Vec<String>) {
+        self.completer.custom_commands = commands;
+    }
 }
 
 impl Validator for ChatHelper {
@@ -394,6 +462,11 @@ impl Highlighter for ChatHelper {
                 result.push_str(&format!("[{}] ", profile).cyan().to_string());
             }
 
+            // Add plan mode indicator if present
+            if components.plan {
+                result.push_str(&"plan ".yellow().to_string());
+            }
+
             // Add warning symbol if present
             if components.warning {
                 result.push_str(&"!".red().to_string());
@@ -432,7 +505,10 @@ pub fn rl(
         .get_bool(Setting::ChatEnableHistoryHints)
         .unwrap_or(false);
     let h = ChatHelper {
-        completer: ChatCompleter::new(sender, receiver),
+        completer: ChatCompleter::new(sender, receiver).with_local_prompt_dirs(
+            os.fs.clone(),
+            local_prompt_dirs(os).into_iter().map(|(_, dir)| dir).collect(),
+        ),
         hinter: ChatHinter::new(history_hints_enabled),
         validator: MultiLineValidator,
     };
@@ -639,4 +715,29 @@ mod tests {
         let hint = hinter.hint(line, pos, &ctx);
         assert_eq!(hint, None);
     }
+
+    #[tokio::test]
+    async fn test_complete_local_prompt() {
+        let os = Os::new().await.unwrap();
+        let dirs = local_prompt_dirs(&os)
+            .into_iter()
+            .map(|(_, dir)| dir)
+            .collect::<Vec<_>>();
+        for dir in &dirs {
+            os.fs.create_dir_all(dir).await.unwrap();
+            os.fs.write(dir.join("review.md"), "Review this").await.unwrap();
+        }
+        os.fs.write(dirs[0].join("revert.md"), "Revert this").await.unwrap();
+        os.fs.write(dirs[0].join("reverse.txt"), "not a prompt").await.unwrap();
+
+        let (prompt_request_sender, _) = tokio::sync::broadcast::channel::<PromptQuery>(5);
+        let (_, prompt_response_receiver) = tokio::sync::broadcast::channel::<PromptQueryResult>(5);
+        let completer = ChatCompleter::new(prompt_request_sender, prompt_response_receiver)
+            .with_local_prompt_dirs(os.fs.clone(), dirs);
+
+        assert_eq!(completer.prompt_completer.complete_local_prompt("rev"), vec![
+            "@revert".to_string(),
+            "@review".to_string()
+        ]);
+    }
 }
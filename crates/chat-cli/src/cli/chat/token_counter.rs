@@ -1,5 +1,10 @@
 use std::ops::Deref;
 
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
 use super::message::{
     AssistantMessage,
     ToolUseResult,
@@ -7,10 +12,7 @@ use super::message::{
     UserMessage,
     UserMessageContent,
 };
-use crate::cli::chat::conversation::{
-    BackendConversationState,
-    ConversationSize,
-};
+use crate::cli::chat::cli::model::ModelInfo;
 
 #[derive(Debug, Clone, Copy)]
 pub struct CharCount(usize);
@@ -60,6 +62,12 @@ impl Deref for TokenCount {
     }
 }
 
+impl From<usize> for TokenCount {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
 impl From<CharCount> for TokenCount {
     fn from(value: CharCount) -> Self {
         Self(TokenCounter::count_tokens_char_count(value.value()))
@@ -72,109 +80,151 @@ impl std::fmt::Display for TokenCount {
     }
 }
 
+impl std::ops::Add for TokenCount {
+    type Output = TokenCount;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.value() + rhs.value())
+    }
+}
+
+impl std::iter::Sum for TokenCount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self(0), |acc, v| acc + v)
+    }
+}
+
+/// The BPE vocabulary used to count tokens for a model. The vocabularies are bundled with the
+/// binary, so counting works offline.
+///
+/// Claude's tokenizer isn't public, so Claude models (and any other model without a published
+/// vocabulary) are counted with `cl100k_base`, which is much closer to their real counts than a
+/// characters per token ratio, especially for code and non-English text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tokenizer {
+    #[default]
+    Cl100kBase,
+    O200kBase,
+}
+
+impl Tokenizer {
+    /// Returns the tokenizer for the given model, or the default one when no model is selected.
+    pub fn for_model(model_info: Option<&ModelInfo>) -> Self {
+        model_info.map_or_else(Self::default, |m| Self::for_model_id(&m.model_id))
+    }
+
+    /// Returns the tokenizer for the given model id.
+    pub fn for_model_id(model_id: &str) -> Self {
+        let model_id = model_id.to_lowercase();
+        if model_id.contains("gpt") || model_id.starts_with("openai") {
+            Self::O200kBase
+        } else {
+            Self::Cl100kBase
+        }
+    }
+
+    /// Returns the exact number of tokens in the content.
+    pub fn count_tokens(self, content: &str) -> TokenCount {
+        let bpe = match self {
+            Self::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            Self::O200kBase => tiktoken_rs::o200k_base_singleton(),
+        };
+        bpe.encode_ordinary(content).len().into()
+    }
+}
+
 pub struct TokenCounter;
 
 impl TokenCounter {
     pub const TOKEN_TO_CHAR_RATIO: usize = 4;
 
-    /// Estimates the number of tokens in the input content.
-    /// Currently uses a simple heuristic: content length / TOKEN_TO_CHAR_RATIO
+    /// Estimates the number of tokens in the input content with the default [Tokenizer].
     ///
     /// Rounds up to the nearest multiple of 10 to avoid giving users a false sense of precision.
     pub fn count_tokens(content: &str) -> usize {
-        Self::count_tokens_char_count(content.len())
+        Self::count_tokens_with(content, Tokenizer::default())
     }
 
-    fn count_tokens_char_count(count: usize) -> usize {
-        (count / Self::TOKEN_TO_CHAR_RATIO + 5) / 10 * 10
+    /// Estimates the number of tokens in the input content with the given [Tokenizer], rounded
+    /// like [TokenCounter::count_tokens].
+    pub fn count_tokens_with(content: &str, tokenizer: Tokenizer) -> usize {
+        Self::round(*tokenizer.count_tokens(content))
     }
 
-    pub const fn token_to_chars(token: usize) -> usize {
-        token * Self::TOKEN_TO_CHAR_RATIO
+    /// Estimates the number of tokens from a number of characters, for when the text itself is
+    /// not available.
+    fn count_tokens_char_count(count: usize) -> usize {
+        Self::round(count / Self::TOKEN_TO_CHAR_RATIO)
     }
-}
-
-/// A trait for types that represent some number of characters (aka bytes). For use in calculating
-/// context window size utilization.
-pub trait CharCounter {
-    /// Returns the number of characters contained within this type.
-    ///
-    /// One "character" is essentially the same as one "byte"
-    fn char_count(&self) -> CharCount;
-}
 
-impl CharCounter for BackendConversationState<'_> {
-    fn char_count(&self) -> CharCount {
-        self.calculate_conversation_size().char_count()
+    fn round(count: usize) -> usize {
+        (count + 5) / 10 * 10
     }
 }
 
-impl CharCounter for ConversationSize {
-    fn char_count(&self) -> CharCount {
-        self.user_messages + self.assistant_messages + self.context_messages
-    }
+/// A trait for types that represent text sent to the model. For use in calculating context window
+/// utilization.
+pub trait TokenCountable {
+    /// Returns the number of tokens contained within this type, as counted by `tokenizer`.
+    fn token_count(&self, tokenizer: Tokenizer) -> TokenCount;
 }
 
-impl CharCounter for UserMessage {
-    fn char_count(&self) -> CharCount {
-        let mut total_chars = 0;
-        total_chars += self.additional_context().len();
+impl TokenCountable for UserMessage {
+    fn token_count(&self, tokenizer: Tokenizer) -> TokenCount {
+        let mut total = tokenizer.count_tokens(self.additional_context());
         match self.content() {
             UserMessageContent::Prompt { prompt } => {
-                total_chars += prompt.len();
+                total = total + tokenizer.count_tokens(prompt);
             },
             UserMessageContent::CancelledToolUses {
                 prompt,
                 tool_use_results,
             } => {
-                total_chars += prompt.as_ref().map_or(0, String::len);
-                total_chars += tool_use_results.as_slice().char_count().0;
+                total = total + tokenizer.count_tokens(prompt.as_deref().unwrap_or_default());
+                total = total + tool_use_results.as_slice().token_count(tokenizer);
             },
             UserMessageContent::ToolUseResults { tool_use_results } => {
-                total_chars += tool_use_results.as_slice().char_count().0;
+                total = total + tool_use_results.as_slice().token_count(tokenizer);
             },
         }
-        total_chars.into()
+        total
     }
 }
 
-impl CharCounter for AssistantMessage {
-    fn char_count(&self) -> CharCount {
-        let mut total_chars = 0;
-        total_chars += self.content().len();
+impl TokenCountable for AssistantMessage {
+    fn token_count(&self, tokenizer: Tokenizer) -> TokenCount {
+        let mut total = tokenizer.count_tokens(self.content());
         if let Some(tool_uses) = self.tool_uses() {
-            total_chars += tool_uses
-                .iter()
-                .map(|v| calculate_value_char_count(&v.args))
-                .reduce(|acc, e| acc + e)
-                .unwrap_or_default();
+            total = total
+                + tool_uses
+                    .iter()
+                    .map(|v| calculate_value_token_count(&v.args, tokenizer))
+                    .sum();
         }
-        total_chars.into()
+        total
     }
 }
 
-impl CharCounter for &[ToolUseResult] {
-    fn char_count(&self) -> CharCount {
+impl TokenCountable for &[ToolUseResult] {
+    fn token_count(&self, tokenizer: Tokenizer) -> TokenCount {
         self.iter()
             .flat_map(|v| &v.content)
-            .fold(0, |acc, v| {
-                acc + match v {
-                    ToolUseResultBlock::Json(v) => calculate_value_char_count(v),
-                    ToolUseResultBlock::Text(s) => s.len(),
-                }
+            .map(|v| match v {
+                ToolUseResultBlock::Json(v) => calculate_value_token_count(v, tokenizer),
+                ToolUseResultBlock::Text(s) => tokenizer.count_tokens(s),
             })
-            .into()
+            .sum()
     }
 }
 
-fn calculate_value_char_count(document: &serde_json::Value) -> usize {
+fn calculate_value_token_count(document: &serde_json::Value, tokenizer: Tokenizer) -> TokenCount {
     match document {
-        serde_json::Value::Null => 1,
-        serde_json::Value::Bool(_) => 1,
-        serde_json::Value::Number(_) => 1,
-        serde_json::Value::String(s) => s.len(),
-        serde_json::Value::Array(vec) => vec.iter().fold(0, |acc, v| acc + calculate_value_char_count(v)),
-        serde_json::Value::Object(map) => map.values().fold(0, |acc, v| acc + calculate_value_char_count(v)),
+        serde_json::Value::Null => 1.into(),
+        serde_json::Value::Bool(_) => 1.into(),
+        serde_json::Value::Number(_) => 1.into(),
+        serde_json::Value::String(s) => tokenizer.count_tokens(s),
+        serde_json::Value::Array(vec) => vec.iter().map(|v| calculate_value_token_count(v, tokenizer)).sum(),
+        serde_json::Value::Object(map) => map.values().map(|v| calculate_value_token_count(v, tokenizer)).sum(),
     }
 }
 
@@ -186,66 +236,68 @@ mod tests {
     #[test]
     fn test_token_count() {
         let text = "This is a test sentence.";
-        let count = TokenCounter::count_tokens(text);
-        assert_eq!(count, (text.len() / 3 + 5) / 10 * 10);
+        assert_eq!(*Tokenizer::Cl100kBase.count_tokens(text), 6);
+        assert_eq!(TokenCounter::count_tokens(text), 10);
     }
 
     #[test]
-    fn test_calculate_value_char_count() {
-        // Test simple types
+    fn test_tokenizer_for_model() {
+        let model = |model_id: &str| ModelInfo {
+            model_id: model_id.to_string(),
+            model_name: None,
+            context_window_tokens: 200_000,
+        };
+        assert_eq!(Tokenizer::for_model(None), Tokenizer::Cl100kBase);
         assert_eq!(
-            calculate_value_char_count(&serde_json::Value::String("hello".to_string())),
-            5
+            Tokenizer::for_model(Some(&model("CLAUDE_SONNET_4_20250514_V1_0"))),
+            Tokenizer::Cl100kBase
         );
         assert_eq!(
-            calculate_value_char_count(&serde_json::Value::Number(serde_json::Number::from(123))),
-            1
+            Tokenizer::for_model(Some(&model("OPENAI_GPT_OSS_120B_1_0"))),
+            Tokenizer::O200kBase
         );
-        assert_eq!(calculate_value_char_count(&serde_json::Value::Bool(true)), 1);
-        assert_eq!(calculate_value_char_count(&serde_json::Value::Null), 1);
-
-        // Test array
-        let array = serde_json::Value::Array(vec![
-            serde_json::Value::String("test".to_string()),
-            serde_json::Value::Number(serde_json::Number::from(42)),
-            serde_json::Value::Bool(false),
-        ]);
-        assert_eq!(calculate_value_char_count(&array), 6); // "test" (4) + Number (1) + Bool (1)
-
-        // Test object
-        let mut obj = serde_json::Map::new();
-        obj.insert("key1".to_string(), serde_json::Value::String("value1".to_string()));
-        obj.insert(
-            "key2".to_string(),
-            serde_json::Value::Number(serde_json::Number::from(99)),
+    }
+
+    #[test]
+    fn test_token_count_is_not_chars_per_token() {
+        // Code and non-English text take up far more tokens than a characters per token ratio
+        // suggests
+        let code = "fn main() { let x: Vec<u8> = vec![0x1f, 0x2e]; println!(\"{x:?}\"); }";
+        assert!(*Tokenizer::Cl100kBase.count_tokens(code) > code.len() / TokenCounter::TOKEN_TO_CHAR_RATIO);
+        let japanese = "日本語のテキストはトークンが多くなります";
+        assert!(*Tokenizer::Cl100kBase.count_tokens(japanese) > japanese.len() / TokenCounter::TOKEN_TO_CHAR_RATIO);
+    }
+
+    #[test]
+    fn test_calculate_value_token_count() {
+        let tokenizer = Tokenizer::default();
+        let count = |text: &str| *tokenizer.count_tokens(text);
+        let value_count = |value: &serde_json::Value| *calculate_value_token_count(value, tokenizer);
+
+        // Test simple types
+        assert_eq!(
+            value_count(&serde_json::Value::String("hello world".to_string())),
+            count("hello world")
         );
-        let object = serde_json::Value::Object(obj);
-        assert_eq!(calculate_value_char_count(&object), 7); // "value1" (6) + Number (1)
-
-        // Test nested structure
-        let mut nested_obj = serde_json::Map::new();
-        let mut inner_obj = serde_json::Map::new();
-        inner_obj.insert(
-            "inner_key".to_string(),
-            serde_json::Value::String("inner_value".to_string()),
+        assert_eq!(
+            value_count(&serde_json::Value::Number(serde_json::Number::from(123))),
+            1
         );
-        nested_obj.insert("outer_key".to_string(), serde_json::Value::Object(inner_obj));
-        nested_obj.insert(
-            "array_key".to_string(),
-            serde_json::Value::Array(vec![
-                serde_json::Value::String("item1".to_string()),
-                serde_json::Value::String("item2".to_string()),
-            ]),
+        assert_eq!(value_count(&serde_json::Value::Bool(true)), 1);
+        assert_eq!(value_count(&serde_json::Value::Null), 1);
+
+        // Test nested structure, where keys aren't counted
+        let complex = serde_json::json!({
+            "outer_key": { "inner_key": "inner_value" },
+            "array_key": ["item1", "item2", 42, false],
+        });
+        assert_eq!(
+            value_count(&complex),
+            count("inner_value") + count("item1") + count("item2") + 2
         );
 
-        let complex = serde_json::Value::Object(nested_obj);
-        assert_eq!(calculate_value_char_count(&complex), 21); // "inner_value" (11) + "item1" (5) + "item2" (5)
-
         // Test empty structures
-        assert_eq!(calculate_value_char_count(&serde_json::Value::Array(vec![])), 0);
-        assert_eq!(
-            calculate_value_char_count(&serde_json::Value::Object(serde_json::Map::new())),
-            0
-        );
+        assert_eq!(value_count(&serde_json::Value::Array(vec![])), 0);
+        assert_eq!(value_count(&serde_json::Value::Object(serde_json::Map::new())), 0);
     }
 }
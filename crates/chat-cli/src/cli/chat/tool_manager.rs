@@ -77,8 +77,13 @@ use crate::cli::chat::tools::fs_read::FsRead;
 use crate::cli::chat::tools::fs_write::FsWrite;
 use crate::cli::chat::tools::gh_issue::GhIssue;
 use crate::cli::chat::tools::knowledge::Knowledge;
+use crate::cli::chat::tools::propose_plan::{
+    PROPOSE_PLAN_TOOL_NAME,
+    ProposePlan,
+};
 use crate::cli::chat::tools::thinking::Thinking;
 use crate::cli::chat::tools::use_aws::UseAws;
+use crate::cli::chat::tools::use_subagent::UseSubagent;
 use crate::cli::chat::tools::{
     Tool,
     ToolOrigin,
@@ -665,6 +670,7 @@ impl ToolManager {
                     },
                         "required": ["command"]})),
                     tool_origin: ToolOrigin::Native,
+                    annotations: None,
                 });
             }
 
@@ -795,6 +801,10 @@ impl ToolManager {
             "report_issue" => Tool::GhIssue(serde_json::from_value::<GhIssue>(value.args).map_err(map_err)?),
             "thinking" => Tool::Thinking(serde_json::from_value::<Thinking>(value.args).map_err(map_err)?),
             "knowledge" => Tool::Knowledge(serde_json::from_value::<Knowledge>(value.args).map_err(map_err)?),
+            "use_subagent" => Tool::UseSubagent(serde_json::from_value::<UseSubagent>(value.args).map_err(map_err)?),
+            PROPOSE_PLAN_TOOL_NAME => {
+                Tool::ProposePlan(serde_json::from_value::<ProposePlan>(value.args).map_err(map_err)?)
+            },
             // Note that this name is namespaced with server_name{DELIMITER}tool_name
             name => {
                 // Note: tn_map also has tools that underwent no transformation. In otherwords, if
@@ -833,17 +843,66 @@ impl ToolManager {
                 params.insert("name".to_owned(), serde_json::Value::String(tool_name.to_owned()));
                 params.insert("arguments".to_owned(), value.args);
                 let params = serde_json::Value::Object(params);
+                let read_only = self
+                    .schema
+                    .get(name)
+                    .and_then(|spec| spec.annotations.as_ref())
+                    .and_then(|annotations| annotations.read_only_hint)
+                    .unwrap_or(false);
                 let custom_tool = CustomTool {
                     name: tool_name.to_owned(),
                     client: client.clone(),
                     method: "tools/call".to_owned(),
                     params: Some(params),
+                    read_only,
                 };
                 Tool::Custom(custom_tool)
             },
         })
     }
 
+    /// Creates a tool manager for a sub-agent running `agent`. It exposes the tools of this manager
+    /// that the agent lists, except for `use_subagent`, and shares the clients of the MCP servers
+    /// they come from rather than launching servers of its own.
+    pub fn for_subagent(&self, agent: &Agent) -> Self {
+        let allow_all = agent.tools.len() == 1 && agent.tools.first().is_some_and(|t| t == "*");
+        let lists = |name: &str, spec: &ToolSpec| match &spec.tool_origin {
+            ToolOrigin::Native => agent
+                .tools
+                .iter()
+                .any(|t| t == "@builtin" || t == name || *t == format!("@builtin/{name}")),
+            ToolOrigin::McpServer(_) => self.tn_map.get(name).is_some_and(|info| {
+                let server_pattern = format!("@{}", info.server_name);
+                let tool_pattern = format!("{server_pattern}{MCP_SERVER_TOOL_DELIMITER}{}", info.host_tool_name);
+                agent.tools.iter().any(|t| *t == server_pattern || *t == tool_pattern)
+            }),
+        };
+
+        let schema = self
+            .schema
+            .iter()
+            .filter(|(name, spec)| {
+                name.as_str() == DUMMY_TOOL_NAME
+                    || (name.as_str() != "use_subagent" && (allow_all || lists(name, spec)))
+            })
+            .map(|(name, spec)| (name.clone(), spec.clone()))
+            .collect::<HashMap<_, _>>();
+        let tn_map = self
+            .tn_map
+            .iter()
+            .filter(|(name, _)| schema.contains_key(*name))
+            .map(|(name, info)| (name.clone(), info.clone()))
+            .collect();
+
+        Self {
+            conversation_id: self.conversation_id.clone(),
+            clients: self.clients.clone(),
+            tn_map,
+            schema,
+            ..Default::default()
+        }
+    }
+
     /// Updates tool managers various states with new information
     pub async fn update(&mut self) {
         // A hashmap of <tool name, tool spec>
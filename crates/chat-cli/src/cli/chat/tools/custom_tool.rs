@@ -2,6 +2,8 @@ use std::collections::HashMap;
 use std::io::Write;
 use std::sync::Arc;
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use crossterm::{
     queue,
     style,
@@ -16,13 +18,25 @@ use serde::{
 use tokio::sync::RwLock;
 use tracing::warn;
 
-use super::InvokeOutput;
+use super::{
+    InvokeOutput,
+    OutputKind,
+};
+use crate::api_client::model::{
+    ImageBlock,
+    ImageSource,
+};
 use crate::cli::agent::{
     Agent,
     PermissionEvalResult,
 };
 use crate::cli::chat::CONTINUATION_LINE;
 use crate::cli::chat::token_counter::TokenCounter;
+use crate::cli::chat::util::images::{
+    ImageMetadata,
+    filter_images,
+    image_format_from_mime_type,
+};
 use crate::mcp_client::{
     Client as McpClient,
     ClientConfig as McpClientConfig,
@@ -38,6 +52,47 @@ use crate::os::Os;
 use crate::util::MCP_SERVER_TOOL_DELIMITER;
 use crate::util::pattern_matching::matches_any_pattern;
 
+/// Converts the result of an MCP tool call into tool output. Image content blocks of a supported
+/// format are forwarded as images, with their base64 data redacted from the text of the result.
+fn tool_call_result_to_output(
+    tool_name: &str,
+    mut result: ToolCallResult,
+    updates: &mut impl Write,
+) -> Result<OutputKind> {
+    let mut images = Vec::new();
+    for content in &mut result.content {
+        if let MessageContent::Image { data, mime_type } = content {
+            let image = image_format_from_mime_type(mime_type).zip(BASE64_STANDARD.decode(data.as_bytes()).ok());
+            *data = match image {
+                Some((format, bytes)) => {
+                    let size = bytes.len() as u64;
+                    images.push((
+                        ImageBlock {
+                            format,
+                            source: ImageSource::Bytes(bytes),
+                        },
+                        ImageMetadata {
+                            filepath: tool_name.to_string(),
+                            size,
+                            filename: format!("{tool_name} image {}", images.len() + 1),
+                        },
+                    ));
+                    format!("Image of size {size} supplied as an image block")
+                },
+                None => format!("Redacted base64 encoded string of an image of size {}", data.len()),
+            };
+        }
+    }
+
+    if images.is_empty() {
+        return Ok(OutputKind::Json(serde_json::json!(result)));
+    }
+    Ok(OutputKind::Mixed {
+        text: serde_json::to_string(&result)?,
+        images: filter_images(updates, images),
+    })
+}
+
 // TODO: support http transport type
 #[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq, JsonSchema)]
 pub struct CustomToolConfig {
@@ -200,10 +255,12 @@ pub struct CustomTool {
     /// Optional parameters to pass to the tool when invoking the method.
     /// Structured as a JSON value to accommodate various parameter types and structures.
     pub params: Option<serde_json::Value>,
+    /// Whether the server annotated the tool with `readOnlyHint`
+    pub read_only: bool,
 }
 
 impl CustomTool {
-    pub async fn invoke(&self, _os: &Os, _updates: impl Write) -> Result<InvokeOutput> {
+    pub async fn invoke(&self, _os: &Os, mut updates: impl Write) -> Result<InvokeOutput> {
         // Assuming a response shape as per https://spec.modelcontextprotocol.io/specification/2024-11-05/server/tools/#calling-tools
         let resp = self.client.request(self.method.as_str(), self.params.clone()).await?;
         let result = match resp.result {
@@ -217,16 +274,9 @@ impl CustomTool {
         };
 
         match serde_json::from_value::<ToolCallResult>(result.clone()) {
-            Ok(mut de_result) => {
-                for content in &mut de_result.content {
-                    if let MessageContent::Image { data, .. } = content {
-                        *data = format!("Redacted base64 encoded string of an image of size {}", data.len());
-                    }
-                }
-                Ok(InvokeOutput {
-                    output: super::OutputKind::Json(serde_json::json!(de_result)),
-                })
-            },
+            Ok(de_result) => Ok(InvokeOutput {
+                output: tool_call_result_to_output(&self.name, de_result, &mut updates)?,
+            }),
             Err(e) => {
                 warn!("Tool call result deserialization failed: {:?}", e);
                 Ok(InvokeOutput {
@@ -350,4 +400,36 @@ mod tests {
         assert_eq!(env_vars.get("KEY1").unwrap(), "Value is test_value");
         assert_eq!(env_vars.get("KEY2").unwrap(), "No substitution");
     }
+
+    #[test]
+    fn test_tool_call_result_images() {
+        let result: ToolCallResult = serde_json::from_value(serde_json::json!({
+            "content": [
+                { "type": "text", "text": "A screenshot" },
+                { "type": "image", "data": BASE64_STANDARD.encode(b"\x89PNG"), "mimeType": "image/png" },
+                { "type": "image", "data": "PHN2Zz4=", "mimeType": "image/svg+xml" },
+            ]
+        }))
+        .unwrap();
+
+        let OutputKind::Mixed { text, images } = tool_call_result_to_output("screenshot", result, &mut vec![]).unwrap()
+        else {
+            panic!("expected mixed output");
+        };
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].0.format, crate::api_client::model::ImageFormat::Png);
+        assert_eq!(images[0].1.filename, "screenshot image 1");
+        assert!(text.contains("A screenshot"));
+        assert!(text.contains("Image of size 4 supplied as an image block"));
+        assert!(text.contains("Redacted base64 encoded string of an image of size 8"));
+        assert!(!text.contains("PHN2Zz4="));
+
+        let result: ToolCallResult =
+            serde_json::from_value(serde_json::json!({ "content": [{ "type": "text", "text": "No images" }] }))
+                .unwrap();
+        assert!(matches!(
+            tool_call_result_to_output("screenshot", result, &mut vec![]).unwrap(),
+            OutputKind::Json(_)
+        ));
+    }
 }
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{
+    HashMap,
+    HashSet,
+};
 use std::io::Write;
 use std::path::{
     Path,
@@ -84,16 +87,31 @@ pub enum FsWrite {
         new_str: String,
         summary: Option<String>,
     },
+    /// Applies a unified diff that may span multiple files. Every hunk is validated before any
+    /// file is touched, and the whole patch is rolled back if a write fails partway through.
+    #[serde(rename = "patch")]
+    Patch { patch: String, summary: Option<String> },
 }
 
 impl FsWrite {
-    pub fn path(&self, os: &Os) -> PathBuf {
-        sanitize_path_tool_arg(os, match self {
-            FsWrite::Create { path, .. } => path.as_str(),
-            FsWrite::StrReplace { path, .. } => path.as_str(),
-            FsWrite::Insert { path, .. } => path.as_str(),
-            FsWrite::Append { path, .. } => path.as_str(),
-        })
+    /// Returns every path modified by this command. Only [FsWrite::Patch] can return more than
+    /// one path.
+    pub fn paths(&self, os: &Os) -> Vec<PathBuf> {
+        match self {
+            FsWrite::Create { path, .. }
+            | FsWrite::StrReplace { path, .. }
+            | FsWrite::Insert { path, .. }
+            | FsWrite::Append { path, .. } => vec![sanitize_path_tool_arg(os, path)],
+            FsWrite::Patch { patch, .. } => parse_unified_diff(patch)
+                .map(|files| {
+                    files
+                        .iter()
+                        .filter_map(|f| f.target_path())
+                        .map(|p| sanitize_path_tool_arg(os, p))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
     }
 
     pub async fn invoke(
@@ -103,12 +121,26 @@ impl FsWrite {
         line_tracker: &mut HashMap<String, FileLineTracker>,
     ) -> Result<InvokeOutput> {
         let cwd = os.env.current_dir()?;
-        let path = self.path(os);
 
         self.update_line_tracker_before_invoke(os, line_tracker).await?;
 
         match self {
-            FsWrite::Create { .. } => {
+            FsWrite::Patch { patch, .. } => {
+                let files = prepare_patch(os, patch)?;
+                for file in &files {
+                    queue!(
+                        output,
+                        style::Print(file.action()),
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(format_path(&cwd, &file.path)),
+                        style::ResetColor,
+                        style::Print("\n"),
+                    )?;
+                }
+                apply_patch(os, &files).await?;
+            },
+            FsWrite::Create { path, .. } => {
+                let path = sanitize_path_tool_arg(os, path);
                 let file_text = self.canonical_create_command_text();
                 if let Some(parent) = path.parent() {
                     os.fs.create_dir_all(parent).await?;
@@ -130,9 +162,8 @@ impl FsWrite {
 
                 write_to_file(os, &path, file_text).await?;
             },
-            FsWrite::StrReplace { old_str, new_str, .. } => {
-                let file = os.fs.read_to_string(&path).await?;
-                let matches = file.match_indices(old_str).collect::<Vec<_>>();
+            FsWrite::StrReplace { path, .. } => {
+                let path = sanitize_path_tool_arg(os, path);
                 queue!(
                     output,
                     style::Print("Updating: "),
@@ -141,19 +172,11 @@ impl FsWrite {
                     style::ResetColor,
                     style::Print("\n"),
                 )?;
-                match matches.len() {
-                    0 => return Err(eyre!("no occurrences of \"{old_str}\" were found")),
-                    1 => {
-                        let file = file.replacen(old_str, new_str, 1);
-                        os.fs.write(&path, file).await?;
-                    },
-                    x => return Err(eyre!("{x} occurrences of old_str were found when only 1 is expected")),
-                }
+                let file = self.updated_file_content(os).await?;
+                os.fs.write(&path, file).await?;
             },
-            FsWrite::Insert {
-                insert_line, new_str, ..
-            } => {
-                let mut file = os.fs.read_to_string(&path).await?;
+            FsWrite::Insert { path, .. } => {
+                let path = sanitize_path_tool_arg(os, path);
                 queue!(
                     output,
                     style::Print("Updating: "),
@@ -162,19 +185,11 @@ impl FsWrite {
                     style::ResetColor,
                     style::Print("\n"),
                 )?;
-
-                // Get the index of the start of the line to insert at.
-                let num_lines = file.lines().enumerate().map(|(i, _)| i + 1).last().unwrap_or(1);
-                let insert_line = insert_line.clamp(&0, &num_lines);
-                let mut i = 0;
-                for _ in 0..*insert_line {
-                    let line_len = &file[i..].find("\n").map_or(file[i..].len(), |i| i + 1);
-                    i += line_len;
-                }
-                file.insert_str(i, new_str);
+                let file = self.updated_file_content(os).await?;
                 write_to_file(os, &path, file).await?;
             },
-            FsWrite::Append { new_str, .. } => {
+            FsWrite::Append { path, .. } => {
+                let path = sanitize_path_tool_arg(os, path);
                 queue!(
                     output,
                     style::Print("Appending to: "),
@@ -183,19 +198,111 @@ impl FsWrite {
                     style::ResetColor,
                     style::Print("\n"),
                 )?;
+                let file = self.updated_file_content(os).await?;
+                write_to_file(os, &path, file).await?;
+            },
+        };
+
+        self.update_line_tracker_after_invoke(os, line_tracker).await?;
+
+        Ok(Default::default())
+    }
 
-                let mut file = os.fs.read_to_string(&path).await?;
+    /// Returns the full contents the file will have after this command is invoked.
+    ///
+    /// Not supported for [FsWrite::Patch], which may modify multiple files.
+    async fn updated_file_content(&self, os: &Os) -> Result<String> {
+        match self {
+            FsWrite::Create { .. } => Ok(self.canonical_create_command_text()),
+            FsWrite::StrReplace {
+                path, old_str, new_str, ..
+            } => {
+                let file = os.fs.read_to_string(sanitize_path_tool_arg(os, path)).await?;
+                match file.match_indices(old_str).count() {
+                    0 => Err(eyre!("no occurrences of \"{old_str}\" were found")),
+                    1 => Ok(file.replacen(old_str, new_str, 1)),
+                    x => Err(eyre!("{x} occurrences of old_str were found when only 1 is expected")),
+                }
+            },
+            FsWrite::Insert {
+                path,
+                insert_line,
+                new_str,
+                ..
+            } => {
+                let mut file = os.fs.read_to_string(sanitize_path_tool_arg(os, path)).await?;
+
+                // Get the index of the start of the line to insert at.
+                let num_lines = file.lines().enumerate().map(|(i, _)| i + 1).last().unwrap_or(1);
+                let insert_line = insert_line.clamp(&0, &num_lines);
+                let mut i = 0;
+                for _ in 0..*insert_line {
+                    let line_len = &file[i..].find("\n").map_or(file[i..].len(), |i| i + 1);
+                    i += line_len;
+                }
+                file.insert_str(i, new_str);
+                Ok(file)
+            },
+            FsWrite::Append { path, new_str, .. } => {
+                let mut file = os.fs.read_to_string(sanitize_path_tool_arg(os, path)).await?;
                 if !file.ends_with_newline() {
                     file.push('\n');
                 }
                 file.push_str(new_str);
-                write_to_file(os, &path, file).await?;
+                Ok(file)
             },
-        };
+            FsWrite::Patch { .. } => bail!("patches may modify multiple files"),
+        }
+    }
 
-        self.update_line_tracker_after_invoke(os, line_tracker).await?;
+    /// Whether the proposed changes can be edited by the user with [Self::proposed_content] and
+    /// [Self::with_user_edits] before being accepted.
+    pub fn is_editable(&self) -> bool {
+        !matches!(self, FsWrite::Patch { .. })
+    }
 
-        Ok(Default::default())
+    /// Returns the path being written to along with the full contents the model proposed for it.
+    pub async fn proposed_content(&self, os: &Os) -> Result<(PathBuf, String)> {
+        let path = self.paths(os).into_iter().next().wrap_err("no path to edit")?;
+        let mut content = self.updated_file_content(os).await?;
+        if !content.ends_with_newline() {
+            content.push('\n');
+        }
+        Ok((path, content))
+    }
+
+    /// Returns a command that writes `edited` to the file instead of the model's `proposed`
+    /// contents, along with a message for the model describing what the user changed. Returns
+    /// [None] if the user did not make any changes.
+    pub fn with_user_edits(&self, proposed: &str, edited: String) -> Option<(FsWrite, String)> {
+        let path = match self {
+            FsWrite::Create { path, .. }
+            | FsWrite::StrReplace { path, .. }
+            | FsWrite::Insert { path, .. }
+            | FsWrite::Append { path, .. } => path.clone(),
+            FsWrite::Patch { .. } => return None,
+        };
+        if proposed == edited {
+            return None;
+        }
+        let diff = similar::TextDiff::from_lines(proposed, &edited)
+            .unified_diff()
+            .context_radius(DIFF_CONTEXT_LINES)
+            .header("proposed", "accepted")
+            .to_string();
+        let note = format!(
+            "The user edited your proposed changes to {path} before accepting them, and the file was written \
+             with the user's version instead. The differences from your proposal are:\n{diff}"
+        );
+        Some((
+            FsWrite::Create {
+                path,
+                file_text: Some(edited),
+                new_str: None,
+                summary: self.get_summary().cloned(),
+            },
+            note,
+        ))
     }
 
     async fn update_line_tracker_before_invoke(
@@ -203,32 +310,33 @@ impl FsWrite {
         os: &Os,
         line_tracker: &mut HashMap<String, FileLineTracker>,
     ) -> Result<()> {
-        let path = self.path(os);
-
-        let curr_lines = if os.fs.exists(&path) {
-            let content = os.fs.read_to_string(&path).await?;
-            content.lines().count()
-        } else {
-            0
-        };
-
-        let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
-        match self {
-            FsWrite::Create { .. } => {
-                // For Create, always set prev_lines to 0 since we're creating a new file
-                if tracker.is_first_write {
-                    tracker.prev_fswrite_lines = 0;
-                }
-            },
-            _ => {
-                // For StrReplace, Insert, Append - if it's the first time we're tracking this file,
-                // set prev_lines to curr_lines so we only track changes from this point forward
-                if tracker.is_first_write {
-                    tracker.prev_fswrite_lines = curr_lines;
-                }
-            },
+        for path in self.paths(os) {
+            let curr_lines = if os.fs.exists(&path) {
+                let content = os.fs.read_to_string(&path).await?;
+                content.lines().count()
+            } else {
+                0
+            };
+
+            let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
+            match self {
+                FsWrite::Create { .. } => {
+                    // For Create, always set prev_lines to 0 since we're creating a new file
+                    if tracker.is_first_write {
+                        tracker.prev_fswrite_lines = 0;
+                    }
+                },
+                _ => {
+                    // For StrReplace, Insert, Append, Patch - if it's the first time we're tracking
+                    // this file, set prev_lines to curr_lines so we only track changes from this
+                    // point forward
+                    if tracker.is_first_write {
+                        tracker.prev_fswrite_lines = curr_lines;
+                    }
+                },
+            }
+            tracker.before_fswrite_lines = curr_lines;
         }
-        tracker.before_fswrite_lines = curr_lines;
 
         Ok(())
     }
@@ -238,24 +346,47 @@ impl FsWrite {
         os: &Os,
         line_tracker: &mut HashMap<String, FileLineTracker>,
     ) -> Result<()> {
-        let path = self.path(os);
-
-        let after_lines = if os.fs.exists(&path) {
-            let content = os.fs.read_to_string(&path).await?;
-            content.lines().count()
-        } else {
-            0
-        };
-
-        let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
-        tracker.after_fswrite_lines = after_lines;
-        tracker.is_first_write = false;
+        for path in self.paths(os) {
+            let after_lines = if os.fs.exists(&path) {
+                let content = os.fs.read_to_string(&path).await?;
+                content.lines().count()
+            } else {
+                0
+            };
+
+            let tracker = line_tracker.entry(path.to_string_lossy().to_string()).or_default();
+            tracker.after_fswrite_lines = after_lines;
+            tracker.is_first_write = false;
+        }
 
         Ok(())
     }
 
     pub fn queue_description(&self, os: &Os, output: &mut impl Write) -> Result<()> {
         let cwd = os.env.current_dir()?;
+        if let FsWrite::Patch { patch, .. } = self {
+            let files = prepare_patch(os, patch)?;
+            for file in &files {
+                queue!(
+                    output,
+                    style::Print(file.action()),
+                    style::SetForegroundColor(Color::Green),
+                    style::Print(format_path(&cwd, &file.path)),
+                    style::ResetColor,
+                    style::Print("\n\n"),
+                )?;
+                let relative_path = format_path(&cwd, &file.path);
+                for hunk in &file.hunks {
+                    let old = stylize_output_if_able(os, &relative_path, &hunk.old_text);
+                    let new = stylize_output_if_able(os, &relative_path, &hunk.new_text);
+                    print_diff(output, &old, &new, hunk.start_line)?;
+                }
+            }
+
+            // Display summary as purpose if available after the diff
+            return super::display_purpose(self.get_summary(), output);
+        }
+
         self.print_relative_path(os, output)?;
         match self {
             FsWrite::Create { path, .. } => {
@@ -336,6 +467,7 @@ impl FsWrite {
 
                 Ok(())
             },
+            FsWrite::Patch { .. } => Ok(()),
         }
     }
 
@@ -360,6 +492,13 @@ impl FsWrite {
                     bail!("Content to append must not be empty")
                 };
             },
+            FsWrite::Patch { patch, .. } => {
+                if patch.trim().is_empty() {
+                    bail!("Patch must not be empty")
+                };
+                // Dry run the whole patch so that no hunk fails after the user has accepted it.
+                prepare_patch(os, patch)?;
+            },
         }
 
         Ok(())
@@ -372,6 +511,7 @@ impl FsWrite {
             FsWrite::StrReplace { path, .. } => path,
             FsWrite::Insert { path, .. } => path,
             FsWrite::Append { path, .. } => path,
+            FsWrite::Patch { .. } => return Ok(()),
         };
         // Sanitize the path to handle tilde expansion
         let path = sanitize_path_tool_arg(os, path);
@@ -413,6 +553,25 @@ impl FsWrite {
             FsWrite::StrReplace { summary, .. } => summary.as_ref(),
             FsWrite::Insert { summary, .. } => summary.as_ref(),
             FsWrite::Append { summary, .. } => summary.as_ref(),
+            FsWrite::Patch { summary, .. } => summary.as_ref(),
+        }
+    }
+
+    /// The paths, as given by the model, that `allowedPaths` and `deniedPaths` are matched against.
+    /// Returns [None] if the paths touched by a patch can't be determined.
+    pub fn permission_paths(&self) -> Option<Vec<String>> {
+        match self {
+            Self::Create { path, .. }
+            | Self::Insert { path, .. }
+            | Self::Append { path, .. }
+            | Self::StrReplace { path, .. } => Some(vec![path.clone()]),
+            Self::Patch { patch, .. } => parse_unified_diff(patch).ok().map(|files| {
+                files
+                    .iter()
+                    .flat_map(|f| [f.old_path.clone(), f.new_path.clone()])
+                    .flatten()
+                    .collect()
+            }),
         }
     }
 
@@ -467,24 +626,23 @@ impl FsWrite {
 
                 match (allow_set, deny_set) {
                     (Ok(allow_set), Ok(deny_set)) => {
-                        match self {
-                            Self::Create { path, .. }
-                            | Self::Insert { path, .. }
-                            | Self::Append { path, .. }
-                            | Self::StrReplace { path, .. } => {
-                                let denied_match_set = deny_set.matches(path);
-                                if !denied_match_set.is_empty() {
-                                    return PermissionEvalResult::Deny({
-                                        denied_match_set
-                                            .iter()
-                                            .filter_map(|i| sanitized_deny_list.get(*i).map(|s| (*s).clone()))
-                                            .collect::<Vec<_>>()
-                                    });
-                                }
-                                if allow_set.is_match(path) {
-                                    return PermissionEvalResult::Allow;
-                                }
-                            },
+                        let Some(paths) = self.permission_paths() else {
+                            return PermissionEvalResult::Ask;
+                        };
+                        for path in &paths {
+                            let denied_match_set = deny_set.matches(path);
+                            if !denied_match_set.is_empty() {
+                                return PermissionEvalResult::Deny({
+                                    denied_match_set
+                                        .iter()
+                                        .filter_map(|i| sanitized_deny_list.get(*i).map(|s| (*s).clone()))
+                                        .collect::<Vec<_>>()
+                                });
+                            }
+                        }
+                        // Every touched path must be allowed for the command to skip the prompt.
+                        if !paths.is_empty() && paths.iter().all(|path| allow_set.is_match(path)) {
+                            return PermissionEvalResult::Allow;
                         }
                         PermissionEvalResult::Ask
                     },
@@ -519,6 +677,410 @@ async fn write_to_file(os: &Os, path: impl AsRef<Path>, mut content: String) ->
     Ok(())
 }
 
+/// A single file's changes parsed from a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FilePatch {
+    /// Path of the file before the change, or [None] if the file is being created.
+    old_path: Option<String>,
+    /// Path of the file after the change, or [None] if the file is being deleted.
+    new_path: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// The path that ends up being written to or removed.
+    fn target_path(&self) -> Option<&str> {
+        self.new_path.as_deref().or(self.old_path.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hunk {
+    /// 1-indexed line number from the `@@ -l,s +l,s @@` header.
+    old_start: usize,
+    lines: Vec<HunkLine>,
+    /// Whether the last of [Self::new_lines] is marked with `\ No newline at end of file`.
+    new_missing_newline: bool,
+}
+
+impl Hunk {
+    /// Lines expected to exist in the original file.
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Lines that replace [Self::old_lines].
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Add(s) => Some(s.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// Parses a unified diff (as produced by `diff -u` or `git diff`) into its per-file patches.
+///
+/// The line counts in hunk headers are ignored since models frequently miscount them; a hunk
+/// instead ends at the next hunk header or file header.
+fn parse_unified_diff(patch: &str) -> Result<Vec<FilePatch>> {
+    fn parse_path(header: &str, prefix: &str) -> Option<String> {
+        // Strip any trailing timestamp, e.g. `--- file.txt\t2024-01-01 00:00:00`.
+        let path = header.split('\t').next().unwrap_or_default().trim();
+        if path == "/dev/null" {
+            return None;
+        }
+        Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+    }
+
+    fn parse_old_start(header: &str) -> Option<usize> {
+        let old_range = header.strip_prefix("@@ -")?.split_whitespace().next()?;
+        old_range.split(',').next()?.parse().ok()
+    }
+
+    let lines = patch.lines().collect::<Vec<_>>();
+    let is_file_header =
+        |i: usize| lines[i].starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ "));
+
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if is_file_header(i) {
+            files.push(FilePatch {
+                old_path: parse_path(&line[4..], "a/"),
+                new_path: parse_path(&lines[i + 1][4..], "b/"),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            let file = files
+                .last_mut()
+                .wrap_err_with(|| format!("hunk header found before any file header: {line}"))?;
+            let old_start = parse_old_start(line).wrap_err_with(|| format!("invalid hunk header: {line}"))?;
+            let mut hunk = Hunk {
+                old_start,
+                lines: Vec::new(),
+                new_missing_newline: false,
+            };
+            i += 1;
+            while i < lines.len() && !lines[i].starts_with("@@") && !is_file_header(i) {
+                let line = lines[i];
+                match line.chars().next() {
+                    Some(' ') => hunk.lines.push(HunkLine::Context(line[1..].to_string())),
+                    Some('-') => hunk.lines.push(HunkLine::Remove(line[1..].to_string())),
+                    Some('+') => hunk.lines.push(HunkLine::Add(line[1..].to_string())),
+                    // Context lines for empty lines commonly lose their leading space.
+                    None => hunk.lines.push(HunkLine::Context(String::new())),
+                    // "\ No newline at end of file", for the line before it
+                    Some('\\') => {
+                        if matches!(hunk.lines.last(), Some(HunkLine::Context(_) | HunkLine::Add(_))) {
+                            hunk.new_missing_newline = true;
+                        }
+                    },
+                    // Anything else (e.g. `diff --git` or `index` lines) ends the hunk.
+                    Some(_) => break,
+                }
+                i += 1;
+            }
+            // Trailing blank lines are almost always separators rather than context.
+            while hunk.lines.last() == Some(&HunkLine::Context(String::new())) {
+                hunk.lines.pop();
+            }
+            file.hunks.push(hunk);
+            continue;
+        }
+
+        // Ignore everything else, e.g. `diff --git`, `index` and mode lines.
+        i += 1;
+    }
+
+    if files.is_empty() {
+        bail!("no file headers (`--- old` followed by `+++ new`) were found in the patch");
+    }
+    for file in &files {
+        match (&file.old_path, &file.new_path) {
+            (None, None) => bail!("a file patch must not have /dev/null as both the old and new path"),
+            (_, Some(path)) if file.hunks.is_empty() => bail!("the patch for {path} does not contain any hunks"),
+            _ => (),
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns the 0-indexed line at which `needle` occurs within `haystack`, choosing the occurrence
+/// closest to `hint` and never matching before `min`.
+fn find_lines(haystack: &[String], needle: &[&str], hint: usize, min: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(hint.clamp(min, haystack.len().max(min)));
+    }
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    let matches_at =
+        |i: usize, cmp: fn(&str, &str) -> bool| needle.iter().enumerate().all(|(j, n)| cmp(&haystack[i + j], n));
+    let candidates = (min..=haystack.len() - needle.len()).collect::<Vec<_>>();
+    let closest = |cmp: fn(&str, &str) -> bool| {
+        candidates
+            .iter()
+            .copied()
+            .filter(|i| matches_at(*i, cmp))
+            .min_by_key(|i| i.abs_diff(hint))
+    };
+    // Fall back to ignoring trailing whitespace, which models routinely drop.
+    closest(|a, b| a == b).or_else(|| closest(|a, b| a.trim_end() == b.trim_end()))
+}
+
+/// A hunk after it has been located within the original file, used for displaying the diff.
+#[derive(Debug)]
+struct ResolvedHunk {
+    /// 1-indexed line in the original file where the hunk starts.
+    start_line: usize,
+    old_text: String,
+    new_text: String,
+}
+
+/// The fully computed result of applying a [FilePatch], prior to anything being written.
+#[derive(Debug)]
+struct PatchedFile {
+    /// The file to write, or to delete if [Self::new_content] is [None].
+    path: PathBuf,
+    /// Set when the file is being renamed, in which case this path is removed.
+    renamed_from: Option<PathBuf>,
+    new_content: Option<String>,
+    hunks: Vec<ResolvedHunk>,
+}
+
+impl PatchedFile {
+    fn action(&self) -> &'static str {
+        match (&self.new_content, &self.renamed_from) {
+            (None, _) => "Deleting: ",
+            (Some(_), Some(_)) => "Renaming to: ",
+            (Some(_), None) if self.hunks.first().is_some_and(|h| h.old_text.is_empty()) => "Creating: ",
+            (Some(_), None) => "Updating: ",
+        }
+    }
+}
+
+/// Parses `patch` and computes the new contents of every file it touches. Errors for every hunk
+/// that fails to apply are reported together, and nothing is written to disk.
+fn prepare_patch(os: &Os, patch: &str) -> Result<Vec<PatchedFile>> {
+    let file_patches = parse_unified_diff(patch)?;
+    let mut seen = HashSet::new();
+    let mut errors = Vec::new();
+    let mut files = Vec::new();
+
+    for file_patch in file_patches {
+        let target = file_patch.target_path().unwrap_or_default().to_string();
+        if !seen.insert(target.clone()) {
+            errors.push(format!("{target}: the file appears more than once in the patch"));
+            continue;
+        }
+
+        let old_path = file_patch.old_path.as_ref().map(|p| sanitize_path_tool_arg(os, p));
+        let new_path = file_patch.new_path.as_ref().map(|p| sanitize_path_tool_arg(os, p));
+        if new_path
+            .as_ref()
+            .is_some_and(|p| old_path.as_ref() != Some(p) && os.fs.exists(p))
+        {
+            match old_path {
+                Some(_) => errors.push(format!(
+                    "{target}: the patch renames a file to it but it already exists"
+                )),
+                None => errors.push(format!("{target}: the patch creates the file but it already exists")),
+            }
+            continue;
+        }
+        let original = match &old_path {
+            Some(path) => match os.fs.read_to_string_sync(path) {
+                Ok(content) => content,
+                Err(err) => {
+                    errors.push(format!("{target}: failed to read the file to patch: {err}"));
+                    continue;
+                },
+            },
+            None => String::new(),
+        };
+
+        // Lines are kept apart from their line endings so that hunks can be matched against them,
+        // and lines the patch doesn't change are written back exactly as they were.
+        let (mut lines, mut endings): (Vec<String>, Vec<&str>) = original
+            .split_inclusive('\n')
+            .map(|line| {
+                let content = line.trim_end_matches('\n').trim_end_matches('\r');
+                (content.to_string(), &line[content.len()..])
+            })
+            .unzip();
+        // Added lines use the line ending of the file, created files get `\n`.
+        let eol = endings.first().copied().filter(|e| !e.is_empty()).unwrap_or("\n");
+        let mut hunks = Vec::new();
+        // Tracks how far applied hunks have shifted line numbers relative to the original file.
+        let mut offset: isize = 0;
+        let mut min = 0;
+        for (i, hunk) in file_patch.hunks.iter().enumerate() {
+            let old_lines = hunk.old_lines();
+            let new_lines = hunk.new_lines();
+            // Pure insertions (`@@ -n,0 ...`) insert after line `n`, otherwise `n` is 1-indexed.
+            let base = if old_lines.is_empty() {
+                hunk.old_start
+            } else {
+                hunk.old_start.saturating_sub(1)
+            };
+            let hint = (base as isize + offset).max(0) as usize;
+            let Some(start) = find_lines(&lines, &old_lines, hint, min) else {
+                errors.push(format!(
+                    "{target}: hunk {} (starting at line {}) does not match the file contents",
+                    i + 1,
+                    hunk.old_start
+                ));
+                continue;
+            };
+            let join = |l: &[&str]| l.iter().map(|l| format!("{l}\n")).collect::<String>();
+            hunks.push(ResolvedHunk {
+                start_line: start + 1,
+                old_text: join(&old_lines),
+                new_text: join(&new_lines),
+            });
+
+            // Context lines are kept as they are in the file, including any trailing whitespace
+            // that the patch dropped.
+            let mut old_line = start;
+            let mut replacement = Vec::with_capacity(new_lines.len());
+            for line in &hunk.lines {
+                match line {
+                    HunkLine::Context(_) => {
+                        replacement.push((lines[old_line].clone(), endings[old_line]));
+                        old_line += 1;
+                    },
+                    HunkLine::Remove(_) => old_line += 1,
+                    HunkLine::Add(line) => replacement.push((line.clone(), eol)),
+                }
+            }
+            if hunk.new_missing_newline {
+                if let Some((_, ending)) = replacement.last_mut() {
+                    *ending = "";
+                }
+            }
+            let (new_contents, new_endings): (Vec<_>, Vec<_>) = replacement.into_iter().unzip();
+            lines.splice(start..old_line, new_contents);
+            endings.splice(start..old_line, new_endings);
+            offset += new_lines.len() as isize - old_lines.len() as isize;
+            min = start + new_lines.len();
+        }
+
+        let Some(new_path) = new_path else {
+            if !lines.is_empty() {
+                errors.push(format!(
+                    "{target}: the patch deletes the file but does not remove all of its lines"
+                ));
+                continue;
+            }
+            files.push(PatchedFile {
+                path: old_path.unwrap_or_default(),
+                renamed_from: None,
+                new_content: None,
+                hunks,
+            });
+            continue;
+        };
+
+        // Only the last line may be missing its line ending, e.g. when lines are added after it.
+        let last = endings.len().saturating_sub(1);
+        let new_content = lines
+            .iter()
+            .zip(endings)
+            .enumerate()
+            .map(|(i, (line, ending))| match ending {
+                "" if i != last => format!("{line}{eol}"),
+                ending => format!("{line}{ending}"),
+            })
+            .collect::<String>();
+        files.push(PatchedFile {
+            renamed_from: old_path.filter(|p| *p != new_path),
+            path: new_path,
+            new_content: Some(new_content),
+            hunks,
+        });
+    }
+
+    if !errors.is_empty() {
+        bail!("The patch was not applied:\n{}", errors.join("\n"));
+    }
+
+    Ok(files)
+}
+
+/// Writes every [PatchedFile] to disk. If any write fails, the files written so far are restored
+/// to their original state so that the patch is applied all or nothing.
+async fn apply_patch(os: &Os, files: &[PatchedFile]) -> Result<()> {
+    // Each entry is a path along with its content prior to the patch, if it existed.
+    let mut backups: Vec<(PathBuf, Option<String>)> = Vec::new();
+
+    let mut result = Ok(());
+    for file in files {
+        let mut paths = vec![&file.path];
+        paths.extend(&file.renamed_from);
+        for path in paths {
+            backups.push((path.clone(), os.fs.read_to_string(path).await.ok()));
+        }
+
+        result = async {
+            match &file.new_content {
+                Some(content) => {
+                    if let Some(parent) = file.path.parent() {
+                        os.fs.create_dir_all(parent).await?;
+                    }
+                    os.fs.write(&file.path, content).await?;
+                    if let Some(from) = &file.renamed_from {
+                        os.fs.remove_file(from).await?;
+                    }
+                },
+                None => os.fs.remove_file(&file.path).await?,
+            }
+            Ok::<_, eyre::Report>(())
+        }
+        .await;
+        if result.is_err() {
+            break;
+        }
+    }
+
+    if let Err(err) = result {
+        for (path, content) in backups.into_iter().rev() {
+            let restored = match content {
+                Some(content) => os.fs.write(&path, content).await,
+                None if os.fs.exists(&path) => os.fs.remove_file(&path).await,
+                None => Ok(()),
+            };
+            if let Err(restore_err) = restored {
+                error!(?restore_err, ?path, "failed to restore file after a failed patch");
+            }
+        }
+        return Err(err.wrap_err("failed to apply the patch, all changes have been reverted"));
+    }
+
+    Ok(())
+}
+
 /// Returns a prefix/suffix pair before and after the content dictated by `[start_line, end_line]`
 /// within `content`. The updated start and end lines containing the original context along with
 /// the suffix and prefix are returned.
@@ -576,15 +1138,28 @@ fn get_lines_with_context(
     )
 }
 
+/// Number of unchanged lines displayed around each change. Longer runs of unchanged lines are
+/// collapsed into a single marker line.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Minimum similarity between a removed and an added line for word-level changes to be
+/// highlighted. Below this, the lines are considered entirely different.
+const INLINE_DIFF_MIN_RATIO: f32 = 0.5;
+
 /// Prints a git-diff style comparison between `old_str` and `new_str`.
 /// - `start_line` - 1-indexed line number that `old_str` and `new_str` start at.
+///
+/// Modified lines have their changed words emphasized, and unchanged regions longer than
+/// [DIFF_CONTEXT_LINES] around each change are collapsed.
 fn print_diff(
     output: &mut impl Write,
     old_str: &StylizedFile,
     new_str: &StylizedFile,
     start_line: usize,
 ) -> Result<()> {
-    let diff = similar::TextDiff::from_lines(&old_str.content, &new_str.content);
+    let diff = similar::TextDiff::from_lines(&old_str.plain, &new_str.plain);
+    let old_styled_lines = old_str.content.lines().collect::<Vec<_>>();
+    let new_styled_lines = new_str.content.lines().collect::<Vec<_>>();
 
     // First, get the gutter width required for both the old and new lines.
     let (mut max_old_i, mut max_new_i) = (1, 1);
@@ -596,92 +1171,236 @@ fn print_diff(
             max_new_i = i + start_line;
         }
     }
-    let old_line_num_width = terminal_width_required_for_line_count(max_old_i);
-    let new_line_num_width = terminal_width_required_for_line_count(max_new_i);
+    let gutter = Gutter {
+        old_width: terminal_width_required_for_line_count(max_old_i),
+        new_width: terminal_width_required_for_line_count(max_new_i),
+        start_line,
+    };
 
     // Now, print
-    fn fmt_index(i: Option<usize>, start_line: usize) -> String {
-        match i {
-            Some(i) => (i + start_line).to_string(),
-            _ => " ".to_string(),
+    let mut old_line_i = 0;
+    for group in diff.grouped_ops(DIFF_CONTEXT_LINES) {
+        let Some(group_old_start) = group.first().map(|op| op.old_range().start) else {
+            continue;
+        };
+        if group_old_start > old_line_i {
+            gutter.print_collapsed(output, new_str, group_old_start - old_line_i)?;
+        }
+
+        for op in &group {
+            let changes = diff.iter_changes(op).collect::<Vec<_>>();
+            let (deletes, inserts): (Vec<&similar::Change<&str>>, Vec<_>) = changes
+                .iter()
+                .filter(|c| c.tag() != similar::ChangeTag::Equal)
+                .partition(|c| c.tag() == similar::ChangeTag::Delete);
+
+            // Pair up modified lines so that only the words that changed are emphasized.
+            let mut emphasis: HashMap<(similar::ChangeTag, usize), Vec<(bool, String)>> = HashMap::new();
+            if deletes.len() == inserts.len() {
+                for (old, new) in deletes.iter().zip(&inserts) {
+                    let old_line = old.value().trim_end_matches(['\r', '\n']);
+                    let new_line = new.value().trim_end_matches(['\r', '\n']);
+                    let word_diff = similar::TextDiff::from_words(old_line, new_line);
+                    if word_diff.ratio() < INLINE_DIFF_MIN_RATIO {
+                        continue;
+                    }
+                    let (mut old_segments, mut new_segments) = (Vec::new(), Vec::new());
+                    for change in word_diff.iter_all_changes() {
+                        let value = change.value().to_string();
+                        match change.tag() {
+                            similar::ChangeTag::Equal => {
+                                old_segments.push((false, value.clone()));
+                                new_segments.push((false, value));
+                            },
+                            similar::ChangeTag::Delete => old_segments.push((true, value)),
+                            similar::ChangeTag::Insert => new_segments.push((true, value)),
+                        }
+                    }
+                    if let Some(i) = old.old_index() {
+                        emphasis.insert((similar::ChangeTag::Delete, i), old_segments);
+                    }
+                    if let Some(i) = new.new_index() {
+                        emphasis.insert((similar::ChangeTag::Insert, i), new_segments);
+                    }
+                }
+            }
+
+            for change in changes {
+                let line = match change.tag() {
+                    similar::ChangeTag::Delete => change
+                        .old_index()
+                        .and_then(|i| emphasis.remove(&(similar::ChangeTag::Delete, i)))
+                        .map(DiffLine::Emphasized)
+                        .or_else(|| {
+                            change
+                                .old_index()
+                                .and_then(|i| old_styled_lines.get(i).copied())
+                                .map(DiffLine::Styled)
+                        }),
+                    similar::ChangeTag::Insert => change
+                        .new_index()
+                        .and_then(|i| emphasis.remove(&(similar::ChangeTag::Insert, i)))
+                        .map(DiffLine::Emphasized)
+                        .or_else(|| {
+                            change
+                                .new_index()
+                                .and_then(|i| new_styled_lines.get(i).copied())
+                                .map(DiffLine::Styled)
+                        }),
+                    similar::ChangeTag::Equal => change
+                        .new_index()
+                        .and_then(|i| new_styled_lines.get(i).copied())
+                        .map(DiffLine::Styled),
+                }
+                .unwrap_or(DiffLine::Styled(change.value().trim_end_matches(['\r', '\n'])));
+                gutter.print_line(
+                    output,
+                    new_str,
+                    change.tag(),
+                    change.old_index(),
+                    change.new_index(),
+                    line,
+                )?;
+            }
         }
+
+        old_line_i = group.last().map_or(old_line_i, |op| op.old_range().end);
     }
-    for change in diff.iter_all_changes() {
+    let old_line_count = diff.old_slices().len();
+    if old_line_count > old_line_i {
+        gutter.print_collapsed(output, new_str, old_line_count - old_line_i)?;
+    }
+    queue!(
+        output,
+        crossterm::terminal::Clear(crossterm::terminal::ClearType::UntilNewLine),
+        style::Print("\n"),
+    )?;
+
+    Ok(())
+}
+
+/// The content of a single line printed by [print_diff].
+enum DiffLine<'a> {
+    /// The (potentially syntax highlighted) line as-is.
+    Styled(&'a str),
+    /// Segments of the line, where `true` marks the words that changed.
+    Emphasized(Vec<(bool, String)>),
+}
+
+/// Line number gutter shared by every line printed by [print_diff].
+struct Gutter {
+    old_width: usize,
+    new_width: usize,
+    start_line: usize,
+}
+
+impl Gutter {
+    fn print_line(
+        &self,
+        output: &mut impl Write,
+        file: &StylizedFile,
+        tag: similar::ChangeTag,
+        old_index: Option<usize>,
+        new_index: Option<usize>,
+        line: DiffLine<'_>,
+    ) -> Result<()> {
         // Define the colors per line.
-        let (text_color, gutter_bg_color, line_bg_color) = match (change.tag(), new_str.truecolor) {
-            (similar::ChangeTag::Equal, true) => (style::Color::Reset, new_str.gutter_bg, new_str.line_bg),
+        let (text_color, gutter_bg_color, line_bg_color, emphasis_bg_color) = match (tag, file.truecolor) {
+            (similar::ChangeTag::Equal, true) => (style::Color::Reset, file.gutter_bg, file.line_bg, file.line_bg),
             (similar::ChangeTag::Delete, true) => (
                 style::Color::Reset,
                 style::Color::Rgb { r: 79, g: 40, b: 40 },
                 style::Color::Rgb { r: 36, g: 25, b: 28 },
+                style::Color::Rgb { r: 110, g: 45, b: 45 },
             ),
             (similar::ChangeTag::Insert, true) => (
                 style::Color::Reset,
                 style::Color::Rgb { r: 40, g: 67, b: 43 },
                 style::Color::Rgb { r: 24, g: 38, b: 30 },
+                style::Color::Rgb { r: 45, g: 100, b: 52 },
             ),
-            (similar::ChangeTag::Equal, false) => (style::Color::Reset, new_str.gutter_bg, new_str.line_bg),
-            (similar::ChangeTag::Delete, false) => (style::Color::Red, new_str.gutter_bg, new_str.line_bg),
-            (similar::ChangeTag::Insert, false) => (style::Color::Green, new_str.gutter_bg, new_str.line_bg),
+            (similar::ChangeTag::Equal, false) => (style::Color::Reset, file.gutter_bg, file.line_bg, file.line_bg),
+            (similar::ChangeTag::Delete, false) => (style::Color::Red, file.gutter_bg, file.line_bg, file.line_bg),
+            (similar::ChangeTag::Insert, false) => (style::Color::Green, file.gutter_bg, file.line_bg, file.line_bg),
         };
         // Define the change tag character to print, if any.
-        let sign = match change.tag() {
+        let sign = match tag {
             similar::ChangeTag::Equal => " ",
             similar::ChangeTag::Delete => "-",
             similar::ChangeTag::Insert => "+",
         };
-
-        let old_i_str = fmt_index(change.old_index(), start_line);
-        let new_i_str = fmt_index(change.new_index(), start_line);
+        let fmt_index = |i: Option<usize>| match i {
+            Some(i) => (i + self.start_line).to_string(),
+            _ => " ".to_string(),
+        };
 
         // Print the gutter and line numbers.
-        queue!(output, style::SetBackgroundColor(gutter_bg_color))?;
         queue!(
             output,
+            style::SetBackgroundColor(gutter_bg_color),
             style::SetForegroundColor(text_color),
             style::Print(sign),
-            style::Print(" ")
-        )?;
-        queue!(
-            output,
-            style::Print(format!(
-                "{:>old_line_num_width$}",
-                old_i_str,
-                old_line_num_width = old_line_num_width
-            ))
+            style::Print(" "),
+            style::Print(format!("{:>width$}", fmt_index(old_index), width = self.old_width)),
+            style::Print(if sign == " " { ", " } else { "  " }),
+            style::Print(format!("{:>width$}", fmt_index(new_index), width = self.new_width)),
+            style::SetForegroundColor(style::Color::Reset),
+            style::Print(":"),
+            style::SetForegroundColor(text_color),
+            style::SetBackgroundColor(line_bg_color),
+            style::Print(" "),
         )?;
-        if sign == " " {
-            queue!(output, style::Print(", "))?;
-        } else {
-            queue!(output, style::Print("  "))?;
+
+        // Print the line.
+        match line {
+            DiffLine::Styled(line) => queue!(output, style::Print(line))?,
+            DiffLine::Emphasized(segments) => {
+                for (emphasized, segment) in segments {
+                    match (emphasized, file.truecolor) {
+                        (false, _) => queue!(output, style::Print(segment))?,
+                        (true, true) => queue!(
+                            output,
+                            style::SetBackgroundColor(emphasis_bg_color),
+                            style::Print(segment),
+                            style::SetBackgroundColor(line_bg_color),
+                        )?,
+                        (true, false) => queue!(
+                            output,
+                            style::SetAttribute(style::Attribute::Reverse),
+                            style::Print(segment),
+                            style::SetAttribute(style::Attribute::NoReverse),
+                        )?,
+                    }
+                }
+            },
         }
         queue!(
             output,
-            style::Print(format!(
-                "{:>new_line_num_width$}",
-                new_i_str,
-                new_line_num_width = new_line_num_width
-            ))
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::UntilNewLine),
+            style::ResetColor,
+            style::Print("\n"),
         )?;
-        // Print the line.
+
+        Ok(())
+    }
+
+    /// Prints a marker in place of `count` unchanged lines.
+    fn print_collapsed(&self, output: &mut impl Write, file: &StylizedFile, count: usize) -> Result<()> {
+        let plural = if count == 1 { "" } else { "s" };
         queue!(
             output,
-            style::SetForegroundColor(style::Color::Reset),
-            style::Print(":"),
-            style::SetForegroundColor(text_color),
-            style::SetBackgroundColor(line_bg_color),
-            style::Print(" "),
-            style::Print(change),
+            style::SetBackgroundColor(file.gutter_bg),
+            style::SetForegroundColor(style::Color::DarkGrey),
+            style::Print(format!("{:>width$} ", "⋮", width = self.old_width + self.new_width + 4)),
+            style::SetBackgroundColor(file.line_bg),
+            style::Print(format!(" {count} unchanged line{plural}")),
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::UntilNewLine),
             style::ResetColor,
+            style::Print("\n"),
         )?;
-    }
-    queue!(
-        output,
-        crossterm::terminal::Clear(crossterm::terminal::ClearType::UntilNewLine),
-        style::Print("\n"),
-    )?;
 
-    Ok(())
+        Ok(())
+    }
 }
 
 /// Returns a 1-indexed line number range of the start and end of `needle` inside `file`.
@@ -718,6 +1437,7 @@ fn stylize_output_if_able(os: &Os, path: impl AsRef<Path>, file_text: &str) -> S
     StylizedFile {
         truecolor: false,
         content: file_text.to_string(),
+        plain: file_text.to_string(),
         gutter_bg: style::Color::Reset,
         line_bg: style::Color::Reset,
     }
@@ -731,6 +1451,8 @@ struct StylizedFile {
     /// File content. If [Self::truecolor] is true, then it has escape codes for styling with 24bit
     /// color.
     content: String,
+    /// File content without any styling, used for computing diffs.
+    plain: String,
     /// Background color for the gutter.
     gutter_bg: style::Color,
     /// Background color for the line content.
@@ -742,6 +1464,7 @@ impl Default for StylizedFile {
         Self {
             truecolor: false,
             content: Default::default(),
+            plain: Default::default(),
             gutter_bg: style::Color::Reset,
             line_bg: style::Color::Reset,
         }
@@ -753,6 +1476,7 @@ impl Default for StylizedFile {
 fn stylized_file(path: impl AsRef<Path>, file_text: impl AsRef<str>) -> Result<StylizedFile> {
     let ps = &*SYNTAX_SET;
     let ts = &*THEME_SET;
+    let plain = file_text.as_ref().to_string();
 
     let extension = path
         .as_ref()
@@ -788,6 +1512,7 @@ fn stylized_file(path: impl AsRef<Path>, file_text: impl AsRef<str>) -> Result<S
     Ok(StylizedFile {
         truecolor: true,
         content: file,
+        plain,
         gutter_bg: syntect_to_crossterm_color(gutter_bg),
         line_bg: syntect_to_crossterm_color(line_bg),
     })
@@ -1446,4 +2171,365 @@ mod tests {
             "after_lines should match the actual line count in the file"
         );
     }
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!(\"hello\");
++    println!(\"goodbye\");
+ }
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++new file
+--- a/old.txt
++++ /dev/null
+";
+        let files = parse_unified_diff(patch).unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].old_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(files[0].new_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(files[0].hunks[0].old_start, 1);
+        assert_eq!(files[0].hunks[0].old_lines(), vec![
+            "fn main() {",
+            "    println!(\"hello\");",
+            "}"
+        ]);
+        assert_eq!(files[0].hunks[0].new_lines(), vec![
+            "fn main() {",
+            "    println!(\"goodbye\");",
+            "}"
+        ]);
+        assert_eq!(files[1].old_path, None);
+        assert_eq!(files[1].target_path(), Some("new.txt"));
+        assert_eq!(files[2].new_path, None);
+        assert_eq!(files[2].target_path(), Some("old.txt"));
+
+        assert!(parse_unified_diff("not a patch").is_err());
+        assert!(
+            parse_unified_diff("--- a/file\n+++ b/file\n").is_err(),
+            "hunks are required"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_tool_patch() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        let mut line_tracker = HashMap::new();
+        os.fs.write("/other.txt", "one\ntwo\nthree\n").await.unwrap();
+        os.fs.write("/delete_me.txt", "bye\n").await.unwrap();
+
+        let patch = format!(
+            "\
+--- a{TEST_FILE_PATH}
++++ b{TEST_FILE_PATH}
+@@ -2,2 +2,2 @@
+ 2: This is line 2
+-3: asdf
++3: fdsa
+--- a/other.txt
++++ b/other.txt
+@@ -3,1 +3,2 @@
+ three
++four
+--- /dev/null
++++ b/nested/created.txt
+@@ -0,0 +1,2 @@
++created
++file
+--- a/delete_me.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+"
+        );
+        let mut tool = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "command": "patch",
+            "patch": patch,
+        }))
+        .unwrap();
+        tool.validate(&os).await.unwrap();
+        tool.invoke(&os, &mut stdout, &mut line_tracker).await.unwrap();
+
+        assert_eq!(
+            os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(),
+            TEST_FILE_CONTENTS.replace("3: asdf", "3: fdsa")
+        );
+        assert_eq!(
+            os.fs.read_to_string("/other.txt").await.unwrap(),
+            "one\ntwo\nthree\nfour\n"
+        );
+        assert_eq!(
+            os.fs.read_to_string("/nested/created.txt").await.unwrap(),
+            "created\nfile\n"
+        );
+        assert!(!os.fs.exists("/delete_me.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_tool_patch_is_atomic() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        let mut line_tracker = HashMap::new();
+        os.fs.write("/other.txt", "one\ntwo\nthree\n").await.unwrap();
+
+        // The first file applies cleanly but the second does not, so neither should change.
+        let patch = format!(
+            "\
+--- a{TEST_FILE_PATH}
++++ b{TEST_FILE_PATH}
+@@ -3 +3 @@
+-3: asdf
++3: fdsa
+--- a/other.txt
++++ b/other.txt
+@@ -2 +2 @@
+-does not exist
++two
+"
+        );
+        let mut tool = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "command": "patch",
+            "patch": patch,
+        }))
+        .unwrap();
+        let err = tool.validate(&os).await.unwrap_err();
+        assert!(err.to_string().contains("other.txt: hunk 1"), "unexpected error: {err}");
+        assert!(tool.invoke(&os, &mut stdout, &mut line_tracker).await.is_err());
+
+        assert_eq!(os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(), TEST_FILE_CONTENTS);
+        assert_eq!(os.fs.read_to_string("/other.txt").await.unwrap(), "one\ntwo\nthree\n");
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_tool_patch_keeps_line_endings() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        let mut line_tracker = HashMap::new();
+        os.fs.write("/crlf.txt", "one\r\ntwo\r\nthree\r\n").await.unwrap();
+        os.fs.write("/no_newline.txt", "one\ntwo\nthree").await.unwrap();
+        os.fs.write("/append.txt", "one\ntwo").await.unwrap();
+
+        let patch = "\
+--- a/crlf.txt
++++ b/crlf.txt
+@@ -2 +2,2 @@
+-two
++2
++2.5
+--- a/no_newline.txt
++++ b/no_newline.txt
+@@ -1 +1 @@
+-one
++1
+--- a/append.txt
++++ b/append.txt
+@@ -2 +2,2 @@
+ two
++three
+\\ No newline at end of file
+";
+        let mut tool = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "command": "patch",
+            "patch": patch,
+        }))
+        .unwrap();
+        tool.validate(&os).await.unwrap();
+        tool.invoke(&os, &mut stdout, &mut line_tracker).await.unwrap();
+
+        assert_eq!(
+            os.fs.read_to_string("/crlf.txt").await.unwrap(),
+            "one\r\n2\r\n2.5\r\nthree\r\n"
+        );
+        assert_eq!(
+            os.fs.read_to_string("/no_newline.txt").await.unwrap(),
+            "1\ntwo\nthree",
+            "the file still has no final newline"
+        );
+        assert_eq!(os.fs.read_to_string("/append.txt").await.unwrap(), "one\ntwo\nthree");
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_tool_patch_checks_deletes_and_renames() {
+        let os = setup_test_directory().await;
+        os.fs.write("/stale.txt", "changed since\n").await.unwrap();
+        os.fs.write("/from.txt", "moved\n").await.unwrap();
+        os.fs.write("/to.txt", "already here\n").await.unwrap();
+
+        let patch = "\
+--- a/stale.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-original
+--- a/from.txt
++++ b/to.txt
+@@ -1 +1 @@
+-moved
++renamed
+";
+        let mut tool = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "command": "patch",
+            "patch": patch,
+        }))
+        .unwrap();
+        let err = prepare_patch(&os, patch).unwrap_err().to_string();
+        assert!(err.contains("stale.txt: hunk 1"), "unexpected error: {err}");
+        assert!(
+            err.contains("to.txt: the patch renames a file to it but it already exists"),
+            "unexpected error: {err}"
+        );
+        assert!(tool.validate(&os).await.is_err());
+
+        // Deletions without hunks only apply to empty files
+        os.fs.write("/empty.txt", "").await.unwrap();
+        assert!(prepare_patch(&os, "--- a/empty.txt\n+++ /dev/null\n").is_ok());
+        assert!(prepare_patch(&os, "--- a/stale.txt\n+++ /dev/null\n").is_err());
+    }
+
+    #[test]
+    fn test_find_lines_prefers_closest_match() {
+        let haystack = ["a", "b", "a", "b", "a", "b"].map(String::from);
+        assert_eq!(find_lines(&haystack, &["a", "b"], 0, 0), Some(0));
+        assert_eq!(find_lines(&haystack, &["a", "b"], 3, 0), Some(2));
+        assert_eq!(find_lines(&haystack, &["a", "b"], 0, 3), Some(4));
+        assert_eq!(
+            find_lines(&haystack, &["a  "], 1, 0),
+            Some(0),
+            "trailing whitespace is ignored"
+        );
+        assert_eq!(find_lines(&haystack, &["c"], 0, 0), None);
+    }
+
+    #[test]
+    fn test_eval_perm_patch() {
+        let agent = Agent {
+            name: "test_agent".to_string(),
+            allowed_tools: HashSet::from(["fs_write".to_string()]),
+            tools_settings: HashMap::from([(
+                ToolSettingTarget("fs_write".to_string()),
+                serde_json::json!({
+                    "allowedPaths": ["src/**"],
+                    "deniedPaths": ["secrets/**"]
+                }),
+            )]),
+            ..Default::default()
+        };
+        let patch_tool = |files: &[&str]| {
+            let patch = files
+                .iter()
+                .map(|f| format!("--- a/{f}\n+++ b/{f}\n@@ -1 +1 @@\n-old\n+new\n"))
+                .collect::<String>();
+            serde_json::from_value::<FsWrite>(serde_json::json!({ "command": "patch", "patch": patch })).unwrap()
+        };
+
+        assert!(matches!(
+            patch_tool(&["src/a.rs", "src/b.rs"]).eval_perm(&agent),
+            PermissionEvalResult::Allow
+        ));
+        assert!(matches!(
+            patch_tool(&["src/a.rs", "README.md"]).eval_perm(&agent),
+            PermissionEvalResult::Ask
+        ));
+        assert!(matches!(
+            patch_tool(&["src/a.rs", "secrets/key"]).eval_perm(&agent),
+            PermissionEvalResult::Deny(_)
+        ));
+    }
+
+    fn plain_stylized(content: &str) -> StylizedFile {
+        StylizedFile {
+            content: content.to_string(),
+            plain: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_print_diff_collapses_unchanged_lines() {
+        let old = (1..=20).map(|i| format!("line {i}\n")).collect::<String>();
+        let new = old.replace("line 10\n", "line ten\n");
+        let mut output = Vec::new();
+        print_diff(&mut output, &plain_stylized(&old), &plain_stylized(&new), 1).unwrap();
+        let output = String::from_utf8(strip_ansi_escapes::strip(output)).unwrap();
+
+        assert!(output.contains("6 unchanged lines"), "{output}");
+        assert!(output.contains("7 unchanged lines"), "{output}");
+        assert!(
+            output.contains("line 7"),
+            "context before the change is shown: {output}"
+        );
+        assert!(
+            output.contains("line 13"),
+            "context after the change is shown: {output}"
+        );
+        assert!(
+            !output.contains("line 6\n"),
+            "lines outside the context are collapsed: {output}"
+        );
+    }
+
+    #[test]
+    fn test_print_diff_emphasizes_changed_words() {
+        let old = "let value = compute(1, 2);\n";
+        let new = "let value = compute(1, 3);\n";
+        let mut output = Vec::new();
+        print_diff(&mut output, &plain_stylized(old), &plain_stylized(new), 1).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let reverse = format!("{}", style::SetAttribute(style::Attribute::Reverse));
+        assert_eq!(output.matches(&reverse).count(), 2, "one emphasized segment per line");
+        assert!(output.contains(&format!("{reverse}2")), "{output}");
+        assert!(output.contains(&format!("{reverse}3")), "{output}");
+
+        // Lines that are entirely different are not emphasized.
+        let mut output = Vec::new();
+        print_diff(
+            &mut output,
+            &plain_stylized("fn foo() {}\n"),
+            &plain_stylized("struct Bar;\n"),
+            1,
+        )
+        .unwrap();
+        assert!(!String::from_utf8(output).unwrap().contains(&reverse));
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_user_edits() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        let mut line_tracker = HashMap::new();
+
+        let tool = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "path": TEST_FILE_PATH,
+            "command": "str_replace",
+            "old_str": "3: asdf",
+            "new_str": "3: proposed",
+        }))
+        .unwrap();
+        let (_, proposed) = tool.proposed_content(&os).await.unwrap();
+        assert_eq!(proposed, TEST_FILE_CONTENTS.replace("3: asdf", "3: proposed"));
+        assert!(tool.with_user_edits(&proposed, proposed.clone()).is_none());
+
+        let edited = TEST_FILE_CONTENTS.replace("3: asdf", "3: edited by the user");
+        let (edited_tool, note) = tool.with_user_edits(&proposed, edited.clone()).unwrap();
+        assert!(note.contains("-3: proposed"), "{note}");
+        assert!(note.contains("+3: edited by the user"), "{note}");
+
+        edited_tool.invoke(&os, &mut stdout, &mut line_tracker).await.unwrap();
+        assert_eq!(os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(), edited);
+
+        let patch = serde_json::from_value::<FsWrite>(serde_json::json!({
+            "command": "patch",
+            "patch": "--- a/file\n+++ b/file\n@@ -1 +1 @@\n-a\n+b\n",
+        }))
+        .unwrap();
+        assert!(!patch.is_editable());
+    }
 }
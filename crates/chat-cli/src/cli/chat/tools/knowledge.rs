@@ -6,6 +6,7 @@ use crossterm::style::{
     Color,
 };
 use eyre::Result;
+use semantic_search_client::SearchFilter;
 use serde::Deserialize;
 use tracing::warn;
 
@@ -67,6 +68,21 @@ pub struct KnowledgeClear {
 pub struct KnowledgeSearch {
     pub query: String,
     pub context_id: Option<String>,
+    /// "workspace", "global" or "all". Defaults to the workspace knowledge base if it exists
+    pub scope: Option<String>,
+    /// Filter expression such as `path:src/** type:markdown`, see [`SearchFilter`]
+    pub filter: Option<String>,
+}
+
+impl KnowledgeSearch {
+    /// The filter expression combined with the context ID, if any
+    fn search_filter(&self) -> Result<SearchFilter> {
+        let mut filter: SearchFilter = self.filter.as_deref().unwrap_or_default().parse()?;
+        if let Some(context_id) = &self.context_id {
+            filter.context = Some(context_id.clone());
+        }
+        Ok(filter)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -146,13 +162,26 @@ impl Knowledge {
                 }
                 Ok(())
             },
-            Knowledge::Search(_) => Ok(()),
+            Knowledge::Search(search) => {
+                if let Some(scope) = &search.scope {
+                    if !["workspace", "global", "all"].contains(&scope.to_lowercase().as_str()) {
+                        eyre::bail!("Invalid scope '{}'. Valid options are: workspace, global, all", scope);
+                    }
+                }
+                search.search_filter()?;
+                Ok(())
+            },
             Knowledge::Show => Ok(()),
             Knowledge::Status => Ok(()),
             Knowledge::Cancel(_) => Ok(()),
         }
     }
 
+    /// Whether the operation only reads the knowledge base
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, Knowledge::Search(_) | Knowledge::Show | Knowledge::Status)
+    }
+
     pub async fn queue_description(&self, os: &Os, updates: &mut impl Write) -> Result<()> {
         match self {
             Knowledge::Add(add) => {
@@ -292,9 +321,28 @@ impl Knowledge {
                         style::Print(context_id),
                         style::ResetColor,
                     )?;
+                } else if let Some(scope) = &search.scope {
+                    queue!(
+                        updates,
+                        style::Print(" in "),
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(scope),
+                        style::ResetColor,
+                        style::Print(" knowledge bases"),
+                    )?;
                 } else {
                     queue!(updates, style::Print(" across all contexts"),)?;
                 }
+
+                if let Some(filter) = search.filter.as_deref().filter(|filter| !filter.trim().is_empty()) {
+                    queue!(
+                        updates,
+                        style::Print(" matching "),
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(filter),
+                        style::ResetColor,
+                    )?;
+                }
             },
             Knowledge::Show => {
                 queue!(updates, style::Print("Showing all knowledge base entries"),)?;
@@ -420,23 +468,40 @@ impl Knowledge {
                 }
             },
             Knowledge::Clear(_) => store
-                .clear()
+                .clear(None)
                 .await
                 .unwrap_or_else(|e| format!("Failed to clear knowledge base: {}", e)),
             Knowledge::Search(search) => {
-                let results = store.search(&search.query, search.context_id.as_deref()).await;
+                let scopes = match store.search_scopes(search.scope.as_deref()) {
+                    Ok(scopes) => scopes,
+                    Err(e) => {
+                        return Ok(InvokeOutput {
+                            output: OutputKind::Text(e),
+                        });
+                    },
+                };
+                let filter = match search.search_filter() {
+                    Ok(filter) => filter,
+                    Err(e) => {
+                        return Ok(InvokeOutput {
+                            output: OutputKind::Text(e.to_string()),
+                        });
+                    },
+                };
+                let results = store.search(&search.query, &filter, &scopes).await;
                 match results {
                     Ok(results) => {
                         if results.is_empty() {
                             format!("No matching entries found for query: \"{}\"", search.query)
                         } else {
-                            let mut output = format!("Search results for \"{}\":\n\n", search.query);
-                            for result in results {
-                                if let Some(text) = result.text() {
-                                    output.push_str(&format!("{}\n\n", text));
-                                }
-                            }
-                            output
+                            // Citations carry the path and line range of each result so that the
+                            // model can read the surrounding code with a targeted fs_read
+                            return Ok(InvokeOutput {
+                                output: OutputKind::Json(serde_json::json!({
+                                    "query": search.query,
+                                    "results": results,
+                                })),
+                            });
                         }
                     },
                     Err(e) => {
@@ -452,10 +517,11 @@ impl Knowledge {
                             "No knowledge base entries found".to_string()
                         } else {
                             let mut output = String::from("Knowledge base entries:\n");
-                            for context in contexts {
-                                output.push_str(&format!("- ID: {}\n  Name: {}\n  Description: {}\n  Persistent: {}\n  Created: {}\n  Last Updated: {}\n  Items: {}\n\n",
+                            for (scope, context) in contexts {
+                                output.push_str(&format!("- ID: {}\n  Name: {}\n  Scope: {}\n  Description: {}\n  Persistent: {}\n  Created: {}\n  Last Updated: {}\n  Items: {}\n\n",
                                     context.id,
                                     context.name,
+                                    scope,
                                     context.description,
                                     context.persistent,
                                     context.created_at.format("%Y-%m-%d %H:%M:%S"),
@@ -535,6 +601,8 @@ impl Knowledge {
             "Status: Cancelled".to_string()
         } else if op.is_failed {
             format!("Status: Failed - {}", op.message)
+        } else if op.is_completed {
+            format!("Status: Completed - {}", op.message)
         } else if op.is_waiting {
             format!("Status: Waiting - {}", op.message)
         } else if op.total > 0 {
@@ -547,10 +615,15 @@ impl Knowledge {
             format!("Status: In Progress - {}", op.message)
         };
 
+        let status_info = match op.changes {
+            Some(changes) if !op.is_completed => format!("{} | Changes: {}", status_info, changes),
+            _ => status_info,
+        };
+
         let operation_desc = op.operation_type.display_name();
 
         // Format with conditional elapsed time and ETA
-        if op.is_cancelled || op.is_failed {
+        if op.is_cancelled || op.is_failed || op.is_completed {
             format!(
                 "Operation ID: {} | Type: {} | {}",
                 op.short_id, operation_desc, status_info
@@ -4,8 +4,10 @@ pub mod fs_read;
 pub mod fs_write;
 pub mod gh_issue;
 pub mod knowledge;
+pub mod propose_plan;
 pub mod thinking;
 pub mod use_aws;
+pub mod use_subagent;
 
 use std::borrow::{
     Borrow,
@@ -30,6 +32,7 @@ use fs_read::FsRead;
 use fs_write::FsWrite;
 use gh_issue::GhIssue;
 use knowledge::Knowledge;
+use propose_plan::ProposePlan;
 use serde::{
     Deserialize,
     Serialize,
@@ -37,6 +40,7 @@ use serde::{
 use thinking::Thinking;
 use tracing::error;
 use use_aws::UseAws;
+use use_subagent::UseSubagent;
 
 use super::consts::{
     MAX_TOOL_RESPONSE_SIZE,
@@ -49,12 +53,13 @@ use super::util::images::RichImageBlocks;
 use crate::cli::agent::{
     Agent,
     PermissionEvalResult,
+    TrustRule,
 };
 use crate::cli::chat::line_tracker::FileLineTracker;
 use crate::os::Os;
 
 pub const DEFAULT_APPROVE: [&str; 1] = ["fs_read"];
-pub const NATIVE_TOOLS: [&str; 7] = [
+pub const NATIVE_TOOLS: [&str; 8] = [
     "fs_read",
     "fs_write",
     #[cfg(windows)]
@@ -65,6 +70,7 @@ pub const NATIVE_TOOLS: [&str; 7] = [
     "gh_issue",
     "knowledge",
     "thinking",
+    "use_subagent",
 ];
 
 /// Represents an executable tool use.
@@ -79,6 +85,8 @@ pub enum Tool {
     GhIssue(GhIssue),
     Knowledge(Knowledge),
     Thinking(Thinking),
+    UseSubagent(UseSubagent),
+    ProposePlan(ProposePlan),
 }
 
 impl Tool {
@@ -96,6 +104,8 @@ impl Tool {
             Tool::GhIssue(_) => "gh_issue",
             Tool::Knowledge(_) => "knowledge",
             Tool::Thinking(_) => "thinking (prerelease)",
+            Tool::UseSubagent(_) => "use_subagent",
+            Tool::ProposePlan(_) => propose_plan::PROPOSE_PLAN_TOOL_NAME,
         }
         .to_owned()
     }
@@ -111,9 +121,82 @@ impl Tool {
             Tool::GhIssue(_) => PermissionEvalResult::Allow,
             Tool::Thinking(_) => PermissionEvalResult::Allow,
             Tool::Knowledge(knowledge) => knowledge.eval_perm(agent),
+            Tool::UseSubagent(use_subagent) => use_subagent.eval_perm(agent),
+            // Accepting the tool is how the user approves the plan
+            Tool::ProposePlan(_) => PermissionEvalResult::Ask,
         }
     }
 
+    /// Whether the tool only reads state, and so can run at the same time as other such tools
+    /// requested in the same turn
+    pub fn is_concurrency_safe(&self) -> bool {
+        match self {
+            Tool::FsRead(_) | Tool::Thinking(_) => true,
+            Tool::UseAws(use_aws) => !use_aws.requires_acceptance(),
+            Tool::Knowledge(knowledge) => knowledge.is_read_only(),
+            Tool::Custom(custom_tool) => custom_tool.read_only,
+            Tool::FsWrite(_)
+            | Tool::ExecuteCommand(_)
+            | Tool::GhIssue(_)
+            | Tool::UseSubagent(_)
+            | Tool::ProposePlan(_) => false,
+        }
+    }
+
+    /// Whether the tool may run other programs, which could read from the terminal
+    pub fn spawns_processes(&self) -> bool {
+        match self {
+            // Sub-agents run their own tools, including commands
+            Tool::ExecuteCommand(_) | Tool::UseAws(_) | Tool::GhIssue(_) | Tool::UseSubagent(_) => true,
+            Tool::FsRead(_)
+            | Tool::FsWrite(_)
+            | Tool::Custom(_)
+            | Tool::Knowledge(_)
+            | Tool::Thinking(_)
+            | Tool::ProposePlan(_) => false,
+        }
+    }
+
+    /// Whether this use of the tool only reads state, and so is allowed in plan mode. Unlike
+    /// [Self::is_concurrency_safe], this allows read-only commands and plan proposals but not
+    /// custom tools, whose `readOnlyHint` is only a hint.
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            Tool::ExecuteCommand(execute_command) => !execute_command.requires_acceptance(None, true),
+            Tool::ProposePlan(_) => true,
+            Tool::Custom(_) => false,
+            Tool::FsRead(_)
+            | Tool::FsWrite(_)
+            | Tool::UseAws(_)
+            | Tool::GhIssue(_)
+            | Tool::Knowledge(_)
+            | Tool::Thinking(_)
+            | Tool::UseSubagent(_) => self.is_concurrency_safe(),
+        }
+    }
+
+    /// The rule that always allows this particular use of the tool, where `tool_name` is the name
+    /// of the tool as it would appear in allowedTools. Commands and paths are escaped so that they
+    /// only match themselves.
+    pub fn trust_rule(&self, tool_name: String) -> TrustRule {
+        let settings = match self {
+            Tool::ExecuteCommand(execute_command) => {
+                Some(("allowedCommands", vec![regex::escape(&execute_command.command)]))
+            },
+            Tool::FsWrite(fs_write) => Some((
+                "allowedPaths",
+                fs_write
+                    .permission_paths()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|path| globset::escape(path))
+                    .collect(),
+            )),
+            _ => None,
+        };
+        TrustRule { tool_name, settings }
+    }
+
     /// Invokes the tool asynchronously
     pub async fn invoke(
         &self,
@@ -130,6 +213,8 @@ impl Tool {
             Tool::GhIssue(gh_issue) => gh_issue.invoke(os, stdout).await,
             Tool::Knowledge(knowledge) => knowledge.invoke(os, stdout).await,
             Tool::Thinking(think) => think.invoke(stdout).await,
+            Tool::UseSubagent(use_subagent) => use_subagent.invoke(os, stdout).await,
+            Tool::ProposePlan(propose_plan) => propose_plan.invoke(stdout).await,
         }
     }
 
@@ -144,6 +229,8 @@ impl Tool {
             Tool::GhIssue(gh_issue) => gh_issue.queue_description(output),
             Tool::Knowledge(knowledge) => knowledge.queue_description(os, output).await,
             Tool::Thinking(thinking) => thinking.queue_description(output),
+            Tool::UseSubagent(use_subagent) => use_subagent.queue_description(output),
+            Tool::ProposePlan(propose_plan) => propose_plan.queue_description(output),
         }
     }
 
@@ -158,6 +245,8 @@ impl Tool {
             Tool::GhIssue(gh_issue) => gh_issue.validate(os).await,
             Tool::Knowledge(knowledge) => knowledge.validate(os).await,
             Tool::Thinking(think) => think.validate(os).await,
+            Tool::UseSubagent(use_subagent) => use_subagent.validate(os).await,
+            Tool::ProposePlan(propose_plan) => propose_plan.validate().await,
         }
     }
 
@@ -181,6 +270,19 @@ pub struct ToolSpec {
     pub input_schema: InputSchema,
     #[serde(skip_serializing, default = "tool_origin")]
     pub tool_origin: ToolOrigin,
+    /// Hints about the tool's behavior advertised by its MCP server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// The subset of MCP tool annotations the client acts on, see
+/// <https://modelcontextprotocol.io/specification/2025-06-18/server/tools#tool>
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    /// The tool does not modify its environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -253,6 +355,9 @@ pub struct QueuedTool {
     pub name: String,
     pub accepted: bool,
     pub tool: Tool,
+    /// Describes the changes the user made to the tool's proposed edits before accepting it, to
+    /// be sent back to the model alongside the tool result.
+    pub user_edit_note: Option<String>,
 }
 
 /// The schema specification describing a tool's fields.
@@ -468,6 +573,143 @@ mod tests {
     use super::*;
     use crate::os::ACTIVE_USER_HOME;
 
+    #[test]
+    fn test_is_read_only() {
+        let command = |command: &str| {
+            Tool::ExecuteCommand(serde_json::from_value(serde_json::json!({ "command": command })).unwrap())
+        };
+        let aws = |operation_name: &str| {
+            Tool::UseAws(
+                serde_json::from_value(serde_json::json!({
+                    "service_name": "s3",
+                    "operation_name": operation_name,
+                    "region": "us-west-2",
+                }))
+                .unwrap(),
+            )
+        };
+
+        assert!(command("ls -la | grep src").is_read_only());
+        assert!(!command("rm -rf build").is_read_only());
+        assert!(!command("ls && rm -rf build").is_read_only());
+        assert!(aws("list-buckets").is_read_only());
+        assert!(!aws("delete-bucket").is_read_only());
+        assert!(
+            !Tool::FsWrite(
+                serde_json::from_value(serde_json::json!({
+                    "command": "create",
+                    "path": "/file.txt",
+                    "file_text": "hello",
+                }))
+                .unwrap()
+            )
+            .is_read_only()
+        );
+    }
+
+    #[test]
+    fn test_trust_rule_allows_only_the_same_use() {
+        let mut agent = Agent {
+            allowed_tools: Default::default(),
+            ..Default::default()
+        };
+        let command = |command: &str| {
+            Tool::ExecuteCommand(serde_json::from_value(serde_json::json!({ "command": command })).unwrap())
+        };
+        let write = |path: &str| {
+            Tool::FsWrite(
+                serde_json::from_value(serde_json::json!({
+                    "command": "create",
+                    "path": path,
+                    "file_text": "hello",
+                }))
+                .unwrap(),
+            )
+        };
+
+        let tool_name = if cfg!(windows) { "execute_cmd" } else { "execute_bash" };
+        agent.add_trust_rule(&command("rm -rf build/*").trust_rule(tool_name.to_string()));
+        agent.add_trust_rule(&write("src/[id].rs").trust_rule("fs_write".to_string()));
+
+        assert_eq!(
+            command("rm -rf build/*").requires_acceptance(&agent),
+            PermissionEvalResult::Allow
+        );
+        assert_eq!(
+            command("rm -rf build").requires_acceptance(&agent),
+            PermissionEvalResult::Ask
+        );
+        assert_eq!(
+            write("src/[id].rs").requires_acceptance(&agent),
+            PermissionEvalResult::Allow
+        );
+        assert_eq!(write("src/i.rs").requires_acceptance(&agent), PermissionEvalResult::Ask);
+    }
+
+    #[test]
+    fn test_concurrency_safe_tools() {
+        let tool = |json: serde_json::Value| -> Tool {
+            match json["tool"].as_str().unwrap() {
+                "fs_read" => Tool::FsRead(serde_json::from_value(json["args"].clone()).unwrap()),
+                "fs_write" => Tool::FsWrite(serde_json::from_value(json["args"].clone()).unwrap()),
+                "use_aws" => Tool::UseAws(serde_json::from_value(json["args"].clone()).unwrap()),
+                "knowledge" => Tool::Knowledge(serde_json::from_value(json["args"].clone()).unwrap()),
+                _ => unreachable!(),
+            }
+        };
+        let aws = |operation_name: &str| {
+            tool(serde_json::json!({ "tool": "use_aws", "args": {
+                "service_name": "s3",
+                "operation_name": operation_name,
+                "region": "us-west-2",
+                "label": "",
+            }}))
+        };
+
+        assert!(
+            tool(serde_json::json!({ "tool": "fs_read", "args": {
+                "operations": [{ "mode": "Line", "path": "/file.txt" }]
+            }}))
+            .is_concurrency_safe()
+        );
+        assert!(
+            !tool(serde_json::json!({ "tool": "fs_write", "args": {
+                "command": "create", "path": "/file.txt", "file_text": "hello"
+            }}))
+            .is_concurrency_safe()
+        );
+        assert!(aws("list-buckets").is_concurrency_safe());
+        assert!(!aws("delete-bucket").is_concurrency_safe());
+        assert!(
+            tool(serde_json::json!({ "tool": "knowledge", "args": { "command": "search", "query": "retries" }}))
+                .is_concurrency_safe()
+        );
+        assert!(
+            !tool(serde_json::json!({ "tool": "knowledge", "args": { "command": "clear", "confirm": true }}))
+                .is_concurrency_safe()
+        );
+    }
+
+    #[test]
+    fn test_tool_spec_annotations() {
+        let spec: ToolSpec = serde_json::from_value(serde_json::json!({
+            "name": "get_weather",
+            "description": "Get the weather",
+            "inputSchema": { "type": "object" },
+            "annotations": { "title": "Weather", "readOnlyHint": true, "openWorldHint": true },
+        }))
+        .unwrap();
+        assert_eq!(spec.annotations.unwrap().read_only_hint, Some(true));
+
+        let spec: ToolSpec = serde_json::from_value(serde_json::json!({
+            "name": "get_weather",
+            "description": "Get the weather",
+            "inputSchema": { "type": "object" },
+        }))
+        .unwrap();
+        assert!(spec.annotations.is_none());
+    }
+
     #[tokio::test]
     async fn test_tilde_path_expansion() {
         let os = Os::new().await.unwrap();
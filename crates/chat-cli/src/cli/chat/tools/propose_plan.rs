@@ -0,0 +1,138 @@
+use std::io::Write;
+
+use crossterm::queue;
+use crossterm::style::{
+    self,
+    Attribute,
+    Color,
+};
+use eyre::{
+    Result,
+    bail,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    InputSchema,
+    InvokeOutput,
+    OutputKind,
+    ToolOrigin,
+    ToolSpec,
+};
+
+pub const PROPOSE_PLAN_TOOL_NAME: &str = "propose_plan";
+
+/// The native tools exposed in plan mode besides [ProposePlan]. Each use of them is still checked
+/// with [super::Tool::is_read_only], since only some of their operations are read-only.
+pub const PLAN_MODE_TOOLS: [&str; 5] = [
+    "fs_read",
+    #[cfg(windows)]
+    "execute_cmd",
+    #[cfg(not(windows))]
+    "execute_bash",
+    "use_aws",
+    "knowledge",
+    "thinking",
+];
+
+/// Instructs the model on how to behave in plan mode.
+pub const PLAN_MODE_INSTRUCTIONS: &str = "You are in plan mode. Only tools that read state are available: use them to investigate the request, but do not attempt to modify any files or resources. Once you understand what needs to be done, call the propose_plan tool with a summary and the steps you would take. The user will either approve the plan, after which all tools become available again so that you can carry it out, or ask you to revise it.\n\n";
+
+/// Presents a plan for the user to approve. Only exposed in plan mode, which approving the plan
+/// ends.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProposePlan {
+    /// What the plan achieves, in a sentence or two
+    pub summary: String,
+    /// The steps to carry out, in order
+    pub steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlanStep {
+    pub description: String,
+    /// The files or resources the step changes
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl ProposePlan {
+    pub fn tool_spec() -> ToolSpec {
+        ToolSpec {
+            name: PROPOSE_PLAN_TOOL_NAME.to_string(),
+            description: "Propose a plan for the user to approve. Call this once you have investigated the request in plan mode, and before making any changes. If the user approves the plan, all tools become available so that you can carry it out.".to_string(),
+            input_schema: InputSchema(json!({
+                "type": "object",
+                "properties": {
+                    "summary": {
+                        "type": "string",
+                        "description": "What the plan achieves, in a sentence or two"
+                    },
+                    "steps": {
+                        "type": "array",
+                        "description": "The steps to carry out, in order",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "description": {
+                                    "type": "string",
+                                    "description": "What the step does"
+                                },
+                                "files": {
+                                    "type": "array",
+                                    "description": "The files or resources the step changes, if any",
+                                    "items": { "type": "string" }
+                                }
+                            },
+                            "required": ["description"]
+                        }
+                    }
+                },
+                "required": ["summary", "steps"]
+            })),
+            tool_origin: ToolOrigin::Native,
+            annotations: None,
+        }
+    }
+
+    pub fn queue_description(&self, output: &mut impl Write) -> Result<()> {
+        queue!(
+            output,
+            style::Print("I propose the following plan:\n\n"),
+            style::SetAttribute(Attribute::Bold),
+            style::Print(&self.summary),
+            style::SetAttribute(Attribute::Reset),
+            style::Print("\n\n"),
+        )?;
+        for (i, step) in self.steps.iter().enumerate() {
+            queue!(output, style::Print(format!("  {}. {}\n", i + 1, step.description)))?;
+            if !step.files.is_empty() {
+                queue!(
+                    output,
+                    style::SetForegroundColor(Color::DarkGrey),
+                    style::Print(format!("     {}\n", step.files.join(", "))),
+                    style::SetForegroundColor(Color::Reset),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Only invoked once the user approves the plan.
+    pub async fn invoke(&self, _updates: impl Write) -> Result<InvokeOutput> {
+        Ok(InvokeOutput {
+            output: OutputKind::Text(
+                "The user approved the plan. Plan mode has ended and all tools are available again: carry out the plan."
+                    .to_string(),
+            ),
+        })
+    }
+
+    pub async fn validate(&mut self) -> Result<()> {
+        if self.steps.is_empty() {
+            bail!("The plan must have at least one step");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use crossterm::queue;
+use crossterm::style::{
+    self,
+    Color,
+};
+use eyre::{
+    Result,
+    bail,
+    eyre,
+};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use super::{
+    InvokeOutput,
+    OutputKind,
+    Tool,
+};
+use crate::api_client::model::ToolResultStatus;
+use crate::cli::agent::{
+    Agent,
+    Agents,
+    PermissionEvalResult,
+};
+use crate::cli::chat::cli::model::ModelInfo;
+use crate::cli::chat::conversation::ConversationState;
+use crate::cli::chat::message::{
+    AssistantToolUse,
+    ToolUseResult,
+    ToolUseResultBlock,
+};
+use crate::cli::chat::parser::{
+    ResponseEvent,
+    SendMessageStream,
+};
+use crate::cli::chat::tool_manager::ToolManager;
+use crate::cli::chat::{
+    CONTINUATION_LINE,
+    PURPOSE_ARROW,
+};
+use crate::os::Os;
+use crate::util::pattern_matching::matches_any_pattern;
+
+/// The most requests a sub-agent may send before it has to give its answer
+const MAX_SUBAGENT_TURNS: usize = 25;
+
+/// Appended to the task so the sub-agent knows only its final reply is passed on
+const SUBAGENT_INSTRUCTIONS: &str = "You are a sub-agent working on the task above on behalf of another agent, which \
+                                     only sees your final reply. Use the tools available to you to complete the \
+                                     task, then reply with a concise summary of what you found or did, including \
+                                     the file paths, commands and facts the other agent needs to act on it.";
+
+/// Delegates a focused task to a child agent with its own conversation, so that only the child's
+/// final summary is added to the parent conversation.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UseSubagent {
+    /// The task for the sub-agent
+    pub task: String,
+    /// Name of the agent config the sub-agent runs with, the active one if not given
+    pub agent: Option<String>,
+
+    #[serde(skip_deserializing)]
+    pub context: Option<SubagentContext>,
+}
+
+/// What a sub-agent needs from the session that delegates to it
+#[derive(Debug, Clone)]
+pub struct SubagentContext {
+    /// The session's agents, with the agent the sub-agent runs with set as the active one
+    pub agents: Agents,
+    /// Resolves the tool uses of the sub-agent, see [ToolManager::for_subagent]
+    pub tool_manager: Arc<ToolManager>,
+    pub model_info: Option<ModelInfo>,
+}
+
+impl UseSubagent {
+    pub fn set_context(&mut self, context: SubagentContext) {
+        self.context = Some(context);
+    }
+
+    pub fn queue_description(&self, output: &mut impl Write) -> Result<()> {
+        queue!(output, style::Print("Delegating to a sub-agent"))?;
+        if let Some(agent) = &self.agent {
+            queue!(
+                output,
+                style::Print(" running "),
+                style::SetForegroundColor(Color::Green),
+                style::Print(agent),
+                style::ResetColor,
+            )?;
+        }
+        queue!(
+            output,
+            style::Print(":\n"),
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print(&self.task),
+            style::ResetColor,
+            style::Print("\n"),
+        )?;
+        Ok(())
+    }
+
+    pub async fn validate(&mut self, _os: &Os) -> Result<()> {
+        if self.task.trim().is_empty() {
+            bail!("The task must not be empty");
+        }
+        if self.context.is_none() {
+            match &self.agent {
+                Some(agent) => bail!("No agent with name {agent} found"),
+                None => bail!("use_subagent: Required tool context (SubagentContext) not set by the program."),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn eval_perm(&self, agent: &Agent) -> PermissionEvalResult {
+        _ = self;
+        if matches_any_pattern(&agent.allowed_tools, "use_subagent") {
+            PermissionEvalResult::Allow
+        } else {
+            PermissionEvalResult::Ask
+        }
+    }
+
+    /// Runs the sub-agent until it replies without using tools, printing a line for each of its
+    /// tool uses and returning its final reply.
+    pub async fn invoke(&self, os: &Os, output: &mut impl Write) -> Result<InvokeOutput> {
+        let Some(context) = self.context.as_ref() else {
+            bail!("use_subagent: Required tool context (SubagentContext) not set by the program.");
+        };
+
+        let mut conversation = ConversationState::new(
+            &uuid::Uuid::new_v4().to_string(),
+            context.agents.clone(),
+            context.tool_manager.schema.clone(),
+            ToolManager::default(),
+            None,
+            os,
+            true,
+        )
+        .await;
+        conversation.model_info = context.model_info.clone();
+        conversation
+            .set_next_user_message(format!("{}\n\n{SUBAGENT_INSTRUCTIONS}", self.task))
+            .await;
+
+        for _ in 0..MAX_SUBAGENT_TURNS {
+            // Hook output would otherwise interleave with the parent's tool output
+            let mut hook_output = Vec::new();
+            let state = conversation
+                .as_sendable_conversation_state(os, &mut hook_output, true)
+                .await?;
+            let mut stream =
+                SendMessageStream::send_message(&os.client, state, Arc::new(Mutex::new(None)), None).await?;
+
+            let (message, request_metadata) = loop {
+                match stream.recv().await {
+                    Some(Ok(ResponseEvent::EndStream {
+                        message,
+                        request_metadata,
+                    })) => break (message, request_metadata),
+                    Some(Ok(_)) => (),
+                    Some(Err(err)) => return Err(err.into()),
+                    None => bail!("The sub-agent's response ended unexpectedly"),
+                }
+            };
+
+            let tool_uses = message.tool_uses().map(<[_]>::to_vec).unwrap_or_default();
+            if tool_uses.is_empty() {
+                return Ok(InvokeOutput {
+                    output: OutputKind::Text(message.content().to_string()),
+                });
+            }
+            conversation.record_assistant_message(message, Some(request_metadata));
+
+            let mut tool_results = Vec::with_capacity(tool_uses.len());
+            for tool_use in tool_uses {
+                tool_results.push(self.run_tool(os, context, tool_use, output).await?);
+            }
+            conversation.add_tool_results(tool_results);
+        }
+
+        Err(eyre!(
+            "The sub-agent did not finish the task within {MAX_SUBAGENT_TURNS} requests"
+        ))
+    }
+
+    /// Runs a tool use of the sub-agent. The sub-agent cannot ask the user for permission, so
+    /// tools that its agent config does not allow are refused.
+    async fn run_tool(
+        &self,
+        os: &Os,
+        context: &SubagentContext,
+        tool_use: AssistantToolUse,
+        output: &mut impl Write,
+    ) -> Result<ToolUseResult> {
+        let tool_use_id = tool_use.id.clone();
+        let name = tool_use.name.clone();
+        let error_result = |message: String| ToolUseResult {
+            tool_use_id: tool_use_id.clone(),
+            content: vec![ToolUseResultBlock::Text(message)],
+            status: ToolResultStatus::Error,
+        };
+
+        let mut tool = match context.tool_manager.get_tool_from_tool_use(tool_use) {
+            Ok(tool) => tool,
+            Err(err) => return Ok(err.into()),
+        };
+        if let Tool::UseSubagent(_) = tool {
+            return Ok(error_result(
+                "Sub-agents cannot delegate to other sub-agents".to_string(),
+            ));
+        }
+        if let Err(err) = tool.validate(os).await {
+            return Ok(error_result(format!("Failed to validate tool parameters: {err}")));
+        }
+
+        let allowed = context.agents.trust_all_tools
+            || context
+                .agents
+                .get_active()
+                .is_some_and(|agent| matches!(tool.requires_acceptance(agent), PermissionEvalResult::Allow));
+
+        queue!(
+            output,
+            style::Print(CONTINUATION_LINE),
+            style::Print("\n"),
+            style::Print(PURPOSE_ARROW),
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print(tool.display_name()),
+        )?;
+
+        let result = if allowed {
+            // The sub-agent's tool output is summarized by the sub-agent rather than shown
+            let mut tool_output = Vec::new();
+            let mut line_tracker = HashMap::new();
+            Box::pin(tool.invoke(os, &mut tool_output, &mut line_tracker)).await
+        } else {
+            Err(eyre!(
+                "The user has not allowed sub-agents to use {name} without asking, and sub-agents cannot ask"
+            ))
+        };
+
+        let (result, color, status) = match result {
+            Ok(result) => (
+                ToolUseResult {
+                    tool_use_id,
+                    content: vec![result.into()],
+                    status: ToolResultStatus::Success,
+                },
+                Color::DarkGrey,
+                "",
+            ),
+            Err(err) => (
+                error_result(format!("An error occurred processing the tool: \n{err}")),
+                Color::Red,
+                if allowed { " (failed)" } else { " (not allowed)" },
+            ),
+        };
+        queue!(
+            output,
+            style::SetForegroundColor(color),
+            style::Print(status),
+            style::ResetColor,
+            style::Print("\n"),
+        )?;
+        output.flush()?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn use_subagent(task: &str) -> UseSubagent {
+        let mut agents = Agents::default();
+        agents.agents.insert("default".to_string(), Agent::default());
+        agents.switch("default").unwrap();
+        UseSubagent {
+            task: task.to_string(),
+            agent: None,
+            context: Some(SubagentContext {
+                agents,
+                tool_manager: Arc::new(ToolManager::default()),
+                model_info: None,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_returns_only_final_summary() {
+        let mut os = Os::new().await.unwrap();
+        os.fs.write("/notes.txt", "the test fails on Windows").await.unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Let me look at the notes",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_read",
+                    "args": { "operations": [{ "mode": "Line", "path": "/notes.txt" }] }
+                },
+                {
+                    "tool_use_id": "2",
+                    "name": "fs_write",
+                    "args": { "command": "create", "file_text": "fixed", "path": "/fix.txt" }
+                }
+            ],
+            [
+                "The test fails on Windows.",
+            ],
+        ]));
+
+        let mut output = Vec::new();
+        let result = use_subagent("Find out why the test fails")
+            .invoke(&os, &mut output)
+            .await
+            .unwrap();
+
+        assert_eq!(result.as_str(), "The test fails on Windows.");
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("fs_read"));
+        assert!(output.contains("fs_write") && output.contains("(not allowed)"));
+        assert!(!os.fs.exists("/fix.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_validate() {
+        let os = Os::new().await.unwrap();
+        assert!(use_subagent("Investigate").validate(&os).await.is_ok());
+        assert!(use_subagent(" ").validate(&os).await.is_err());
+
+        let mut missing_agent = use_subagent("Investigate");
+        missing_agent.agent = Some("missing".to_string());
+        missing_agent.context = None;
+        let err = missing_agent.validate(&os).await.unwrap_err();
+        assert_eq!(err.to_string(), "No agent with name missing found");
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use clap::ValueEnum;
+use similar::TextDiff;
+
+use super::conversation::ConversationState;
+use super::message::{
+    ToolUseResult,
+    ToolUseResultBlock,
+};
+use super::tools::fs_write::FsWrite;
+use super::util::truncate_safe;
+use crate::api_client::model::ToolResultStatus;
+
+/// Tool outputs are truncated to this many bytes in transcripts
+const MAX_TOOL_OUTPUT_LEN: usize = 10_000;
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; \
+                          line-height: 1.5; }
+pre { background: #f6f8fa; padding: 0.75rem; overflow-x: auto; }
+.text { white-space: pre-wrap; }
+.tool { border-left: 3px solid #d0d7de; padding-left: 0.75rem; margin: 1rem 0; }
+.failed { color: #cf222e; }
+.add { color: #116329; }
+.del { color: #cf222e; }";
+
+/// Formats a conversation can be exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TranscriptFormat {
+    /// Markdown
+    Md,
+    /// A standalone HTML page
+    Html,
+}
+
+impl TranscriptFormat {
+    /// Returns the format matching the extension of `path`, Markdown unless it is an HTML file.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => Self::Html,
+            _ => Self::Md,
+        }
+    }
+}
+
+/// A part of the conversation in the order it is shown in a transcript
+#[derive(Debug)]
+enum Entry<'a> {
+    /// Summary of the history replaced by compaction
+    Summary(&'a str),
+    User(&'a str),
+    Assistant(&'a str),
+    ToolUse(ToolUse<'a>),
+}
+
+#[derive(Debug)]
+struct ToolUse<'a> {
+    name: &'a str,
+    /// The arguments as pretty printed JSON
+    input: String,
+    /// The change made by a file write, shown instead of its arguments
+    diff: Option<String>,
+    output: Option<String>,
+    failed: bool,
+}
+
+/// Renders the conversation into a document that can be read without the CLI, e.g. to share it
+/// in a PR description.
+pub fn render_transcript(conversation: &ConversationState, format: TranscriptFormat) -> String {
+    let entries = entries(conversation);
+    match format {
+        TranscriptFormat::Md => render_markdown(&entries),
+        TranscriptFormat::Html => render_html(&entries),
+    }
+}
+
+fn entries(conversation: &ConversationState) -> Vec<Entry<'_>> {
+    let results = conversation
+        .history()
+        .iter()
+        .filter_map(|entry| entry.user().tool_use_results())
+        .flatten()
+        .map(|result| (result.tool_use_id.as_str(), result))
+        .collect::<HashMap<_, _>>();
+
+    let mut entries = vec![];
+    if let Some(summary) = conversation.latest_summary() {
+        entries.push(Entry::Summary(summary));
+    }
+    for entry in conversation.history() {
+        if let Some(prompt) = entry.user().prompt() {
+            entries.push(Entry::User(prompt));
+        }
+        let content = entry.assistant().content();
+        if !content.trim().is_empty() {
+            entries.push(Entry::Assistant(content));
+        }
+        for tool_use in entry.assistant().tool_uses().unwrap_or_default() {
+            let result = results.get(tool_use.id.as_str());
+            entries.push(Entry::ToolUse(ToolUse {
+                name: &tool_use.name,
+                input: serde_json::to_string_pretty(&tool_use.args).unwrap_or_default(),
+                diff: (tool_use.name == "fs_write")
+                    .then(|| fs_write_diff(&tool_use.args))
+                    .flatten(),
+                output: result.map(|result| tool_output(result)),
+                failed: result.is_some_and(|result| matches!(result.status, ToolResultStatus::Error)),
+            }));
+        }
+    }
+    entries
+}
+
+/// Returns the change an fs_write tool use makes as a unified diff.
+fn fs_write_diff(args: &serde_json::Value) -> Option<String> {
+    let added_lines = |path: &str, text: &str| {
+        let mut diff = format!("+++ {path}\n");
+        for line in text.lines() {
+            diff.push_str(&format!("+{line}\n"));
+        }
+        diff
+    };
+
+    Some(match serde_json::from_value::<FsWrite>(args.clone()).ok()? {
+        FsWrite::Create {
+            path,
+            file_text,
+            new_str,
+            ..
+        } => added_lines(&path, &file_text.or(new_str).unwrap_or_default()),
+        FsWrite::StrReplace {
+            path, old_str, new_str, ..
+        } => TextDiff::from_lines(&old_str, &new_str)
+            .unified_diff()
+            .header(&path, &path)
+            .to_string(),
+        FsWrite::Insert { path, new_str, .. } | FsWrite::Append { path, new_str, .. } => added_lines(&path, &new_str),
+        FsWrite::Patch { patch, .. } => patch,
+    })
+}
+
+fn tool_output(result: &ToolUseResult) -> String {
+    let output = result
+        .content
+        .iter()
+        .map(|block| match block {
+            ToolUseResultBlock::Json(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+            ToolUseResultBlock::Text(text) => text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if output.len() > MAX_TOOL_OUTPUT_LEN {
+        format!("{}\n... output truncated", truncate_safe(&output, MAX_TOOL_OUTPUT_LEN))
+    } else {
+        output
+    }
+}
+
+fn render_markdown(entries: &[Entry<'_>]) -> String {
+    let mut md = String::from("# Conversation transcript\n");
+    let mut in_assistant_turn = false;
+    for entry in entries {
+        match entry {
+            Entry::Summary(summary) => {
+                md.push_str("\n<details>\n<summary>Summary of the earlier conversation</summary>\n\n");
+                md.push_str(summary.trim());
+                md.push_str("\n\n</details>\n");
+            },
+            Entry::User(prompt) => {
+                md.push_str(&format!("\n## User\n\n{}\n", prompt.trim()));
+                in_assistant_turn = false;
+            },
+            Entry::Assistant(_) | Entry::ToolUse(_) if !in_assistant_turn => {
+                md.push_str("\n## Assistant\n");
+                in_assistant_turn = true;
+            },
+            _ => (),
+        }
+        match entry {
+            Entry::Assistant(content) => md.push_str(&format!("\n{}\n", content.trim())),
+            Entry::ToolUse(tool_use) => {
+                let status = if tool_use.failed { " (failed)" } else { "" };
+                md.push_str(&format!("\n**Tool use: `{}`**{status}\n\n", tool_use.name));
+                match &tool_use.diff {
+                    Some(diff) => md.push_str(&fenced(diff, "diff")),
+                    None => md.push_str(&fenced(&tool_use.input, "json")),
+                }
+                if let Some(output) = &tool_use.output {
+                    md.push_str("\n<details>\n<summary>Output</summary>\n\n");
+                    md.push_str(&fenced(output, ""));
+                    md.push_str("\n</details>\n");
+                }
+            },
+            _ => (),
+        }
+    }
+    md
+}
+
+/// Returns `content` as a code block, fenced by more backticks than it contains in a row.
+fn fenced(content: &str, language: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{language}\n{}\n{fence}\n", content.trim_end_matches('\n'))
+}
+
+fn render_html(entries: &[Entry<'_>]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Conversation transcript</title>\n\
+         <style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n<h1>Conversation transcript</h1>\n"
+    );
+    let mut in_assistant_turn = false;
+    for entry in entries {
+        match entry {
+            Entry::Summary(summary) => {
+                html.push_str("<details>\n<summary>Summary of the earlier conversation</summary>\n");
+                html.push_str(&text_to_html(summary));
+                html.push_str("</details>\n");
+            },
+            Entry::User(prompt) => {
+                html.push_str("<h2>User</h2>\n");
+                html.push_str(&text_to_html(prompt));
+                in_assistant_turn = false;
+            },
+            Entry::Assistant(_) | Entry::ToolUse(_) if !in_assistant_turn => {
+                html.push_str("<h2>Assistant</h2>\n");
+                in_assistant_turn = true;
+            },
+            _ => (),
+        }
+        match entry {
+            Entry::Assistant(content) => html.push_str(&text_to_html(content)),
+            Entry::ToolUse(tool_use) => {
+                html.push_str("<div class=\"tool\">\n");
+                html.push_str(&format!(
+                    "<p><strong>Tool use: <code>{}</code></strong>",
+                    escape_html(tool_use.name)
+                ));
+                if tool_use.failed {
+                    html.push_str(" <span class=\"failed\">(failed)</span>");
+                }
+                html.push_str("</p>\n");
+                match &tool_use.diff {
+                    Some(diff) => {
+                        html.push_str("<pre><code>");
+                        for line in diff.lines() {
+                            let line = escape_html(line);
+                            match line.chars().next() {
+                                Some('+') => html.push_str(&format!("<span class=\"add\">{line}</span>\n")),
+                                Some('-') => html.push_str(&format!("<span class=\"del\">{line}</span>\n")),
+                                _ => html.push_str(&format!("{line}\n")),
+                            }
+                        }
+                        html.push_str("</code></pre>\n");
+                    },
+                    None => html.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&tool_use.input))),
+                }
+                if let Some(output) = &tool_use.output {
+                    html.push_str(&format!(
+                        "<details>\n<summary>Output</summary>\n<pre><code>{}</code></pre>\n</details>\n",
+                        escape_html(output)
+                    ));
+                }
+                html.push_str("</div>\n");
+            },
+            _ => (),
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Converts message text to HTML, keeping its line breaks and showing fenced code blocks as
+/// preformatted text.
+fn text_to_html(text: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph = vec![];
+    let mut code: Option<Vec<&str>> = None;
+    for line in text.trim().lines() {
+        match (&mut code, line.trim_start().starts_with("```")) {
+            (Some(lines), true) => {
+                html.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&lines.join("\n"))));
+                code = None;
+            },
+            (Some(lines), false) => lines.push(line),
+            (None, true) => {
+                push_paragraph(&mut html, &mut paragraph);
+                code = Some(vec![]);
+            },
+            (None, false) => paragraph.push(line),
+        }
+    }
+    // An unterminated code block runs until the end of the message
+    if let Some(lines) = code {
+        html.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&lines.join("\n"))));
+    }
+    push_paragraph(&mut html, &mut paragraph);
+    html
+}
+
+fn push_paragraph(html: &mut String, lines: &mut Vec<&str>) {
+    if !lines.is_empty() {
+        html.push_str(&format!("<p class=\"text\">{}</p>\n", escape_html(&lines.join("\n"))));
+        lines.clear();
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::agent::Agents;
+    use crate::cli::chat::message::{
+        AssistantMessage,
+        AssistantToolUse,
+    };
+    use crate::cli::chat::tool_manager::ToolManager;
+    use crate::os::Os;
+
+    async fn conversation(os: &Os) -> ConversationState {
+        let mut conversation = ConversationState::new(
+            "fake_conv_id",
+            Agents::default(),
+            HashMap::new(),
+            ToolManager::default(),
+            None,
+            os,
+            false,
+        )
+        .await;
+        conversation
+            .set_next_user_message("Rename `foo` to <bar>".to_string())
+            .await;
+        conversation.record_assistant_message(
+            AssistantMessage::new_tool_use(None, "I'll update the file.".to_string(), vec![AssistantToolUse {
+                id: "1".to_string(),
+                name: "fs_write".to_string(),
+                args: serde_json::json!({
+                    "command": "str_replace",
+                    "path": "/lib.rs",
+                    "old_str": "fn foo() {}\n",
+                    "new_str": "fn bar() {}\n",
+                }),
+                ..Default::default()
+            }]),
+            None,
+        );
+        conversation.add_tool_results(vec![ToolUseResult {
+            tool_use_id: "1".to_string(),
+            content: vec![ToolUseResultBlock::Text("Replaced 1 occurrence".to_string())],
+            status: ToolResultStatus::Success,
+        }]);
+        conversation.record_assistant_message(AssistantMessage::new_response(None, "Done.".to_string()), None);
+        conversation
+    }
+
+    #[tokio::test]
+    async fn test_render_markdown() {
+        let os = Os::new().await.unwrap();
+        let conversation = conversation(&os).await;
+        insta::assert_snapshot!(render_transcript(&conversation, TranscriptFormat::Md), @r#"
+        # Conversation transcript
+
+        ## User
+
+        Rename `foo` to <bar>
+
+        ## Assistant
+
+        I'll update the file.
+
+        **Tool use: `fs_write`**
+
+        ```diff
+        --- /lib.rs
+        +++ /lib.rs
+        @@ -1 +1 @@
+        -fn foo() {}
+        +fn bar() {}
+        ```
+
+        <details>
+        <summary>Output</summary>
+
+        ```
+        Replaced 1 occurrence
+        ```
+
+        </details>
+
+        Done.
+        "#);
+    }
+
+    #[tokio::test]
+    async fn test_render_html_escapes_text() {
+        let os = Os::new().await.unwrap();
+        let conversation = conversation(&os).await;
+        let html = render_transcript(&conversation, TranscriptFormat::Html);
+        assert!(html.contains("<p class=\"text\">Rename `foo` to &lt;bar&gt;</p>"));
+        assert!(html.contains("<span class=\"del\">-fn foo() {}</span>"));
+        assert!(html.contains("<span class=\"add\">+fn bar() {}</span>"));
+        assert!(html.contains("<pre><code>Replaced 1 occurrence</code></pre>"));
+    }
+
+    #[test]
+    fn test_fenced() {
+        assert_eq!(fenced("a\n", "json"), "```json\na\n```\n");
+        assert_eq!(fenced("```\nb\n```", ""), "````\n```\nb\n```\n````\n");
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(TranscriptFormat::from_path("out.HTML"), TranscriptFormat::Html);
+        assert_eq!(TranscriptFormat::from_path("out.md"), TranscriptFormat::Md);
+        assert_eq!(TranscriptFormat::from_path("out"), TranscriptFormat::Md);
+    }
+}
@@ -8,6 +8,11 @@ use crossterm::style::{
     self,
     Color,
 };
+use eyre::{
+    Result,
+    bail,
+    eyre,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -22,6 +27,8 @@ use crate::cli::chat::consts::{
     MAX_IMAGE_SIZE,
     MAX_NUMBER_OF_IMAGES_PER_REQUEST,
 };
+use crate::database::settings::Setting;
+use crate::os::Os;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ImageMetadata {
@@ -84,7 +91,13 @@ pub fn handle_images_from_paths(output: &mut impl Write, paths: &[String]) -> Ri
         }
     }
 
-    let (mut valid_images, images_exceeding_size_limit): (RichImageBlocks, RichImageBlocks) = extracted_images
+    filter_images(output, extracted_images)
+}
+
+/// Drops the images exceeding [MAX_IMAGE_SIZE] and those beyond
+/// [MAX_NUMBER_OF_IMAGES_PER_REQUEST], warning about each on `output`.
+pub fn filter_images(output: &mut impl Write, images: RichImageBlocks) -> RichImageBlocks {
+    let (mut valid_images, images_exceeding_size_limit): (RichImageBlocks, RichImageBlocks) = images
         .into_iter()
         .partition(|(_, metadata)| metadata.size as usize <= MAX_IMAGE_SIZE);
 
@@ -114,17 +127,10 @@ pub fn handle_images_from_paths(output: &mut impl Write, paths: &[String]) -> Ri
         )
         .ok();
         for (_, metadata) in &images_exceeding_size_limit {
-            let image_size_str = if metadata.size > 1024 * 1024 {
-                format!("{:.2} MB", metadata.size as f64 / (1024.0 * 1024.0))
-            } else if metadata.size > 1024 {
-                format!("{:.2} KB", metadata.size as f64 / 1024.0)
-            } else {
-                format!("{} bytes", metadata.size)
-            };
             execute!(
                 &mut *output,
                 style::SetForegroundColor(Color::DarkYellow),
-                style::Print(format!("  - {} ({})\n", metadata.filename, image_size_str)),
+                style::Print(format!("  - {} ({})\n", metadata.filename, format_size(metadata.size))),
                 style::SetForegroundColor(Color::Reset)
             )
             .ok();
@@ -133,6 +139,17 @@ pub fn handle_images_from_paths(output: &mut impl Write, paths: &[String]) -> Ri
     valid_images
 }
 
+/// Formats a size in bytes for display, e.g. `1.50 MB`.
+pub fn format_size(size: u64) -> String {
+    if size > 1024 * 1024 {
+        format!("{:.2} MB", size as f64 / (1024.0 * 1024.0))
+    } else if size > 1024 {
+        format!("{:.2} KB", size as f64 / 1024.0)
+    } else {
+        format!("{} bytes", size)
+    }
+}
+
 /// This function checks if the file path has a supported image type
 /// and returns true if it does, otherwise false.
 /// Supported image types are: jpg, jpeg, png, gif, webp
@@ -182,6 +199,93 @@ pub fn get_image_block_from_file_path(maybe_file_path: &str) -> Option<ImageBloc
     Some(image_block)
 }
 
+/// Returns the format of an image from its MIME type, e.g. `image/png`.
+pub fn image_format_from_mime_type(mime_type: &str) -> Option<ImageFormat> {
+    let subtype = mime_type.trim().to_lowercase().strip_prefix("image/")?.to_string();
+    ImageFormat::from_str(&subtype).ok()
+}
+
+/// Detects the format of an image from its leading bytes.
+pub fn image_format_from_bytes(bytes: &[u8]) -> Option<ImageFormat> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some(ImageFormat::Png),
+        [0xff, 0xd8, 0xff, ..] => Some(ImageFormat::Jpeg),
+        [b'G', b'I', b'F', b'8', ..] => Some(ImageFormat::Gif),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(ImageFormat::Webp),
+        _ => None,
+    }
+}
+
+/// Reads the image on the clipboard by running the command set with `chat.clipboardImageCommand`,
+/// or otherwise `wl-paste` on Wayland and `xclip` on X11.
+pub async fn get_image_from_clipboard(os: &Os) -> Result<RichImageBlock> {
+    let Some(mut command) = clipboard_image_command(os)? else {
+        bail!(
+            "No clipboard command found for this platform. Set {} to a command that prints the clipboard's image",
+            Setting::ChatClipboardImageCommand
+        );
+    };
+    let program = command.remove(0);
+    let output = tokio::process::Command::new(&program)
+        .args(command)
+        .output()
+        .await
+        .map_err(|err| eyre!("Failed to run {program}: {err}"))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        bail!("There is no image on the clipboard");
+    }
+
+    let bytes = output.stdout;
+    let Some(format) = image_format_from_bytes(&bytes) else {
+        bail!("The clipboard does not contain a supported image (jpg, jpeg, png, gif or webp)");
+    };
+    let size = bytes.len() as u64;
+    if size as usize > MAX_IMAGE_SIZE {
+        bail!(
+            "The image on the clipboard ({}) exceeds the size limit ({}MB)",
+            format_size(size),
+            MAX_IMAGE_SIZE / (1024 * 1024)
+        );
+    }
+
+    let extension = match format {
+        ImageFormat::Gif => "gif",
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Png => "png",
+        ImageFormat::Webp => "webp",
+    };
+    Ok((
+        ImageBlock {
+            format,
+            source: ImageSource::Bytes(bytes),
+        },
+        ImageMetadata {
+            filepath: "clipboard".to_string(),
+            size,
+            filename: format!("clipboard.{extension}"),
+        },
+    ))
+}
+
+fn clipboard_image_command(os: &Os) -> Result<Option<Vec<String>>> {
+    if let Some(command) = os.database.settings.get_string(Setting::ChatClipboardImageCommand) {
+        return match shlex::split(&command) {
+            Some(parts) if !parts.is_empty() => Ok(Some(parts)),
+            _ => bail!("Failed to parse {}: {command}", Setting::ChatClipboardImageCommand),
+        };
+    }
+
+    let is_set = |var: &str| os.env.get(var).is_ok_and(|value| !value.is_empty());
+    let command: &[&str] = if is_set("WAYLAND_DISPLAY") {
+        &["wl-paste", "--no-newline", "--type", "image/png"]
+    } else if is_set("DISPLAY") {
+        &["xclip", "-selection", "clipboard", "-target", "image/png", "-out"]
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(command.iter().map(|&part| part.to_string()).collect()))
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -220,6 +324,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_image_format_detection() {
+        assert_eq!(image_format_from_mime_type("image/png"), Some(ImageFormat::Png));
+        assert_eq!(image_format_from_mime_type("image/JPEG"), Some(ImageFormat::Jpeg));
+        assert_eq!(image_format_from_mime_type("image/svg+xml"), None);
+        assert_eq!(image_format_from_mime_type("text/plain"), None);
+
+        assert_eq!(image_format_from_bytes(b"\x89PNG\r\n"), Some(ImageFormat::Png));
+        assert_eq!(image_format_from_bytes(b"\xFF\xD8\xFF\xE0"), Some(ImageFormat::Jpeg));
+        assert_eq!(image_format_from_bytes(b"GIF89a"), Some(ImageFormat::Gif));
+        assert_eq!(image_format_from_bytes(b"RIFF\0\0\0\0WEBPVP8"), Some(ImageFormat::Webp));
+        assert_eq!(image_format_from_bytes(b"hello"), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_image_from_clipboard() {
+        let mut os = Os::new().await.unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let image_path = temp_dir.path().join("clipboard.png");
+        std::fs::write(&image_path, b"\x89PNG\r\nfake_image_data").unwrap();
+        let text_path = temp_dir.path().join("clipboard.txt");
+        std::fs::write(&text_path, b"not an image").unwrap();
+
+        let set_command = async |os: &mut Os, path: &Path| {
+            let command = format!("cat '{}'", path.display());
+            os.database
+                .settings
+                .set(Setting::ChatClipboardImageCommand, command)
+                .await
+                .unwrap();
+        };
+
+        set_command(&mut os, &image_path).await;
+        let (block, metadata) = get_image_from_clipboard(&os).await.unwrap();
+        assert_eq!(block.format, ImageFormat::Png);
+        assert_eq!(metadata.filename, "clipboard.png");
+        assert_eq!(metadata.size, 21);
+
+        set_command(&mut os, &text_path).await;
+        let err = get_image_from_clipboard(&os).await.unwrap_err();
+        assert!(err.to_string().contains("does not contain a supported image"));
+
+        set_command(&mut os, &temp_dir.path().join("missing.png")).await;
+        let err = get_image_from_clipboard(&os).await.unwrap_err();
+        assert_eq!(err.to_string(), "There is no image on the clipboard");
+    }
+
     #[test]
     fn test_handle_images_from_paths() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -4,6 +4,7 @@ pub mod issue;
 pub mod test;
 pub mod ui;
 
+use std::cmp::Reverse;
 use std::io::Write;
 use std::time::Duration;
 
@@ -14,7 +15,7 @@ use aws_smithy_types::{
 use eyre::Result;
 
 use super::ChatError;
-use super::token_counter::TokenCounter;
+use super::token_counter::Tokenizer;
 
 pub fn truncate_safe(s: &str, max_bytes: usize) -> &str {
     if s.len() <= max_bytes {
@@ -159,17 +160,30 @@ fn should_play_bell() -> bool {
 /// until the total size is below the limit
 ///
 /// # Arguments
-/// * `files` - A mutable reference to a vector of tuples: (filename, content). This file will be
-///   sorted but the content will not be changed.
+/// * `files` - A mutable reference to a vector of tuples: (filename, content). If any files have
+///   to be dropped, this will be sorted from the most tokens to the fewest, otherwise it is left
+///   as is. The content will not be changed.
+/// * `limit` - The maximum total size, in tokens
+/// * `tokenizer` - The tokenizer of the model the files are sent to
 ///
 /// Returns the dropped files
-pub fn drop_matched_context_files(files: &mut [(String, String)], limit: usize) -> Result<Vec<(String, String)>> {
-    files.sort_by(|a, b| TokenCounter::count_tokens(&b.1).cmp(&TokenCounter::count_tokens(&a.1)));
+pub fn drop_matched_context_files(
+    files: &mut [(String, String)],
+    limit: usize,
+    tokenizer: Tokenizer,
+) -> Result<Vec<(String, String)>> {
+    // A token is at least one byte, so there is nothing to drop (or tokenize) if the files are
+    // smaller than the limit in bytes
+    if files.iter().map(|(_, content)| content.len()).sum::<usize>() <= limit {
+        return Ok(Vec::new());
+    }
+
+    files.sort_by_cached_key(|(_, content)| Reverse(tokenizer.count_tokens(content)));
     let mut total_size = 0;
     let mut dropped_files = Vec::new();
 
     for (filename, content) in files.iter() {
-        let size = TokenCounter::count_tokens(content);
+        let size = *tokenizer.count_tokens(content);
         if total_size + size > limit {
             dropped_files.push((filename.clone(), content.clone()));
         } else {
@@ -281,7 +295,7 @@ mod tests {
         ];
         let limit = 9;
 
-        let dropped_files = drop_matched_context_files(&mut files, limit).unwrap();
+        let dropped_files = drop_matched_context_files(&mut files, limit, Tokenizer::default()).unwrap();
         assert_eq!(dropped_files.len(), 1);
         assert_eq!(dropped_files[0].0, "file3");
         assert_eq!(files.len(), 2);
@@ -2,6 +2,7 @@ use eyre::Result;
 
 use crate::cli::agent::Agent;
 use crate::cli::chat::context::ContextManager;
+use crate::cli::chat::token_counter::Tokenizer;
 use crate::os::Os;
 
 pub const TEST_FILE_CONTENTS: &str = "\
@@ -18,7 +19,7 @@ pub const TEST_HIDDEN_FILE_PATH: &str = "/aaaa2/.hidden";
 pub fn create_test_context_manager(context_file_size: Option<usize>) -> Result<ContextManager> {
     let context_file_size = context_file_size.unwrap_or(150_000);
     let agent = Agent::default();
-    ContextManager::from_agent(&agent, context_file_size)
+    ContextManager::from_agent(&agent, context_file_size, Tokenizer::default())
 }
 
 /// Sets up the following filesystem structure:
@@ -6,6 +6,7 @@ mod feed;
 mod issue;
 mod mcp;
 mod settings;
+mod usage;
 mod user;
 
 use std::fmt::Display;
@@ -37,8 +38,12 @@ use tracing::{
     debug,
 };
 
-use crate::cli::chat::ChatArgs;
+use crate::cli::chat::{
+    ChatArgs,
+    ChatExportArgs,
+};
 use crate::cli::mcp::McpSubcommand;
+use crate::cli::usage::UsageSubcommand;
 use crate::cli::user::{
     LoginArgs,
     WhoamiArgs,
@@ -116,6 +121,11 @@ pub enum RootSubcommand {
     /// Model Context Protocol (MCP)
     #[command(subcommand)]
     Mcp(McpSubcommand),
+    /// Report the requests, tokens and tool calls of your chat sessions
+    #[command(subcommand)]
+    Usage(UsageSubcommand),
+    /// Export a conversation to a Markdown or HTML transcript
+    Export(ChatExportArgs),
 }
 
 impl RootSubcommand {
@@ -159,6 +169,8 @@ impl RootSubcommand {
             Self::Version { changelog } => Cli::print_version(changelog),
             Self::Chat(args) => args.execute(os).await,
             Self::Mcp(args) => args.execute(os, &mut std::io::stderr()).await,
+            Self::Usage(args) => args.execute(os).await,
+            Self::Export(args) => args.execute(os).await,
         }
     }
 }
@@ -183,6 +195,8 @@ impl Display for RootSubcommand {
             Self::Issue(_) => "issue",
             Self::Version { .. } => "version",
             Self::Mcp(_) => "mcp",
+            Self::Usage(_) => "usage",
+            Self::Export(_) => "export",
         };
 
         write!(f, "{name}")
@@ -327,6 +341,7 @@ impl Cli {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::cli::usage::ReportGrouping;
     use crate::util::CHAT_BINARY_NAME;
     use crate::util::test::assert_parse;
 
@@ -365,6 +380,7 @@ mod test {
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
+                plan: false,
             })),
             verbose: 2,
             help_all: false,
@@ -404,6 +420,7 @@ mod test {
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
+                plan: false,
             })
         );
     }
@@ -420,6 +437,7 @@ mod test {
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: false,
+                plan: false,
             })
         );
     }
@@ -436,6 +454,7 @@ mod test {
                 trust_all_tools: true,
                 trust_tools: None,
                 no_interactive: false,
+                plan: false,
             })
         );
     }
@@ -452,6 +471,7 @@ mod test {
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: true,
+                plan: false,
             })
         );
         assert_parse!(
@@ -464,6 +484,7 @@ mod test {
                 trust_all_tools: false,
                 trust_tools: None,
                 no_interactive: true,
+                plan: false,
             })
         );
     }
@@ -480,6 +501,7 @@ mod test {
                 trust_all_tools: true,
                 trust_tools: None,
                 no_interactive: false,
+                plan: false,
             })
         );
     }
@@ -496,6 +518,7 @@ mod test {
                 trust_all_tools: false,
                 trust_tools: Some(vec!["".to_string()]),
                 no_interactive: false,
+                plan: false,
             })
         );
     }
@@ -512,6 +535,53 @@ mod test {
                 trust_all_tools: false,
                 trust_tools: Some(vec!["fs_read".to_string(), "fs_write".to_string()]),
                 no_interactive: false,
+                plan: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        assert_parse!(
+            ["export", "conversation.json", "-o", "transcript.html"],
+            RootSubcommand::Export(ChatExportArgs {
+                session: Some("conversation.json".to_string()),
+                output: Some("transcript.html".to_string()),
+                format: None,
+                force: false,
+            })
+        );
+        assert_parse!(
+            ["chat", "export"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: false,
+                input: Some("export".to_string()),
+                agent: None,
+                model: None,
+                trust_all_tools: false,
+                trust_tools: None,
+                no_interactive: false,
+                plan: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_usage_report() {
+        assert_parse!(
+            ["usage", "report", "--since", "7d", "--format", "json"],
+            RootSubcommand::Usage(UsageSubcommand::Report {
+                since: std::time::Duration::from_secs(7 * 24 * 60 * 60),
+                by: ReportGrouping::Day,
+                format: OutputFormat::Json,
+            })
+        );
+        assert_parse!(
+            ["usage", "report", "--by", "session"],
+            RootSubcommand::Usage(UsageSubcommand::Report {
+                since: std::time::Duration::from_secs(7 * 24 * 60 * 60),
+                by: ReportGrouping::Session,
+                format: OutputFormat::Plain,
             })
         );
     }
@@ -0,0 +1,206 @@
+use std::fmt::Write as _;
+use std::process::ExitCode;
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+use clap::{
+    Subcommand,
+    ValueEnum,
+};
+use eyre::{
+    Result,
+    eyre,
+};
+use serde_json::json;
+
+use super::OutputFormat;
+use crate::database::{
+    UsageGrouping,
+    UsageSummary,
+};
+use crate::os::Os;
+
+#[derive(Debug, PartialEq, Eq, Subcommand)]
+pub enum UsageSubcommand {
+    /// Summarize the requests, tokens and tool calls of your chat sessions
+    Report {
+        /// How far back to report, e.g. 12h, 7d or 4w
+        #[arg(long, value_parser = parse_since, default_value = "7d")]
+        since: Duration,
+        /// Whether to sum the usage per day or per chat session
+        #[arg(long, value_enum, default_value_t)]
+        by: ReportGrouping,
+        /// Format of the output
+        #[arg(long, short, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportGrouping {
+    /// One row per day
+    #[default]
+    Day,
+    /// One row per chat session
+    Session,
+}
+
+impl From<ReportGrouping> for UsageGrouping {
+    fn from(value: ReportGrouping) -> Self {
+        match value {
+            ReportGrouping::Day => Self::Day,
+            ReportGrouping::Session => Self::Session,
+        }
+    }
+}
+
+impl UsageSubcommand {
+    pub async fn execute(self, os: &mut Os) -> Result<ExitCode> {
+        match self {
+            Self::Report { since, by, format } => {
+                let since_ms = unix_ms_before(since);
+                let usage = os.database.get_usage(since_ms, None, by.into())?;
+                let total = os.database.get_usage(since_ms, None, UsageGrouping::Total)?.pop();
+
+                let key_header = match by {
+                    ReportGrouping::Day => "Day",
+                    ReportGrouping::Session => "Session",
+                };
+                format.print(
+                    || match usage.is_empty() {
+                        true => "No usage recorded in this period".to_string(),
+                        false => format_usage_table(key_header, &usage, total.as_ref()),
+                    },
+                    || json!({ "usage": usage, "total": total }),
+                );
+
+                Ok(ExitCode::SUCCESS)
+            },
+        }
+    }
+}
+
+/// Unix timestamp (milliseconds) of `duration` ago.
+pub fn unix_ms_before(duration: Duration) -> u64 {
+    SystemTime::now()
+        .checked_sub(duration)
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+/// Renders usage summaries as a table, with `key_header` as the heading of the first column and
+/// `total` as the last row.
+pub fn format_usage_table(key_header: &str, usage: &[UsageSummary], total: Option<&UsageSummary>) -> String {
+    let headers = [
+        key_header,
+        "Requests",
+        "Input tokens",
+        "Output tokens",
+        "Tool calls",
+        "First chunk",
+        "Models",
+    ];
+    let row = |summary: &UsageSummary| {
+        [
+            summary.key.clone(),
+            summary.requests.to_string(),
+            summary.input_tokens.to_string(),
+            summary.output_tokens.to_string(),
+            summary.tool_calls.to_string(),
+            summary
+                .avg_time_to_first_chunk_ms
+                .map_or("-".to_string(), |ms| format!("{ms} ms")),
+            summary.model_ids.join(", "),
+        ]
+    };
+    let mut rows = usage.iter().map(row).collect::<Vec<_>>();
+    if let Some(total) = total {
+        rows.push(row(total));
+        rows.last_mut().unwrap()[0] = "Total".to_string();
+    }
+
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    let mut write_row = |cells: &[&str]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let _ = writeln!(table, "{}", line.trim_end());
+    };
+    write_row(&headers);
+    for row in &rows {
+        write_row(&row.each_ref().map(String::as_str));
+    }
+
+    table.trim_end().to_string()
+}
+
+/// Parses a duration such as `30m`, `12h`, `7d` or `4w`.
+fn parse_since(arg: &str) -> Result<Duration> {
+    let invalid = || eyre!("Invalid duration '{arg}'. Expected a number followed by m, h, d or w, e.g. 7d");
+    let (count, unit) = arg.split_at(arg.find(|c: char| !c.is_ascii_digit()).unwrap_or(arg.len()));
+    let count = count.parse::<u64>().ok().ok_or_else(invalid)?;
+    let unit_secs = match unit {
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    count
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(key: &str, requests: u64, avg_time_to_first_chunk_ms: Option<u64>) -> UsageSummary {
+        UsageSummary {
+            key: key.to_string(),
+            requests,
+            input_tokens: requests * 1000,
+            output_tokens: requests * 100,
+            tool_calls: requests,
+            avg_time_to_first_chunk_ms,
+            model_ids: vec!["claude-sonnet-4".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("30m").unwrap(), Duration::from_secs(30 * 60));
+        assert_eq!(parse_since("7d").unwrap(), Duration::from_secs(7 * 24 * 60 * 60));
+        assert_eq!(parse_since("2w").unwrap(), Duration::from_secs(14 * 24 * 60 * 60));
+        assert!(parse_since("7").is_err());
+        assert!(parse_since("d").is_err());
+        assert!(parse_since("7y").is_err());
+        assert!(parse_since(&format!("{}w", u64::MAX / 60)).is_err());
+    }
+
+    #[test]
+    fn test_format_usage_table() {
+        let usage = [summary("2025-01-01", 2, Some(800)), summary("2025-01-02", 10, None)];
+        let total = summary("total", 12, Some(800));
+        insta::assert_snapshot!(format_usage_table("Day", &usage, Some(&total)), @r"
+        Day         Requests  Input tokens  Output tokens  Tool calls  First chunk  Models
+        2025-01-01  2         2000          200            2           800 ms       claude-sonnet-4
+        2025-01-02  10        10000         1000           10          -            claude-sonnet-4
+        Total       12        12000         1200           12          800 ms       claude-sonnet-4
+        ");
+    }
+}
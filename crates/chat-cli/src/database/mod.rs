@@ -70,7 +70,8 @@ const MIGRATIONS: &[Migration] = migrations![
     "004_state_table",
     "005_auth_table",
     "006_make_state_blob",
-    "007_conversations_table"
+    "007_conversations_table",
+    "008_usage_table"
 ];
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
@@ -152,6 +153,47 @@ impl<T> From<PoisonError<T>> for DatabaseError {
     }
 }
 
+/// The usage of a single request, as recorded in the usage table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageRecord {
+    pub conversation_id: String,
+    /// Unix timestamp (milliseconds) of when the request was sent.
+    pub timestamp_ms: u64,
+    pub model_id: Option<String>,
+    /// Input tokens as reported by the backend, or estimated if not reported.
+    pub input_tokens: u64,
+    /// Output tokens as reported by the backend, or estimated if not reported.
+    pub output_tokens: u64,
+    pub tool_calls: u64,
+    pub time_to_first_chunk_ms: Option<u64>,
+}
+
+/// How [Database::get_usage] groups the recorded requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageGrouping {
+    /// One summary per conversation, keyed by the conversation id.
+    Session,
+    /// One summary per local calendar day, keyed by the date (YYYY-MM-DD).
+    Day,
+    /// A single summary of all requests, keyed by "total".
+    Total,
+}
+
+/// The summed usage of a group of requests.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    /// The conversation id or date, depending on the [UsageGrouping].
+    pub key: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub tool_calls: u64,
+    /// Average time to first chunk of the requests that received one.
+    pub avg_time_to_first_chunk_ms: Option<u64>,
+    pub model_ids: Vec<String>,
+}
+
 #[derive(Debug)]
 pub enum Table {
     /// The state table contains persistent application state.
@@ -355,6 +397,62 @@ impl Database {
         self.set_json_entry(Table::Conversations, path, state)
     }
 
+    /// Add the usage of a request to the usage ledger.
+    pub fn record_usage(&self, record: &UsageRecord) -> Result<(), DatabaseError> {
+        self.pool.get()?.execute(
+            "INSERT INTO usage (conversation_id, timestamp, model_id, input_tokens, output_tokens, tool_calls, \
+             time_to_first_chunk) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                record.conversation_id,
+                record.timestamp_ms,
+                record.model_id,
+                record.input_tokens,
+                record.output_tokens,
+                record.tool_calls,
+                record.time_to_first_chunk_ms,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the usage recorded since `since_ms` (a unix timestamp in milliseconds), summed per
+    /// group and ordered by the time of the group's first request. If `conversation_id` is given,
+    /// only the usage of that conversation is included.
+    pub fn get_usage(
+        &self,
+        since_ms: u64,
+        conversation_id: Option<&str>,
+        grouping: UsageGrouping,
+    ) -> Result<Vec<UsageSummary>, DatabaseError> {
+        let key = match grouping {
+            UsageGrouping::Session => "conversation_id",
+            UsageGrouping::Day => "date(timestamp / 1000, 'unixepoch', 'localtime')",
+            UsageGrouping::Total => "'total'",
+        };
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {key}, COUNT(*), SUM(input_tokens), SUM(output_tokens), SUM(tool_calls), \
+             AVG(time_to_first_chunk), GROUP_CONCAT(DISTINCT model_id) FROM usage \
+             WHERE timestamp >= ?1 AND (?2 IS NULL OR conversation_id = ?2) GROUP BY 1 ORDER BY MIN(timestamp)"
+        ))?;
+        let rows = stmt.query_map(params![since_ms, conversation_id], |row| {
+            Ok(UsageSummary {
+                key: row.get(0)?,
+                requests: row.get(1)?,
+                input_tokens: row.get(2)?,
+                output_tokens: row.get(3)?,
+                tool_calls: row.get(4)?,
+                avg_time_to_first_chunk_ms: row.get::<_, Option<f64>>(5)?.map(|ms| ms.round() as u64),
+                model_ids: row
+                    .get::<_, Option<String>>(6)?
+                    .map(|ids| ids.split(',').map(str::to_string).collect())
+                    .unwrap_or_default(),
+            })
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
     pub async fn get_secret(&self, key: &str) -> Result<Option<Secret>, DatabaseError> {
         trace!(key, "getting secret");
         Ok(self.get_entry::<String>(Table::Auth, key)?.map(Into::into))
@@ -587,6 +685,63 @@ mod tests {
         assert!(db.get_entry::<bool>(Table::State, "bool").unwrap().is_some());
     }
 
+    #[tokio::test]
+    async fn test_usage() {
+        let db = Database::new().await.unwrap();
+        let record = |conversation_id: &str, timestamp_ms: u64, model_id: &str, ttfc: Option<u64>| UsageRecord {
+            conversation_id: conversation_id.to_string(),
+            timestamp_ms,
+            model_id: Some(model_id.to_string()),
+            input_tokens: 100,
+            output_tokens: 10,
+            tool_calls: 1,
+            time_to_first_chunk_ms: ttfc,
+        };
+        db.record_usage(&record("old", 1_000, "model-a", Some(100))).unwrap();
+        db.record_usage(&record("a", 2_000, "model-a", Some(100))).unwrap();
+        db.record_usage(&record("b", 3_000, "model-b", None)).unwrap();
+        db.record_usage(&record("a", 4_000, "model-b", Some(300))).unwrap();
+
+        let sessions = db.get_usage(2_000, None, UsageGrouping::Session).unwrap();
+        assert_eq!(sessions, vec![
+            UsageSummary {
+                key: "a".to_string(),
+                requests: 2,
+                input_tokens: 200,
+                output_tokens: 20,
+                tool_calls: 2,
+                avg_time_to_first_chunk_ms: Some(200),
+                model_ids: vec!["model-a".to_string(), "model-b".to_string()],
+            },
+            UsageSummary {
+                key: "b".to_string(),
+                requests: 1,
+                input_tokens: 100,
+                output_tokens: 10,
+                tool_calls: 1,
+                avg_time_to_first_chunk_ms: None,
+                model_ids: vec!["model-b".to_string()],
+            },
+        ]);
+
+        let days = db.get_usage(0, None, UsageGrouping::Day).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].requests, 4);
+        assert_eq!(days[0].input_tokens, 400);
+
+        let total = db.get_usage(2_000, None, UsageGrouping::Total).unwrap();
+        assert_eq!(total.len(), 1);
+        assert_eq!(total[0].key, "total");
+        assert_eq!(total[0].requests, 3);
+        assert_eq!(total[0].avg_time_to_first_chunk_ms, Some(200));
+        assert!(db.get_usage(5_000, None, UsageGrouping::Total).unwrap().is_empty());
+
+        let session = db.get_usage(0, Some("a"), UsageGrouping::Session).unwrap();
+        assert_eq!(session.len(), 1);
+        assert_eq!(session[0].key, "a");
+        assert_eq!(session[0].requests, 2);
+    }
+
     #[tokio::test]
     #[ignore = "not on ci"]
     async fn test_set_password() {
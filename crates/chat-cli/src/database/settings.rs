@@ -28,6 +28,8 @@ pub enum Setting {
     KnowledgeChunkSize,
     KnowledgeChunkOverlap,
     KnowledgeIndexType,
+    KnowledgeEmbeddingUrl,
+    KnowledgeEmbeddingModel,
     SkimCommandKey,
     ChatGreetingEnabled,
     ApiTimeout,
@@ -43,6 +45,7 @@ pub enum Setting {
     ChatDefaultAgent,
     ChatDisableAutoCompaction,
     ChatEnableHistoryHints,
+    ChatClipboardImageCommand,
 }
 
 impl AsRef<str> for Setting {
@@ -59,6 +62,8 @@ impl AsRef<str> for Setting {
             Self::KnowledgeChunkSize => "knowledge.chunkSize",
             Self::KnowledgeChunkOverlap => "knowledge.chunkOverlap",
             Self::KnowledgeIndexType => "knowledge.indexType",
+            Self::KnowledgeEmbeddingUrl => "knowledge.embeddingUrl",
+            Self::KnowledgeEmbeddingModel => "knowledge.embeddingModel",
             Self::SkimCommandKey => "chat.skimCommandKey",
             Self::ChatGreetingEnabled => "chat.greeting.enabled",
             Self::ApiTimeout => "api.timeout",
@@ -74,6 +79,7 @@ impl AsRef<str> for Setting {
             Self::ChatDefaultAgent => "chat.defaultAgent",
             Self::ChatDisableAutoCompaction => "chat.disableAutoCompaction",
             Self::ChatEnableHistoryHints => "chat.enableHistoryHints",
+            Self::ChatClipboardImageCommand => "chat.clipboardImageCommand",
         }
     }
 }
@@ -100,6 +106,8 @@ impl TryFrom<&str> for Setting {
             "knowledge.chunkSize" => Ok(Self::KnowledgeChunkSize),
             "knowledge.chunkOverlap" => Ok(Self::KnowledgeChunkOverlap),
             "knowledge.indexType" => Ok(Self::KnowledgeIndexType),
+            "knowledge.embeddingUrl" => Ok(Self::KnowledgeEmbeddingUrl),
+            "knowledge.embeddingModel" => Ok(Self::KnowledgeEmbeddingModel),
             "chat.skimCommandKey" => Ok(Self::SkimCommandKey),
             "chat.greeting.enabled" => Ok(Self::ChatGreetingEnabled),
             "api.timeout" => Ok(Self::ApiTimeout),
@@ -115,6 +123,7 @@ impl TryFrom<&str> for Setting {
             "chat.defaultAgent" => Ok(Self::ChatDefaultAgent),
             "chat.disableAutoCompaction" => Ok(Self::ChatDisableAutoCompaction),
             "chat.enableHistoryHints" => Ok(Self::ChatEnableHistoryHints),
+            "chat.clipboardImageCommand" => Ok(Self::ChatClipboardImageCommand),
             _ => Err(DatabaseError::InvalidSetting(value.to_string())),
         }
     }
@@ -0,0 +1,11 @@
+CREATE TABLE usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    conversation_id TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    model_id TEXT,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    tool_calls INTEGER NOT NULL,
+    time_to_first_chunk INTEGER
+);
+CREATE INDEX usage_timestamp ON usage (timestamp);
@@ -469,10 +469,12 @@ where
         };
         tracing::trace!(target: "mcp", "To {}:\n{:#?}", self.server_name, request);
         let msg = JsonRpcMessage::Request(request);
+        // Listen before sending so that the response can't arrive in between, which matters when
+        // several requests are in flight
+        let mut listener = self.transport.get_listener();
         time::timeout(Duration::from_millis(self.timeout), self.transport.send(&msg))
             .await
             .map_err(send_map_err)??;
-        let mut listener = self.transport.get_listener();
         let mut resp = time::timeout(Duration::from_millis(self.timeout), async {
             // we want to ignore all other messages sent by the server at this point and let the
             // background loop handle them
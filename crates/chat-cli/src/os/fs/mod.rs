@@ -403,6 +403,17 @@ impl Fs {
         }
     }
 
+    /// Returns an iterator over the entries within a directory.
+    ///
+    /// This is a proxy to [`std::fs::read_dir`].
+    pub fn read_dir_sync(&self, path: impl AsRef<Path>) -> io::Result<std::fs::ReadDir> {
+        match self {
+            Self::Real => std::fs::read_dir(path),
+            Self::Chroot(root) => std::fs::read_dir(append(root.path(), path)),
+            Self::Fake(_) => panic!("unimplemented"),
+        }
+    }
+
     /// Returns the canonical, absolute form of a path with all intermediate
     /// components normalized and symbolic links resolved.
     ///
@@ -34,6 +34,9 @@ type Result<T, E = DirectoryError> = std::result::Result<T, E>;
 
 const WORKSPACE_AGENT_DIR_RELATIVE: &str = ".amazonq/cli-agents";
 const GLOBAL_AGENT_DIR_RELATIVE_TO_HOME: &str = ".aws/amazonq/cli-agents";
+const WORKSPACE_PROMPTS_DIR_RELATIVE: &str = ".amazonq/prompts";
+const GLOBAL_PROMPTS_DIR_RELATIVE_TO_HOME: &str = ".aws/amazonq/prompts";
+const WORKSPACE_KNOWLEDGE_DIR_RELATIVE: &str = ".amazonq/knowledge";
 
 /// The directory of the users home
 ///
@@ -174,6 +177,17 @@ pub fn agent_config_dir(workspace_dir: PathBuf) -> Result<PathBuf> {
     Ok(workspace_dir.join(WORKSPACE_AGENT_DIR_RELATIVE))
 }
 
+/// The directory containing prompt files available in every workspace
+pub fn chat_global_prompts_dir(os: &Os) -> Result<PathBuf> {
+    Ok(home_dir(os)?.join(GLOBAL_PROMPTS_DIR_RELATIVE_TO_HOME))
+}
+
+/// The directory containing prompt files shared through the current workspace
+pub fn chat_local_prompts_dir(os: &Os) -> Result<PathBuf> {
+    let cwd = os.env.current_dir()?;
+    Ok(cwd.join(WORKSPACE_PROMPTS_DIR_RELATIVE))
+}
+
 /// The directory to the directory containing config for the `/context` feature in `q chat`.
 pub fn chat_global_context_path(os: &Os) -> Result<PathBuf> {
     Ok(home_dir(os)?.join(".aws").join("amazonq").join("global_context.json"))
@@ -190,6 +204,12 @@ pub fn knowledge_bases_dir(os: &Os) -> Result<PathBuf> {
     Ok(home_dir(os)?.join(".aws").join("amazonq").join("knowledge_bases"))
 }
 
+/// The directory for the knowledge base of the current workspace
+pub fn workspace_knowledge_base_dir(os: &Os) -> Result<PathBuf> {
+    let cwd = os.env.current_dir()?;
+    Ok(cwd.join(WORKSPACE_KNOWLEDGE_DIR_RELATIVE))
+}
+
 /// The path to the fig settings file
 pub fn settings_path() -> Result<PathBuf> {
     Ok(fig_data_dir()?.join("settings.json"))
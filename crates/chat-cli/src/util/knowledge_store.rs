@@ -1,16 +1,28 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{
+    Path,
+    PathBuf,
+};
 use std::sync::{
     Arc,
     LazyLock as Lazy,
 };
 
 use eyre::Result;
-use semantic_search_client::KnowledgeContext;
 use semantic_search_client::client::AsyncSemanticSearchClient;
+use semantic_search_client::config::{
+    HttpEmbeddingConfig,
+    SemanticSearchConfig,
+};
 use semantic_search_client::embedding::EmbeddingType;
 use semantic_search_client::types::{
     AddContextRequest,
-    SearchResult,
+    Citation,
+};
+use semantic_search_client::{
+    KnowledgeContext,
+    SearchFilter,
+    SystemStatus,
 };
 use tokio::sync::Mutex;
 use tracing::debug;
@@ -26,6 +38,9 @@ pub struct AddOptions {
     pub include_patterns: Vec<String>,
     pub exclude_patterns: Vec<String>,
     pub embedding_type: Option<String>,
+    pub auto_update: bool,
+    /// Knowledge base to add the context to. Defaults to [KnowledgeStore::default_scope]
+    pub scope: Option<KnowledgeScope>,
 }
 
 impl AddOptions {
@@ -70,6 +85,8 @@ impl AddOptions {
             include_patterns: default_include,
             exclude_patterns: default_exclude,
             embedding_type: default_embedding_type,
+            auto_update: false,
+            scope: None,
         }
     }
 
@@ -87,6 +104,68 @@ impl AddOptions {
         self.embedding_type = embedding_type;
         self
     }
+
+    pub fn with_auto_update(mut self, auto_update: bool) -> Self {
+        self.auto_update = auto_update;
+        self
+    }
+
+    pub fn with_scope(mut self, scope: Option<KnowledgeScope>) -> Self {
+        self.scope = scope;
+        self
+    }
+}
+
+/// Which knowledge base a context is stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnowledgeScope {
+    /// Stored in `.amazonq/knowledge` of the current workspace and searched by default while it
+    /// exists
+    Workspace,
+    /// Stored in the user's home directory and shared by every workspace
+    Global,
+}
+
+impl KnowledgeScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KnowledgeScope::Workspace => "workspace",
+            KnowledgeScope::Global => "global",
+        }
+    }
+
+    /// Resolve a scope filter (`"workspace"`, `"global"` or `"all"`) to the knowledge bases it
+    /// covers. Without a filter, only the workspace knowledge base is used if it is open.
+    pub fn resolve(filter: Option<&str>, workspace_open: bool) -> Result<Vec<Self>, String> {
+        let open = if workspace_open {
+            vec![KnowledgeScope::Workspace, KnowledgeScope::Global]
+        } else {
+            vec![KnowledgeScope::Global]
+        };
+        match filter {
+            None => Ok(open.into_iter().take(1).collect()),
+            Some(filter) if filter.eq_ignore_ascii_case("all") => Ok(open),
+            Some(filter) => filter.parse().map(|scope| vec![scope]),
+        }
+    }
+}
+
+impl std::fmt::Display for KnowledgeScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for KnowledgeScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "workspace" => Ok(KnowledgeScope::Workspace),
+            "global" => Ok(KnowledgeScope::Global),
+            _ => Err(format!("Invalid scope '{}'. Valid options are: workspace, global", s)),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -104,9 +183,13 @@ impl std::fmt::Display for KnowledgeError {
 
 impl std::error::Error for KnowledgeError {}
 
-/// Async knowledge store - just a thin wrapper!
+/// Async knowledge store - a thin wrapper around the global knowledge base and, once created,
+/// the knowledge base of the current workspace
 pub struct KnowledgeStore {
     client: AsyncSemanticSearchClient,
+    workspace: Option<AsyncSemanticSearchClient>,
+    /// Config for the workspace knowledge base, which is created on first use
+    workspace_config: Option<SemanticSearchConfig>,
 }
 
 impl KnowledgeStore {
@@ -179,13 +262,7 @@ impl KnowledgeStore {
     }
 
     /// Create SemanticSearchConfig from database settings with fallbacks to defaults
-    fn create_config_from_db_settings(
-        os: &crate::os::Os,
-        base_dir: PathBuf,
-    ) -> semantic_search_client::config::SemanticSearchConfig {
-        use semantic_search_client::config::SemanticSearchConfig;
-        use semantic_search_client::embedding::EmbeddingType;
-
+    fn create_config_from_db_settings(os: &crate::os::Os, base_dir: PathBuf) -> SemanticSearchConfig {
         use crate::database::settings::Setting;
 
         // Create default config first
@@ -216,24 +293,148 @@ impl KnowledgeStore {
             .and_then(|s| EmbeddingType::from_str(&s))
             .unwrap_or_default();
 
+        // An embedding endpoint is only configured once both its URL and model are set
+        let http_embedding = os
+            .database
+            .settings
+            .get_string(Setting::KnowledgeEmbeddingUrl)
+            .zip(os.database.settings.get_string(Setting::KnowledgeEmbeddingModel))
+            .map(|(url, model)| {
+                let mut http_config = HttpEmbeddingConfig::new(url, model);
+                http_config.api_key = os.env.get("Q_KNOWLEDGE_EMBEDDING_API_KEY").ok();
+                http_config
+            });
+
         SemanticSearchConfig {
             chunk_size,
             chunk_overlap,
             max_files,
             embedding_type,
+            http_embedding,
             base_dir,
             ..default_config
         }
     }
 
-    /// Create instance with database settings from OS
+    /// Create instance with database settings from OS. The workspace knowledge base is opened if
+    /// it already exists in the current directory.
     pub async fn new_with_os_settings(os: &crate::os::Os, base_dir: PathBuf) -> Result<Self> {
         let config = Self::create_config_from_db_settings(os, base_dir.clone());
         let client = AsyncSemanticSearchClient::with_config(&base_dir, config)
             .await
             .map_err(|e| eyre::eyre!("Failed to create client: {}", e))?;
 
-        Ok(Self { client })
+        let workspace_config = directories::workspace_knowledge_base_dir(os)
+            .ok()
+            // The global knowledge base is never opened a second time as a workspace one
+            .filter(|workspace_dir| *workspace_dir != base_dir)
+            .map(|workspace_dir| Self::create_config_from_db_settings(os, workspace_dir));
+
+        let mut store = Self {
+            client,
+            workspace: None,
+            workspace_config,
+        };
+        if store
+            .workspace_config
+            .as_ref()
+            .is_some_and(|config| config.base_dir.exists())
+        {
+            store.workspace_client().await.map_err(|e| eyre::eyre!(e))?;
+        }
+
+        Ok(store)
+    }
+
+    /// The knowledge base new contexts are added to and searched by default: the workspace one if
+    /// it exists, otherwise the global one
+    pub fn default_scope(&self) -> KnowledgeScope {
+        if self.workspace.is_some() {
+            KnowledgeScope::Workspace
+        } else {
+            KnowledgeScope::Global
+        }
+    }
+
+    /// Resolve the scopes to search: the given scope, both for `"all"`, or the default scope
+    pub fn search_scopes(&self, scope: Option<&str>) -> Result<Vec<KnowledgeScope>, String> {
+        KnowledgeScope::resolve(scope, self.workspace.is_some())
+    }
+
+    /// The knowledge bases that are open, workspace first
+    fn clients(&self) -> Vec<(KnowledgeScope, &AsyncSemanticSearchClient)> {
+        let mut clients = Vec::new();
+        if let Some(workspace) = &self.workspace {
+            clients.push((KnowledgeScope::Workspace, workspace));
+        }
+        clients.push((KnowledgeScope::Global, &self.client));
+        clients
+    }
+
+    /// The client of the given knowledge base, creating the workspace one if needed
+    async fn client_for(&mut self, scope: KnowledgeScope) -> Result<&AsyncSemanticSearchClient, String> {
+        match scope {
+            KnowledgeScope::Workspace => self.workspace_client().await,
+            KnowledgeScope::Global => Ok(&self.client),
+        }
+    }
+
+    /// The client of the workspace knowledge base, created on first use
+    async fn workspace_client(&mut self) -> Result<&AsyncSemanticSearchClient, String> {
+        if self.workspace.is_none() {
+            let config = self
+                .workspace_config
+                .clone()
+                .ok_or_else(|| "No workspace knowledge base is available in this directory".to_string())?;
+            let base_dir = config.base_dir.clone();
+            let client = AsyncSemanticSearchClient::with_config(&base_dir, config)
+                .await
+                .map_err(|e| format!("Failed to open workspace knowledge base: {}", e))?;
+            self.workspace = Some(client);
+        }
+
+        self.workspace
+            .as_ref()
+            .ok_or_else(|| "No workspace knowledge base is available in this directory".to_string())
+    }
+
+    /// Find a context in the open knowledge bases, preferring the workspace one
+    async fn find_context(
+        &self,
+        matches: impl Fn(&KnowledgeContext) -> bool,
+    ) -> Option<(KnowledgeScope, &AsyncSemanticSearchClient, KnowledgeContext)> {
+        for (scope, client) in self.clients() {
+            if let Some(context) = client.get_contexts().await.into_iter().find(|context| matches(context)) {
+                return Some((scope, client, context));
+            }
+        }
+        None
+    }
+
+    /// Find a context by its source path
+    async fn find_context_by_path(
+        &self,
+        path: &str,
+    ) -> Option<(KnowledgeScope, &AsyncSemanticSearchClient, KnowledgeContext)> {
+        for (scope, client) in self.clients() {
+            if let Some(context) = client.get_context_by_path(path).await {
+                return Some((scope, client, context));
+            }
+        }
+        None
+    }
+
+    /// Find a context by name
+    async fn find_context_by_name(
+        &self,
+        name: &str,
+    ) -> Option<(KnowledgeScope, &AsyncSemanticSearchClient, KnowledgeContext)> {
+        for (scope, client) in self.clients() {
+            if let Some(context) = client.get_context_by_name(name).await {
+                return Some((scope, client, context));
+            }
+        }
+        None
     }
 
     /// Add context with flexible options
@@ -279,14 +480,20 @@ impl KnowledgeStore {
                 Some(s) => match EmbeddingType::from_str(s) {
                     Some(et) => Some(et),
                     None => {
-                        return Err(format!("Invalid embedding type '{}'. Valid options are: fast, best", s));
+                        return Err(format!(
+                            "Invalid embedding type '{}'. Valid options are: fast, best, hybrid, http",
+                            s
+                        ));
                     },
                 },
                 None => None,
             },
+            auto_update: options.auto_update,
         };
 
-        match self.client.add_context(request).await {
+        let scope = options.scope.unwrap_or_else(|| self.default_scope());
+        let client = self.client_for(scope).await?;
+        match client.add_context(request).await {
             Ok((operation_id, _)) => {
                 let mut message = format!(
                     "🚀 Started indexing '{}'\n📁 Path: {}\n🆔 Operation ID: {}",
@@ -294,6 +501,9 @@ impl KnowledgeStore {
                     canonical_path.display(),
                     &operation_id.to_string()[..8]
                 );
+                if scope == KnowledgeScope::Workspace {
+                    message.push_str("\n📌 Added to the workspace knowledge base");
+                }
                 if !options.include_patterns.is_empty() || !options.exclude_patterns.is_empty() {
                     message.push_str("\n📋 Pattern filtering applied:");
                     if !options.include_patterns.is_empty() {
@@ -304,6 +514,9 @@ impl KnowledgeStore {
                     }
                     message.push_str("\n✅ Only matching files will be indexed");
                 }
+                if options.auto_update {
+                    message.push_str("\n👀 Auto-update enabled: changed files will be re-indexed automatically");
+                }
                 Ok(message)
             },
             Err(e) => {
@@ -317,62 +530,121 @@ impl KnowledgeStore {
         }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<KnowledgeContext>, KnowledgeError> {
-        Ok(self.client.get_contexts().await)
+    /// Add a context unless its path is already indexed in the target knowledge base.
+    ///
+    /// Returns `None` if the path was already indexed.
+    pub async fn add_if_missing(
+        &mut self,
+        name: &str,
+        path_str: &str,
+        options: AddOptions,
+    ) -> Option<Result<String, String>> {
+        let scope = options.scope.unwrap_or_else(|| self.default_scope());
+        match self.client_for(scope).await {
+            Ok(client) if client.get_context_by_path(path_str).await.is_some() => None,
+            Ok(_) => Some(self.add(name, path_str, options).await),
+            Err(e) => Some(Err(e)),
+        }
     }
 
-    /// Search - delegates to async client
-    pub async fn search(&self, query: &str, _context_id: Option<&str>) -> Result<Vec<SearchResult>, KnowledgeError> {
-        let results = self
-            .client
-            .search_all(query, None)
-            .await
-            .map_err(|e| KnowledgeError::ClientError(e.to_string()))?;
+    /// Contexts of every open knowledge base, workspace first
+    pub async fn get_all(&self) -> Result<Vec<(KnowledgeScope, KnowledgeContext)>, KnowledgeError> {
+        let mut contexts = Vec::new();
+        for (scope, client) in self.clients() {
+            contexts.extend(client.get_contexts().await.into_iter().map(|context| (scope, context)));
+        }
+        Ok(contexts)
+    }
 
-        let mut flattened = Vec::new();
-        for (_, context_results) in results {
-            flattened.extend(context_results);
+    /// Reasons why contexts of the open knowledge bases couldn't be loaded, keyed by context ID
+    pub async fn get_load_errors(&self) -> HashMap<String, String> {
+        let mut errors = HashMap::new();
+        for (_, client) in self.clients() {
+            errors.extend(client.get_load_errors().await);
         }
+        errors
+    }
 
-        flattened.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(std::cmp::Ordering::Equal));
+    /// Search - delegates to the async clients of the given scopes, returning the results that
+    /// match `filter` as citations sorted by score
+    pub async fn search(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        scopes: &[KnowledgeScope],
+    ) -> Result<Vec<Citation>, KnowledgeError> {
+        let mut citations = Vec::new();
+        for (scope, client) in self.clients() {
+            if scopes.contains(&scope) {
+                citations.extend(
+                    client
+                        .search_citations(query, None, filter)
+                        .await
+                        .map_err(|e| KnowledgeError::ClientError(e.to_string()))?,
+                );
+            }
+        }
+        citations.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal));
 
-        Ok(flattened)
+        Ok(citations)
     }
 
-    /// Get status data - delegates to async client
-    pub async fn get_status_data(&self) -> Result<semantic_search_client::SystemStatus, String> {
-        self.client
-            .get_status_data()
-            .await
-            .map_err(|e| format!("Failed to get status data: {}", e))
+    /// Get status data - delegates to the async clients and combines their status
+    pub async fn get_status_data(&self) -> Result<SystemStatus, String> {
+        let mut combined: Option<SystemStatus> = None;
+        for (_, client) in self.clients() {
+            let status = client
+                .get_status_data()
+                .await
+                .map_err(|e| format!("Failed to get status data: {}", e))?;
+            combined = Some(match combined {
+                None => status,
+                Some(mut combined) => {
+                    combined.total_contexts += status.total_contexts;
+                    combined.persistent_contexts += status.persistent_contexts;
+                    combined.volatile_contexts += status.volatile_contexts;
+                    combined.active_count += status.active_count;
+                    combined.waiting_count += status.waiting_count;
+                    combined.operations.extend(status.operations);
+                    combined.watchers.extend(status.watchers);
+                    combined
+                },
+            });
+        }
+        combined.ok_or_else(|| "Failed to get status data: no knowledge base is open".to_string())
     }
 
-    /// Cancel operation - delegates to async client
+    /// Cancel operation - delegates to the async client running it
     pub async fn cancel_operation(&mut self, operation_id: Option<&str>) -> Result<String, String> {
         if let Some(short_id) = operation_id {
-            let available_ops = self.client.list_operation_ids().await;
+            let mut available_ops = Vec::new();
+            for (_, client) in self.clients() {
+                available_ops.extend(client.list_operation_ids().await);
+            }
             if available_ops.is_empty() {
                 // This is fine.
                 return Ok("No operations to cancel".to_string());
             }
 
-            // Try to parse as full UUID first
-            if let Ok(uuid) = Uuid::parse_str(short_id) {
-                self.client.cancel_operation(uuid).await.map_err(|e| e.to_string())
-            } else {
-                // Try to find by short ID (first 8 characters)
-                if let Some(full_uuid) = self.client.find_operation_by_short_id(short_id).await {
-                    self.client.cancel_operation(full_uuid).await.map_err(|e| e.to_string())
-                } else {
-                    Err(format!(
-                        "No operation found matching ID: {}\nAvailable operations:\n{}",
-                        short_id,
-                        available_ops.join("\n")
-                    ))
+            // Full UUIDs are normalized so that they match like short IDs (first 8 characters)
+            let id = Uuid::parse_str(short_id).map_or_else(|_| short_id.to_string(), |uuid| uuid.to_string());
+            for (_, client) in self.clients() {
+                if let Some(uuid) = client.find_operation_by_short_id(&id).await {
+                    return client.cancel_operation(uuid).await.map_err(|e| e.to_string());
                 }
             }
+            Err(format!(
+                "No operation found matching ID: {}\nAvailable operations:\n{}",
+                short_id,
+                available_ops.join("\n")
+            ))
         } else {
-            // Cancel most recent operation (not all operations)
+            // Cancel most recent operation (not all operations), in the knowledge base running one
+            for (_, client) in self.clients() {
+                if !client.list_operation_ids().await.is_empty() {
+                    return client.cancel_most_recent_operation().await.map_err(|e| e.to_string());
+                }
+            }
             self.client
                 .cancel_most_recent_operation()
                 .await
@@ -380,9 +652,11 @@ impl KnowledgeStore {
         }
     }
 
-    /// Clear all contexts (background operation)
-    pub async fn clear(&mut self) -> Result<String, String> {
-        match self.client.clear_all().await {
+    /// Clear all contexts of a knowledge base, the default one if no scope is given (background
+    /// operation)
+    pub async fn clear(&mut self, scope: Option<KnowledgeScope>) -> Result<String, String> {
+        let scope = scope.unwrap_or_else(|| self.default_scope());
+        match self.client_for(scope).await?.clear_all().await {
             Ok((operation_id, _cancel_token)) => Ok(format!(
                 "🚀 Started clearing all contexts in background.\n📊 Use 'knowledge status' to check progress.\n🆔 Operation ID: {}",
                 &operation_id.to_string()[..8]
@@ -391,18 +665,23 @@ impl KnowledgeStore {
         }
     }
 
-    /// Clear all contexts immediately (synchronous operation)
-    pub async fn clear_immediate(&mut self) -> Result<String, String> {
-        match self.client.clear_all_immediate().await {
-            Ok(count) => Ok(format!("✅ Successfully cleared {} knowledge base entries", count)),
+    /// Clear all contexts of a knowledge base immediately, the default one if no scope is given
+    /// (synchronous operation)
+    pub async fn clear_immediate(&mut self, scope: Option<KnowledgeScope>) -> Result<String, String> {
+        let scope = scope.unwrap_or_else(|| self.default_scope());
+        match self.client_for(scope).await?.clear_all_immediate().await {
+            Ok(count) => Ok(format!(
+                "✅ Successfully cleared {} {} knowledge base entries",
+                count, scope
+            )),
             Err(e) => Err(format!("Failed to clear knowledge base: {}", e)),
         }
     }
 
     /// Remove context by path
     pub async fn remove_by_path(&mut self, path: &str) -> Result<(), String> {
-        if let Some(context) = self.client.get_context_by_path(path).await {
-            self.client
+        if let Some((_, client, context)) = self.find_context_by_path(path).await {
+            client
                 .remove_context_by_id(&context.id)
                 .await
                 .map_err(|e| e.to_string())
@@ -413,8 +692,8 @@ impl KnowledgeStore {
 
     /// Remove context by name
     pub async fn remove_by_name(&mut self, name: &str) -> Result<(), String> {
-        if let Some(context) = self.client.get_context_by_name(name).await {
-            self.client
+        if let Some((_, client, context)) = self.find_context_by_name(name).await {
+            client
                 .remove_context_by_id(&context.id)
                 .await
                 .map_err(|e| e.to_string())
@@ -425,32 +704,104 @@ impl KnowledgeStore {
 
     /// Remove context by ID
     pub async fn remove_by_id(&mut self, context_id: &str) -> Result<(), String> {
-        self.client
-            .remove_context_by_id(context_id)
+        let (_, client, _) = self
+            .find_context(|context| context.id == context_id)
+            .await
+            .ok_or_else(|| format!("Context '{}' not found", context_id))?;
+        client.remove_context_by_id(context_id).await.map_err(|e| e.to_string())
+    }
+
+    /// Enable or disable automatic re-indexing of a context, found by path or name
+    pub async fn set_auto_update(&mut self, path_or_name: &str, enabled: bool) -> Result<String, String> {
+        let (_, client, context) = match self.find_context_by_path(path_or_name).await {
+            Some(found) => found,
+            None => self
+                .find_context_by_name(path_or_name)
+                .await
+                .ok_or_else(|| format!("No context found with path or name '{}'", path_or_name))?,
+        };
+
+        client
+            .set_auto_update(&context.id, enabled)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(if enabled {
+            format!(
+                "👀 Auto-update enabled for '{}': changed files will be re-indexed automatically",
+                context.name
+            )
+        } else {
+            format!("Auto-update disabled for '{}'", context.name)
+        })
+    }
+
+    /// Export a context, found by name or path, as a bundle that can be imported elsewhere
+    pub async fn export(&mut self, name_or_path: &str, file: &Path) -> Result<String, String> {
+        let (_, client, context) = match self.find_context_by_name(name_or_path).await {
+            Some(found) => found,
+            None => self
+                .find_context_by_path(name_or_path)
+                .await
+                .ok_or_else(|| format!("No context found with name or path '{}'", name_or_path))?,
+        };
+
+        let manifest = client
+            .export_context(&context.id, file)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(format!(
+            "📦 Exported '{}' to {}\n📄 {} items, index type {}{}",
+            context.name,
+            file.display(),
+            context.item_count,
+            context.embedding_type.description(),
+            manifest
+                .embedding_model
+                .map(|model| format!(" ({})", model))
+                .unwrap_or_default()
+        ))
+    }
+
+    /// Import a context from a bundle into the given knowledge base, pointing its files at
+    /// `source_path` if given
+    pub async fn import(
+        &mut self,
+        file: &Path,
+        source_path: Option<&Path>,
+        scope: Option<KnowledgeScope>,
+    ) -> Result<String, String> {
+        let scope = scope.unwrap_or_else(|| self.default_scope());
+        let client = self.client_for(scope).await?;
+        let context = client
+            .import_context(file, source_path)
             .await
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+
+        let mut message = format!(
+            "📥 Imported '{}' into the {} knowledge base\n📄 {} items, index type {}",
+            context.name,
+            scope,
+            context.item_count,
+            context.embedding_type.description()
+        );
+        if let Some(path) = &context.source_path {
+            message.push_str(&format!("\n📁 Path: {}", path));
+        }
+        Ok(message)
     }
 
     /// Update context by path
     pub async fn update_by_path(&mut self, path_str: &str) -> Result<String, String> {
-        if let Some(context) = self.client.get_context_by_path(path_str).await {
-            // Remove the existing context first
-            self.client
-                .remove_context_by_id(&context.id)
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Then add it back with the same name and original patterns
-            let options = AddOptions {
-                description: None,
-                include_patterns: context.include_patterns.clone(),
-                exclude_patterns: context.exclude_patterns.clone(),
-                embedding_type: None,
-            };
-            self.add(&context.name, path_str, options).await
+        if let Some((scope, _, context)) = self.find_context_by_path(path_str).await {
+            self.update_context(scope, &context, path_str).await
         } else {
             // Debug: List all available contexts
-            let available_paths = self.client.list_context_paths().await;
+            let mut available_paths = Vec::new();
+            for (_, client) in self.clients() {
+                available_paths.extend(client.list_context_paths().await);
+            }
             if available_paths.is_empty() {
                 Err("No contexts found. Add a context first with 'knowledge add <name> <path>'".to_string())
             } else {
@@ -465,17 +816,53 @@ impl KnowledgeStore {
 
     /// Update context by ID
     pub async fn update_context_by_id(&mut self, context_id: &str, path_str: &str) -> Result<String, String> {
-        let contexts = self.get_all().await.map_err(|e| e.to_string())?;
-        let context = contexts
-            .iter()
-            .find(|c| c.id == context_id)
+        let (scope, _, context) = self
+            .find_context(|context| context.id == context_id)
+            .await
             .ok_or_else(|| format!("Context '{}' not found", context_id))?;
 
-        let context_name = context.name.clone();
+        self.update_context(scope, &context, path_str).await
+    }
+
+    /// Update context by name
+    pub async fn update_context_by_name(&mut self, name: &str, path_str: &str) -> Result<String, String> {
+        if let Some((scope, _, context)) = self.find_context_by_name(name).await {
+            self.update_context(scope, &context, path_str).await
+        } else {
+            Err(format!("Context with name '{}' not found", name))
+        }
+    }
+
+    /// Re-index a context from `path_str`. When the path is the context's own source only changed
+    /// files are re-indexed, otherwise the context is rebuilt from the new path.
+    async fn update_context(
+        &mut self,
+        scope: KnowledgeScope,
+        context: &KnowledgeContext,
+        path_str: &str,
+    ) -> Result<String, String> {
+        let client = self.client_for(scope).await?;
+        let is_same_source = context.source_path.as_ref().is_some_and(|source| {
+            match (Path::new(source).canonicalize(), Path::new(path_str).canonicalize()) {
+                (Ok(source), Ok(path)) => source == path,
+                _ => false,
+            }
+        });
+
+        if is_same_source {
+            return match client.update_context(&context.id).await {
+                Ok((operation_id, _cancel_token)) => Ok(format!(
+                    "🔄 Started updating '{}' in background.\n📁 Only changed files will be re-indexed.\n📊 Use 'knowledge status' to check progress.\n🆔 Operation ID: {}",
+                    context.name,
+                    &operation_id.to_string()[..8]
+                )),
+                Err(e) => Err(format!("Failed to start update: {}", e)),
+            };
+        }
 
         // Remove the existing context first
-        self.client
-            .remove_context_by_id(context_id)
+        client
+            .remove_context_by_id(&context.id)
             .await
             .map_err(|e| e.to_string())?;
 
@@ -485,30 +872,10 @@ impl KnowledgeStore {
             include_patterns: context.include_patterns.clone(),
             exclude_patterns: context.exclude_patterns.clone(),
             embedding_type: None,
+            auto_update: context.auto_update,
+            scope: Some(scope),
         };
-        self.add(&context_name, path_str, options).await
-    }
-
-    /// Update context by name
-    pub async fn update_context_by_name(&mut self, name: &str, path_str: &str) -> Result<String, String> {
-        if let Some(context) = self.client.get_context_by_name(name).await {
-            // Remove the existing context first
-            self.client
-                .remove_context_by_id(&context.id)
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Then add it back with the same name and original patterns
-            let options = AddOptions {
-                description: None,
-                include_patterns: context.include_patterns.clone(),
-                exclude_patterns: context.exclude_patterns.clone(),
-                embedding_type: None,
-            };
-            self.add(name, path_str, options).await
-        } else {
-            Err(format!("Context with name '{}' not found", name))
-        }
+        self.add(&context.name, path_str, options).await
     }
 }
 
@@ -544,6 +911,23 @@ mod tests {
         assert_eq!(config.base_dir, base_dir);
     }
 
+    #[test]
+    fn test_resolve_scopes() {
+        use KnowledgeScope::*;
+
+        assert_eq!(KnowledgeScope::resolve(None, true).unwrap(), vec![Workspace]);
+        assert_eq!(KnowledgeScope::resolve(None, false).unwrap(), vec![Global]);
+        assert_eq!(KnowledgeScope::resolve(Some("all"), true).unwrap(), vec![
+            Workspace, Global
+        ]);
+        assert_eq!(KnowledgeScope::resolve(Some("all"), false).unwrap(), vec![Global]);
+        assert_eq!(KnowledgeScope::resolve(Some("Global"), true).unwrap(), vec![Global]);
+        assert_eq!(KnowledgeScope::resolve(Some("workspace"), false).unwrap(), vec![
+            Workspace
+        ]);
+        assert!(KnowledgeScope::resolve(Some("team"), true).is_err());
+    }
+
     #[tokio::test]
     async fn test_knowledge_bases_dir_structure() {
         let temp_dir = TempDir::new().unwrap();
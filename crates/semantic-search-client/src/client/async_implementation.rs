@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::{
     Path,
     PathBuf,
@@ -7,9 +8,17 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use super::background::BackgroundWorker;
+use super::background::{
+    BackgroundWorker,
+    ContextWatcher,
+};
 // Use the new modular structure
-use super::context::ContextManager;
+use super::context::{
+    BundleManifest,
+    ContextBundle,
+    ContextManager,
+    context_bundle,
+};
 use super::model::ModelDownloader;
 use super::operation::OperationManager;
 use crate::client::embedder_factory;
@@ -22,6 +31,7 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::search_filter::SearchFilter;
 use crate::types::*;
 
 /// Async Semantic Search Client with proper cancellation support
@@ -32,6 +42,7 @@ pub struct AsyncSemanticSearchClient {
     job_tx: mpsc::UnboundedSender<IndexingJob>,
     context_manager: ContextManager,
     operation_manager: OperationManager,
+    context_watcher: ContextWatcher,
 }
 
 impl AsyncSemanticSearchClient {
@@ -83,9 +94,10 @@ impl AsyncSemanticSearchClient {
         config::ensure_models_dir(&base_dir)?;
         ModelDownloader::ensure_models_downloaded(&config.embedding_type).await?;
 
-        let embedder = embedder_factory::create_embedder(config.embedding_type)?;
-        let context_manager = ContextManager::new(&base_dir).await?;
+        let embedder = embedder_factory::create_embedder(&config)?;
+        let context_manager = ContextManager::new(&base_dir, &*embedder).await?;
         let operation_manager = OperationManager::new();
+        let context_watcher = ContextWatcher::new();
 
         let (job_tx, job_rx) = mpsc::unbounded_channel();
 
@@ -93,6 +105,7 @@ impl AsyncSemanticSearchClient {
             job_rx,
             context_manager.clone(),
             operation_manager.clone(),
+            context_watcher.clone(),
             config.clone(),
             base_dir.clone(),
         )
@@ -107,6 +120,7 @@ impl AsyncSemanticSearchClient {
             job_tx,
             context_manager,
             operation_manager,
+            context_watcher,
         };
 
         client.context_manager.load_persistent_contexts().await?;
@@ -232,6 +246,7 @@ impl AsyncSemanticSearchClient {
     ///
     /// This method will return an error if:
     /// - The specified path does not exist or is not accessible
+    /// - The requested embedding type needs a different embedder than the configured one
     /// - The path is already being indexed by another operation
     /// - The background worker cannot be started
     /// - Required models are not available and cannot be downloaded
@@ -257,6 +272,7 @@ impl AsyncSemanticSearchClient {
     ///     include_patterns: Some(vec!["*.txt".to_string(), "*.md".to_string()]),
     ///     exclude_patterns: Some(vec!["*.tmp".to_string()]),
     ///     embedding_type: None, // Use default
+    ///     auto_update: false,
     /// };
     ///
     /// let (operation_id, cancel_token) = client.add_context(request).await?;
@@ -276,6 +292,21 @@ impl AsyncSemanticSearchClient {
             .check_path_exists(&canonical_path, &self.operation_manager)
             .await?;
 
+        // Contexts are embedded, and searched, with the embedder of the configured embedding type
+        if let Some(embedding_type) = request.embedding_type {
+            let configured = self.config.embedding_type;
+            if embedding_type
+                .embedder_type()
+                .is_some_and(|embedder_type| Some(embedder_type) != configured.embedder_type())
+            {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "The {} index type can't be used when the configured embedding type is {}",
+                    embedding_type.description(),
+                    configured.description()
+                )));
+            }
+        }
+
         // Validate patterns early to fail fast
         if let Some(ref include_patterns) = request.include_patterns {
             crate::pattern_filter::PatternFilter::new(include_patterns, &[])
@@ -310,6 +341,72 @@ impl AsyncSemanticSearchClient {
             include_patterns: request.include_patterns.clone(),
             exclude_patterns: request.exclude_patterns.clone(),
             embedding_type: request.embedding_type,
+            auto_update: request.auto_update,
+        };
+
+        self.job_tx
+            .send(job)
+            .map_err(|_send_error| SemanticSearchError::OperationFailed("Background worker unavailable".to_string()))?;
+
+        Ok((operation_id, cancel_token))
+    }
+
+    /// Re-indexes a context from its source path in the background.
+    ///
+    /// Only files that were added, modified or removed since the context was last indexed are
+    /// processed; the changes found are reported in the operation's status.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - The unique identifier of the context to update
+    ///
+    /// # Returns
+    ///
+    /// Returns a tuple of the operation ID and a cancellation token for the update.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if:
+    /// - The context does not exist or has no source path
+    /// - The context is already being updated
+    /// - The background worker is unavailable
+    pub async fn update_context(&self, context_id: &str) -> Result<(Uuid, CancellationToken)> {
+        let context = self
+            .context_manager
+            .get_contexts()
+            .await
+            .into_iter()
+            .find(|context| context.id == context_id)
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+        let path = context.source_path.clone().ok_or_else(|| {
+            SemanticSearchError::InvalidArgument(format!("Context '{}' has no source path", context.name))
+        })?;
+
+        if self.operation_manager.is_updating(&path).await {
+            return Err(SemanticSearchError::InvalidArgument(format!(
+                "Already updating this context: {}",
+                context.name
+            )));
+        }
+
+        let operation_id = Uuid::new_v4();
+        let cancel_token = CancellationToken::new();
+
+        self.operation_manager
+            .register_operation(
+                operation_id,
+                OperationType::Updating {
+                    name: context.name.clone(),
+                    path,
+                },
+                cancel_token.clone(),
+            )
+            .await;
+
+        let job = IndexingJob::UpdateContext {
+            id: operation_id,
+            cancel: cancel_token.clone(),
+            context_id: context.id,
         };
 
         self.job_tx
@@ -349,6 +446,13 @@ impl AsyncSemanticSearchClient {
         self.context_manager.get_contexts().await
     }
 
+    /// Returns the persistent contexts that couldn't be loaded, and so aren't searched, keyed by
+    /// context ID with the reason. A context is rejected when its vectors were computed with a
+    /// different embedding model than the configured one.
+    pub async fn get_load_errors(&self) -> HashMap<ContextId, String> {
+        self.context_manager.get_load_errors().await
+    }
+
     /// Performs a semantic search across all available contexts.
     ///
     /// This method searches through all indexed contexts using the provided query text,
@@ -406,6 +510,33 @@ impl AsyncSemanticSearchClient {
         &self,
         query_text: &str,
         result_limit: Option<usize>,
+    ) -> Result<Vec<(ContextId, SearchResults)>> {
+        self.search_all_filtered(query_text, result_limit, &SearchFilter::default())
+            .await
+    }
+
+    /// Performs a search across all available contexts, considering only the chunks that match a
+    /// filter.
+    ///
+    /// The filter is applied before ranking, in both the BM25 and the vector indexes, so the
+    /// results are the best matches among the matching chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `query_text` - The search query string
+    /// * `result_limit` - Optional limit on the number of results per context. If `None`, uses the
+    ///   default limit from configuration
+    /// * `filter` - Conditions on the path, file type, language and context of the results
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`search_all`](Self::search_all), and an error if the filter's
+    /// path glob is invalid.
+    pub async fn search_all_filtered(
+        &self,
+        query_text: &str,
+        result_limit: Option<usize>,
+        filter: &SearchFilter,
     ) -> Result<Vec<(ContextId, SearchResults)>> {
         if query_text.is_empty() {
             return Err(SemanticSearchError::InvalidArgument(
@@ -415,10 +546,50 @@ impl AsyncSemanticSearchClient {
 
         let effective_limit = result_limit.unwrap_or(self.config.default_results);
         self.context_manager
-            .search_all(query_text, effective_limit, &*self.embedder)
+            .search_all(query_text, effective_limit, &*self.embedder, filter)
             .await
     }
 
+    /// Searches all contexts and returns the results as citations.
+    ///
+    /// Results from every context are merged and sorted by score, and each one carries the
+    /// context it came from together with its source path and line range (or page and section for
+    /// documents), so that callers can point at or read back the exact location.
+    ///
+    /// # Arguments
+    ///
+    /// * `query_text` - The search query string
+    /// * `result_limit` - Optional limit on the number of results per context. If `None`, uses the
+    ///   default limit from configuration
+    /// * `filter` - Conditions on the path, file type, language and context of the results
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`search_all_filtered`](Self::search_all_filtered).
+    pub async fn search_citations(
+        &self,
+        query_text: &str,
+        result_limit: Option<usize>,
+        filter: &SearchFilter,
+    ) -> Result<Vec<Citation>> {
+        let results = self.search_all_filtered(query_text, result_limit, filter).await?;
+        let contexts = self.get_contexts().await;
+
+        let mut citations = results
+            .iter()
+            .filter_map(|(context_id, results)| {
+                contexts
+                    .iter()
+                    .find(|context| context.id == *context_id)
+                    .map(|context| (context, results))
+            })
+            .flat_map(|(context, results)| results.iter().map(|result| Citation::new(context, result)))
+            .collect::<Vec<_>>();
+        citations.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal));
+
+        Ok(citations)
+    }
+
     /// Cancels a running background operation.
     ///
     /// This method attempts to cancel an operation identified by its UUID.
@@ -496,7 +667,41 @@ impl AsyncSemanticSearchClient {
     ///
     /// Returns a `Result<SystemStatus>` containing detailed system information.
     pub async fn get_status_data(&self) -> Result<SystemStatus> {
-        self.operation_manager.get_status_data(&self.context_manager).await
+        let mut status = self.operation_manager.get_status_data(&self.context_manager).await?;
+        let contexts = self.context_manager.get_contexts_ref().read().await;
+        status.watchers = self.context_watcher.status(&contexts);
+        Ok(status)
+    }
+
+    /// Enables or disables automatic re-indexing of a context.
+    ///
+    /// While enabled, the background worker watches the context's source path and re-indexes the
+    /// files that changed once they stop changing.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - The unique identifier of the context
+    /// * `enabled` - Whether the context should be kept up to date automatically
+    pub async fn set_auto_update(&self, context_id: &str, enabled: bool) -> Result<()> {
+        let persistent = {
+            let mut contexts = self.context_manager.get_contexts_ref().write().await;
+            let context = contexts
+                .get_mut(context_id)
+                .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+            if enabled && context.source_path.is_none() {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "Context '{}' has no source path to watch",
+                    context.name
+                )));
+            }
+            context.auto_update = enabled;
+            context.persistent
+        };
+
+        if persistent {
+            self.context_manager.save_contexts_metadata(&self.base_dir).await?;
+        }
+        Ok(())
     }
 
     /// Clears all contexts from the knowledge base asynchronously.
@@ -553,6 +758,141 @@ impl AsyncSemanticSearchClient {
             .await
     }
 
+    /// Exports a persistent context as a portable bundle.
+    ///
+    /// The bundle is a zip archive holding the context's data points and indexes, its metadata and
+    /// the name of the embedding model it was indexed with, together with a SHA-256 hash of every
+    /// file so that imports can verify it.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - The unique identifier of the context to export
+    /// * `dest` - Path of the bundle to write
+    pub async fn export_context(&self, context_id: &str, dest: &Path) -> Result<BundleManifest> {
+        let context = self
+            .context_manager
+            .get_contexts_ref()
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+        if !context.persistent {
+            return Err(SemanticSearchError::InvalidArgument(format!(
+                "Context '{}' is not persistent and can't be exported",
+                context.name
+            )));
+        }
+
+        let context_dir = self.base_dir.join(context_id);
+        let dest = dest.to_path_buf();
+        tokio::task::spawn_blocking(move || context_bundle::write_bundle(&context, &context_dir, &dest))
+            .await
+            .map_err(|e| SemanticSearchError::OperationFailed(format!("Export task failed: {}", e)))?
+    }
+
+    /// Imports a context from a bundle written by [`Self::export_context`].
+    ///
+    /// The bundle is rejected if its format version is newer than this client supports, if it
+    /// was indexed with a different embedding model or if any file fails its integrity check. The
+    /// context gets a new ID, so the same bundle can be imported into several knowledge bases.
+    ///
+    /// # Arguments
+    ///
+    /// * `bundle_path` - Path of the bundle to import
+    /// * `source_path` - Local directory the context's source path corresponds to. When given, the
+    ///   indexed file paths are rewritten to point under it so citations and updates use the local
+    ///   files.
+    pub async fn import_context(&self, bundle_path: &Path, source_path: Option<&Path>) -> Result<KnowledgeContext> {
+        let path = bundle_path.to_path_buf();
+        let bundle = tokio::task::spawn_blocking(move || ContextBundle::open(&path))
+            .await
+            .map_err(|e| SemanticSearchError::OperationFailed(format!("Import task failed: {}", e)))??;
+        let mut context = bundle.manifest().context.clone();
+        if !context.embedding_type.is_bm25() {
+            context.embedding_model = bundle.manifest().embedding_model.clone();
+            let current = self.embedder.model_name();
+            if context.embedding_model != current {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "Bundle was indexed with embedding model '{}' but the configured embedding backend uses '{}'",
+                    context.embedding_model.as_deref().unwrap_or("none"),
+                    current.as_deref().unwrap_or("none")
+                )));
+            }
+        }
+
+        if self.context_manager.get_context_by_name(&context.name).await.is_some() {
+            return Err(SemanticSearchError::InvalidArgument(format!(
+                "A context named '{}' already exists",
+                context.name
+            )));
+        }
+
+        let relocation = match source_path {
+            Some(local) => {
+                let local = local
+                    .canonicalize()
+                    .map_err(|_io_error| SemanticSearchError::InvalidPath(local.display().to_string()))?;
+                let original = context.source_path.clone().ok_or_else(|| {
+                    SemanticSearchError::InvalidArgument(format!(
+                        "Context '{}' has no source path to relocate",
+                        context.name
+                    ))
+                })?;
+                context.source_path = Some(local.to_string_lossy().to_string());
+                Some((PathBuf::from(original), local))
+            },
+            None => None,
+        };
+        if let Some(source) = &context.source_path {
+            if let Some(existing) = self.context_manager.get_context_by_path(source).await {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "Path already exists in knowledge base: {} (Context: '{}')",
+                    source, existing.name
+                )));
+            }
+        }
+
+        context.id = Uuid::new_v4().to_string();
+        context.persistent = true;
+        context.auto_update = false;
+
+        let context_dir = self.base_dir.join(&context.id);
+        let extract_dir = context_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            bundle.extract(&extract_dir)?;
+            if let Some((from, to)) = relocation {
+                if let Err(e) = context_bundle::relocate_context(&extract_dir, &from, &to) {
+                    let _ = std::fs::remove_dir_all(&extract_dir);
+                    return Err(e);
+                }
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| SemanticSearchError::OperationFailed(format!("Import task failed: {}", e)))??;
+
+        self.context_manager
+            .get_contexts_ref()
+            .write()
+            .await
+            .insert(context.id.clone(), context.clone());
+        let loaded = match self.context_manager.load_persistent_context(&context.id).await {
+            Ok(()) => self.context_manager.save_contexts_metadata(&self.base_dir).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = loaded {
+            let _ = self
+                .context_manager
+                .remove_context_by_id(&context.id, &self.base_dir)
+                .await;
+            let _ = std::fs::remove_dir_all(&context_dir);
+            return Err(e);
+        }
+
+        Ok(context)
+    }
+
     /// Retrieves a context by its source path.
     ///
     /// This method finds a context that was created from the specified file or directory path.
@@ -596,3 +936,57 @@ impl AsyncSemanticSearchClient {
         self.context_manager.list_context_paths().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::config::HttpEmbeddingConfig;
+    use crate::embedding::EmbeddingType;
+
+    fn request(path: &Path, embedding_type: EmbeddingType) -> AddContextRequest {
+        AddContextRequest {
+            path: path.to_path_buf(),
+            name: embedding_type.description().to_string(),
+            description: String::new(),
+            persistent: false,
+            include_patterns: None,
+            exclude_patterns: None,
+            embedding_type: Some(embedding_type),
+            auto_update: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_context_rejects_other_embedders() {
+        let mut server = mockito::Server::new_async().await;
+        let embeddings = server.mock("POST", "/embeddings").expect(0).create_async().await;
+        let temp_dir = TempDir::new().unwrap();
+        let docs = temp_dir.path().join("docs");
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::write(docs.join("notes.md"), "Some notes").unwrap();
+
+        let config = SemanticSearchConfig {
+            embedding_type: EmbeddingType::Fast,
+            http_embedding: Some(HttpEmbeddingConfig::new(server.url(), "nomic-embed-text")),
+            base_dir: temp_dir.path().join("knowledge"),
+            ..Default::default()
+        };
+        let client = AsyncSemanticSearchClient::with_config(temp_dir.path().join("knowledge"), config)
+            .await
+            .unwrap();
+
+        // The configured Fast type has no embedder, so Http contexts would get meaningless vectors
+        let error = client
+            .add_context(request(&docs, EmbeddingType::Http))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Http index type") && error.contains("is Fast"), "{}", error);
+        assert!(client.get_status_data().await.unwrap().operations.is_empty());
+
+        client.add_context(request(&docs, EmbeddingType::Fast)).await.unwrap();
+        embeddings.assert_async().await;
+    }
+}
@@ -1,8 +1,10 @@
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use tokio::sync::{
     Semaphore,
+    SemaphorePermit,
     mpsc,
 };
 use tokio_util::sync::CancellationToken;
@@ -12,9 +14,14 @@ use uuid::Uuid;
 use super::super::context::{
     ContextCreator,
     ContextManager,
+    FileManifest,
 };
 use super::super::operation::OperationManager;
 use super::file_processor::FileProcessor;
+use super::file_watcher::{
+    AUTO_UPDATE_CHECK_INTERVAL,
+    ContextWatcher,
+};
 use crate::client::{
     embedder_factory,
     utils,
@@ -36,6 +43,7 @@ pub struct BackgroundWorker {
     indexing_semaphore: Arc<Semaphore>,
     file_processor: FileProcessor,
     context_creator: ContextCreator,
+    context_watcher: ContextWatcher,
 }
 
 impl BackgroundWorker {
@@ -44,10 +52,11 @@ impl BackgroundWorker {
         job_rx: mpsc::UnboundedReceiver<IndexingJob>,
         context_manager: ContextManager,
         operation_manager: OperationManager,
+        context_watcher: ContextWatcher,
         config: SemanticSearchConfig,
         base_dir: PathBuf,
     ) -> crate::error::Result<Self> {
-        let embedder = embedder_factory::create_embedder(config.embedding_type)?;
+        let embedder = embedder_factory::create_embedder(&config)?;
         let file_processor = FileProcessor::new(config.clone());
         let context_creator = ContextCreator::new();
 
@@ -61,6 +70,7 @@ impl BackgroundWorker {
             indexing_semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_OPERATIONS)),
             file_processor,
             context_creator,
+            context_watcher,
         })
     }
 
@@ -68,7 +78,21 @@ impl BackgroundWorker {
     pub async fn run(mut self) {
         debug!("Background worker started for async semantic search client");
 
-        while let Some(job) = self.job_rx.recv().await {
+        let mut auto_update = tokio::time::interval(AUTO_UPDATE_CHECK_INTERVAL);
+        auto_update.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            let job = tokio::select! {
+                job = self.job_rx.recv() => match job {
+                    Some(job) => job,
+                    None => break,
+                },
+                _ = auto_update.tick() => {
+                    self.auto_update_contexts().await;
+                    continue;
+                },
+            };
+
             match job {
                 IndexingJob::AddDirectory {
                     id,
@@ -80,6 +104,7 @@ impl BackgroundWorker {
                     include_patterns,
                     exclude_patterns,
                     embedding_type,
+                    auto_update,
                 } => {
                     let params = IndexingParams {
                         path,
@@ -89,10 +114,14 @@ impl BackgroundWorker {
                         include_patterns,
                         exclude_patterns,
                         embedding_type,
+                        auto_update,
                     };
 
                     self.process_add_directory(id, params, cancel).await;
                 },
+                IndexingJob::UpdateContext { id, cancel, context_id } => {
+                    self.process_update_context(id, context_id, cancel).await;
+                },
                 IndexingJob::Clear { id, cancel } => {
                     self.process_clear(id, cancel).await;
                 },
@@ -114,15 +143,30 @@ impl BackgroundWorker {
             return;
         }
 
+        let Some(_permit) = self.acquire_indexing_slot(operation_id).await else {
+            return;
+        };
+
+        let result = self.perform_indexing(operation_id, params, cancel_token).await;
+
+        match result {
+            Ok(context_id) => {
+                debug!("Successfully indexed context: {}", context_id);
+                self.mark_operation_completed(operation_id).await;
+            },
+            Err(e) => {
+                tracing::error!("Indexing failed: {}", e);
+                self.mark_operation_failed(operation_id, e).await;
+            },
+        }
+    }
+
+    async fn acquire_indexing_slot(&self, operation_id: Uuid) -> Option<SemaphorePermit<'_>> {
         self.update_operation_status(operation_id, "Waiting in queue...".to_string())
             .await;
 
-        let _permit = match self.indexing_semaphore.try_acquire() {
-            Ok(permit) => {
-                self.update_operation_status(operation_id, "Acquired slot, starting indexing...".to_string())
-                    .await;
-                permit
-            },
+        let permit = match self.indexing_semaphore.try_acquire() {
+            Ok(permit) => permit,
             Err(_) => {
                 self.update_operation_status(
                     operation_id,
@@ -133,32 +177,19 @@ impl BackgroundWorker {
                 )
                 .await;
                 match self.indexing_semaphore.acquire().await {
-                    Ok(permit) => {
-                        self.update_operation_status(operation_id, "Acquired slot, starting indexing...".to_string())
-                            .await;
-                        permit
-                    },
+                    Ok(permit) => permit,
                     Err(_) => {
                         self.mark_operation_failed(operation_id, "Semaphore unavailable".to_string())
                             .await;
-                        return;
+                        return None;
                     },
                 }
             },
         };
 
-        let result = self.perform_indexing(operation_id, params, cancel_token).await;
-
-        match result {
-            Ok(context_id) => {
-                debug!("Successfully indexed context: {}", context_id);
-                self.mark_operation_completed(operation_id).await;
-            },
-            Err(e) => {
-                tracing::error!("Indexing failed: {}", e);
-                self.mark_operation_failed(operation_id, e).await;
-            },
-        }
+        self.update_operation_status(operation_id, "Acquired slot, starting indexing...".to_string())
+            .await;
+        Some(permit)
     }
 
     async fn perform_indexing(
@@ -176,11 +207,7 @@ impl BackgroundWorker {
         }
 
         let context_id = utils::generate_context_id();
-        let context_dir = if params.persistent {
-            self.base_dir.join(&context_id)
-        } else {
-            std::env::temp_dir().join("semantic_search").join(&context_id)
-        };
+        let context_dir = self.context_dir(&context_id, params.persistent);
 
         tokio::fs::create_dir_all(&context_dir)
             .await
@@ -221,7 +248,7 @@ impl BackgroundWorker {
             return Err("Failed: Operation was cancelled before file processing".to_string());
         }
 
-        let items = self
+        let (items, failed_files) = self
             .file_processor
             .process_directory_files(
                 &params.path,
@@ -253,6 +280,17 @@ impl BackgroundWorker {
             )
             .await?;
 
+        // Record what was indexed from each file so later updates can skip unchanged files
+        let mut manifest = FileManifest::from_items(&items);
+        for path in &failed_files {
+            if let Err(e) = manifest.record_file(path, Vec::new()) {
+                tracing::warn!("Failed to record {} in file manifest: {}", path.display(), e);
+            }
+        }
+        if let Err(e) = manifest.save(&context_dir) {
+            tracing::warn!("Failed to save file manifest for context {}: {}", context_id, e);
+        }
+
         self.store_context_metadata(
             &context_id,
             &params.name,
@@ -263,12 +301,251 @@ impl BackgroundWorker {
             &params.exclude_patterns,
             file_count,
             effective_embedding_type,
+            params.auto_update,
         )
         .await?;
 
         Ok(context_id)
     }
 
+    async fn process_update_context(&self, operation_id: Uuid, context_id: String, cancel_token: CancellationToken) {
+        debug!("Processing UpdateContext job: {}", context_id);
+
+        if cancel_token.is_cancelled() {
+            self.mark_operation_cancelled(operation_id).await;
+            return;
+        }
+
+        let Some(_permit) = self.acquire_indexing_slot(operation_id).await else {
+            return;
+        };
+
+        match self.perform_update(operation_id, &context_id, &cancel_token).await {
+            Ok(()) => {
+                debug!("Successfully updated context: {}", context_id);
+                self.mark_update_completed(operation_id).await;
+            },
+            Err(e) => {
+                tracing::error!("Update failed: {}", e);
+                self.mark_operation_failed(operation_id, e).await;
+            },
+        }
+    }
+
+    /// Re-index only the files of a context that were added, modified or removed since it was last
+    /// indexed, based on the context's file manifest.
+    async fn perform_update(
+        &self,
+        operation_id: Uuid,
+        context_id: &str,
+        cancel_token: &CancellationToken,
+    ) -> std::result::Result<(), String> {
+        let context = {
+            let contexts = self.context_manager.get_contexts_ref().read().await;
+            contexts
+                .get(context_id)
+                .cloned()
+                .ok_or_else(|| format!("Failed: Context '{}' not found", context_id))?
+        };
+        let source_path = context
+            .source_path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("Failed: Context '{}' has no source path", context.name))?;
+        if !source_path.exists() {
+            return Err(format!("Failed: Path '{}' does not exist", source_path.display()));
+        }
+
+        let context_dir = self.context_dir(context_id, context.persistent);
+        let include_patterns = (!context.include_patterns.is_empty()).then(|| context.include_patterns.clone());
+        let exclude_patterns = (!context.exclude_patterns.is_empty()).then(|| context.exclude_patterns.clone());
+
+        self.update_operation_status(operation_id, "Checking for changed files...".to_string())
+            .await;
+        let files = self
+            .file_processor
+            .list_files(&source_path, &include_patterns, &exclude_patterns)
+            .await?;
+        if files.len() > self.config.max_files {
+            return Err(format!(
+                "Failed: Directory contains {} files, which exceeds the maximum limit of {} files",
+                files.len(),
+                self.config.max_files
+            ));
+        }
+
+        // Contexts indexed before manifests were recorded are re-indexed in full, replacing all of
+        // their existing data points
+        let (mut manifest, mut stale_ids) = match FileManifest::load(&context_dir) {
+            Ok(Some(manifest)) => (manifest, std::collections::HashSet::new()),
+            _ => {
+                let existing_ids = self
+                    .existing_data_point_ids(context_id, &context_dir, context.embedding_type)
+                    .await;
+                let manifest = FileManifest {
+                    next_id: existing_ids.iter().max().map_or(0, |id| id + 1),
+                    ..Default::default()
+                };
+                (manifest, existing_ids)
+            },
+        };
+
+        let changes = manifest.diff(&files);
+        let summary = changes.summary();
+        self.update_operation_changes(operation_id, summary, format!("Found changes: {}", summary))
+            .await;
+
+        if cancel_token.is_cancelled() {
+            return Err("Operation was cancelled".to_string());
+        }
+
+        stale_ids.extend(
+            manifest.remove_files(
+                changes
+                    .removed
+                    .iter()
+                    .map(String::as_str)
+                    .chain(changes.modified.iter().filter_map(|path| path.to_str())),
+            ),
+        );
+
+        let changed_files = changes
+            .added
+            .iter()
+            .chain(&changes.modified)
+            .cloned()
+            .collect::<Vec<_>>();
+        let processed = self
+            .file_processor
+            .process_files(&changed_files, operation_id, cancel_token, &self.operation_manager)
+            .await?;
+
+        let mut items = Vec::new();
+        for (path, file_items) in processed {
+            let ids = manifest.allocate_ids(file_items.len());
+            if let Err(e) = manifest.record_file(&path, ids.clone().collect()) {
+                tracing::warn!("Failed to record {} in file manifest: {}", path.display(), e);
+            }
+            items.extend(ids.zip(file_items));
+        }
+
+        if !items.is_empty() || !stale_ids.is_empty() {
+            self.context_creator
+                .update_context(
+                    context_id,
+                    &context_dir,
+                    &items,
+                    &stale_ids,
+                    context.embedding_type,
+                    operation_id,
+                    cancel_token,
+                    &self.operation_manager,
+                    &*self.embedder,
+                    &self.context_manager,
+                )
+                .await?;
+        }
+
+        manifest
+            .save(&context_dir)
+            .map_err(|e| format!("Failed to save file manifest: {}", e))?;
+
+        {
+            let mut contexts = self.context_manager.get_contexts_ref().write().await;
+            if let Some(context) = contexts.get_mut(context_id) {
+                context.item_count = files.len();
+                context.updated_at = chrono::Utc::now();
+            }
+        }
+        if context.persistent {
+            self.context_manager
+                .save_contexts_metadata(&self.base_dir)
+                .await
+                .map_err(|e| format!("Failed to save contexts metadata: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// IDs of all data points in a context, for contexts that have no file manifest
+    async fn existing_data_point_ids(
+        &self,
+        context_id: &str,
+        context_dir: &std::path::Path,
+        embedding_type: crate::embedding::EmbeddingType,
+    ) -> std::collections::HashSet<usize> {
+        let mut ids = std::collections::HashSet::new();
+        if embedding_type.is_bm25() || embedding_type.is_hybrid() {
+            if let Ok(context) = self
+                .context_manager
+                .get_or_load_bm25_context(context_id, context_dir)
+                .await
+            {
+                ids.extend(context.lock().await.get_data_points().iter().map(|point| point.id));
+            }
+        }
+        if !embedding_type.is_bm25() {
+            if let Ok(context) = self
+                .context_manager
+                .get_or_load_semantic_context(context_id, context_dir)
+                .await
+            {
+                ids.extend(context.lock().await.get_data_points().iter().map(|point| point.id));
+            }
+        }
+        ids
+    }
+
+    /// Re-index the contexts with auto-update enabled whose files changed
+    async fn auto_update_contexts(&self) {
+        let contexts = {
+            let contexts = self.context_manager.get_contexts_ref().read().await;
+            contexts
+                .values()
+                .filter(|context| context.auto_update && context.source_path.is_some())
+                .map(|context| (context.clone(), self.context_dir(&context.id, context.persistent)))
+                .collect::<Vec<_>>()
+        };
+        self.context_watcher.sync(&contexts, &self.file_processor).await;
+        let due = self.context_watcher.take_due();
+
+        for (context, _) in contexts.into_iter().filter(|(context, _)| due.contains(&context.id)) {
+            let Some(path) = context.source_path.clone() else {
+                continue;
+            };
+            // Leave contexts alone while an update started by the user is running, it picks up
+            // the changed files itself
+            if self.operation_manager.is_updating(&path).await {
+                continue;
+            }
+
+            debug!("Files changed in context {}, updating automatically", context.id);
+            let operation_id = Uuid::new_v4();
+            let cancel_token = CancellationToken::new();
+            self.operation_manager
+                .register_operation(
+                    operation_id,
+                    OperationType::Updating {
+                        name: context.name.clone(),
+                        path,
+                    },
+                    cancel_token.clone(),
+                )
+                .await;
+            self.process_update_context(operation_id, context.id.clone(), cancel_token)
+                .await;
+            self.context_watcher.mark_updated(&context.id);
+        }
+    }
+
+    fn context_dir(&self, context_id: &str, persistent: bool) -> PathBuf {
+        if persistent {
+            self.base_dir.join(context_id)
+        } else {
+            std::env::temp_dir().join("semantic_search").join(context_id)
+        }
+    }
+
     async fn process_clear(&self, operation_id: Uuid, cancel_token: CancellationToken) {
         debug!("Processing Clear job");
 
@@ -379,6 +656,36 @@ impl BackgroundWorker {
         }
     }
 
+    async fn update_operation_changes(&self, operation_id: Uuid, changes: IndexChanges, message: String) {
+        if let Ok(mut operations) = self.operation_manager.get_active_operations_ref().try_write() {
+            if let Some(operation) = operations.get_mut(&operation_id) {
+                if let Ok(mut progress) = operation.progress.try_lock() {
+                    progress.changes = Some(changes);
+                    progress.message = message;
+                }
+            }
+        }
+    }
+
+    /// Updates stay listed for a short while after finishing so that what changed can be seen in
+    /// the status
+    async fn mark_update_completed(&self, operation_id: Uuid) {
+        if let Ok(mut operations) = self.operation_manager.get_active_operations_ref().try_write() {
+            if let Some(operation) = operations.get_mut(&operation_id) {
+                if let Ok(mut progress) = operation.progress.try_lock() {
+                    progress.message = match progress.changes {
+                        Some(changes) if changes.has_changes() => format!("Update complete: {}", changes),
+                        _ => "Update complete: no changes".to_string(),
+                    };
+                    progress.current = 0;
+                    progress.total = 0;
+                    progress.completed_at = Some(SystemTime::now());
+                }
+            }
+        }
+        debug!("Operation {} completed", operation_id);
+    }
+
     async fn mark_operation_completed(&self, operation_id: Uuid) {
         if let Ok(mut operations) = self.operation_manager.get_active_operations_ref().try_write() {
             operations.remove(&operation_id);
@@ -422,8 +729,9 @@ impl BackgroundWorker {
         exclude_patterns: &Option<Vec<String>>,
         item_count: usize,
         embedding_type: crate::embedding::EmbeddingType,
+        auto_update: bool,
     ) -> std::result::Result<(), String> {
-        let context = KnowledgeContext::new(
+        let mut context = KnowledgeContext::new(
             context_id.to_string(),
             name,
             description,
@@ -436,6 +744,12 @@ impl BackgroundWorker {
             item_count,
             embedding_type,
         );
+        context.auto_update = auto_update;
+        if !embedding_type.is_bm25() {
+            // Recorded so the context is never searched or extended with a different model
+            context.embedding_model = self.embedder.model_name();
+            context.embedding_dim = self.embedder.dimension();
+        }
 
         {
             let mut contexts = self.context_manager.get_contexts_ref().write().await;
@@ -452,3 +766,232 @@ impl BackgroundWorker {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::embedding::{
+        EmbeddingType,
+        MockTextEmbedder,
+    };
+
+    /// Cancels `cancel`, once set, when asked for embeddings, like a user cancelling while an
+    /// update is generating them
+    struct CancellingEmbedder {
+        embedder: MockTextEmbedder,
+        cancel: Arc<Mutex<Option<CancellationToken>>>,
+    }
+
+    impl TextEmbedderTrait for CancellingEmbedder {
+        fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            self.embedder.embed(text)
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> crate::error::Result<Vec<Vec<f32>>> {
+            if let Some(cancel) = self.cancel.lock().unwrap().as_ref() {
+                cancel.cancel();
+            }
+            self.embedder.embed_batch(texts)
+        }
+
+        fn model_name(&self) -> Option<String> {
+            None
+        }
+
+        fn dimension(&self) -> Option<usize> {
+            Some(self.embedder.dimension())
+        }
+    }
+
+    /// Embeds the query and texts about failures close to each other, texts about mascots far from
+    /// them and everything else in between, whatever words they share
+    struct TopicEmbedder;
+
+    impl TopicEmbedder {
+        const QUERY: &str = "why does it crash";
+    }
+
+    impl TextEmbedderTrait for TopicEmbedder {
+        fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            Ok(if text == Self::QUERY || text.contains("fails") {
+                vec![1.0, 0.0]
+            } else if text.contains("mascot") {
+                vec![0.0, 1.0]
+            } else {
+                vec![1.0, 1.0]
+            })
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> crate::error::Result<Vec<Vec<f32>>> {
+            texts.iter().map(|text| self.embed(text)).collect()
+        }
+
+        fn model_name(&self) -> Option<String> {
+            None
+        }
+
+        fn dimension(&self) -> Option<usize> {
+            Some(2)
+        }
+    }
+
+    async fn worker(base_dir: &std::path::Path, embedder: impl TextEmbedderTrait + 'static) -> BackgroundWorker {
+        let config = SemanticSearchConfig {
+            base_dir: base_dir.to_path_buf(),
+            ..Default::default()
+        };
+        let (_job_tx, job_rx) = mpsc::unbounded_channel();
+        BackgroundWorker {
+            job_rx,
+            context_manager: ContextManager::new(base_dir, &embedder).await.unwrap(),
+            operation_manager: OperationManager::new(),
+            embedder: Box::new(embedder),
+            file_processor: FileProcessor::new(config.clone()),
+            config,
+            base_dir: base_dir.to_path_buf(),
+            indexing_semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_OPERATIONS)),
+            context_creator: ContextCreator::new(),
+            context_watcher: ContextWatcher::new(),
+        }
+    }
+
+    /// Sorted IDs of the data points in each index of a context and in its file manifest
+    async fn indexed_ids(worker: &BackgroundWorker, context_id: &str) -> [Vec<usize>; 3] {
+        let context_dir = worker.context_dir(context_id, true);
+        let bm25 = worker
+            .context_manager
+            .get_or_load_bm25_context(context_id, &context_dir)
+            .await
+            .unwrap();
+        let semantic = worker
+            .context_manager
+            .get_or_load_semantic_context(context_id, &context_dir)
+            .await
+            .unwrap();
+        let manifest = FileManifest::load(&context_dir).unwrap().unwrap();
+
+        let mut ids = [
+            bm25.lock()
+                .await
+                .get_data_points()
+                .iter()
+                .map(|p| p.id)
+                .collect::<Vec<_>>(),
+            semantic.lock().await.get_data_points().iter().map(|p| p.id).collect(),
+            manifest.files.values().flat_map(|f| f.chunk_ids.clone()).collect(),
+        ];
+        for ids in &mut ids {
+            ids.sort_unstable();
+        }
+        ids
+    }
+
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    #[tokio::test]
+    async fn test_cancelled_update_changes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("a.txt"), "The first file").unwrap();
+        std::fs::write(source.join("b.txt"), "The second file").unwrap();
+
+        let cancel = Arc::new(Mutex::new(None));
+        let embedder = CancellingEmbedder {
+            embedder: MockTextEmbedder::new(8),
+            cancel: Arc::clone(&cancel),
+        };
+        let worker = worker(&temp_dir.path().join("knowledge"), embedder).await;
+        let params = IndexingParams {
+            path: source.clone(),
+            name: "source".to_string(),
+            description: String::new(),
+            persistent: true,
+            include_patterns: None,
+            exclude_patterns: None,
+            embedding_type: Some(EmbeddingType::Hybrid),
+            auto_update: false,
+        };
+        let context_id = worker
+            .perform_indexing(Uuid::new_v4(), params, CancellationToken::new())
+            .await
+            .unwrap();
+        let indexed = indexed_ids(&worker, &context_id).await;
+        assert!(!indexed[0].is_empty());
+        assert!(indexed.iter().all(|ids| *ids == indexed[0]), "{:?}", indexed);
+
+        std::fs::write(source.join("b.txt"), "The second file, changed").unwrap();
+        std::fs::write(source.join("c.txt"), "A third file").unwrap();
+
+        // Cancelled while embedding the changed files, after the BM25 data points were created
+        let cancel_token = CancellationToken::new();
+        *cancel.lock().unwrap() = Some(cancel_token.clone());
+        let error = worker
+            .perform_update(Uuid::new_v4(), &context_id, &cancel_token)
+            .await
+            .unwrap_err();
+        assert!(error.contains("cancelled"), "{}", error);
+        assert_eq!(indexed_ids(&worker, &context_id).await, indexed);
+
+        *cancel.lock().unwrap() = None;
+        worker
+            .perform_update(Uuid::new_v4(), &context_id, &CancellationToken::new())
+            .await
+            .unwrap();
+        let updated = indexed_ids(&worker, &context_id).await;
+        assert_ne!(updated[0], indexed[0]);
+        assert!(updated.iter().all(|ids| *ids == updated[0]), "{:?}", updated);
+    }
+
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    #[tokio::test]
+    async fn test_hybrid_search_finds_keyword_and_semantic_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        let files = [
+            // Shares no words with the query, but is about the same thing
+            ("failure.txt", "The service fails to start after an upgrade"),
+            // Shares a word with the query, but is about something else entirely
+            ("mascot.txt", "The crash test dummy is our team mascot"),
+            ("lunch.txt", "Lunch is served at noon"),
+            ("parking.txt", "Parking is on the second floor"),
+            ("invoices.txt", "Invoices are sent monthly"),
+        ];
+        for (name, text) in files {
+            std::fs::write(source.join(name), text).unwrap();
+        }
+
+        let worker = worker(&temp_dir.path().join("knowledge"), TopicEmbedder).await;
+        let params = IndexingParams {
+            path: source.clone(),
+            name: "source".to_string(),
+            description: String::new(),
+            persistent: true,
+            include_patterns: None,
+            exclude_patterns: None,
+            embedding_type: Some(EmbeddingType::Hybrid),
+            auto_update: false,
+        };
+        worker
+            .perform_indexing(Uuid::new_v4(), params, CancellationToken::new())
+            .await
+            .unwrap();
+
+        let results = worker
+            .context_manager
+            .search_all(TopicEmbedder::QUERY, 2, &TopicEmbedder, &crate::SearchFilter::default())
+            .await
+            .unwrap();
+        let mut found = results
+            .iter()
+            .flat_map(|(_, results)| results)
+            .filter_map(|result| std::path::Path::new(result.path()?).file_name()?.to_str())
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        assert_eq!(found, vec!["failure.txt", "mascot.txt"]);
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::path::{
+    Path,
+    PathBuf,
+};
 
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
@@ -100,7 +103,7 @@ impl FileProcessor {
         include_patterns: &Option<Vec<String>>,
         exclude_patterns: &Option<Vec<String>>,
         operation_manager: &OperationManager,
-    ) -> std::result::Result<Vec<serde_json::Value>, String> {
+    ) -> std::result::Result<(Vec<serde_json::Value>, Vec<PathBuf>), String> {
         self.update_operation_status(
             operation_manager,
             operation_id,
@@ -111,6 +114,7 @@ impl FileProcessor {
         let pattern_filter = Self::create_pattern_filter(include_patterns, exclude_patterns)?;
         let mut processed_files = 0;
         let mut items = Vec::new();
+        let mut failed_files = Vec::new();
 
         for entry in walkdir::WalkDir::new(dir_path)
             .follow_links(true)
@@ -139,7 +143,11 @@ impl FileProcessor {
 
             match process_file_with_config(path, Some(self.config.chunk_size), Some(self.config.chunk_overlap)) {
                 Ok(mut file_items) => items.append(&mut file_items),
-                Err(_) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to process {}: {}", path.display(), e);
+                    failed_files.push(path.to_path_buf());
+                    continue;
+                },
             }
 
             processed_files += 1;
@@ -156,7 +164,81 @@ impl FileProcessor {
             }
         }
 
-        Ok(items)
+        Ok((items, failed_files))
+    }
+
+    /// List the files in a directory that would be indexed, skipping hidden files and applying
+    /// the include and exclude patterns
+    pub async fn list_files(
+        &self,
+        dir_path: &Path,
+        include_patterns: &Option<Vec<String>>,
+        exclude_patterns: &Option<Vec<String>>,
+    ) -> std::result::Result<Vec<PathBuf>, String> {
+        let dir_path = dir_path.to_path_buf();
+        let pattern_filter = Self::create_pattern_filter(include_patterns, exclude_patterns)?;
+
+        tokio::task::spawn_blocking(move || {
+            walkdir::WalkDir::new(&dir_path)
+                .follow_links(true)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .filter(|e| {
+                    !e.path()
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|s| s.starts_with('.'))
+                })
+                .filter(|e| {
+                    pattern_filter
+                        .as_ref()
+                        .is_none_or(|filter| filter.should_include(e.path()))
+                })
+                .map(|e| e.into_path())
+                .collect()
+        })
+        .await
+        .map_err(|e| format!("File listing task failed: {}", e))
+    }
+
+    /// Process the given files, returning the items of each. Files that can't be processed have
+    /// no items, so that they are recorded as indexed rather than retried on every update.
+    pub async fn process_files(
+        &self,
+        paths: &[PathBuf],
+        operation_id: Uuid,
+        cancel_token: &CancellationToken,
+        operation_manager: &OperationManager,
+    ) -> std::result::Result<Vec<(PathBuf, Vec<serde_json::Value>)>, String> {
+        let mut results = Vec::with_capacity(paths.len());
+
+        for (i, path) in paths.iter().enumerate() {
+            if cancel_token.is_cancelled() {
+                return Err("Operation was cancelled during file processing".to_string());
+            }
+
+            if i % 10 == 0 {
+                self.update_operation_progress(
+                    operation_manager,
+                    operation_id,
+                    i as u64,
+                    paths.len() as u64,
+                    format!("Indexing changed files ({}/{})", i, paths.len()),
+                )
+                .await;
+            }
+
+            match process_file_with_config(path, Some(self.config.chunk_size), Some(self.config.chunk_overlap)) {
+                Ok(items) => results.push((path.clone(), items)),
+                Err(e) => {
+                    tracing::warn!("Failed to process {}: {}", path.display(), e);
+                    results.push((path.clone(), Vec::new()));
+                },
+            }
+        }
+
+        Ok(results)
     }
 
     fn create_pattern_filter(
@@ -201,3 +283,35 @@ impl FileProcessor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_process_files_keeps_failed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes.md");
+        let broken = dir.path().join("broken.pdf");
+        fs::write(&notes, "# Notes\n\nSome notes").unwrap();
+        fs::write(&broken, "not a pdf").unwrap();
+
+        let processor = FileProcessor::new(SemanticSearchConfig::default());
+        let processed = processor
+            .process_files(
+                &[notes.clone(), broken.clone()],
+                Uuid::new_v4(),
+                &CancellationToken::new(),
+                &OperationManager::new(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(processed.len(), 2);
+        assert_eq!(processed[0].0, notes);
+        assert!(!processed[0].1.is_empty());
+        assert_eq!(processed[1], (broken, Vec::new()));
+    }
+}
@@ -0,0 +1,474 @@
+use std::collections::HashMap;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::{
+    Arc,
+    Mutex,
+};
+use std::time::{
+    Duration,
+    Instant,
+    SystemTime,
+};
+
+use notify::event::{
+    CreateKind,
+    ModifyKind,
+    RemoveKind,
+};
+use notify::{
+    Event,
+    EventKind,
+    RecommendedWatcher,
+    RecursiveMode,
+    Watcher,
+};
+
+use super::super::context::FileManifest;
+use super::file_processor::FileProcessor;
+use crate::pattern_filter::PatternFilter;
+use crate::types::{
+    ContextId,
+    KnowledgeContext,
+    WatcherStatus,
+};
+
+/// How often the background worker checks whether watched changes are due for re-indexing. This
+/// only looks at the changes collected by the watchers, it doesn't touch the file system.
+pub const AUTO_UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long files have to stop changing before an automatic update starts, so that a burst of
+/// edits (e.g. a branch checkout) is re-indexed once
+pub const AUTO_UPDATE_DEBOUNCE: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl ChangeKind {
+    /// The kind of change an event makes to its paths, or `None` for events that can't change
+    /// what is indexed
+    fn from_event(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(Self::Added),
+            // Touching a file doesn't change its content
+            EventKind::Modify(ModifyKind::Metadata(_)) => None,
+            EventKind::Modify(_) => Some(Self::Modified),
+            EventKind::Remove(_) => Some(Self::Removed),
+            EventKind::Access(_) | EventKind::Any | EventKind::Other => None,
+        }
+    }
+}
+
+/// Paths changed in a context that haven't been re-indexed yet
+#[derive(Debug)]
+struct PendingChanges {
+    paths: HashMap<PathBuf, ChangeKind>,
+    /// When the last change was seen
+    last_change: Instant,
+}
+
+#[derive(Debug, Default)]
+struct WatchState {
+    /// Watches the source path of the context until dropped
+    watcher: Option<RecommendedWatcher>,
+    error: Option<String>,
+    pending: Option<PendingChanges>,
+    watching_since: Option<SystemTime>,
+    last_updated: Option<SystemTime>,
+}
+
+impl WatchState {
+    fn record(&mut self, path: PathBuf, kind: ChangeKind) {
+        let pending = self.pending.get_or_insert_with(|| PendingChanges {
+            paths: HashMap::new(),
+            last_change: Instant::now(),
+        });
+        pending.last_change = Instant::now();
+
+        let kind = match (pending.paths.get(&path), kind) {
+            // Created and deleted again before it was indexed
+            (Some(ChangeKind::Added), ChangeKind::Removed) => {
+                pending.paths.remove(&path);
+                return;
+            },
+            (Some(ChangeKind::Added), _) => ChangeKind::Added,
+            (Some(ChangeKind::Removed), ChangeKind::Added) => ChangeKind::Modified,
+            (_, kind) => kind,
+        };
+        pending.paths.insert(path, kind);
+    }
+}
+
+/// Watches the source paths of contexts with auto-update enabled and decides when they should be
+/// re-indexed.
+///
+/// File system events are collected per context as they arrive, so checking for due contexts is
+/// cheap. The source path is only scanned once, when watching starts, to catch up with changes
+/// made while it wasn't watched. Cloning shares the watcher state, which lets the client report it
+/// in the system status while the background worker drives the updates.
+#[derive(Debug, Clone, Default)]
+pub struct ContextWatcher {
+    states: Arc<Mutex<HashMap<ContextId, WatchState>>>,
+}
+
+impl ContextWatcher {
+    /// Create a new watcher with no watched contexts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching the given contexts, and stop watching any other context.
+    ///
+    /// # Arguments
+    ///
+    /// * `contexts` - Contexts with auto-update enabled, with the directory holding their index
+    /// * `file_processor` - Used to list the files matched by newly watched contexts
+    pub async fn sync(&self, contexts: &[(KnowledgeContext, PathBuf)], file_processor: &FileProcessor) {
+        let (new_contexts, removed) = {
+            let mut states = self.states.lock().unwrap();
+            let removed_ids = states
+                .keys()
+                .filter(|id| !contexts.iter().any(|(context, _)| context.id == **id))
+                .cloned()
+                .collect::<Vec<_>>();
+            let removed = removed_ids
+                .iter()
+                .filter_map(|id| states.remove(id))
+                .collect::<Vec<_>>();
+            let new_contexts = contexts
+                .iter()
+                .filter(|(context, _)| !states.contains_key(&context.id))
+                .cloned()
+                .collect::<Vec<_>>();
+            for (context, _) in &new_contexts {
+                states.insert(context.id.clone(), WatchState::default());
+            }
+            (new_contexts, removed)
+        };
+        // Stop the removed watchers outside the lock, which their event handlers take
+        drop(removed);
+
+        for (context, context_dir) in new_contexts {
+            let Some(source_path) = context.source_path.as_ref().map(PathBuf::from) else {
+                continue;
+            };
+            let watcher = self.watch(&context, &source_path, &context_dir);
+            let missed_changes = Self::detect_changes(&context, &source_path, &context_dir, file_processor).await;
+
+            let mut states = self.states.lock().unwrap();
+            let Some(state) = states.get_mut(&context.id) else {
+                continue;
+            };
+            match watcher {
+                Ok(watcher) => {
+                    state.watcher = Some(watcher);
+                    state.watching_since = Some(SystemTime::now());
+                },
+                Err(e) => {
+                    tracing::warn!("Failed to watch {} for changes: {}", source_path.display(), e);
+                    state.error = Some(e.to_string());
+                },
+            }
+            match missed_changes {
+                Ok(changes) => {
+                    for (path, kind) in changes {
+                        state.record(path, kind);
+                    }
+                },
+                Err(e) => tracing::debug!("Failed to check context {} for changes: {}", context.id, e),
+            }
+        }
+    }
+
+    /// Take the contexts whose files changed and have been quiet for [`AUTO_UPDATE_DEBOUNCE`].
+    /// Changes seen after this call are collected for the next update.
+    pub fn take_due(&self) -> Vec<ContextId> {
+        let mut states = self.states.lock().unwrap();
+        states
+            .iter_mut()
+            .filter(|(_, state)| {
+                state
+                    .pending
+                    .as_ref()
+                    .is_some_and(|pending| pending.last_change.elapsed() >= AUTO_UPDATE_DEBOUNCE)
+            })
+            .map(|(id, state)| {
+                state.pending = None;
+                id.clone()
+            })
+            .collect()
+    }
+
+    /// Record that a context was re-indexed after changes were detected
+    pub fn mark_updated(&self, context_id: &str) {
+        if let Some(state) = self.states.lock().unwrap().get_mut(context_id) {
+            state.last_updated = Some(SystemTime::now());
+        }
+    }
+
+    /// Watcher state of every context with auto-update enabled
+    pub fn status(&self, contexts: &HashMap<ContextId, KnowledgeContext>) -> Vec<WatcherStatus> {
+        let states = self.states.lock().unwrap();
+        let mut watchers = contexts
+            .values()
+            .filter(|context| context.auto_update)
+            .map(|context| {
+                let state = states.get(&context.id);
+                WatcherStatus {
+                    context_id: context.id.clone(),
+                    name: context.name.clone(),
+                    path: context.source_path.clone().unwrap_or_default(),
+                    pending: state.and_then(|state| state.pending.as_ref().map(|pending| pending.paths.len())),
+                    error: state.and_then(|state| state.error.clone()),
+                    watching_since: state.and_then(|state| state.watching_since),
+                    last_updated: state.and_then(|state| state.last_updated),
+                }
+            })
+            .collect::<Vec<_>>();
+        watchers.sort_by(|a, b| a.name.cmp(&b.name));
+        watchers
+    }
+
+    /// Watch the source path of a context, recording the changes to the files it matches
+    fn watch(
+        &self,
+        context: &KnowledgeContext,
+        source_path: &Path,
+        context_dir: &Path,
+    ) -> notify::Result<RecommendedWatcher> {
+        let states = Arc::clone(&self.states);
+        let context_id = context.id.clone();
+        let context_dir = context_dir.to_path_buf();
+        let pattern_filter = (!context.include_patterns.is_empty() || !context.exclude_patterns.is_empty())
+            .then(|| PatternFilter::new(&context.include_patterns, &context.exclude_patterns).ok())
+            .flatten();
+
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::debug!("File watcher error: {}", e);
+                    return;
+                },
+            };
+            let Some(kind) = ChangeKind::from_event(&event.kind) else {
+                return;
+            };
+            // Directories moved in or out don't report the files in them, and can't be matched
+            // against the patterns
+            let is_dir = matches!(
+                event.kind,
+                EventKind::Create(CreateKind::Folder) | EventKind::Remove(RemoveKind::Folder)
+            ) || event.paths.iter().all(|path| path.is_dir());
+
+            let Ok(mut states) = states.lock() else {
+                return;
+            };
+            let Some(state) = states.get_mut(&context_id) else {
+                return;
+            };
+            for path in event.paths {
+                // The index of a context can be inside its own source path
+                if path.starts_with(&context_dir) {
+                    continue;
+                }
+                let is_hidden = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|s| s.starts_with('.'));
+                let is_included = pattern_filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.should_include(&path));
+                if is_dir || (!is_hidden && is_included) {
+                    state.record(path, kind);
+                }
+            }
+        })?;
+        watcher.watch(source_path, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    }
+
+    /// Compare the files of a context against its manifest, returning the files that changed
+    async fn detect_changes(
+        context: &KnowledgeContext,
+        source_path: &Path,
+        context_dir: &Path,
+        file_processor: &FileProcessor,
+    ) -> Result<Vec<(PathBuf, ChangeKind)>, String> {
+        if !source_path.exists() {
+            return Err(format!("Path '{}' does not exist", source_path.display()));
+        }
+
+        let include_patterns = (!context.include_patterns.is_empty()).then(|| context.include_patterns.clone());
+        let exclude_patterns = (!context.exclude_patterns.is_empty()).then(|| context.exclude_patterns.clone());
+        let files = file_processor
+            .list_files(source_path, &include_patterns, &exclude_patterns)
+            .await?;
+
+        let context_dir = context_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            // Contexts indexed before manifests were recorded need one full update to create it
+            let Some(mut manifest) = FileManifest::load(&context_dir).map_err(|e| e.to_string())? else {
+                return Ok(files.into_iter().map(|path| (path, ChangeKind::Added)).collect());
+            };
+
+            let changes = manifest.diff(&files);
+            Ok(changes
+                .added
+                .into_iter()
+                .map(|path| (path, ChangeKind::Added))
+                .chain(changes.modified.into_iter().map(|path| (path, ChangeKind::Modified)))
+                .chain(
+                    changes
+                        .removed
+                        .into_iter()
+                        .map(|path| (PathBuf::from(path), ChangeKind::Removed)),
+                )
+                .collect())
+        })
+        .await
+        .map_err(|e| format!("Failed to check for changes: {}", e))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::config::SemanticSearchConfig;
+
+    fn auto_update_context(source: &Path) -> KnowledgeContext {
+        let mut context = KnowledgeContext::new(
+            "ctx".to_string(),
+            "notes",
+            "",
+            true,
+            Some(source.to_string_lossy().to_string()),
+            (vec![], vec!["*.log".to_string()]),
+            1,
+            Default::default(),
+        );
+        context.auto_update = true;
+        context
+    }
+
+    fn pending_paths(watcher: &ContextWatcher) -> Vec<(PathBuf, ChangeKind)> {
+        let states = watcher.states.lock().unwrap();
+        let mut paths = states["ctx"]
+            .pending
+            .as_ref()
+            .map(|pending| pending.paths.clone().into_iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths
+    }
+
+    /// Backdate the pending changes of the test context past the debounce period
+    fn expire_debounce(watcher: &ContextWatcher) {
+        if let Some(pending) = watcher
+            .states
+            .lock()
+            .unwrap()
+            .get_mut("ctx")
+            .and_then(|s| s.pending.as_mut())
+        {
+            pending.last_change -= AUTO_UPDATE_DEBOUNCE;
+        }
+    }
+
+    #[test]
+    fn test_record_merges_changes() {
+        let mut state = WatchState::default();
+        state.record(PathBuf::from("a"), ChangeKind::Added);
+        state.record(PathBuf::from("a"), ChangeKind::Modified);
+        state.record(PathBuf::from("b"), ChangeKind::Removed);
+        state.record(PathBuf::from("b"), ChangeKind::Added);
+        state.record(PathBuf::from("c"), ChangeKind::Added);
+        state.record(PathBuf::from("c"), ChangeKind::Removed);
+
+        let paths = &state.pending.unwrap().paths;
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[Path::new("a")], ChangeKind::Added);
+        assert_eq!(paths[Path::new("b")], ChangeKind::Modified);
+    }
+
+    #[tokio::test]
+    async fn test_sync_catches_up_with_missed_changes() {
+        let source = tempfile::tempdir().unwrap();
+        let context_dir = tempfile::tempdir().unwrap();
+        let file = source.path().join("notes.md");
+        fs::write(&file, "notes").unwrap();
+
+        let mut manifest = FileManifest::default();
+        manifest.record_file(&file, vec![0]).unwrap();
+        manifest.save(context_dir.path()).unwrap();
+
+        // Changed while nothing was watching
+        let more = source.path().join("more.md");
+        fs::write(&more, "more notes").unwrap();
+
+        let context = auto_update_context(source.path());
+        let contexts = vec![(context.clone(), context_dir.path().to_path_buf())];
+        let watcher = ContextWatcher::new();
+        watcher
+            .sync(&contexts, &FileProcessor::new(SemanticSearchConfig::default()))
+            .await;
+
+        assert_eq!(pending_paths(&watcher), vec![(more, ChangeKind::Added)]);
+        // Still pending until the files have been quiet for the debounce period
+        assert!(watcher.take_due().is_empty());
+        expire_debounce(&watcher);
+        assert_eq!(watcher.take_due(), vec!["ctx".to_string()]);
+        assert!(pending_paths(&watcher).is_empty());
+
+        watcher.mark_updated("ctx");
+        let status = watcher.status(&HashMap::from([("ctx".to_string(), context)]));
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].pending, None);
+        assert!(status[0].watching_since.is_some());
+        assert!(status[0].last_updated.is_some());
+
+        watcher
+            .sync(&[], &FileProcessor::new(SemanticSearchConfig::default()))
+            .await;
+        assert!(watcher.states.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_watch_collects_changed_paths() {
+        let source = tempfile::tempdir().unwrap();
+        let context_dir = tempfile::tempdir().unwrap();
+        FileManifest::default().save(context_dir.path()).unwrap();
+
+        let contexts = vec![(auto_update_context(source.path()), context_dir.path().to_path_buf())];
+        let watcher = ContextWatcher::new();
+        watcher
+            .sync(&contexts, &FileProcessor::new(SemanticSearchConfig::default()))
+            .await;
+        assert!(pending_paths(&watcher).is_empty());
+
+        let notes = source.path().join("notes.md");
+        fs::write(&notes, "notes").unwrap();
+        // Ignored by the hidden file and exclude pattern filters
+        fs::write(source.path().join(".hidden.md"), "hidden").unwrap();
+        fs::write(source.path().join("debug.log"), "log").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pending_paths(&watcher).is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        // Give the watcher time to deliver the events of the ignored files
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let paths = pending_paths(&watcher);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].0, notes);
+    }
+}
@@ -2,5 +2,8 @@
 pub mod background_worker;
 /// File processing utilities
 pub mod file_processor;
+/// Change detection for contexts with auto-update enabled
+pub mod file_watcher;
 
 pub use background_worker::BackgroundWorker;
+pub use file_watcher::ContextWatcher;
@@ -1,3 +1,7 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+};
 use std::fs::{
     self,
     File,
@@ -10,6 +14,7 @@ use std::path::PathBuf;
 
 use crate::error::Result;
 use crate::index::BM25Index;
+use crate::search_filter::FilterMatcher;
 use crate::types::BM25DataPoint;
 
 /// BM25 context for managing persistent BM25 search data
@@ -21,6 +26,9 @@ pub struct BM25Context {
     /// BM25 search index (rebuilt from data points)
     index: Option<BM25Index>,
 
+    /// Position of each data point in `data_points`, by data point ID
+    positions: HashMap<usize, usize>,
+
     /// Path to the data file
     data_path: PathBuf,
 
@@ -40,6 +48,7 @@ impl BM25Context {
         let mut context = Self {
             data_points: Vec::new(),
             index: None,
+            positions: HashMap::new(),
             data_path: data_path.clone(),
             avgdl,
         };
@@ -55,6 +64,7 @@ impl BM25Context {
         if !context.data_points.is_empty() {
             context.rebuild_index()?;
         }
+        context.rebuild_positions();
 
         Ok(context)
     }
@@ -80,21 +90,52 @@ impl BM25Context {
         Ok(())
     }
 
+    fn rebuild_positions(&mut self) {
+        self.positions = self
+            .data_points
+            .iter()
+            .enumerate()
+            .map(|(position, point)| (point.id, position))
+            .collect();
+    }
+
     /// Add data points to the context
     pub fn add_data_points(&mut self, data_points: Vec<BM25DataPoint>) -> Result<usize> {
         let count = data_points.len();
 
-        // Add to our data points
-        self.data_points.extend(data_points);
-
-        // Always rebuild index when we have data points
-        if !self.data_points.is_empty() {
-            self.rebuild_index()?;
+        // Add new documents to an existing index, otherwise build it from scratch
+        match &self.index {
+            Some(index) => {
+                for point in &data_points {
+                    index.add_document_with_id(point.content.clone(), point.id);
+                }
+                self.data_points.extend(data_points);
+            },
+            None => {
+                self.data_points.extend(data_points);
+                if !self.data_points.is_empty() {
+                    self.rebuild_index()?;
+                }
+            },
         }
+        self.rebuild_positions();
 
         Ok(count)
     }
 
+    /// Remove the data points with the given IDs, returning how many were removed
+    pub fn remove_data_points(&mut self, ids: &HashSet<usize>) -> usize {
+        let before = self.data_points.len();
+        if let Some(index) = &self.index {
+            for point in self.data_points.iter().filter(|point| ids.contains(&point.id)) {
+                index.remove_document(point.id);
+            }
+        }
+        self.data_points.retain(|point| !ids.contains(&point.id));
+        self.rebuild_positions();
+        before - self.data_points.len()
+    }
+
     /// Search the context
     pub fn search(&self, query: &str, limit: usize) -> Vec<(usize, f32)> {
         match &self.index {
@@ -107,6 +148,20 @@ impl BM25Context {
         }
     }
 
+    /// Search the context, ranking only the data points that match a filter
+    pub(crate) fn search_filtered(&self, query: &str, limit: usize, filter: &FilterMatcher<'_>) -> Vec<(usize, f32)> {
+        // BM25 scores each document on its own, so scoring everything and dropping the
+        // non-matching documents ranks the matching ones exactly as if they were searched alone
+        self.search(query, self.data_points.len())
+            .into_iter()
+            .filter(|(id, _)| {
+                self.get_data_point_by_id(*id)
+                    .is_some_and(|point| filter.matches(&point.payload))
+            })
+            .take(limit)
+            .collect()
+    }
+
     /// Get data points
     pub fn get_data_points(&self) -> &[BM25DataPoint] {
         &self.data_points
@@ -116,4 +171,11 @@ impl BM25Context {
     pub fn get_data_point(&self, index: usize) -> Option<&BM25DataPoint> {
         self.data_points.get(index)
     }
+
+    /// Get a specific data point by its ID, as returned by [`Self::search`]
+    pub fn get_data_point_by_id(&self, id: usize) -> Option<&BM25DataPoint> {
+        self.positions
+            .get(&id)
+            .and_then(|&position| self.data_points.get(position))
+    }
 }
@@ -0,0 +1,413 @@
+use std::fs::{
+    self,
+    File,
+};
+use std::io::{
+    Read,
+    Write,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+use zip::ZipArchive;
+use zip::write::SimpleFileOptions;
+
+use super::FileManifest;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::types::KnowledgeContext;
+
+/// Version of the bundle format written by [`write_bundle`]. Bundles with a newer version are
+/// rejected on import.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Archive entry describing the bundle
+const BUNDLE_MANIFEST_FILE: &str = "bundle.json";
+
+/// A file of the context directory stored in a bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// File name inside the context directory
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// SHA-256 of the content, hex encoded
+    pub sha256: String,
+}
+
+/// Describes what a bundle contains and how it was built
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Bundle format version
+    pub format_version: u32,
+    /// Version of the semantic search client that wrote the bundle
+    pub created_with: String,
+    /// When the bundle was written
+    pub created_at: DateTime<Utc>,
+    /// Metadata of the exported context
+    pub context: KnowledgeContext,
+    /// Name of the embedding model the context's vectors were computed with, `None` for BM25
+    pub embedding_model: Option<String>,
+    /// Files of the context directory, with their hashes
+    pub files: Vec<BundleFile>,
+}
+
+/// A bundle opened for import, whose manifest has been checked against this build
+#[derive(Debug)]
+pub struct ContextBundle {
+    path: PathBuf,
+    manifest: BundleManifest,
+}
+
+/// Write a persistent context and the files of its directory to a bundle at `dest`.
+///
+/// The bundle is written next to `dest` first and moved into place once complete, so a failed
+/// export never leaves a truncated bundle behind.
+pub fn write_bundle(context: &KnowledgeContext, context_dir: &Path, dest: &Path) -> Result<BundleManifest> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(context_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            entries.push(entry.path());
+        }
+    }
+    entries.sort();
+
+    let parent = dest
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut temp = tempfile::NamedTempFile::new_in(parent)?;
+    let mut zip = zip::ZipWriter::new(temp.as_file_mut());
+    let options = SimpleFileOptions::default();
+
+    let mut files = Vec::new();
+    for path in entries {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let content = fs::read(&path)?;
+        zip.start_file(name.as_str(), options).map_err(zip_error)?;
+        zip.write_all(&content)?;
+        files.push(BundleFile {
+            name,
+            size: content.len() as u64,
+            sha256: sha256_hex(&content),
+        });
+    }
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        created_with: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        context: context.clone(),
+        embedding_model: if context.embedding_type.is_bm25() {
+            None
+        } else {
+            context
+                .embedding_model
+                .clone()
+                .or_else(|| context.embedding_type.model_name())
+        },
+        files,
+    };
+    zip.start_file(BUNDLE_MANIFEST_FILE, options).map_err(zip_error)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    zip.finish().map_err(zip_error)?;
+
+    temp.persist(dest).map_err(|e| SemanticSearchError::IoError(e.error))?;
+    Ok(manifest)
+}
+
+impl ContextBundle {
+    /// Open a bundle and check that this build supports its format version
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut archive = open_archive(path)?;
+        let manifest_json = {
+            let mut entry = archive.by_name(BUNDLE_MANIFEST_FILE).map_err(|_missing| {
+                SemanticSearchError::InvalidArgument(format!("{} is not a knowledge bundle", path.display()))
+            })?;
+            let mut json = String::new();
+            entry.read_to_string(&mut json)?;
+            json
+        };
+
+        // Check the version before parsing the rest, which may have changed shape
+        let raw: serde_json::Value = serde_json::from_str(&manifest_json)?;
+        let version = raw.get("format_version").and_then(|v| v.as_u64()).unwrap_or(0);
+        if version == 0 || version > u64::from(BUNDLE_FORMAT_VERSION) {
+            return Err(SemanticSearchError::InvalidArgument(format!(
+                "Unsupported bundle format version {} (supported up to {})",
+                version, BUNDLE_FORMAT_VERSION
+            )));
+        }
+
+        let manifest: BundleManifest = serde_json::from_value(raw).map_err(|e| {
+            SemanticSearchError::InvalidArgument(format!(
+                "Bundle can't be used on this platform or is malformed: {}",
+                e
+            ))
+        })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            manifest,
+        })
+    }
+
+    /// The bundle's manifest
+    pub fn manifest(&self) -> &BundleManifest {
+        &self.manifest
+    }
+
+    /// Extract the context files into `context_dir`, verifying the size and hash of each one.
+    ///
+    /// The directory is removed again if any file fails verification.
+    pub fn extract(&self, context_dir: &Path) -> Result<()> {
+        fs::create_dir_all(context_dir)?;
+        let result = self.extract_files(context_dir);
+        if result.is_err() {
+            let _ = fs::remove_dir_all(context_dir);
+        }
+        result
+    }
+
+    fn extract_files(&self, context_dir: &Path) -> Result<()> {
+        let mut archive = open_archive(&self.path)?;
+        for file in &self.manifest.files {
+            // Only plain file names are written, anything else could escape the context directory
+            if Path::new(&file.name).file_name().and_then(|name| name.to_str()) != Some(file.name.as_str()) {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "Bundle contains an invalid file name: {}",
+                    file.name
+                )));
+            }
+
+            // Read at most one byte more than declared, so that an entry that decompresses to more
+            // than its declared size fails the check below without being read into memory
+            let mut content = Vec::new();
+            archive
+                .by_name(&file.name)
+                .map_err(|_missing| SemanticSearchError::InvalidArgument(format!("Bundle is missing {}", file.name)))?
+                .take(file.size.saturating_add(1))
+                .read_to_end(&mut content)?;
+
+            if content.len() as u64 != file.size || sha256_hex(&content) != file.sha256 {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "Integrity check failed for {}: the bundle is corrupted",
+                    file.name
+                )));
+            }
+            File::create(context_dir.join(&file.name))?.write_all(&content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrite the indexed file paths of an extracted context from the source path it was indexed at
+/// to `to`, so that citations and incremental updates refer to the local copy of the files.
+pub fn relocate_context(context_dir: &Path, from: &Path, to: &Path) -> Result<()> {
+    for entry in fs::read_dir(context_dir)? {
+        let path = entry?.path();
+        let is_manifest = path
+            .file_name()
+            .is_some_and(|name| name == super::file_manifest::FILE_MANIFEST_FILE);
+        if is_manifest || path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+
+        let mut data: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        relocate_payload_paths(&mut data, from, to);
+        fs::write(&path, serde_json::to_string(&data)?)?;
+    }
+
+    if let Some(mut manifest) = FileManifest::load(context_dir)? {
+        manifest.files = manifest
+            .files
+            .into_iter()
+            .map(|(path, record)| (relocate_path(&path, from, to).unwrap_or(path), record))
+            .collect();
+        manifest.save(context_dir)?;
+    }
+    Ok(())
+}
+
+/// Rewrite the `path` of every data point payload in `value`
+fn relocate_payload_paths(value: &mut serde_json::Value, from: &Path, to: &Path) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                relocate_payload_paths(item, from, to);
+            }
+        },
+        serde_json::Value::Object(map) => {
+            if let Some(serde_json::Value::Object(payload)) = map.get_mut("payload") {
+                if let Some(serde_json::Value::String(path)) = payload.get_mut("path") {
+                    if let Some(relocated) = relocate_path(path, from, to) {
+                        *path = relocated;
+                    }
+                }
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "payload" {
+                    relocate_payload_paths(child, from, to);
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+/// `path` moved from under `from` to under `to`, or `None` if it isn't under `from`
+fn relocate_path(path: &str, from: &Path, to: &Path) -> Option<String> {
+    Path::new(path)
+        .strip_prefix(from)
+        .ok()
+        .map(|rest| to.join(rest).to_string_lossy().to_string())
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<File>> {
+    ZipArchive::new(File::open(path)?).map_err(|e| {
+        SemanticSearchError::InvalidArgument(format!("{} is not a knowledge bundle: {}", path.display(), e))
+    })
+}
+
+fn zip_error(error: zip::result::ZipError) -> SemanticSearchError {
+    SemanticSearchError::OperationFailed(format!("Failed to write bundle: {}", error))
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::EmbeddingType;
+
+    fn test_context(source_path: &Path) -> KnowledgeContext {
+        KnowledgeContext::new(
+            "ctx-1".to_string(),
+            "docs",
+            "Knowledge context for docs",
+            true,
+            Some(source_path.to_string_lossy().to_string()),
+            (vec![], vec![]),
+            1,
+            EmbeddingType::Fast,
+        )
+    }
+
+    #[test]
+    fn test_bundle_roundtrip_and_relocation() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("ci/docs");
+        let context_dir = dir.path().join("kb/ctx-1");
+        fs::create_dir_all(&context_dir).unwrap();
+
+        let indexed = source.join("guide.md").to_string_lossy().to_string();
+        let points = serde_json::json!([
+            { "id": 0, "content": "hello", "payload": { "path": indexed, "text": "hello" } }
+        ]);
+        fs::write(context_dir.join("data.bm25.json"), points.to_string()).unwrap();
+        let mut manifest = FileManifest::default();
+        manifest
+            .files
+            .insert(indexed.clone(), super::super::file_manifest::FileRecord {
+                hash: "abc".to_string(),
+                modified: 0,
+                size: 5,
+                chunk_ids: vec![0],
+            });
+        manifest.save(&context_dir).unwrap();
+
+        let bundle_path = dir.path().join("docs.qkb");
+        let written = write_bundle(&test_context(&source), &context_dir, &bundle_path).unwrap();
+        assert_eq!(written.format_version, BUNDLE_FORMAT_VERSION);
+        assert_eq!(written.embedding_model, None);
+        assert_eq!(written.files.len(), 2);
+
+        let bundle = ContextBundle::open(&bundle_path).unwrap();
+        assert_eq!(bundle.manifest().context.name, "docs");
+
+        let imported_dir = dir.path().join("local/ctx-2");
+        bundle.extract(&imported_dir).unwrap();
+        let local = dir.path().join("checkout/docs");
+        relocate_context(&imported_dir, &source, &local).unwrap();
+
+        let expected = local.join("guide.md").to_string_lossy().to_string();
+        let data: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(imported_dir.join("data.bm25.json")).unwrap()).unwrap();
+        assert_eq!(data[0]["payload"]["path"], expected.as_str());
+        let manifest = FileManifest::load(&imported_dir).unwrap().unwrap();
+        assert!(manifest.files.contains_key(&expected));
+    }
+
+    #[test]
+    fn test_corrupted_bundle_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let context_dir = dir.path().join("ctx-1");
+        fs::create_dir_all(&context_dir).unwrap();
+        fs::write(context_dir.join("data.bm25.json"), "[]").unwrap();
+
+        let bundle_path = dir.path().join("docs.qkb");
+        let mut manifest = write_bundle(&test_context(dir.path()), &context_dir, &bundle_path).unwrap();
+
+        // Rewrite the bundle with a manifest whose hash doesn't match the content
+        manifest.files[0].sha256 = sha256_hex(b"something else");
+        let mut zip = zip::ZipWriter::new(File::create(&bundle_path).unwrap());
+        zip.start_file("data.bm25.json", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"[]").unwrap();
+        zip.start_file(BUNDLE_MANIFEST_FILE, SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(serde_json::to_string(&manifest).unwrap().as_bytes())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let bundle = ContextBundle::open(&bundle_path).unwrap();
+        let imported_dir = dir.path().join("imported");
+        let error = bundle.extract(&imported_dir).unwrap_err();
+        assert!(error.to_string().contains("Integrity check failed"));
+        assert!(!imported_dir.exists());
+
+        // Entries larger than their declared size are rejected too
+        manifest.files[0].sha256 = sha256_hex(b"[]");
+        let mut zip = zip::ZipWriter::new(File::create(&bundle_path).unwrap());
+        zip.start_file("data.bm25.json", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"[]   ").unwrap();
+        zip.start_file(BUNDLE_MANIFEST_FILE, SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(serde_json::to_string(&manifest).unwrap().as_bytes())
+            .unwrap();
+        zip.finish().unwrap();
+        let error = ContextBundle::open(&bundle_path)
+            .unwrap()
+            .extract(&imported_dir)
+            .unwrap_err();
+        assert!(error.to_string().contains("Integrity check failed"));
+
+        // Bundles from a newer format version are refused outright
+        manifest.format_version = BUNDLE_FORMAT_VERSION + 1;
+        let mut zip = zip::ZipWriter::new(File::create(&bundle_path).unwrap());
+        zip.start_file(BUNDLE_MANIFEST_FILE, SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(serde_json::to_string(&manifest).unwrap().as_bytes())
+            .unwrap();
+        zip.finish().unwrap();
+        assert!(ContextBundle::open(&bundle_path).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{
+    HashMap,
+    HashSet,
+};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -50,7 +53,29 @@ impl ContextCreator {
         embedder: &dyn TextEmbedderTrait,
         context_manager: &ContextManager,
     ) -> std::result::Result<(), String> {
-        if embedding_type.is_bm25() {
+        if embedding_type.is_hybrid() {
+            // Hybrid contexts keep both indexes over the same data points, so that search results
+            // from each can be matched up by id and fused
+            self.create_bm25_context(
+                context_dir,
+                items,
+                operation_id,
+                cancel_token,
+                operation_manager,
+                context_manager,
+            )
+            .await?;
+            self.create_semantic_context(
+                context_dir,
+                items,
+                operation_id,
+                cancel_token,
+                operation_manager,
+                embedder,
+                context_manager,
+            )
+            .await
+        } else if embedding_type.is_bm25() {
             self.create_bm25_context(
                 context_dir,
                 items,
@@ -170,29 +195,10 @@ impl ContextCreator {
         let mut semantic_context = SemanticContext::new(context_dir.join("data.json"))
             .map_err(|e| format!("Failed to create semantic context: {}", e))?;
 
-        let mut data_points = Vec::new();
-        let total_items = items.len();
-
-        for (i, item) in items.iter().enumerate() {
-            if cancel_token.is_cancelled() {
-                return Err("Operation was cancelled during embedding generation".to_string());
-            }
-
-            if i % 10 == 0 {
-                self.update_operation_progress(
-                    operation_manager,
-                    operation_id,
-                    i as u64,
-                    total_items as u64,
-                    format!("Generating embeddings ({}/{})", i, total_items),
-                )
-                .await;
-            }
-
-            let data_point = Self::create_data_point_from_item(item, i, embedder)
-                .map_err(|e| format!("Failed to create data point: {}", e))?;
-            data_points.push(data_point);
-        }
+        let items = items.iter().enumerate().collect::<Vec<_>>();
+        let data_points = self
+            .create_data_points(&items, operation_id, cancel_token, operation_manager, embedder)
+            .await?;
 
         if cancel_token.is_cancelled() {
             return Err("Operation was cancelled before building index".to_string());
@@ -223,6 +229,84 @@ impl ContextCreator {
         Ok(())
     }
 
+    /// Apply an incremental update to an existing context: remove the data points in `stale_ids`
+    /// and add `items`, each paired with the ID of the data point to create for it. Only the new
+    /// items are embedded. If the update fails or is cancelled, neither index is changed.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_context(
+        &self,
+        context_id: &str,
+        context_dir: &Path,
+        items: &[(usize, serde_json::Value)],
+        stale_ids: &HashSet<usize>,
+        embedding_type: EmbeddingType,
+        operation_id: Uuid,
+        cancel_token: &CancellationToken,
+        operation_manager: &OperationManager,
+        embedder: &dyn TextEmbedderTrait,
+        context_manager: &ContextManager,
+    ) -> std::result::Result<(), String> {
+        let update_bm25 = embedding_type.is_bm25() || embedding_type.is_hybrid();
+        let update_semantic = !embedding_type.is_bm25();
+
+        // Everything that can be cancelled or fail is done before either index is changed, so that
+        // a hybrid context's indexes are updated together or not at all
+        let bm25 = if update_bm25 {
+            let data_points = items
+                .iter()
+                .map(|(id, item)| Self::create_bm25_data_point_from_item(item, *id))
+                .collect::<Result<Vec<_>>>()
+                .map_err(|e| format!("Failed to create BM25 data point: {}", e))?;
+            let context = context_manager
+                .get_or_load_bm25_context(context_id, context_dir)
+                .await
+                .map_err(|e| format!("Failed to load BM25 context: {}", e))?;
+            Some((context, data_points))
+        } else {
+            None
+        };
+        let semantic = if update_semantic {
+            let context = context_manager
+                .get_or_load_semantic_context(context_id, context_dir)
+                .await
+                .map_err(|e| format!("Failed to load semantic context: {}", e))?;
+            let items = items.iter().map(|(id, item)| (*id, item)).collect::<Vec<_>>();
+            let data_points = self
+                .create_data_points(&items, operation_id, cancel_token, operation_manager, embedder)
+                .await?;
+            Some((context, data_points))
+        } else {
+            None
+        };
+
+        if cancel_token.is_cancelled() {
+            return Err("Operation was cancelled before updating the index".to_string());
+        }
+        self.update_operation_status(operation_manager, operation_id, "Updating index...".to_string())
+            .await;
+
+        if let Some((context, data_points)) = bm25 {
+            let mut context = context.lock().await;
+            context.remove_data_points(stale_ids);
+            context
+                .add_data_points(data_points)
+                .map_err(|e| format!("Failed to add BM25 data points: {}", e))?;
+            let _ = context.save();
+        }
+        if let Some((context, data_points)) = semantic {
+            let mut context = context.lock().await;
+            context
+                .remove_data_points(stale_ids)
+                .map_err(|e| format!("Failed to remove data points: {}", e))?;
+            context
+                .add_data_points(data_points)
+                .map_err(|e| format!("Failed to add data points: {}", e))?;
+            let _ = context.save();
+        }
+
+        Ok(())
+    }
+
     fn create_bm25_data_point_from_item(item: &serde_json::Value, id: usize) -> Result<BM25DataPoint> {
         let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
 
@@ -241,14 +325,60 @@ impl ContextCreator {
         })
     }
 
-    fn create_data_point_from_item(
-        item: &serde_json::Value,
-        id: usize,
+    /// Embed the text of each item, a batch at a time, into a data point with the ID it is paired
+    /// with
+    async fn create_data_points(
+        &self,
+        items: &[(usize, &serde_json::Value)],
+        operation_id: Uuid,
+        cancel_token: &CancellationToken,
+        operation_manager: &OperationManager,
         embedder: &dyn TextEmbedderTrait,
-    ) -> Result<DataPoint> {
-        let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
-        let vector = embedder.embed(text)?;
+    ) -> std::result::Result<Vec<DataPoint>, String> {
+        let mut data_points = Vec::with_capacity(items.len());
+        for batch in items.chunks(embedder.batch_size().max(1)) {
+            if cancel_token.is_cancelled() {
+                return Err("Operation was cancelled during embedding generation".to_string());
+            }
 
+            self.update_operation_progress(
+                operation_manager,
+                operation_id,
+                data_points.len() as u64,
+                items.len() as u64,
+                format!("Generating embeddings ({}/{})", data_points.len(), items.len()),
+            )
+            .await;
+
+            let texts = batch
+                .iter()
+                .map(|(_, item)| Self::item_text(item).to_string())
+                .collect::<Vec<_>>();
+            let vectors = embedder
+                .embed_batch(&texts)
+                .map_err(|e| format!("Failed to create data points: {}", e))?;
+            if vectors.len() != batch.len() {
+                return Err(format!(
+                    "Failed to create data points: got {} embeddings for {} texts",
+                    vectors.len(),
+                    batch.len()
+                ));
+            }
+            data_points.extend(
+                batch
+                    .iter()
+                    .zip(vectors)
+                    .map(|((id, item), vector)| Self::create_data_point_from_item(item, *id, vector)),
+            );
+        }
+        Ok(data_points)
+    }
+
+    fn item_text(item: &serde_json::Value) -> &str {
+        item.get("text").and_then(|v| v.as_str()).unwrap_or("")
+    }
+
+    fn create_data_point_from_item(item: &serde_json::Value, id: usize, vector: Vec<f32>) -> DataPoint {
         let payload: HashMap<String, serde_json::Value> = if let serde_json::Value::Object(map) = item {
             map.clone().into_iter().collect()
         } else {
@@ -257,7 +387,7 @@ impl ContextCreator {
             map
         };
 
-        Ok(DataPoint { id, payload, vector })
+        DataPoint { id, payload, vector }
     }
 
     async fn update_operation_status(&self, operation_manager: &OperationManager, operation_id: Uuid, message: String) {
@@ -287,3 +417,73 @@ impl ContextCreator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use super::*;
+    use crate::embedding::MockTextEmbedder;
+
+    /// Records the number of texts of each call to `embed_batch`
+    struct BatchRecorder {
+        embedder: MockTextEmbedder,
+        batches: StdMutex<Vec<usize>>,
+    }
+
+    impl TextEmbedderTrait for BatchRecorder {
+        fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            panic!("texts are embedded in batches");
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.batches.lock().unwrap().push(texts.len());
+            self.embedder.embed_batch(texts)
+        }
+
+        fn model_name(&self) -> Option<String> {
+            None
+        }
+
+        fn dimension(&self) -> Option<usize> {
+            Some(self.embedder.dimension())
+        }
+
+        fn batch_size(&self) -> usize {
+            2
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_data_points_in_batches() {
+        let embedder = BatchRecorder {
+            embedder: MockTextEmbedder::new(4),
+            batches: StdMutex::new(Vec::new()),
+        };
+        let items = (0..5)
+            .map(|i| serde_json::json!({ "text": format!("chunk {}", i) }))
+            .collect::<Vec<_>>();
+        let items = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i + 10, item))
+            .collect::<Vec<_>>();
+
+        let data_points = ContextCreator::new()
+            .create_data_points(
+                &items,
+                Uuid::new_v4(),
+                &CancellationToken::new(),
+                &OperationManager::new(),
+                &embedder,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(*embedder.batches.lock().unwrap(), vec![2, 2, 1]);
+        assert_eq!(data_points.iter().map(|point| point.id).collect::<Vec<_>>(), vec![
+            10, 11, 12, 13, 14
+        ]);
+        assert_eq!(data_points[3].payload["text"], "chunk 3");
+    }
+}
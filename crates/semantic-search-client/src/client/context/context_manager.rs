@@ -25,6 +25,14 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::index::{
+    DEFAULT_RRF_K,
+    reciprocal_rank_fusion,
+};
+use crate::search_filter::{
+    FilterMatcher,
+    SearchFilter,
+};
 use crate::types::*;
 
 type VolatileContexts = Arc<RwLock<HashMap<ContextId, Arc<Mutex<SemanticContext>>>>>;
@@ -33,6 +41,9 @@ type BM25Contexts = Arc<RwLock<HashMap<ContextId, Arc<Mutex<BM25Context>>>>>;
 const SEMANTIC_DATA_FILE: &str = "data.json";
 const BM25_DATA_FILE: &str = "data.bm25.json";
 const DEFAULT_BM25_SCORE: f64 = 100.0;
+/// How many candidates hybrid search takes from each index per requested result, so that
+/// documents ranked moderately well by both indexes can still make it into the fused results
+const HYBRID_CANDIDATE_MULTIPLIER: usize = 3;
 
 #[derive(Clone)]
 /// Context manager for handling contexts
@@ -41,11 +52,17 @@ pub struct ContextManager {
     volatile_contexts: VolatileContexts,
     bm25_contexts: BM25Contexts,
     base_dir: PathBuf,
+    /// Model of the embedder that queries and updates the semantic indexes
+    embedder_model: Option<String>,
+    /// Dimension of the embedder's vectors, if known up front
+    embedder_dim: Option<usize>,
+    /// Why persistent contexts that failed to load couldn't be loaded
+    load_errors: Arc<RwLock<HashMap<ContextId, String>>>,
 }
 
 impl ContextManager {
-    /// Create new context manager
-    pub async fn new(base_dir: &Path) -> Result<Self> {
+    /// Create new context manager for contexts searched with `embedder`
+    pub async fn new(base_dir: &Path, embedder: &dyn TextEmbedderTrait) -> Result<Self> {
         let contexts_file = base_dir.join("contexts.json");
         let persistent_contexts: HashMap<ContextId, KnowledgeContext> = utils::load_json_from_file(&contexts_file)?;
 
@@ -54,6 +71,9 @@ impl ContextManager {
             volatile_contexts: Arc::new(RwLock::new(HashMap::new())),
             bm25_contexts: Arc::new(RwLock::new(HashMap::new())),
             base_dir: base_dir.to_path_buf(),
+            embedder_model: embedder.model_name(),
+            embedder_dim: embedder.dimension(),
+            load_errors: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
@@ -72,23 +92,40 @@ impl ContextManager {
         }
     }
 
-    /// Search all contexts
+    /// Search all contexts, considering only the chunks that match `filter`
     pub async fn search_all(
         &self,
         query_text: &str,
         effective_limit: usize,
         embedder: &dyn TextEmbedderTrait,
+        filter: &SearchFilter,
     ) -> Result<Vec<(ContextId, SearchResults)>> {
+        let filter = filter.matcher()?;
+        let filter = filter.as_ref();
         let mut all_results = Vec::new();
         let contexts_metadata = self.contexts.read().await;
 
         for (context_id, context_meta) in contexts_metadata.iter() {
-            if context_meta.embedding_type.is_bm25() {
-                if let Some(results) = self.search_bm25_context(context_id, query_text, effective_limit).await {
+            if filter.is_some_and(|filter| !filter.matches_context(context_id, &context_meta.name)) {
+                continue;
+            }
+
+            if context_meta.embedding_type.is_hybrid() {
+                if let Some(results) = self
+                    .search_hybrid_context(context_id, query_text, effective_limit, embedder, filter)
+                    .await?
+                {
+                    all_results.push((context_id.clone(), results));
+                }
+            } else if context_meta.embedding_type.is_bm25() {
+                if let Some(results) = self
+                    .search_bm25_context(context_id, query_text, effective_limit, filter)
+                    .await
+                {
                     all_results.push((context_id.clone(), results));
                 }
             } else if let Some(results) = self
-                .search_semantic_context(context_id, query_text, effective_limit, embedder)
+                .search_semantic_context(context_id, query_text, effective_limit, embedder, filter)
                 .await?
             {
                 all_results.push((context_id.clone(), results));
@@ -107,18 +144,27 @@ impl ContextManager {
         Ok(all_results)
     }
 
-    async fn search_bm25_context(&self, context_id: &str, query_text: &str, limit: usize) -> Option<SearchResults> {
+    async fn search_bm25_context(
+        &self,
+        context_id: &str,
+        query_text: &str,
+        limit: usize,
+        filter: Option<&FilterMatcher<'_>>,
+    ) -> Option<SearchResults> {
         let bm25_contexts = tokio::time::timeout(Duration::from_millis(100), self.bm25_contexts.read())
             .await
             .ok()?;
         let context_arc = bm25_contexts.get(context_id)?;
         let context = context_arc.try_lock().ok()?;
 
-        let search_results = context.search(query_text, limit);
+        let search_results = match filter {
+            Some(filter) => context.search_filtered(query_text, limit, filter),
+            None => context.search(query_text, limit),
+        };
         let results: Vec<SearchResult> = search_results
             .into_iter()
             .filter_map(|(id, score)| {
-                context.get_data_points().get(id).map(|data_point| {
+                context.get_data_point_by_id(id).map(|data_point| {
                     let vector = vec![0.0; 384];
                     let point = DataPoint {
                         id: data_point.id,
@@ -139,6 +185,7 @@ impl ContextManager {
         query_text: &str,
         limit: usize,
         embedder: &dyn TextEmbedderTrait,
+        filter: Option<&FilterMatcher<'_>>,
     ) -> Result<Option<SearchResults>> {
         let query_vector = embedder.embed(query_text)?;
         let volatile_contexts = tokio::time::timeout(Duration::from_millis(100), self.volatile_contexts.read())
@@ -147,7 +194,11 @@ impl ContextManager {
 
         if let Some(context_arc) = volatile_contexts.get(context_id) {
             if let Ok(context_guard) = context_arc.try_lock() {
-                match context_guard.search(&query_vector, limit) {
+                let results = match filter {
+                    Some(filter) => context_guard.search_filtered(&query_vector, limit, filter),
+                    None => context_guard.search(&query_vector, limit),
+                };
+                match results {
                     Ok(results) => Ok(if results.is_empty() { None } else { Some(results) }),
                     Err(e) => {
                         warn!("Failed to search context {}: {}", context_id, e);
@@ -162,6 +213,50 @@ impl ContextManager {
         }
     }
 
+    /// Searches both indexes of a hybrid context and merges them with reciprocal rank fusion. The
+    /// fused score is normalized to `[0, 1]` and reported as a distance (`1 - score`) so that it
+    /// sorts alongside the results of other contexts.
+    async fn search_hybrid_context(
+        &self,
+        context_id: &str,
+        query_text: &str,
+        limit: usize,
+        embedder: &dyn TextEmbedderTrait,
+        filter: Option<&FilterMatcher<'_>>,
+    ) -> Result<Option<SearchResults>> {
+        let candidates = limit.saturating_mul(HYBRID_CANDIDATE_MULTIPLIER);
+        let lexical = self
+            .search_bm25_context(context_id, query_text, candidates, filter)
+            .await
+            .unwrap_or_default();
+        let semantic = self
+            .search_semantic_context(context_id, query_text, candidates, embedder, filter)
+            .await?
+            .unwrap_or_default();
+
+        let rankings = [
+            lexical.iter().map(|result| result.point.id).collect::<Vec<_>>(),
+            semantic.iter().map(|result| result.point.id).collect::<Vec<_>>(),
+        ];
+        let max_score = rankings.len() as f32 / (DEFAULT_RRF_K + 1.0);
+
+        // Prefer the semantic data point since it carries the real embedding
+        let mut points: HashMap<usize, DataPoint> = lexical.into_iter().map(|r| (r.point.id, r.point)).collect();
+        points.extend(semantic.into_iter().map(|r| (r.point.id, r.point)));
+
+        let results: SearchResults = reciprocal_rank_fusion(&rankings, DEFAULT_RRF_K)
+            .into_iter()
+            .take(limit)
+            .filter_map(|(id, score)| {
+                points
+                    .remove(&id)
+                    .map(|point| SearchResult::new(point, 1.0 - score / max_score))
+            })
+            .collect();
+
+        Ok(if results.is_empty() { None } else { Some(results) })
+    }
+
     /// Check if path exists or is being indexed
     pub async fn check_path_exists(
         &self,
@@ -223,13 +318,20 @@ impl ContextManager {
         for id in context_ids {
             if let Err(e) = self.load_persistent_context(&id).await {
                 tracing::error!("Failed to load persistent context {}: {}", id, e);
+                self.load_errors.write().await.insert(id, e.to_string());
             }
         }
 
         Ok(())
     }
 
-    async fn load_persistent_context(&self, context_id: &str) -> Result<()> {
+    /// Persistent contexts that couldn't be loaded and so aren't searched, with the reason
+    pub async fn get_load_errors(&self) -> HashMap<ContextId, String> {
+        self.load_errors.read().await.clone()
+    }
+
+    /// Load the indexes of a persistent context from its directory
+    pub async fn load_persistent_context(&self, context_id: &str) -> Result<()> {
         let embedding_type = self.get_context_embedding_type(context_id).await;
         let Some(embedding_type) = embedding_type else {
             return Ok(());
@@ -240,13 +342,46 @@ impl ContextManager {
             return Ok(());
         }
 
-        if embedding_type.is_bm25() {
+        if embedding_type.is_hybrid() {
+            self.load_bm25_context(context_id, &context_dir).await?;
+            self.load_semantic_context(context_id, &context_dir).await
+        } else if embedding_type.is_bm25() {
             self.load_bm25_context(context_id, &context_dir).await
         } else {
             self.load_semantic_context(context_id, &context_dir).await
         }
     }
 
+    /// Get a BM25 context, loading it from its directory if it isn't loaded yet
+    pub async fn get_or_load_bm25_context(
+        &self,
+        context_id: &str,
+        context_dir: &Path,
+    ) -> Result<Arc<Mutex<BM25Context>>> {
+        self.load_bm25_context(context_id, context_dir).await?;
+        self.bm25_contexts
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))
+    }
+
+    /// Get a semantic context, loading it from its directory if it isn't loaded yet
+    pub async fn get_or_load_semantic_context(
+        &self,
+        context_id: &str,
+        context_dir: &Path,
+    ) -> Result<Arc<Mutex<SemanticContext>>> {
+        self.load_semantic_context(context_id, context_dir).await?;
+        self.volatile_contexts
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))
+    }
+
     async fn get_context_embedding_type(&self, context_id: &str) -> Option<EmbeddingType> {
         let contexts = self.contexts.read().await;
         contexts.get(context_id).map(|ctx| ctx.embedding_type)
@@ -278,14 +413,65 @@ impl ContextManager {
             }
         }
 
+        let embedding_dim = self.check_embedder_compatible(context_id).await?;
         let data_file = context_dir.join(SEMANTIC_DATA_FILE);
         let semantic_context = SemanticContext::new(data_file)?;
+        if let Some(dim) = embedding_dim {
+            if let Some(point) = semantic_context
+                .get_data_points()
+                .iter()
+                .find(|p| p.vector.len() != dim)
+            {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "Context {} mixes embedding dimensions: data point {} has {} dimensions, expected {}",
+                    context_id,
+                    point.id,
+                    point.vector.len(),
+                    dim
+                )));
+            }
+        }
 
         let mut volatile_contexts = self.volatile_contexts.write().await;
         volatile_contexts.insert(context_id.to_string(), Arc::new(Mutex::new(semantic_context)));
         Ok(())
     }
 
+    /// Check that a context's vectors were computed with the same model, and have the same number
+    /// of dimensions, as the embedder that would search and extend them. Returns the context's
+    /// recorded dimension.
+    async fn check_embedder_compatible(&self, context_id: &str) -> Result<Option<usize>> {
+        let contexts = self.contexts.read().await;
+        let Some(context) = contexts.get(context_id) else {
+            return Ok(None);
+        };
+
+        if let Some(model) = &context.embedding_model {
+            if self.embedder_model.as_ref() != Some(model) {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "'{}' was indexed with embedding model '{}' but the configured embedding backend uses {}. \
+                     Switch back to it or re-index the context",
+                    context.name,
+                    model,
+                    self.embedder_model
+                        .as_ref()
+                        .map_or_else(|| "no embedding model".to_string(), |current| format!("'{}'", current))
+                )));
+            }
+        }
+
+        if let (Some(indexed), Some(current)) = (context.embedding_dim, self.embedder_dim) {
+            if indexed != current {
+                return Err(SemanticSearchError::InvalidArgument(format!(
+                    "'{}' has {}-dimensional vectors but the configured embedding model produces {} dimensions",
+                    context.name, indexed, current
+                )));
+            }
+        }
+
+        Ok(context.embedding_dim)
+    }
+
     /// Clear all contexts immediately
     pub async fn clear_all_immediate(&self, base_dir: &Path) -> Result<usize> {
         let context_count = {
@@ -297,6 +483,7 @@ impl ContextManager {
             let mut contexts = self.contexts.write().await;
             contexts.clear();
         }
+        self.load_errors.write().await.clear();
 
         {
             let mut volatile_contexts = self.volatile_contexts.write().await;
@@ -317,6 +504,7 @@ impl ContextManager {
             let mut contexts = self.contexts.write().await;
             contexts.remove(context_id);
         }
+        self.load_errors.write().await.remove(context_id);
 
         {
             let mut volatile_contexts = self.volatile_contexts.write().await;
@@ -408,3 +596,158 @@ impl ContextManager {
         &self.bm25_contexts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::embedding::MockTextEmbedder;
+
+    fn context(id: &str, model: Option<&str>, dim: Option<usize>) -> KnowledgeContext {
+        typed_context(id, EmbeddingType::Mock, model, dim)
+    }
+
+    fn typed_context(
+        id: &str,
+        embedding_type: EmbeddingType,
+        model: Option<&str>,
+        dim: Option<usize>,
+    ) -> KnowledgeContext {
+        let mut context =
+            KnowledgeContext::new(id.to_string(), id, "", true, None, (vec![], vec![]), 0, embedding_type);
+        context.embedding_model = model.map(str::to_string);
+        context.embedding_dim = dim;
+        context
+    }
+
+    #[tokio::test]
+    async fn test_rejects_contexts_from_other_embedders() {
+        let temp_dir = TempDir::new().unwrap();
+        let contexts: HashMap<_, _> = [
+            context("legacy", None, None),
+            context("same", None, Some(3)),
+            context("other-model", Some("all-MiniLM-L6-v2"), Some(3)),
+            context("other-dim", None, Some(384)),
+        ]
+        .into_iter()
+        .map(|context| {
+            std::fs::create_dir_all(temp_dir.path().join(&context.id)).unwrap();
+            (context.id.clone(), context)
+        })
+        .collect();
+        utils::save_json_to_file(&temp_dir.path().join("contexts.json"), &contexts).unwrap();
+
+        let manager = ContextManager::new(temp_dir.path(), &MockTextEmbedder::new(3))
+            .await
+            .unwrap();
+        manager.load_persistent_contexts().await.unwrap();
+
+        let errors = manager.get_load_errors().await;
+        let mut rejected: Vec<_> = errors.keys().map(String::as_str).collect();
+        rejected.sort_unstable();
+        assert_eq!(rejected, vec!["other-dim", "other-model"]);
+        assert!(errors["other-model"].contains("all-MiniLM-L6-v2"));
+        assert!(errors["other-dim"].contains("384-dimensional"));
+
+        let loaded = manager.get_volatile_contexts_ref().read().await;
+        assert!(loaded.contains_key("legacy") && loaded.contains_key("same"));
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_before_ranking() {
+        let temp_dir = TempDir::new().unwrap();
+        let embedder = MockTextEmbedder::new(8);
+        let contexts: HashMap<_, _> = [
+            typed_context("lexical", EmbeddingType::Fast, None, None),
+            typed_context("semantic", EmbeddingType::Mock, None, Some(8)),
+        ]
+        .into_iter()
+        .map(|context| (context.id.clone(), context))
+        .collect();
+        utils::save_json_to_file(&temp_dir.path().join("contexts.json"), &contexts).unwrap();
+        let manager = ContextManager::new(temp_dir.path(), &embedder).await.unwrap();
+
+        // Many chunks outside of src rank above the only one inside it
+        let mut files = vec![("/repo/src/lib.rs", "Code", "search search")];
+        files.extend(std::iter::repeat_n(
+            ("/repo/docs/guide.md", "Markdown", "search search search"),
+            20,
+        ));
+        let payload = |path: &str, file_type: &str, text: &str| {
+            HashMap::from([
+                ("path".to_string(), serde_json::json!(path)),
+                ("file_type".to_string(), serde_json::json!(file_type)),
+                ("text".to_string(), serde_json::json!(text)),
+            ])
+        };
+
+        let mut lexical = BM25Context::new(temp_dir.path().join("lexical").join(BM25_DATA_FILE), 5.0).unwrap();
+        lexical
+            .add_data_points(
+                files
+                    .iter()
+                    .enumerate()
+                    .map(|(id, (path, file_type, text))| BM25DataPoint {
+                        id,
+                        payload: payload(path, file_type, text),
+                        content: (*text).to_string(),
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        let mut semantic = SemanticContext::new(temp_dir.path().join("semantic").join(SEMANTIC_DATA_FILE)).unwrap();
+        semantic
+            .add_data_points(
+                files
+                    .iter()
+                    .enumerate()
+                    .map(|(id, (path, file_type, text))| DataPoint {
+                        id,
+                        payload: payload(path, file_type, text),
+                        vector: embedder.embed(text).unwrap(),
+                    })
+                    .collect(),
+            )
+            .unwrap();
+        manager
+            .get_bm25_contexts_ref()
+            .write()
+            .await
+            .insert("lexical".to_string(), Arc::new(Mutex::new(lexical)));
+        manager
+            .get_volatile_contexts_ref()
+            .write()
+            .await
+            .insert("semantic".to_string(), Arc::new(Mutex::new(semantic)));
+
+        let search = |expression: &str| {
+            let filter: SearchFilter = expression.parse().unwrap();
+            let manager = manager.clone();
+            let embedder = MockTextEmbedder::new(8);
+            async move {
+                let mut results = manager.search_all("search", 3, &embedder, &filter).await.unwrap();
+                results.sort_by(|(a, _), (b, _)| a.cmp(b));
+                results
+                    .into_iter()
+                    .map(|(id, results)| {
+                        let paths: Vec<String> = results.iter().filter_map(|r| r.path().map(str::to_string)).collect();
+                        (id, paths)
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let src_only = vec!["/repo/src/lib.rs".to_string()];
+        assert_eq!(search("path:src").await, vec![
+            ("lexical".to_string(), src_only.clone()),
+            ("semantic".to_string(), src_only.clone()),
+        ]);
+        assert_eq!(search("lang:rs context:semantic").await, vec![(
+            "semantic".to_string(),
+            src_only
+        )]);
+        assert_eq!(search("type:markdown context:lexical").await[0].1.len(), 3);
+        assert!(search("type:pdf").await.is_empty());
+    }
+}
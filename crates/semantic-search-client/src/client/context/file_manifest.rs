@@ -0,0 +1,267 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::UNIX_EPOCH;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+
+use crate::client::utils;
+use crate::error::Result;
+use crate::types::IndexChanges;
+
+/// File in a context's directory that records which files were indexed into which chunks
+pub const FILE_MANIFEST_FILE: &str = "files.json";
+
+/// What was indexed for a single file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRecord {
+    /// SHA-256 of the file content, hex encoded
+    pub hash: String,
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified: u64,
+    /// File size in bytes
+    pub size: u64,
+    /// IDs of the data points created from this file
+    pub chunk_ids: Vec<usize>,
+}
+
+/// Files that changed since a context was last indexed, see [`FileManifest::diff`]
+#[derive(Debug, Default)]
+pub struct FileChanges {
+    /// Files that weren't indexed before
+    pub added: Vec<PathBuf>,
+    /// Indexed files whose content changed
+    pub modified: Vec<PathBuf>,
+    /// Indexed files that are no longer present
+    pub removed: Vec<String>,
+    /// Number of indexed files that are unchanged
+    pub unchanged: usize,
+}
+
+impl FileChanges {
+    /// Counts of each kind of change, for progress reporting
+    pub fn summary(&self) -> IndexChanges {
+        IndexChanges {
+            added: self.added.len(),
+            modified: self.modified.len(),
+            removed: self.removed.len(),
+            unchanged: self.unchanged,
+        }
+    }
+}
+
+/// Per-file content hashes and modification times for a context, used to re-index only the files
+/// that changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileManifest {
+    /// Indexed files, keyed by path
+    pub files: HashMap<String, FileRecord>,
+    /// ID to give the next data point added to the context
+    pub next_id: usize,
+}
+
+impl FileManifest {
+    /// Load the manifest from a context directory. Returns `None` for contexts indexed before
+    /// manifests were recorded.
+    pub fn load(context_dir: &Path) -> Result<Option<Self>> {
+        let path = context_dir.join(FILE_MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    /// Save the manifest to a context directory
+    pub fn save(&self, context_dir: &Path) -> Result<()> {
+        utils::save_json_to_file(&context_dir.join(FILE_MANIFEST_FILE), self)
+    }
+
+    /// Build the manifest for a freshly indexed context, whose data point IDs are the positions
+    /// of the processed `items`.
+    pub fn from_items(items: &[serde_json::Value]) -> Self {
+        let mut chunk_ids: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (id, item) in items.iter().enumerate() {
+            if let Some(path) = item.get("path").and_then(|p| p.as_str()) {
+                chunk_ids.entry(path).or_default().push(id);
+            }
+        }
+
+        let mut manifest = Self {
+            files: HashMap::new(),
+            next_id: items.len(),
+        };
+        for (path, ids) in chunk_ids {
+            if let Err(e) = manifest.record_file(Path::new(path), ids) {
+                tracing::warn!("Failed to record {} in file manifest: {}", path, e);
+            }
+        }
+        manifest
+    }
+
+    /// Record the current state of a file and the data points created from it
+    pub fn record_file(&mut self, path: &Path, chunk_ids: Vec<usize>) -> Result<()> {
+        let (modified, size) = file_stamp(path)?;
+        let hash = hash_file(path)?;
+        self.files.insert(path.to_string_lossy().to_string(), FileRecord {
+            hash,
+            modified,
+            size,
+            chunk_ids,
+        });
+        Ok(())
+    }
+
+    /// Compare the manifest against the files currently matched by a context.
+    ///
+    /// Files whose modification time and size are unchanged are assumed to be unchanged without
+    /// reading them. Otherwise the content hash decides, and files that were only touched have
+    /// their recorded modification time refreshed.
+    pub fn diff(&mut self, files: &[PathBuf]) -> FileChanges {
+        let mut changes = FileChanges::default();
+        let mut seen = HashSet::new();
+
+        for path in files {
+            let key = path.to_string_lossy().to_string();
+            let Some(record) = self.files.get_mut(&key) else {
+                changes.added.push(path.clone());
+                continue;
+            };
+            seen.insert(key);
+
+            let Ok((modified, size)) = file_stamp(path) else {
+                changes.modified.push(path.clone());
+                continue;
+            };
+            if record.modified == modified && record.size == size {
+                changes.unchanged += 1;
+                continue;
+            }
+
+            match hash_file(path) {
+                Ok(hash) if hash == record.hash => {
+                    record.modified = modified;
+                    record.size = size;
+                    changes.unchanged += 1;
+                },
+                _ => changes.modified.push(path.clone()),
+            }
+        }
+
+        changes.removed = self.files.keys().filter(|key| !seen.contains(*key)).cloned().collect();
+        changes.removed.sort();
+        changes
+    }
+
+    /// Forget the given files, returning the IDs of the data points that were created from them
+    pub fn remove_files<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) -> HashSet<usize> {
+        paths
+            .into_iter()
+            .filter_map(|path| self.files.remove(path))
+            .flat_map(|record| record.chunk_ids)
+            .collect()
+    }
+
+    /// Reserve `count` consecutive data point IDs
+    pub fn allocate_ids(&mut self, count: usize) -> std::ops::Range<usize> {
+        let start = self.next_id;
+        self.next_id += count;
+        start..self.next_id
+    }
+}
+
+fn file_stamp(path: &Path) -> std::io::Result<(u64, u64)> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    Ok((modified, metadata.len()))
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(fs::read(path)?);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept.md");
+        let edited = dir.path().join("edited.md");
+        let deleted = dir.path().join("deleted.md");
+        let created = dir.path().join("created.md");
+        fs::write(&kept, "kept").unwrap();
+        fs::write(&edited, "before").unwrap();
+        fs::write(&deleted, "deleted").unwrap();
+
+        let items = [&kept, &edited, &edited, &deleted]
+            .iter()
+            .map(|path| serde_json::json!({ "path": path.to_string_lossy(), "text": "" }))
+            .collect::<Vec<_>>();
+        let mut manifest = FileManifest::from_items(&items);
+        assert_eq!(manifest.next_id, 4);
+        assert_eq!(manifest.files[&*edited.to_string_lossy()].chunk_ids, vec![1, 2]);
+
+        fs::write(&edited, "after, and longer").unwrap();
+        fs::remove_file(&deleted).unwrap();
+        fs::write(&created, "created").unwrap();
+
+        let changes = manifest.diff(&[kept.clone(), edited.clone(), created.clone()]);
+        assert_eq!(changes.added, vec![created]);
+        assert_eq!(changes.modified, vec![edited.clone()]);
+        assert_eq!(changes.removed, vec![deleted.to_string_lossy().to_string()]);
+        assert_eq!(changes.summary(), IndexChanges {
+            added: 1,
+            modified: 1,
+            removed: 1,
+            unchanged: 1,
+        });
+
+        let stale = manifest.remove_files(
+            changes
+                .removed
+                .iter()
+                .map(String::as_str)
+                .chain(changes.modified.iter().filter_map(|p| p.to_str())),
+        );
+        assert_eq!(stale, HashSet::from([1, 2, 3]));
+        assert_eq!(manifest.allocate_ids(2), 4..6);
+        assert_eq!(manifest.next_id, 6);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(FileManifest::load(dir.path()).unwrap().is_none());
+
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "notes").unwrap();
+        let mut manifest = FileManifest::default();
+        manifest.record_file(&file, vec![0]).unwrap();
+        manifest.next_id = 1;
+        manifest.save(dir.path()).unwrap();
+
+        let loaded = FileManifest::load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.files, manifest.files);
+        assert_eq!(loaded.next_id, 1);
+    }
+}
@@ -1,13 +1,25 @@
 /// BM25 context implementation
 pub mod bm25_context;
+/// Portable bundles of persistent contexts
+pub mod context_bundle;
 /// Context creation utilities
 pub mod context_creator;
 /// Context management
 pub mod context_manager;
+/// Per-file hashes used to re-index only changed files
+pub mod file_manifest;
 /// Semantic context implementation
 pub mod semantic_context;
 
 pub use bm25_context::BM25Context;
+pub use context_bundle::{
+    BundleManifest,
+    ContextBundle,
+};
 pub use context_creator::ContextCreator;
 pub use context_manager::ContextManager;
+pub use file_manifest::{
+    FileChanges,
+    FileManifest,
+};
 pub use semantic_context::SemanticContext;
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fs::{
     self,
     File,
@@ -10,6 +11,7 @@ use std::path::PathBuf;
 
 use crate::error::Result;
 use crate::index::VectorIndex;
+use crate::search_filter::FilterMatcher;
 use crate::types::{
     DataPoint,
     SearchResult,
@@ -101,6 +103,24 @@ impl SemanticContext {
         Ok(count)
     }
 
+    /// Remove the data points with the given IDs, returning how many were removed. The vector
+    /// index doesn't support deletion, so it is rebuilt from the remaining vectors.
+    pub fn remove_data_points(&mut self, ids: &HashSet<usize>) -> Result<usize> {
+        let before = self.data_points.len();
+        self.data_points.retain(|point| !ids.contains(&point.id));
+        let removed = before - self.data_points.len();
+
+        if removed > 0 {
+            if self.data_points.is_empty() {
+                self.index = None;
+            } else {
+                self.rebuild_index()?;
+            }
+        }
+
+        Ok(removed)
+    }
+
     /// Update the index with data points in a specific range
     pub fn update_index_by_range(&mut self, start_idx: usize, end_idx: usize) -> Result<()> {
         // If we don't have an index yet, or if the index is small and we're adding many points,
@@ -143,6 +163,37 @@ impl SemanticContext {
         Ok(search_results)
     }
 
+    /// Search for similar items to the given vector among the data points that match a filter
+    pub(crate) fn search_filtered(
+        &self,
+        query_vector: &[f32],
+        limit: usize,
+        filter: &FilterMatcher<'_>,
+    ) -> Result<Vec<SearchResult>> {
+        let Some(index) = &self.index else {
+            return Ok(Vec::new());
+        };
+
+        // The index is keyed by each data point's position, not its ID, so these are positions
+        // and come out sorted
+        let allowed_ids: Vec<usize> = self
+            .data_points
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| filter.matches(&point.payload))
+            .map(|(position, _)| position)
+            .collect();
+        if allowed_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let results = index.search_filtered(query_vector, limit, limit.max(100), &allowed_ids);
+        Ok(results
+            .into_iter()
+            .map(|(id, distance)| SearchResult::new(self.data_points[id].clone(), distance))
+            .collect())
+    }
+
     /// Get the data points for serialization
     pub fn get_data_points(&self) -> &Vec<DataPoint> {
         &self.data_points
@@ -1,29 +1,41 @@
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
 use crate::embedding::CandleTextEmbedder;
+#[cfg(feature = "onnx")]
+use crate::embedding::OnnxTextEmbedder;
+use crate::config::SemanticSearchConfig;
 use crate::embedding::MockTextEmbedder; // Used for Fast type since BM25 doesn't need embeddings
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
 use crate::embedding::ModelType;
 use crate::embedding::{
     EmbeddingType,
+    HttpTextEmbedder,
     TextEmbedderTrait,
 };
-use crate::error::Result;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
 
 /// Creates a text embedder based on the specified embedding type
 ///
 /// # Arguments
 ///
-/// * `embedding_type` - Type of embedding engine to use
+/// * `config` - Configuration whose embedding type selects the engine
 ///
 /// # Returns
 ///
 /// A text embedder instance
 #[cfg(any(target_os = "macos", target_os = "windows"))]
-pub fn create_embedder(embedding_type: EmbeddingType) -> Result<Box<dyn TextEmbedderTrait>> {
-    let embedder: Box<dyn TextEmbedderTrait> = match embedding_type {
+pub fn create_embedder(config: &SemanticSearchConfig) -> Result<Box<dyn TextEmbedderTrait>> {
+    let embedder: Box<dyn TextEmbedderTrait> = match config.embedding_type {
         EmbeddingType::Fast => Box::new(MockTextEmbedder::new(384)), // BM25 doesn't use embeddings
         #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
-        EmbeddingType::Best => Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?),
+        EmbeddingType::Best | EmbeddingType::Hybrid => {
+            Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?)
+        },
+        #[cfg(feature = "onnx")]
+        EmbeddingType::Onnx => Box::new(OnnxTextEmbedder::new()?),
+        EmbeddingType::Http => Box::new(create_http_embedder(config)?),
         #[cfg(test)]
         EmbeddingType::Mock => Box::new(MockTextEmbedder::new(384)),
     };
@@ -36,20 +48,32 @@ pub fn create_embedder(embedding_type: EmbeddingType) -> Result<Box<dyn TextEmbe
 ///
 /// # Arguments
 ///
-/// * `embedding_type` - Type of embedding engine to use
+/// * `config` - Configuration whose embedding type selects the engine
 ///
 /// # Returns
 ///
 /// A text embedder instance
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-pub fn create_embedder(embedding_type: EmbeddingType) -> Result<Box<dyn TextEmbedderTrait>> {
-    let embedder: Box<dyn TextEmbedderTrait> = match embedding_type {
+pub fn create_embedder(config: &SemanticSearchConfig) -> Result<Box<dyn TextEmbedderTrait>> {
+    let embedder: Box<dyn TextEmbedderTrait> = match config.embedding_type {
         EmbeddingType::Fast => Box::new(MockTextEmbedder::new(384)), // BM25 doesn't use embeddings
         #[cfg(not(target_arch = "aarch64"))]
-        EmbeddingType::Best => Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?),
+        EmbeddingType::Best | EmbeddingType::Hybrid => {
+            Box::new(CandleTextEmbedder::with_model_type(ModelType::MiniLML6V2)?)
+        },
+        #[cfg(feature = "onnx")]
+        EmbeddingType::Onnx => Box::new(OnnxTextEmbedder::new()?),
+        EmbeddingType::Http => Box::new(create_http_embedder(config)?),
         #[cfg(test)]
         EmbeddingType::Mock => Box::new(MockTextEmbedder::new(384)),
     };
 
     Ok(embedder)
 }
+
+fn create_http_embedder(config: &SemanticSearchConfig) -> Result<HttpTextEmbedder> {
+    let http_config = config.http_embedding.clone().ok_or_else(|| {
+        SemanticSearchError::InvalidArgument("The Http embedding type requires an embedding endpoint".to_string())
+    })?;
+    HttpTextEmbedder::new(http_config, std::time::Duration::from_millis(config.timeout))
+}
@@ -23,6 +23,10 @@ use crate::error::{
     SemanticSearchError,
 };
 use crate::processing::process_file_with_config;
+use crate::search_filter::{
+    FilterMatcher,
+    SearchFilter,
+};
 use crate::types::{
     ContextId,
     ContextMap,
@@ -112,7 +116,7 @@ impl SemanticSearchClient {
             // Continue with default config if initialization fails
         }
 
-        let embedder = embedder_factory::create_embedder(config.embedding_type)?;
+        let embedder = embedder_factory::create_embedder(&config)?;
 
         // Load metadata for persistent contexts
         let contexts_file = base_dir.join("contexts.json");
@@ -716,6 +720,28 @@ impl SemanticSearchClient {
     ///
     /// A vector of (context_id, results) pairs
     pub fn search_all(&self, query_text: &str, result_limit: Option<usize>) -> Result<Vec<(ContextId, SearchResults)>> {
+        self.search_all_filtered(query_text, result_limit, &SearchFilter::default())
+    }
+
+    /// Search across all contexts, considering only the chunks that match a filter
+    ///
+    /// # Arguments
+    ///
+    /// * `query_text` - Search query
+    /// * `result_limit` - Maximum number of results to return per context (if None, uses
+    ///   default_results from config)
+    /// * `filter` - Conditions on the path, file type, language and context of the results, applied
+    ///   before ranking
+    ///
+    /// # Returns
+    ///
+    /// A vector of (context_id, results) pairs
+    pub fn search_all_filtered(
+        &self,
+        query_text: &str,
+        result_limit: Option<usize>,
+        filter: &SearchFilter,
+    ) -> Result<Vec<(ContextId, SearchResults)>> {
         // Validate inputs
         if query_text.is_empty() {
             return Err(SemanticSearchError::InvalidArgument(
@@ -726,6 +752,8 @@ impl SemanticSearchClient {
         // Use the configured default_results if limit is None
         let effective_limit = result_limit.unwrap_or_else(|| config::get_config().default_results);
 
+        let filter = filter.matcher()?;
+
         // Generate an embedding for the query
         let query_vector = self.embedder.embed(query_text)?;
 
@@ -733,11 +761,15 @@ impl SemanticSearchClient {
 
         // Search in all volatile contexts
         for (context_id, context) in &self.volatile_contexts {
+            if !self.context_matches(context_id, filter.as_ref()) {
+                continue;
+            }
+
             let context_guard = context.lock().map_err(|e| {
                 SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e))
             })?;
 
-            match context_guard.search(&query_vector, effective_limit) {
+            match Self::search_semantic_context(&context_guard, &query_vector, effective_limit, filter.as_ref()) {
                 Ok(results) => {
                     if !results.is_empty() {
                         all_results.push((context_id.clone(), results));
@@ -782,6 +814,30 @@ impl SemanticSearchClient {
         context_id: &str,
         query_text: &str,
         result_limit: Option<usize>,
+    ) -> Result<SearchResults> {
+        self.search_context_filtered(context_id, query_text, result_limit, &SearchFilter::default())
+    }
+
+    /// Search in a specific context, considering only the chunks that match a filter
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - ID of the context to search in
+    /// * `query_text` - Search query
+    /// * `result_limit` - Maximum number of results to return (if None, uses default_results from
+    ///   config)
+    /// * `filter` - Conditions on the path, file type, language and context of the results, applied
+    ///   before ranking
+    ///
+    /// # Returns
+    ///
+    /// A vector of search results
+    pub fn search_context_filtered(
+        &self,
+        context_id: &str,
+        query_text: &str,
+        result_limit: Option<usize>,
+        filter: &SearchFilter,
     ) -> Result<SearchResults> {
         // Validate inputs
         if context_id.is_empty() {
@@ -798,6 +854,10 @@ impl SemanticSearchClient {
 
         // Use the configured default_results if limit is None
         let effective_limit = result_limit.unwrap_or_else(|| config::get_config().default_results);
+        let filter = filter.matcher()?;
+        if !self.context_matches(context_id, filter.as_ref()) {
+            return Ok(Vec::new());
+        }
 
         // Generate an embedding for the query
         let query_vector = self.embedder.embed(query_text)?;
@@ -811,7 +871,28 @@ impl SemanticSearchClient {
             .lock()
             .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e)))?;
 
-        context_guard.search(&query_vector, effective_limit)
+        Self::search_semantic_context(&context_guard, &query_vector, effective_limit, filter.as_ref())
+    }
+
+    /// Check whether a context can hold chunks matching a filter
+    fn context_matches(&self, context_id: &str, filter: Option<&FilterMatcher<'_>>) -> bool {
+        let name = self
+            .persistent_contexts
+            .get(context_id)
+            .map_or("", |context| context.name.as_str());
+        filter.is_none_or(|filter| filter.matches_context(context_id, name))
+    }
+
+    fn search_semantic_context(
+        context: &SemanticContext,
+        query_vector: &[f32],
+        limit: usize,
+        filter: Option<&FilterMatcher<'_>>,
+    ) -> Result<SearchResults> {
+        match filter {
+            Some(filter) => context.search_filtered(query_vector, limit, filter),
+            None => context.search(query_vector, limit),
+        }
     }
 
     /// Get all contexts
@@ -24,6 +24,7 @@ pub mod hosted_model_client;
 pub use async_implementation::AsyncSemanticSearchClient;
 pub use context::{
     BM25Context,
+    BundleManifest,
     SemanticContext,
 };
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
@@ -14,12 +14,19 @@ impl ModelDownloader {
     pub async fn ensure_models_downloaded(embedding_type: &EmbeddingType) -> Result<()> {
         match embedding_type {
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
-            EmbeddingType::Best => {
+            EmbeddingType::Best | EmbeddingType::Hybrid => {
                 Self::download_best_model().await?;
             },
             EmbeddingType::Fast => {
                 // BM25 doesn't require model downloads
             },
+            #[cfg(feature = "onnx")]
+            EmbeddingType::Onnx => {
+                // fastembed downloads ONNX models itself when the embedder is created
+            },
+            EmbeddingType::Http => {
+                // The embedding server hosts the model
+            },
             #[cfg(test)]
             EmbeddingType::Mock => {
                 // Mock doesn't require model downloads
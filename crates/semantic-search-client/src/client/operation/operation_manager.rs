@@ -172,6 +172,9 @@ impl OperationManager {
 
         operations.retain(|_, handle| {
             if let Ok(progress) = handle.progress.try_lock() {
+                if let Some(completed_at) = progress.completed_at {
+                    return now.duration_since(completed_at).unwrap_or_default() < cleanup_threshold;
+                }
                 let is_cancelled = progress.message.to_lowercase().contains("cancelled");
                 let is_failed = progress.message.to_lowercase().contains("failed");
                 if is_cancelled || is_failed {
@@ -199,9 +202,10 @@ impl OperationManager {
                 let is_failed = progress.message.to_lowercase().contains("failed");
                 let is_cancelled = progress.message.to_lowercase().contains("cancelled");
                 let is_waiting = Self::is_operation_waiting(&progress);
+                let is_completed = progress.completed_at.is_some();
 
-                if is_cancelled {
-                    // Don't count cancelled operations
+                if is_cancelled || is_completed {
+                    // Don't count cancelled or finished operations
                 } else if is_failed || is_waiting {
                     waiting_count += 1;
                 } else {
@@ -218,8 +222,10 @@ impl OperationManager {
                     message: progress.message.clone(),
                     is_cancelled,
                     is_failed,
-                    is_waiting,
+                    is_waiting: is_waiting && !is_completed,
+                    is_completed,
                     eta: progress.calculate_eta(),
+                    changes: progress.changes,
                 };
 
                 operation_statuses.push(operation_status);
@@ -234,6 +240,20 @@ impl OperationManager {
             active_count,
             waiting_count,
             max_concurrent: MAX_CONCURRENT_OPERATIONS,
+            watchers: Vec::new(),
+        })
+    }
+
+    /// Check whether an update of the context at `path` is still running
+    pub async fn is_updating(&self, path: &str) -> bool {
+        let operations = self.active_operations.read().await;
+        operations.values().any(|handle| {
+            matches!(&handle.operation_type, OperationType::Updating { path: updating, .. } if updating == path)
+                && handle.progress.try_lock().is_ok_and(|progress| {
+                    progress.completed_at.is_none()
+                        && !progress.message.contains("cancelled")
+                        && !progress.message.contains("failed")
+                })
         })
     }
 
@@ -47,6 +47,45 @@ pub struct SemanticSearchConfig {
 
     /// Embedding engine type to use
     pub embedding_type: EmbeddingType,
+
+    /// Embedding endpoint used when `embedding_type` is [`EmbeddingType::Http`]
+    #[serde(default)]
+    pub http_embedding: Option<HttpEmbeddingConfig>,
+}
+
+/// An OpenAI-compatible embedding endpoint, such as a local embedding server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpEmbeddingConfig {
+    /// Base URL of the API, e.g. `http://localhost:8080/v1`. Requests are sent to
+    /// `{url}/embeddings`.
+    pub url: String,
+
+    /// Model to request embeddings from
+    pub model: String,
+
+    /// Bearer token sent with each request
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Maximum number of texts sent in a single request
+    #[serde(default = "default_http_batch_size")]
+    pub batch_size: usize,
+}
+
+impl HttpEmbeddingConfig {
+    /// Create a configuration for the given endpoint and model
+    pub fn new(url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            model: model.into(),
+            api_key: None,
+            batch_size: default_http_batch_size(),
+        }
+    }
+}
+
+fn default_http_batch_size() -> usize {
+    32
 }
 
 impl SemanticSearchConfig {
@@ -93,6 +132,7 @@ impl Default for SemanticSearchConfig {
             max_files: 10000, // Default limit of 10000 files
             hosted_models_base_url: "https://desktop-release.q.us-east-1.amazonaws.com/models".to_string(),
             embedding_type: EmbeddingType::default(),
+            http_embedding: None,
         }
     }
 }
@@ -341,6 +381,7 @@ mod tests {
             max_files: 10000,
             hosted_models_base_url: "http://test.example.com/models".to_string(),
             embedding_type: EmbeddingType::default(),
+            http_embedding: None,
         };
 
         // Update the config
@@ -118,6 +118,11 @@ impl CandleTextEmbedder {
         Self::with_model_config(model_path, tokenizer_path, config)
     }
 
+    /// Configuration of the model in use
+    pub fn model_config(&self) -> &ModelConfig {
+        &self.config
+    }
+
     /// Generate an embedding for a text
     ///
     /// # Arguments
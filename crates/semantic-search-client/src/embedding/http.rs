@@ -0,0 +1,267 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::runtime::Runtime;
+use tracing::debug;
+
+use crate::config::HttpEmbeddingConfig;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// Text embedder that requests embeddings from an OpenAI-compatible `/embeddings` endpoint
+pub struct HttpTextEmbedder {
+    config: HttpEmbeddingConfig,
+    client: reqwest::Client,
+    /// Embedders are synchronous but get called from inside the async runtime, so requests run on
+    /// a runtime of their own. Only `None` once dropped.
+    runtime: Option<Runtime>,
+    /// Dimension of the embeddings, known once the server has returned some
+    dimension: OnceLock<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
+}
+
+impl HttpTextEmbedder {
+    /// Create an embedder for the given endpoint
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Endpoint, model and credentials to use
+    /// * `timeout` - Timeout of each request
+    pub fn new(config: HttpEmbeddingConfig, timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| SemanticSearchError::EmbeddingError(format!("Failed to create HTTP client: {}", e)))?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("http-embedder")
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            config,
+            client,
+            runtime: Some(runtime),
+            dimension: OnceLock::new(),
+        })
+    }
+
+    /// Model requested from the endpoint
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    /// Dimension of the embeddings, `None` until the server has returned some
+    pub fn dimension(&self) -> Option<usize> {
+        self.dimension.get().copied()
+    }
+
+    /// Maximum number of texts sent in a single request
+    pub fn batch_size(&self) -> usize {
+        self.config.batch_size.max(1)
+    }
+
+    /// Generate an embedding for a text
+    pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text.to_string()])?
+            .pop()
+            .ok_or_else(|| SemanticSearchError::EmbeddingError("Embedding server returned no embedding".to_string()))
+    }
+
+    /// Generate embeddings for multiple texts, in requests of at most the configured batch size
+    pub fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size()) {
+            embeddings.extend(self.request(batch)?);
+        }
+        Ok(embeddings)
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/embeddings", self.config.url.trim_end_matches('/'))
+    }
+
+    fn request(&self, input: &[String]) -> Result<Vec<Vec<f32>>> {
+        debug!("Requesting {} embeddings from {}", input.len(), self.endpoint());
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let request = self.send(input);
+        self.runtime
+            .as_ref()
+            .expect("the runtime is only taken on drop")
+            .spawn(async move {
+                let _ = tx.send(request.await);
+            });
+        let response = rx.recv().map_err(|_recv_error| {
+            SemanticSearchError::EmbeddingError("Embedding request was aborted".to_string())
+        })??;
+
+        let mut data = response.data;
+        if data.len() != input.len() {
+            return Err(SemanticSearchError::EmbeddingError(format!(
+                "Embedding server returned {} embeddings for {} texts",
+                data.len(),
+                input.len()
+            )));
+        }
+        data.sort_by_key(|item| item.index);
+
+        let embeddings: Vec<Vec<f32>> = data.into_iter().map(|item| item.embedding).collect();
+        for embedding in &embeddings {
+            let dimension = *self.dimension.get_or_init(|| embedding.len());
+            if embedding.len() != dimension {
+                return Err(SemanticSearchError::EmbeddingError(format!(
+                    "Embedding server returned a {}-dimensional embedding, expected {} dimensions",
+                    embedding.len(),
+                    dimension
+                )));
+            }
+        }
+        Ok(embeddings)
+    }
+
+    /// Request embeddings for `input`, to be awaited on the embedder's runtime
+    fn send(&self, input: &[String]) -> impl Future<Output = Result<EmbeddingResponse>> + Send + 'static {
+        let endpoint = self.endpoint();
+        let mut request = self.client.post(&endpoint).json(&serde_json::json!({
+            "model": self.config.model,
+            "input": input,
+        }));
+        if let Some(api_key) = &self.config.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        async move {
+            let response = request.send().await.map_err(|e| {
+                SemanticSearchError::EmbeddingError(format!("Failed to reach embedding server {}: {}", endpoint, e))
+            })?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(SemanticSearchError::EmbeddingError(format!(
+                    "Embedding server returned {}: {}",
+                    status,
+                    body.trim()
+                )));
+            }
+
+            response.json().await.map_err(|e| {
+                SemanticSearchError::EmbeddingError(format!("Invalid response from embedding server: {}", e))
+            })
+        }
+    }
+}
+
+impl Drop for HttpTextEmbedder {
+    fn drop(&mut self) {
+        // Embedders may be dropped inside the async runtime, where a runtime can't block to shut down
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    fn embedder(server: &mockito::Server, batch_size: usize) -> HttpTextEmbedder {
+        let mut config = HttpEmbeddingConfig::new(format!("{}/v1/", server.url()), "nomic-embed-text");
+        config.api_key = Some("secret".to_string());
+        config.batch_size = batch_size;
+        HttpTextEmbedder::new(config, Duration::from_secs(5)).unwrap()
+    }
+
+    #[test]
+    fn test_embed_batch() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/v1/embeddings")
+            .match_header("authorization", "Bearer secret")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "model": "nomic-embed-text",
+                "input": ["first", "second"],
+            })))
+            .with_header("content-type", "application/json")
+            // Results may come back in any order and are put back in input order by index
+            .with_body(
+                r#"{"object":"list","data":[
+                    {"object":"embedding","index":1,"embedding":[0.0,1.0,0.0]},
+                    {"object":"embedding","index":0,"embedding":[1.0,0.0,0.0]}
+                ],"model":"nomic-embed-text"}"#,
+            )
+            .expect(2)
+            .create();
+
+        let embedder = embedder(&server, 2);
+        assert_eq!(embedder.dimension(), None);
+
+        let texts = ["first", "second", "first", "second"].map(String::from);
+        let embeddings = embedder.embed_batch(&texts).unwrap();
+        assert_eq!(embeddings, vec![
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+        ]);
+        assert_eq!(embedder.dimension(), Some(3));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_embed_inside_runtime() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/embeddings")
+            .with_body(r#"{"data":[{"index":0,"embedding":[1.0,0.0]}]}"#)
+            .expect(3)
+            .create_async()
+            .await;
+
+        // Indexing embeds from the async runtime, and requests share the embedder's client
+        let embedder = embedder(&server, 1);
+        for _ in 0..3 {
+            assert_eq!(embedder.embed("text").unwrap(), vec![1.0, 0.0]);
+        }
+        drop(embedder);
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_embed_errors() {
+        let mut server = mockito::Server::new();
+        let _error = server
+            .mock("POST", "/v1/embeddings")
+            .match_body(Matcher::PartialJson(serde_json::json!({ "input": ["unknown model"] })))
+            .with_status(404)
+            .with_body("model not found")
+            .create();
+        let _short = server
+            .mock("POST", "/v1/embeddings")
+            .match_body(Matcher::PartialJson(serde_json::json!({ "input": ["a", "b"] })))
+            .with_body(r#"{"data":[{"index":0,"embedding":[1.0]}]}"#)
+            .create();
+
+        let embedder = embedder(&server, 8);
+        let error = embedder.embed("unknown model").unwrap_err().to_string();
+        assert!(error.contains("404") && error.contains("model not found"), "{}", error);
+
+        let error = embedder.embed_batch(&["a".to_string(), "b".to_string()]).unwrap_err();
+        assert!(error.to_string().contains("1 embeddings for 2 texts"));
+    }
+}
@@ -12,6 +12,11 @@ impl MockTextEmbedder {
         Self { dimension }
     }
 
+    /// Number of dimensions of the generated embeddings
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
     /// Generate a deterministic embedding for a text
     ///
     /// # Arguments
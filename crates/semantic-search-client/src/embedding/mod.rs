@@ -4,8 +4,13 @@ mod benchmark_utils;
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
 mod candle;
 mod candle_models;
+mod http;
 /// Mock embedder for testing and as placeholder for BM25
 pub mod mock;
+#[cfg(feature = "onnx")]
+mod onnx;
+#[cfg(feature = "onnx")]
+mod onnx_models;
 mod trait_def;
 
 pub use benchmark_utils::{
@@ -20,7 +25,12 @@ pub use candle_models::{
     ModelConfig,
     ModelType,
 };
+pub use http::HttpTextEmbedder;
 pub use mock::MockTextEmbedder;
+#[cfg(feature = "onnx")]
+pub use onnx::TextEmbedder as OnnxTextEmbedder;
+#[cfg(feature = "onnx")]
+pub use onnx_models::OnnxModelType;
 pub use trait_def::{
     EmbeddingType,
     TextEmbedderTrait,
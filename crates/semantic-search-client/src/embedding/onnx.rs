@@ -83,7 +83,7 @@ impl TextEmbedder {
             Ok(embeddings) => Ok(embeddings.into_iter().next().unwrap()),
             Err(e) => {
                 error!("Failed to embed text: {}", e);
-                Err(SemanticSearchError::FastembedError(e.to_string()))
+                Err(SemanticSearchError::EmbeddingError(e.to_string()))
             },
         }
     }
@@ -103,7 +103,7 @@ impl TextEmbedder {
             Ok(embeddings) => Ok(embeddings),
             Err(e) => {
                 error!("Failed to embed batch of texts: {}", e);
-                Err(SemanticSearchError::FastembedError(e.to_string()))
+                Err(SemanticSearchError::EmbeddingError(e.to_string()))
             },
         }
     }
@@ -144,7 +144,7 @@ fn initialize_model(model_type: OnnxModelType, models_dir: &std::path::Path) ->
         Ok(model) => Ok(model),
         Err(e) => {
             error!("Failed to initialize fastembed model: {}", e);
-            Err(SemanticSearchError::FastembedError(e.to_string()))
+            Err(SemanticSearchError::EmbeddingError(e.to_string()))
         },
     }
 }
@@ -13,6 +13,17 @@ pub enum EmbeddingType {
     /// Best embedding using all-MiniLM-L6-v2 (not available on Linux ARM)
     #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
     Best,
+    /// Both BM25 and all-MiniLM-L6-v2, with results merged by reciprocal rank fusion (not
+    /// available on Linux ARM)
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    Hybrid,
+    /// Quantized all-MiniLM-L6-v2 run with ONNX Runtime (requires the `onnx` feature and the ONNX
+    /// Runtime shared library)
+    #[cfg(feature = "onnx")]
+    Onnx,
+    /// Embeddings from an OpenAI-compatible `/embeddings` endpoint, see
+    /// [`crate::config::HttpEmbeddingConfig`]
+    Http,
     /// Use Mock embedding engine (only available in tests)
     #[cfg(test)]
     Mock,
@@ -43,7 +54,10 @@ impl EmbeddingType {
     pub fn to_model_type(&self) -> Option<super::ModelType> {
         match self {
             Self::Fast => None, // BM25 doesn't use Candle models
-            Self::Best => Some(super::ModelType::MiniLML6V2),
+            Self::Best | Self::Hybrid => Some(super::ModelType::MiniLML6V2),
+            #[cfg(feature = "onnx")]
+            Self::Onnx => None,
+            Self::Http => None,
             #[cfg(test)]
             Self::Mock => None,
         }
@@ -57,7 +71,52 @@ impl EmbeddingType {
     /// Check if this embedding type uses Candle
     #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
     pub fn is_candle(&self) -> bool {
-        matches!(self, Self::Best)
+        matches!(self, Self::Best | Self::Hybrid)
+    }
+
+    /// Check if this embedding type builds both a BM25 and a semantic index
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    pub fn is_hybrid(&self) -> bool {
+        matches!(self, Self::Hybrid)
+    }
+
+    /// Check if this embedding type builds both a BM25 and a semantic index
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    pub fn is_hybrid(&self) -> bool {
+        false
+    }
+
+    /// Embedding type whose embedder computes the vectors of this type, `None` for BM25, which
+    /// doesn't use embeddings
+    pub fn embedder_type(&self) -> Option<Self> {
+        match self {
+            Self::Fast => None,
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            Self::Hybrid => Some(Self::Best),
+            other => Some(*other),
+        }
+    }
+
+    /// Name of the built-in embedding model whose vectors this embedding type stores, `None` for
+    /// BM25 and for endpoints whose model is configured separately
+    #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+    pub fn model_name(&self) -> Option<String> {
+        #[cfg(feature = "onnx")]
+        if matches!(self, Self::Onnx) {
+            return Some(super::OnnxModelType::default().get_model_name().to_string());
+        }
+        self.to_model_type().map(|model_type| model_type.get_config().name)
+    }
+
+    /// Name of the built-in embedding model whose vectors this embedding type stores, `None` for
+    /// BM25 and for endpoints whose model is configured separately
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    pub fn model_name(&self) -> Option<String> {
+        #[cfg(feature = "onnx")]
+        if matches!(self, Self::Onnx) {
+            return Some(super::OnnxModelType::default().get_model_name().to_string());
+        }
+        None
     }
 
     /// Get a human-readable description of the embedding type
@@ -66,6 +125,11 @@ impl EmbeddingType {
             Self::Fast => "Fast",
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
             Self::Best => "Best",
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            Self::Hybrid => "Hybrid",
+            #[cfg(feature = "onnx")]
+            Self::Onnx => "Onnx",
+            Self::Http => "Http",
             #[cfg(test)]
             Self::Mock => "Mock",
         }
@@ -78,6 +142,11 @@ impl EmbeddingType {
             "fast" => Some(Self::Fast),
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
             "best" => Some(Self::Best),
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            "hybrid" => Some(Self::Hybrid),
+            #[cfg(feature = "onnx")]
+            "onnx" => Some(Self::Onnx),
+            "http" => Some(Self::Http),
             #[cfg(test)]
             "mock" => Some(Self::Mock),
             _ => None,
@@ -90,6 +159,11 @@ impl EmbeddingType {
             Self::Fast => "Fast",
             #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
             Self::Best => "Best",
+            #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
+            Self::Hybrid => "Hybrid",
+            #[cfg(feature = "onnx")]
+            Self::Onnx => "Onnx",
+            Self::Http => "Http",
             #[cfg(test)]
             Self::Mock => "Mock",
         }
@@ -105,6 +179,18 @@ pub trait TextEmbedderTrait: Send + Sync {
 
     /// Generate embeddings for multiple texts
     fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Name of the model the embeddings come from, `None` for embedders that don't use a model
+    fn model_name(&self) -> Option<String>;
+
+    /// Number of dimensions of the embeddings, `None` if it isn't known until something has been
+    /// embedded
+    fn dimension(&self) -> Option<usize>;
+
+    /// Number of texts to pass to [`Self::embed_batch`] at a time
+    fn batch_size(&self) -> usize {
+        32
+    }
 }
 
 #[cfg(not(all(target_os = "linux", target_arch = "aarch64")))]
@@ -116,6 +202,18 @@ impl TextEmbedderTrait for super::CandleTextEmbedder {
     fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         self.embed_batch(texts)
     }
+
+    fn model_name(&self) -> Option<String> {
+        Some(self.model_config().name.clone())
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        Some(self.model_config().config.hidden_size)
+    }
+
+    fn batch_size(&self) -> usize {
+        self.model_config().batch_size
+    }
 }
 
 impl TextEmbedderTrait for super::MockTextEmbedder {
@@ -126,4 +224,53 @@ impl TextEmbedderTrait for super::MockTextEmbedder {
     fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         self.embed_batch(texts)
     }
+
+    fn model_name(&self) -> Option<String> {
+        None
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        Some(self.dimension())
+    }
+}
+
+#[cfg(feature = "onnx")]
+impl TextEmbedderTrait for super::OnnxTextEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed(text)
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_batch(texts)
+    }
+
+    fn model_name(&self) -> Option<String> {
+        Some(self.model_type().get_model_name().to_string())
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        Some(self.model_type().get_embedding_dim())
+    }
+}
+
+impl TextEmbedderTrait for super::HttpTextEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed(text)
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_batch(texts)
+    }
+
+    fn model_name(&self) -> Option<String> {
+        Some(self.model().to_string())
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        self.dimension()
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size()
+    }
 }
@@ -1,5 +1,10 @@
 mod bm25_index;
+mod rank_fusion;
 mod vector_index;
 
 pub use bm25_index::BM25Index;
+pub use rank_fusion::{
+    DEFAULT_RRF_K,
+    reciprocal_rank_fusion,
+};
 pub use vector_index::VectorIndex;
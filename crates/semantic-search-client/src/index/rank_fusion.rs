@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Rank offset used by reciprocal rank fusion. Larger values flatten the difference between the
+/// top ranks; 60 is the value from the original RRF paper and works well in practice.
+pub const DEFAULT_RRF_K: f32 = 60.0;
+
+/// Merge several ranked result lists with reciprocal rank fusion.
+///
+/// Each item scores `1 / (k + rank)` for every list it appears in (ranks start at 1), and items
+/// are returned ordered by their summed score, highest first. Only ranks are used, so lists whose
+/// scores aren't comparable (e.g. BM25 scores and cosine distances) can be fused directly.
+///
+/// # Arguments
+///
+/// * `rankings` - Result lists, each ordered best first
+/// * `k` - Rank offset, see [`DEFAULT_RRF_K`]
+///
+/// # Returns
+///
+/// The fused `(item, score)` pairs, ordered by descending score
+pub fn reciprocal_rank_fusion<T: Copy + Eq + Hash>(rankings: &[Vec<T>], k: f32) -> Vec<(T, f32)> {
+    let mut scores: HashMap<T, f32> = HashMap::new();
+    // Remember where each item was first seen so that ties are broken deterministically
+    let mut order = Vec::new();
+
+    for ranking in rankings {
+        for (rank, item) in ranking.iter().enumerate() {
+            let score = scores.entry(*item).or_insert_with(|| {
+                order.push(*item);
+                0.0
+            });
+            *score += 1.0 / (k + rank as f32 + 1.0);
+        }
+    }
+
+    let mut fused = order.into_iter().map(|item| (item, scores[&item])).collect::<Vec<_>>();
+    fused.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_items_in_both_lists_rank_first() {
+        let lexical = vec![1, 2, 3];
+        let semantic = vec![4, 3, 5];
+        let fused = reciprocal_rank_fusion(&[lexical, semantic], DEFAULT_RRF_K);
+        let ids = fused.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+
+        assert_eq!(ids, vec![3, 1, 4, 2, 5]);
+        assert!((fused[0].1 - (1.0 / 63.0 + 1.0 / 62.0)).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_empty_rankings() {
+        assert!(reciprocal_rank_fusion::<usize>(&[], DEFAULT_RRF_K).is_empty());
+        let fused = reciprocal_rank_fusion(&[vec![], vec![7]], DEFAULT_RRF_K);
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].0, 7);
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::RwLock;
 
+use hnsw_rs::filter::FilterT;
 use hnsw_rs::hnsw::Hnsw;
 use hnsw_rs::prelude::DistCosine;
 use tracing::{
@@ -76,6 +77,34 @@ impl VectorIndex {
             .collect()
     }
 
+    /// Search for nearest neighbors among the given IDs only
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query vector
+    /// * `limit` - Maximum number of results to return
+    /// * `ef_search` - Size of the dynamic candidate list for search
+    /// * `allowed_ids` - Sorted IDs the results are restricted to
+    ///
+    /// # Returns
+    ///
+    /// A vector of (id, distance) pairs
+    pub fn search_filtered(
+        &self,
+        query: &[f32],
+        limit: usize,
+        ef_search: usize,
+        allowed_ids: &[usize],
+    ) -> Vec<(usize, f32)> {
+        let index = self.index.read().unwrap();
+        let results = index.search_filter(query, limit, ef_search, Some(&AllowedIds(allowed_ids)));
+
+        results
+            .into_iter()
+            .map(|neighbor| (neighbor.d_id, neighbor.distance))
+            .collect()
+    }
+
     /// Get the number of elements in the index
     ///
     /// # Returns
@@ -94,3 +123,12 @@ impl VectorIndex {
         self.len() == 0
     }
 }
+
+/// Restricts an HNSW search to a sorted set of IDs
+struct AllowedIds<'a>(&'a [usize]);
+
+impl FilterT for AllowedIds<'_> {
+    fn hnsw_filter(&self, id: &usize) -> bool {
+        self.0.binary_search(id).is_ok()
+    }
+}
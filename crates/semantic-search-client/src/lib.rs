@@ -20,6 +20,8 @@ pub mod model_validator;
 pub mod pattern_filter;
 /// File processing utilities
 pub mod processing;
+/// Metadata filters for search
+pub mod search_filter;
 /// Data types for semantic search operations
 pub mod types;
 
@@ -29,17 +31,21 @@ pub mod embedding;
 pub use client::{
     AsyncSemanticSearchClient,
     BM25Context,
+    BundleManifest,
     SemanticSearchClient,
 };
 pub use config::SemanticSearchConfig;
+pub use search_filter::SearchFilter;
 pub use error::{
     Result,
     SemanticSearchError,
 };
 pub use types::{
     BM25DataPoint,
+    Citation,
     DataPoint,
     FileType,
+    IndexChanges,
     KnowledgeContext,
     OperationStatus,
     OperationType,
@@ -47,4 +53,5 @@ pub use types::{
     ProgressStatus,
     SearchResult,
     SystemStatus,
+    WatcherStatus,
 };
@@ -57,7 +57,7 @@ impl PatternFilter {
     }
 
     /// Match a pattern against a path, handling both absolute and relative paths
-    fn matches_pattern(pattern: &Pattern, file_path: &Path) -> bool {
+    pub(crate) fn matches_pattern(pattern: &Pattern, file_path: &Path) -> bool {
         let path_str = file_path.to_string_lossy();
 
         // Try direct match first (for relative paths)
@@ -0,0 +1,179 @@
+use std::io::{
+    Read,
+    Seek,
+};
+
+use quick_xml::Reader;
+use quick_xml::events::{
+    BytesStart,
+    Event,
+};
+
+use super::{
+    DocumentSection,
+    MAX_DECODED_BYTES,
+    normalize_text,
+    read_limited,
+};
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// Extract the text of a DOCX document, split into sections at each heading.
+///
+/// Paragraphs styled as headings (`Heading1`..`Heading9` or `Title`) start a new section whose
+/// heading is the paragraph's text.
+pub fn extract_docx(reader: impl Read + Seek) -> Result<Vec<DocumentSection>> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid DOCX file: {}", e)))?;
+    let entry = archive
+        .by_name("word/document.xml")
+        .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid DOCX file: {}", e)))?;
+    let document = String::from_utf8(read_limited(entry, MAX_DECODED_BYTES)?)
+        .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid DOCX document: {}", e)))?;
+
+    parse_document_xml(&document)
+}
+
+fn parse_document_xml(xml: &str) -> Result<Vec<DocumentSection>> {
+    let mut reader = Reader::from_str(xml);
+
+    let mut sections = Vec::new();
+    let mut current = String::new();
+    let mut heading = None;
+    let mut paragraph = String::new();
+    let mut is_heading = false;
+    let mut in_text = false;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid DOCX document: {}", e)))?;
+        match event {
+            Event::Start(e) if e.name().as_ref() == b"w:p" => {
+                paragraph.clear();
+                is_heading = false;
+            },
+            Event::Start(e) if e.name().as_ref() == b"w:t" => in_text = true,
+            Event::End(e) if e.name().as_ref() == b"w:t" => in_text = false,
+            Event::Empty(e) if e.name().as_ref() == b"w:pStyle" => is_heading = is_heading_style(&e),
+            Event::Empty(e) if e.name().as_ref() == b"w:tab" => paragraph.push('\t'),
+            Event::Empty(e) if matches!(e.name().as_ref(), b"w:br" | b"w:cr") => paragraph.push('\n'),
+            Event::Text(e) if in_text => {
+                let text = e
+                    .unescape()
+                    .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid DOCX document: {}", e)))?;
+                paragraph.push_str(&text);
+            },
+            Event::End(e) if e.name().as_ref() == b"w:p" => {
+                if is_heading && !paragraph.trim().is_empty() {
+                    push_section(&mut sections, &mut current, heading.take());
+                    heading = Some(paragraph.trim().to_string());
+                }
+                current.push_str(&paragraph);
+                current.push('\n');
+                paragraph.clear();
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+    }
+
+    push_section(&mut sections, &mut current, heading);
+    Ok(sections)
+}
+
+fn is_heading_style(style: &BytesStart<'_>) -> bool {
+    style
+        .try_get_attribute("w:val")
+        .ok()
+        .flatten()
+        .and_then(|value| value.unescape_value().ok())
+        .is_some_and(|value| {
+            let value = value.to_ascii_lowercase();
+            value == "title" || value.starts_with("heading")
+        })
+}
+
+fn push_section(sections: &mut Vec<DocumentSection>, current: &mut String, heading: Option<String>) {
+    let text = normalize_text(current);
+    current.clear();
+    if !text.is_empty() {
+        sections.push(DocumentSection {
+            text,
+            page: None,
+            heading,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{
+        Cursor,
+        Write,
+    };
+
+    use super::*;
+
+    fn paragraph(text: &str, style: Option<&str>) -> String {
+        let properties = style
+            .map(|style| format!(r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, style))
+            .unwrap_or_default();
+        format!(
+            r#"<w:p>{}<w:r><w:t xml:space="preserve">{}</w:t></w:r></w:p>"#,
+            properties, text
+        )
+    }
+
+    #[test]
+    fn test_extract_docx_sections() {
+        let body = [
+            paragraph("Preface text", None),
+            paragraph("Design", Some("Heading1")),
+            paragraph("The &lt;core&gt; design.", None),
+            r#"<w:p><w:r><w:t>Split</w:t></w:r><w:r><w:tab/><w:t>runs</w:t></w:r></w:p>"#.to_string(),
+            paragraph("Rollout", Some("Heading2")),
+            paragraph("Ship it.", None),
+        ]
+        .concat();
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            body
+        );
+
+        let mut buffer = Cursor::new(Vec::new());
+        let mut zip = zip::ZipWriter::new(&mut buffer);
+        zip.start_file("word/document.xml", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(document.as_bytes()).unwrap();
+        zip.finish().unwrap();
+        buffer.set_position(0);
+
+        let sections = extract_docx(buffer).unwrap();
+        assert_eq!(sections, vec![
+            DocumentSection {
+                text: "Preface text".to_string(),
+                page: None,
+                heading: None,
+            },
+            DocumentSection {
+                text: "Design\nThe <core> design.\nSplit\truns".to_string(),
+                page: None,
+                heading: Some("Design".to_string()),
+            },
+            DocumentSection {
+                text: "Rollout\nShip it.".to_string(),
+                page: None,
+                heading: Some("Rollout".to_string()),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_extract_docx_rejects_other_files() {
+        assert!(extract_docx(Cursor::new(b"not a zip".to_vec())).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use super::{
+    DocumentSection,
+    normalize_text,
+};
+
+/// Elements whose content is never shown as text
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "noscript", "template", "svg", "head"];
+
+/// Elements that start a new line of text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Convert an HTML document to plain text, split into sections at each heading.
+///
+/// Tags are stripped, the content of scripts, styles and similar elements is dropped, character
+/// references are decoded and whitespace is collapsed except inside `<pre>`. Each `<h1>`-`<h6>`
+/// starts a new section whose heading is the heading's text.
+pub fn extract_html(html: &str) -> Vec<DocumentSection> {
+    let mut sections = Vec::new();
+    let mut current = String::new();
+    let mut heading: Option<String> = None;
+    // Text of the heading element being read, if inside one
+    let mut heading_text: Option<String> = None;
+    let mut pre_depth = 0usize;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        if rest.starts_with('<') {
+            let Some(tag) = parse_tag(rest) else {
+                // A stray `<` is text
+                push_text(&mut current, &mut heading_text, "<", pre_depth > 0);
+                rest = &rest[1..];
+                continue;
+            };
+            rest = &rest[tag.len..];
+
+            if !tag.closing && SKIPPED_ELEMENTS.contains(&tag.name.as_str()) {
+                if !tag.self_closing {
+                    rest = skip_element(rest, &tag.name);
+                }
+                continue;
+            }
+
+            if is_heading(&tag.name) {
+                if tag.closing {
+                    if let Some(text) = heading_text.take() {
+                        let text = collapse_whitespace(&text);
+                        current.push_str(&text);
+                        current.push('\n');
+                        heading = (!text.is_empty()).then_some(text);
+                    }
+                } else {
+                    flush_section(&mut sections, &mut current, heading.take());
+                    heading_text = Some(String::new());
+                }
+                continue;
+            }
+
+            if tag.name == "pre" {
+                pre_depth = if tag.closing {
+                    pre_depth.saturating_sub(1)
+                } else {
+                    pre_depth + 1
+                };
+            }
+            if BLOCK_ELEMENTS.contains(&tag.name.as_str()) {
+                let target = heading_text.as_mut().unwrap_or(&mut current);
+                target.truncate(target.trim_end_matches(' ').len());
+                if !target.is_empty() && !target.ends_with('\n') {
+                    target.push('\n');
+                }
+            }
+            continue;
+        }
+
+        let end = rest.find('<').unwrap_or(rest.len());
+        let text = decode_entities(&rest[..end]);
+        push_text(&mut current, &mut heading_text, &text, pre_depth > 0);
+        rest = &rest[end..];
+    }
+
+    if let Some(text) = heading_text {
+        current.push_str(&text);
+    }
+    flush_section(&mut sections, &mut current, heading);
+    sections
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    /// Length of the tag in bytes, including the angle brackets
+    len: usize,
+}
+
+/// Parse the tag at the start of `input`, which starts with `<`
+fn parse_tag(input: &str) -> Option<Tag> {
+    let bytes = input.as_bytes();
+    let mut pos = 1;
+    let closing = bytes.get(pos) == Some(&b'/');
+    if closing {
+        pos += 1;
+    }
+
+    // Doctypes, processing instructions and CDATA are skipped as nameless tags
+    let special = matches!(bytes.get(pos), Some(b'!' | b'?'));
+    if !special && !bytes.get(pos).is_some_and(u8::is_ascii_alphabetic) {
+        return None;
+    }
+    let name_start = pos;
+    while bytes
+        .get(pos)
+        .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b':')
+    {
+        pos += 1;
+    }
+    let name = input[name_start..pos].to_ascii_lowercase();
+
+    // Find the end of the tag, skipping quoted attribute values
+    let mut quote = None;
+    while let Some(&byte) = bytes.get(pos) {
+        match (quote, byte) {
+            (Some(q), b) if b == q => quote = None,
+            (Some(_), _) => {},
+            (None, b'"' | b'\'') => quote = Some(byte),
+            (None, b'>') => {
+                return Some(Tag {
+                    name,
+                    closing,
+                    self_closing: bytes[pos - 1] == b'/',
+                    len: pos + 1,
+                });
+            },
+            _ => {},
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Skip past the closing tag of the element `name`
+fn skip_element<'a>(input: &'a str, name: &str) -> &'a str {
+    let closing = format!("</{}", name);
+    let lower = input.to_ascii_lowercase();
+    match lower.find(&closing) {
+        Some(start) => {
+            let rest = &input[start..];
+            rest.find('>').map_or("", |end| &rest[end + 1..])
+        },
+        None => "",
+    }
+}
+
+fn is_heading(name: &str) -> bool {
+    matches!(name, "h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+}
+
+fn push_text(current: &mut String, heading_text: &mut Option<String>, text: &str, preformatted: bool) {
+    let target = heading_text.as_mut().unwrap_or(current);
+    if preformatted {
+        target.push_str(text);
+        return;
+    }
+
+    let collapsed = collapse_whitespace(text);
+    if text.starts_with(char::is_whitespace) && !target.is_empty() && !target.ends_with(char::is_whitespace) {
+        target.push(' ');
+    }
+    target.push_str(&collapsed);
+    if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+        target.push(' ');
+    }
+}
+
+fn flush_section(sections: &mut Vec<DocumentSection>, current: &mut String, heading: Option<String>) {
+    let text = normalize_text(current);
+    current.clear();
+    if !text.is_empty() {
+        sections.push(DocumentSection {
+            text,
+            page: None,
+            heading,
+        });
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode named and numeric character references
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest[1..].find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..=end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "hellip" => Some('…'),
+                "copy" => Some('©'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 2))
+        });
+
+        match decoded {
+            Some((c, len)) => {
+                result.push(c);
+                rest = &rest[len..];
+            },
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_html_strips_markup() {
+        let html = r#"<!DOCTYPE html>
+<html>
+<head><title>Ignored</title><style>body { color: red; }</style></head>
+<body>
+  <script>var x = "<p>not text</p>";</script>
+  <p>Intro   text with <b>bold</b> <i>italic</i> &amp; <a href="/x?a=1&b=2">a link</a>.</p>
+  <!-- a comment -->
+  <h1>Getting <em>Started</em></h1>
+  <p>First&nbsp;step</p>
+  <ul><li>One</li><li>Two</li></ul>
+  <h2>Code</h2>
+  <pre>fn main() {
+    println!("&lt;hi&gt;");
+}</pre>
+</body>
+</html>"#;
+
+        let sections = extract_html(html);
+        assert_eq!(sections.len(), 3);
+
+        assert_eq!(sections[0].heading, None);
+        assert_eq!(sections[0].text, "Intro text with bold italic & a link.");
+
+        assert_eq!(sections[1].heading.as_deref(), Some("Getting Started"));
+        assert_eq!(sections[1].text, "Getting Started\nFirst step\nOne\nTwo");
+
+        assert_eq!(sections[2].heading.as_deref(), Some("Code"));
+        assert_eq!(sections[2].text, "Code\nfn main() {\nprintln!(\"<hi>\");\n}");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#65;&#x42; &unknown; & c"),
+            "a <b> AB &unknown; & c"
+        );
+    }
+}
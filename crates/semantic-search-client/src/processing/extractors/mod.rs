@@ -0,0 +1,80 @@
+use std::io::Read;
+
+/// Text extraction from DOCX documents
+mod docx;
+/// HTML to text conversion
+mod html;
+/// Text extraction from PDF documents
+mod pdf;
+
+pub use docx::extract_docx;
+pub use html::extract_html;
+pub use pdf::extract_pdf;
+
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// Maximum size of a decompressed document part, so that a small compressed file can't exhaust
+/// memory while it's extracted
+const MAX_DECODED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A part of a document whose text is chunked and referenced on its own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSection {
+    /// Plain text of the section
+    pub text: String,
+    /// 1-based page number, for paginated documents
+    pub page: Option<usize>,
+    /// Title of the heading the section starts with, if any
+    pub heading: Option<String>,
+}
+
+/// Read a decompressing reader to the end, failing if it yields more than `limit` bytes
+fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(decoded_size_error(limit));
+    }
+    Ok(data)
+}
+
+fn decoded_size_error(limit: u64) -> SemanticSearchError {
+    SemanticSearchError::InvalidArgument(format!(
+        "Decompressed content exceeds the limit of {} MB",
+        limit / (1024 * 1024)
+    ))
+}
+
+/// Trim every line of extracted text and collapse runs of blank lines
+fn normalize_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !result.is_empty() {
+            result.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        result.push_str(line);
+        blank_lines = 0;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_limited() {
+        assert_eq!(read_limited(&b"0123456789"[..], 10).unwrap().len(), 10);
+        assert!(read_limited(&b"0123456789"[..], 9).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use lopdf::Document;
+
+use super::{
+    DocumentSection,
+    MAX_DECODED_BYTES,
+    decoded_size_error,
+    normalize_text,
+};
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// Extract the text of each page of a PDF document.
+///
+/// Text is decoded with each font's encoding or `ToUnicode` map, so text shown in fonts with
+/// neither is skipped, as are pages that can't be decoded. Encrypted documents aren't supported.
+///
+/// # Returns
+///
+/// One section per page with text, with its 1-based page number
+pub fn extract_pdf(data: &[u8]) -> Result<Vec<DocumentSection>> {
+    check_stream_sizes(data, MAX_DECODED_BYTES)?;
+    let document = Document::load_mem(data)
+        .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid PDF file: {}", e)))?;
+    if document.is_encrypted() {
+        return Err(SemanticSearchError::InvalidArgument(
+            "Encrypted PDF files are not supported".to_string(),
+        ));
+    }
+
+    let sections = document
+        .get_pages()
+        .into_keys()
+        .filter_map(|page| {
+            let text = normalize_text(&document.extract_text(&[page]).ok()?);
+            (!text.is_empty()).then_some(DocumentSection {
+                text,
+                page: Some(page as usize),
+                heading: None,
+            })
+        })
+        .collect();
+    Ok(sections)
+}
+
+/// Fail if the Flate-compressed streams of a PDF file decompress to more than `limit` bytes in
+/// total. The raw file is scanned rather than the loaded document, because loading it already
+/// decompresses its object streams in memory.
+fn check_stream_sizes(data: &[u8], limit: u64) -> Result<()> {
+    let mut total = 0;
+    let mut buffer = [0; 8192];
+    let mut offset = 0;
+    while let Some(position) = data[offset..].windows(6).position(|window| window == b"stream") {
+        let keyword = offset + position;
+        offset = keyword + b"stream".len();
+        let content = match &data[offset..] {
+            [b'\r', b'\n', content @ ..] | [b'\n', content @ ..] if !data[..keyword].ends_with(b"end") => content,
+            _ => continue,
+        };
+        // Streams that aren't Flate-compressed fail to decode right away and add nothing
+        let mut decoder = ZlibDecoder::new(content);
+        while let Ok(read @ 1..) = decoder.read(&mut buffer) {
+            total += read as u64;
+            if total > limit {
+                return Err(decoded_size_error(limit));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use lopdf::{
+        Object,
+        Stream,
+        dictionary,
+    };
+
+    use super::*;
+
+    /// Assemble a PDF file with one page per content stream, all using Helvetica as `/F1`
+    fn build_pdf(contents: Vec<Stream>) -> Vec<u8> {
+        let mut document = Document::with_version("1.7");
+        let pages_id = document.new_object_id();
+        let font_id = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let kids = contents
+            .into_iter()
+            .map(|content| {
+                let content_id = document.add_object(content);
+                document
+                    .add_object(dictionary! {
+                        "Type" => "Page",
+                        "Parent" => pages_id,
+                        "Contents" => content_id,
+                        "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+                    })
+                    .into()
+            })
+            .collect::<Vec<Object>>();
+        let count = kids.len() as i64;
+        document.objects.insert(
+            pages_id,
+            dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => count,
+            }
+            .into(),
+        );
+        let catalog_id = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        document.trailer.set("Root", catalog_id);
+
+        let mut pdf = Vec::new();
+        document.save_to(&mut pdf).unwrap();
+        pdf
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_extract_pdf_pages() {
+        let pdf = build_pdf(vec![
+            Stream::new(
+                dictionary! {},
+                b"BT /F1 12 Tf 72 712 Td (Design \\(draft\\)) Tj ET BT /F1 12 Tf [(Hel) 20 (lo) -300 (world)] TJ ET"
+                    .to_vec(),
+            ),
+            // Blank pages don't get a section
+            Stream::new(dictionary! {}, b"0 0 m 10 10 l S".to_vec()),
+            Stream::new(
+                dictionary! { "Filter" => "FlateDecode" },
+                deflate(b"BT /F1 12 Tf 1 0 0 1 72 700 Tm (Third page) Tj ET"),
+            ),
+        ]);
+
+        let sections = extract_pdf(&pdf).unwrap();
+        assert_eq!(sections, vec![
+            DocumentSection {
+                text: "Design (draft)\nHello world".to_string(),
+                page: Some(1),
+                heading: None,
+            },
+            DocumentSection {
+                text: "Third page".to_string(),
+                page: Some(3),
+                heading: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_extract_pdf_rejects_oversized_streams() {
+        let content = b"BT /F1 12 Tf (Repeated text) Tj ET\n".repeat(100);
+        let pdf = build_pdf(vec![Stream::new(
+            dictionary! { "Filter" => "FlateDecode" },
+            deflate(&content),
+        )]);
+
+        assert!(check_stream_sizes(&pdf, content.len() as u64).is_ok());
+        assert!(check_stream_sizes(&pdf, content.len() as u64 - 1).is_err());
+
+        // The limit applies to all streams together
+        let pdf = build_pdf(vec![
+            Stream::new(dictionary! { "Filter" => "FlateDecode" }, deflate(&content)),
+            Stream::new(dictionary! { "Filter" => "FlateDecode" }, deflate(&content)),
+        ]);
+        assert!(check_stream_sizes(&pdf, 2 * content.len() as u64).is_ok());
+        assert!(check_stream_sizes(&pdf, 2 * content.len() as u64 - 1).is_err());
+    }
+
+    #[test]
+    fn test_extract_pdf_rejects_other_files() {
+        assert!(extract_pdf(b"just some text").is_err());
+    }
+}
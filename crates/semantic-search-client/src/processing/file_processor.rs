@@ -7,6 +7,16 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::processing::extractors::{
+    DocumentSection,
+    extract_docx,
+    extract_html,
+    extract_pdf,
+};
+use crate::processing::structured_chunker::{
+    chunk_code,
+    chunk_markdown,
+};
 use crate::processing::text_chunker::chunk_text;
 use crate::types::FileType;
 
@@ -55,7 +65,12 @@ pub fn get_file_type(path: &Path) -> FileType {
         Some("kt" | "kts") => FileType::Code,
         Some("cs") => FileType::Code,
         Some("sh" | "bash" | "zsh") => FileType::Code,
-        Some("html" | "htm" | "xml") => FileType::Code,
+        Some("xml") => FileType::Code,
+
+        // Documents whose text is extracted before chunking
+        Some("pdf") => FileType::Pdf,
+        Some("docx") => FileType::Docx,
+        Some("html" | "htm" | "xhtml") => FileType::Html,
         Some("css" | "scss" | "sass" | "less") => FileType::Code,
         Some("sql") => FileType::Code,
         Some("yaml" | "yml") => FileType::Code,
@@ -71,7 +86,7 @@ pub fn get_file_type(path: &Path) -> FileType {
             _ => FileType::Unknown,
         },
 
-        // Default to unknown (includes office docs, PDFs, etc.)
+        // Default to unknown (includes other office documents, images, etc.)
         _ => FileType::Unknown,
     }
 }
@@ -113,24 +128,53 @@ pub fn process_file_with_config(
     }
 
     let file_type = get_file_type(path);
-    let content = fs::read_to_string(path).map_err(|e| {
+    let read_error = |e: std::io::Error| {
         SemanticSearchError::IoError(std::io::Error::new(
             e.kind(),
             format!("Failed to read file {}: {}", path.display(), e),
         ))
-    })?;
+    };
 
     match file_type {
+        FileType::Pdf | FileType::Docx | FileType::Html => {
+            let sections = match file_type {
+                FileType::Pdf => extract_pdf(&fs::read(path).map_err(read_error)?)?,
+                FileType::Docx => extract_docx(fs::File::open(path).map_err(read_error)?)?,
+                _ => extract_html(&String::from_utf8_lossy(&fs::read(path).map_err(read_error)?)),
+            };
+            Ok(process_document_sections(
+                path,
+                file_type,
+                &sections,
+                chunk_size,
+                chunk_overlap,
+            ))
+        },
         FileType::Text | FileType::Markdown | FileType::Code | FileType::Json => {
-            // For text-based files (including JSON), chunk the content and create multiple data points
+            let content = fs::read_to_string(path).map_err(read_error)?;
+            // Code and markdown are chunked along their structure, keeping the original text and the
+            // line range of each chunk. Other text-based files (including JSON) are chunked by words.
             // Use the configured chunk size and overlap
-            let chunks = chunk_text(&content, chunk_size, chunk_overlap);
+            let chunks: Vec<(String, Option<(usize, usize)>)> = match file_type {
+                FileType::Code => chunk_code(&content, chunk_size, chunk_overlap)
+                    .into_iter()
+                    .map(|chunk| (chunk.text, Some((chunk.start_line, chunk.end_line))))
+                    .collect(),
+                FileType::Markdown => chunk_markdown(&content, chunk_size, chunk_overlap)
+                    .into_iter()
+                    .map(|chunk| (chunk.text, Some((chunk.start_line, chunk.end_line))))
+                    .collect(),
+                _ => chunk_text(&content, chunk_size, chunk_overlap)
+                    .into_iter()
+                    .map(|chunk| (chunk, None))
+                    .collect(),
+            };
             let path_str = path.to_string_lossy().to_string();
             let file_type_str = format!("{:?}", file_type);
 
             let mut results = Vec::new();
 
-            for (i, chunk) in chunks.iter().enumerate() {
+            for (i, (chunk, lines)) in chunks.iter().enumerate() {
                 let mut metadata = serde_json::Map::new();
                 metadata.insert("text".to_string(), Value::String(chunk.clone()));
                 metadata.insert("path".to_string(), Value::String(path_str.clone()));
@@ -138,6 +182,11 @@ pub fn process_file_with_config(
                 metadata.insert("chunk_index".to_string(), Value::Number((i as u64).into()));
                 metadata.insert("total_chunks".to_string(), Value::Number((chunks.len() as u64).into()));
 
+                if let Some((start_line, end_line)) = lines {
+                    metadata.insert("start_line".to_string(), Value::Number((*start_line as u64).into()));
+                    metadata.insert("end_line".to_string(), Value::Number((*end_line as u64).into()));
+                }
+
                 // For code files, add additional metadata
                 if file_type == FileType::Code {
                     metadata.insert(
@@ -179,6 +228,62 @@ pub fn process_file_with_config(
     }
 }
 
+/// Chunk the sections extracted from a document, recording the page or heading each chunk came
+/// from
+fn process_document_sections(
+    path: &Path,
+    file_type: FileType,
+    sections: &[DocumentSection],
+    chunk_size: Option<usize>,
+    chunk_overlap: Option<usize>,
+) -> Vec<Value> {
+    let path_str = path.to_string_lossy().to_string();
+    let file_type_str = format!("{:?}", file_type);
+
+    let mut chunks = Vec::new();
+    for section in sections {
+        for chunk in chunk_text(&section.text, chunk_size, chunk_overlap) {
+            chunks.push((chunk, section));
+        }
+    }
+
+    let total_chunks = chunks.len().max(1);
+    let mut results = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, (chunk, section))| {
+            let mut metadata = serde_json::Map::new();
+            metadata.insert("text".to_string(), Value::String(chunk));
+            metadata.insert("path".to_string(), Value::String(path_str.clone()));
+            metadata.insert("file_type".to_string(), Value::String(file_type_str.clone()));
+            metadata.insert("chunk_index".to_string(), Value::Number((i as u64).into()));
+            metadata.insert("total_chunks".to_string(), Value::Number((total_chunks as u64).into()));
+
+            if let Some(page) = section.page {
+                metadata.insert("page".to_string(), Value::Number((page as u64).into()));
+            }
+            if let Some(heading) = &section.heading {
+                metadata.insert("section".to_string(), Value::String(heading.clone()));
+            }
+
+            Value::Object(metadata)
+        })
+        .collect::<Vec<_>>();
+
+    // Documents without any extractable text (e.g. scanned PDFs) still get an entry
+    if results.is_empty() {
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("text".to_string(), Value::String(String::new()));
+        metadata.insert("path".to_string(), Value::String(path_str));
+        metadata.insert("file_type".to_string(), Value::String(file_type_str));
+        metadata.insert("chunk_index".to_string(), Value::Number(0.into()));
+        metadata.insert("total_chunks".to_string(), Value::Number(1.into()));
+        results.push(Value::Object(metadata));
+    }
+
+    results
+}
+
 /// Process a directory and extract content from all files
 ///
 /// # Arguments
@@ -240,7 +345,7 @@ mod tests {
             ("component.tsx", FileType::Code),
             ("Main.java", FileType::Code),
             ("main.c", FileType::Code),
-            ("index.html", FileType::Code),
+            ("config.xml", FileType::Code),
             ("styles.css", FileType::Code),
             ("config.yaml", FileType::Code),
             ("Cargo.toml", FileType::Code),
@@ -260,9 +365,14 @@ mod tests {
             ("Main.RS", FileType::Code),
             ("README.MD", FileType::Markdown),
             ("notes.TXT", FileType::Text),
+            // Documents
+            ("design.pdf", FileType::Pdf),
+            ("Spec.DOCX", FileType::Docx),
+            ("index.html", FileType::Html),
+            ("page.htm", FileType::Html),
             // Unknown files
             ("image.png", FileType::Unknown),
-            ("document.pdf", FileType::Unknown),
+            ("slides.pptx", FileType::Unknown),
             ("binary.exe", FileType::Unknown),
             ("unknown_file", FileType::Unknown),
         ];
@@ -281,7 +391,7 @@ mod tests {
     fn test_unknown_file_types() {
         // Binary files and unsupported formats
         assert_eq!(get_file_type(&PathBuf::from("image.png")), FileType::Unknown);
-        assert_eq!(get_file_type(&PathBuf::from("document.pdf")), FileType::Unknown);
+        assert_eq!(get_file_type(&PathBuf::from("document.doc")), FileType::Unknown);
         assert_eq!(get_file_type(&PathBuf::from("archive.zip")), FileType::Unknown);
         assert_eq!(get_file_type(&PathBuf::from("binary.exe")), FileType::Unknown);
         assert_eq!(get_file_type(&PathBuf::from("data.db")), FileType::Unknown);
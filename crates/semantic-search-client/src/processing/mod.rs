@@ -1,12 +1,27 @@
+/// Text extraction from PDF, DOCX and HTML documents
+pub mod extractors;
 /// File processing utilities for handling different file types and extracting content
 pub mod file_processor;
+/// Structure-aware chunking for code and markdown that preserves the original text
+pub mod structured_chunker;
 /// Text chunking utilities for breaking down text into manageable pieces for embedding
 pub mod text_chunker;
 
+pub use extractors::{
+    DocumentSection,
+    extract_docx,
+    extract_html,
+    extract_pdf,
+};
 pub use file_processor::{
     get_file_type,
     process_directory,
     process_file,
     process_file_with_config,
 };
+pub use structured_chunker::{
+    TextChunk,
+    chunk_code,
+    chunk_markdown,
+};
 pub use text_chunker::chunk_text;
@@ -0,0 +1,459 @@
+use crate::config;
+
+/// A chunk of a file's original text along with the lines it was taken from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChunk {
+    /// The chunk text, exactly as it appears in the file
+    pub text: String,
+    /// First line of the chunk (1-based)
+    pub start_line: usize,
+    /// Last line of the chunk (1-based, inclusive)
+    pub end_line: usize,
+}
+
+/// Lines that close or continue the previous construct rather than starting a new one
+const CONTINUATION_PREFIXES: &[&str] = &["}", ")", "]", "</", ".", "&&", "||", "?"];
+
+/// Keywords that continue the previous construct when they start a line
+const CONTINUATION_KEYWORDS: &[&str] = &[
+    "end", "else", "elif", "elsif", "except", "finally", "catch", "rescue", "ensure", "fi", "done", "esac", "where",
+];
+
+/// Comments, attributes and decorators, which belong to the construct that follows them
+const LEADING_PREFIXES: &[&str] = &["//", "/*", "*", "#", "@", "--"];
+
+/// A range of lines `[start, end)` that should be kept in the same chunk where possible
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    start: usize,
+    end: usize,
+    /// Whether this block must not be merged into the chunk before it, e.g. because it begins a
+    /// section or an item that had to be split
+    starts_chunk: bool,
+}
+
+impl Block {
+    fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+            starts_chunk: false,
+        }
+    }
+}
+
+/// The lines of a document along with their word counts, which is how chunk sizes are measured
+struct Lines<'a> {
+    lines: Vec<&'a str>,
+    words: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(text: &'a str) -> Self {
+        let lines = text.lines().collect::<Vec<_>>();
+        let words = lines.iter().map(|line| line.split_whitespace().count()).collect();
+        Self { lines, words }
+    }
+
+    fn word_count(&self, start: usize, end: usize) -> usize {
+        self.words[start..end].iter().sum()
+    }
+
+    fn is_blank(&self, line: usize) -> bool {
+        self.words[line] == 0
+    }
+}
+
+/// Chunk source code along the boundaries of its top level items (functions, impls, classes and
+/// so on), preserving the original text.
+///
+/// Boundaries are found from indentation rather than by parsing: an item starts on a line at the
+/// outermost indentation that doesn't close or continue the previous item, and comments,
+/// attributes and decorators stay with the item that follows them. Items larger than
+/// `chunk_size` words are split on the items nested inside them, and only split on arbitrary line
+/// boundaries as a last resort, in which case consecutive pieces share roughly `overlap` words.
+///
+/// # Arguments
+///
+/// * `text` - The source code to chunk
+/// * `chunk_size` - Optional chunk size in words (if None, uses config value)
+/// * `overlap` - Optional overlap in words (if None, uses config value)
+pub fn chunk_code(text: &str, chunk_size: Option<usize>, overlap: Option<usize>) -> Vec<TextChunk> {
+    let config = config::get_config();
+    let chunk_size = chunk_size.unwrap_or(config.chunk_size).max(1);
+    let overlap = overlap.unwrap_or(config.chunk_overlap);
+
+    let lines = Lines::new(text);
+    let mut blocks = Vec::new();
+    code_blocks(&lines, 0, lines.lines.len(), chunk_size, overlap, &mut blocks);
+    pack(&lines, &blocks, chunk_size)
+}
+
+/// Chunk markdown along its headings, preserving the original text.
+///
+/// Each section runs from a heading to the next heading, and small sections are merged with the
+/// ones that follow them. Sections larger than `chunk_size` words are split on paragraphs, keeping
+/// fenced code blocks whole where possible.
+///
+/// # Arguments
+///
+/// * `text` - The markdown to chunk
+/// * `chunk_size` - Optional chunk size in words (if None, uses config value)
+/// * `overlap` - Optional overlap in words (if None, uses config value)
+pub fn chunk_markdown(text: &str, chunk_size: Option<usize>, overlap: Option<usize>) -> Vec<TextChunk> {
+    let config = config::get_config();
+    let chunk_size = chunk_size.unwrap_or(config.chunk_size).max(1);
+    let overlap = overlap.unwrap_or(config.chunk_overlap);
+
+    let lines = Lines::new(text);
+    let in_fence = fenced_lines(&lines);
+
+    let mut sections = Vec::new();
+    let mut start = 0;
+    for (i, (line, fenced)) in lines.lines.iter().zip(&in_fence).enumerate() {
+        if i > start && !fenced && is_heading(line) {
+            sections.push(Block::new(start, i));
+            start = i;
+        }
+    }
+    sections.push(Block::new(start, lines.lines.len()));
+
+    let mut blocks = Vec::new();
+    let mut after_split = false;
+    for mut section in sections {
+        if lines.word_count(section.start, section.end) <= chunk_size {
+            // Don't let the tail of a split section absorb the next heading
+            section.starts_chunk = after_split;
+            blocks.push(section);
+            after_split = false;
+            continue;
+        }
+
+        // Split on blank lines outside of code fences, starting a new chunk at the heading
+        let first = blocks.len();
+        let mut start = section.start;
+        for (i, fenced) in in_fence.iter().enumerate().take(section.end).skip(section.start) {
+            if lines.is_blank(i) && !fenced {
+                push_paragraph(&lines, Block::new(start, i + 1), chunk_size, overlap, &mut blocks);
+                start = i + 1;
+            }
+        }
+        push_paragraph(&lines, Block::new(start, section.end), chunk_size, overlap, &mut blocks);
+        if let Some(block) = blocks.get_mut(first) {
+            block.starts_chunk = true;
+        }
+        after_split = true;
+    }
+
+    pack(&lines, &blocks, chunk_size)
+}
+
+fn push_paragraph(lines: &Lines<'_>, paragraph: Block, chunk_size: usize, overlap: usize, blocks: &mut Vec<Block>) {
+    if lines.word_count(paragraph.start, paragraph.end) <= chunk_size {
+        blocks.push(paragraph);
+    } else {
+        line_windows(lines, paragraph.start, paragraph.end, chunk_size, overlap, blocks);
+    }
+}
+
+/// Splits `[start, end)` into items at its outermost indentation, recursing into items that are
+/// too large to fit into a single chunk.
+fn code_blocks(
+    lines: &Lines<'_>,
+    start: usize,
+    end: usize,
+    chunk_size: usize,
+    overlap: usize,
+    blocks: &mut Vec<Block>,
+) {
+    let Some(level) = (start..end)
+        .filter(|&i| !lines.is_blank(i))
+        .map(|i| indentation(lines.lines[i]))
+        .min()
+    else {
+        return;
+    };
+
+    let mut items = Vec::new();
+    let mut item_start = start;
+    let mut previous: Option<&str> = None;
+    for i in start..end {
+        if lines.is_blank(i) {
+            continue;
+        }
+
+        let line = lines.lines[i];
+        let trimmed = line.trim_start();
+        let starts_item = indentation(line) == level
+            && !is_continuation(trimmed)
+            && previous.is_none_or(|previous| !is_leading(previous));
+        if starts_item && i > item_start {
+            items.push(Block::new(item_start, i));
+            item_start = i;
+        }
+        previous = Some(trimmed);
+    }
+    items.push(Block::new(item_start, end));
+
+    for item in items {
+        if lines.word_count(item.start, item.end) <= chunk_size {
+            blocks.push(item);
+            continue;
+        }
+
+        // Split the item's body, attaching its header and footer to the first and last pieces so
+        // they aren't left on their own
+        let mut nested = (item.start..item.end).filter(|&i| !lines.is_blank(i) && indentation(lines.lines[i]) > level);
+        let Some(body_start) = nested.next() else {
+            line_windows(lines, item.start, item.end, chunk_size, overlap, blocks);
+            continue;
+        };
+        let body_end = nested.next_back().unwrap_or(body_start) + 1;
+
+        let first = blocks.len();
+        code_blocks(lines, body_start, body_end, chunk_size, overlap, blocks);
+        blocks[first].start = item.start;
+        blocks[first].starts_chunk = true;
+        if let Some(last) = blocks.last_mut() {
+            last.end = item.end;
+        }
+    }
+}
+
+/// Splits `[start, end)` into windows of at most `chunk_size` words on line boundaries, with
+/// consecutive windows sharing up to `overlap` words worth of lines.
+fn line_windows(
+    lines: &Lines<'_>,
+    start: usize,
+    end: usize,
+    chunk_size: usize,
+    overlap: usize,
+    blocks: &mut Vec<Block>,
+) {
+    let mut window_start = start;
+    while window_start < end {
+        let mut window_end = window_start;
+        let mut words = 0;
+        while window_end < end && (window_end == window_start || words + lines.words[window_end] <= chunk_size) {
+            words += lines.words[window_end];
+            window_end += 1;
+        }
+        blocks.push(Block::new(window_start, window_end));
+        if window_end == end {
+            break;
+        }
+
+        // Step back over trailing lines that fit in the overlap, always making progress
+        let mut next_start = window_end;
+        let mut overlap_words = 0;
+        while next_start - 1 > window_start && overlap_words + lines.words[next_start - 1] <= overlap {
+            overlap_words += lines.words[next_start - 1];
+            next_start -= 1;
+        }
+        window_start = next_start;
+    }
+}
+
+/// Greedily merges consecutive blocks into chunks of at most `chunk_size` words. Blocks that are
+/// larger than `chunk_size` on their own, or that must start a chunk, begin a new chunk.
+fn pack(lines: &Lines<'_>, blocks: &[Block], chunk_size: usize) -> Vec<TextChunk> {
+    let mut chunks = Vec::new();
+    let mut current: Option<(Block, usize)> = None;
+
+    for block in blocks {
+        let words = lines.word_count(block.start, block.end);
+        match &mut current {
+            Some((chunk, chunk_words)) if !block.starts_chunk && *chunk_words + words <= chunk_size => {
+                chunk.end = chunk.end.max(block.end);
+                *chunk_words += words;
+            },
+            _ => {
+                if let Some((chunk, _)) = current.take() {
+                    chunks.extend(to_chunk(lines, chunk));
+                }
+                current = Some((*block, words));
+            },
+        }
+    }
+    if let Some((chunk, _)) = current {
+        chunks.extend(to_chunk(lines, chunk));
+    }
+
+    chunks
+}
+
+/// Builds the chunk for a block, dropping leading and trailing blank lines.
+fn to_chunk(lines: &Lines<'_>, block: Block) -> Option<TextChunk> {
+    let start = (block.start..block.end).find(|&i| !lines.is_blank(i))?;
+    let end = (start..block.end).rfind(|&i| !lines.is_blank(i))? + 1;
+    Some(TextChunk {
+        text: lines.lines[start..end].join("\n"),
+        start_line: start + 1,
+        end_line: end,
+    })
+}
+
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+fn is_continuation(trimmed: &str) -> bool {
+    let keyword = trimmed
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default();
+    CONTINUATION_PREFIXES.iter().any(|prefix| trimmed.starts_with(prefix)) || CONTINUATION_KEYWORDS.contains(&keyword)
+}
+
+fn is_leading(trimmed: &str) -> bool {
+    LEADING_PREFIXES.iter().any(|prefix| trimmed.starts_with(prefix))
+}
+
+fn is_heading(line: &str) -> bool {
+    if indentation(line) >= 4 {
+        return false;
+    }
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    (1..=6).contains(&level) && trimmed[level..].chars().next().is_none_or(char::is_whitespace)
+}
+
+/// Marks the lines that are inside of (or delimit) a fenced code block.
+fn fenced_lines(lines: &Lines<'_>) -> Vec<bool> {
+    let mut fence: Option<&str> = None;
+    lines
+        .lines
+        .iter()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let marker = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker));
+            match (fence, marker) {
+                (None, Some(marker)) => {
+                    fence = Some(marker);
+                    true
+                },
+                (Some(open), Some(marker)) if open == marker => {
+                    fence = None;
+                    true
+                },
+                (fence, _) => fence.is_some(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST_SOURCE: &str = r#"use std::fmt;
+
+/// A point
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn sum(&self) -> i32 {
+        let total = self.x + self.y;
+        total
+    }
+}
+"#;
+
+    #[test]
+    fn test_chunk_code_keeps_items_whole() {
+        let chunks = chunk_code(RUST_SOURCE, Some(14), Some(0));
+        let texts = chunks.iter().map(|c| c.text.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(texts[0], "use std::fmt;");
+        assert_eq!(
+            texts[1],
+            "/// A point\n#[derive(Debug)]\npub struct Point {\n    x: i32,\n    y: i32,\n}"
+        );
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (3, 8));
+
+        // The impl is too large for one chunk, so it is split on its methods
+        assert_eq!(chunks.len(), 4);
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (10, 13));
+        assert!(texts[2].starts_with("impl Point {\n    pub fn new"));
+        assert!(texts.last().unwrap().starts_with("    pub fn sum"));
+        assert!(texts.last().unwrap().ends_with("    }\n}"));
+        assert_eq!(chunks.last().unwrap().end_line, 19);
+
+        // Every line of the file is covered, in order and with its original text
+        let rejoined = texts.join("\n");
+        for line in RUST_SOURCE.lines().filter(|l| !l.trim().is_empty()) {
+            assert!(rejoined.contains(line), "missing {line:?}");
+        }
+    }
+
+    #[test]
+    fn test_chunk_code_merges_small_items() {
+        let source = "def a():\n    return 1\n\n\ndef b():\n    return 2\n";
+        let chunks = chunk_code(source, Some(100), Some(0));
+        assert_eq!(chunks, vec![TextChunk {
+            text: "def a():\n    return 1\n\n\ndef b():\n    return 2".to_string(),
+            start_line: 1,
+            end_line: 6,
+        }]);
+    }
+
+    #[test]
+    fn test_chunk_code_falls_back_to_lines() {
+        let source = (1..=10).map(|i| format!("x{i} = {i}")).collect::<Vec<_>>().join("\n");
+        let chunks = chunk_code(&source, Some(6), Some(3));
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 2));
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (3, 4));
+
+        // A statement with no nested structure is split into overlapping windows
+        let statement = format!(
+            "let query = builder()\n{}",
+            (1..=6).map(|i| format!(".with{i}()")).collect::<Vec<_>>().join("\n")
+        );
+        let chunks = chunk_code(&statement, Some(4), Some(1));
+        let ranges = chunks.iter().map(|c| (c.start_line, c.end_line)).collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(1, 1), (2, 5), (5, 7)]);
+    }
+
+    #[test]
+    fn test_chunk_markdown_splits_on_headings() {
+        let markdown = "# Title\n\nIntro text here.\n\n## Install\n\n```sh\n# not a heading\ncargo install\n```\n\n## Usage\n\nRun it with some options.\n";
+        let chunks = chunk_markdown(markdown, Some(10), Some(0));
+        let texts = chunks.iter().map(|c| c.text.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(texts, vec![
+            "# Title\n\nIntro text here.",
+            "## Install\n\n```sh\n# not a heading\ncargo install\n```",
+            "## Usage\n\nRun it with some options.",
+        ]);
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (5, 10));
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (12, 14));
+    }
+
+    #[test]
+    fn test_chunk_markdown_splits_large_sections_on_paragraphs() {
+        let markdown = "## Notes\n\none two three four\n\nfive six seven eight\n\nnine ten";
+        let chunks = chunk_markdown(markdown, Some(6), Some(0));
+        let texts = chunks.iter().map(|c| c.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec![
+            "## Notes\n\none two three four",
+            "five six seven eight\n\nnine ten"
+        ]);
+    }
+
+    #[test]
+    fn test_chunk_empty() {
+        assert!(chunk_code("", Some(10), Some(0)).is_empty());
+        assert!(chunk_markdown("\n\n", Some(10), Some(0)).is_empty());
+    }
+}
@@ -62,6 +62,7 @@ mod tests {
                     max_files: 1000, // Add missing max_files field
                     hosted_models_base_url: "http://test.example.com/models".to_string(),
                     embedding_type: crate::embedding::EmbeddingType::default(),
+                    http_embedding: None,
                 };
                 // Use a different approach that doesn't access private static
                 let _ = crate::config::init_config(&std::env::temp_dir());
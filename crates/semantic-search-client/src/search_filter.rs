@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use glob::Pattern;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value;
+
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::pattern_filter::PatternFilter;
+
+/// Restricts a search to the chunks whose metadata matches every given condition
+///
+/// Filters are applied before ranking, so a filtered search returns the best matches among the
+/// matching chunks rather than the matching chunks among the best overall matches.
+///
+/// A filter can be written as an expression of space-separated `key:value` terms, for example
+/// `path:src/** type:markdown`, with the keys:
+///
+/// * `path` - Glob matched against the file path or any of its parent directories, so both `src/**`
+///   and `src` restrict the search to a `src` directory
+/// * `type` (or `file_type`) - File type, e.g. `code`, `markdown`, `text`, `json`, `pdf`
+/// * `lang` (or `language`) - File extension, e.g. `rs` or `md`
+/// * `context` - Name or ID of the context
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchFilter {
+    /// Glob the file path, or one of its parent directories, must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// File type, compared case-insensitively
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<String>,
+    /// File extension, compared case-insensitively
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Name or ID of the context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+impl SearchFilter {
+    /// Check whether the filter has no conditions, and so matches everything
+    pub fn is_empty(&self) -> bool {
+        self.path.is_none() && self.file_type.is_none() && self.language.is_none() && self.context.is_none()
+    }
+
+    /// Compile the filter, `None` if it matches everything
+    pub(crate) fn matcher(&self) -> Result<Option<FilterMatcher<'_>>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+
+        let path = self
+            .path
+            .as_deref()
+            .map(|path| {
+                Pattern::new(path.trim_end_matches('/'))
+                    .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid path filter '{}': {}", path, e)))
+            })
+            .transpose()?;
+
+        Ok(Some(FilterMatcher { filter: self, path }))
+    }
+}
+
+impl FromStr for SearchFilter {
+    type Err = SemanticSearchError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut filter = Self::default();
+        for term in s.split_whitespace() {
+            let (key, value) = term
+                .split_once(':')
+                .filter(|(_, value)| !value.is_empty())
+                .ok_or_else(|| {
+                    SemanticSearchError::InvalidArgument(format!("Invalid filter term '{}', expected key:value", term))
+                })?;
+
+            let field = match key.to_lowercase().as_str() {
+                "path" => &mut filter.path,
+                "type" | "file_type" => &mut filter.file_type,
+                "lang" | "language" => &mut filter.language,
+                "context" => &mut filter.context,
+                _ => {
+                    return Err(SemanticSearchError::InvalidArgument(format!(
+                        "Unknown filter key '{}'. Valid keys are: path, type, lang, context",
+                        key
+                    )));
+                },
+            };
+            *field = Some(value.to_string());
+        }
+
+        // Catch invalid globs up front rather than on the first search
+        filter.matcher()?;
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for SearchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms = [
+            ("path", &self.path),
+            ("type", &self.file_type),
+            ("lang", &self.language),
+            ("context", &self.context),
+        ];
+        let terms: Vec<String> = terms
+            .iter()
+            .filter_map(|(key, value)| value.as_ref().map(|value| format!("{}:{}", key, value)))
+            .collect();
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
+/// A compiled [`SearchFilter`]
+pub(crate) struct FilterMatcher<'a> {
+    filter: &'a SearchFilter,
+    path: Option<Pattern>,
+}
+
+impl FilterMatcher<'_> {
+    /// Check whether the chunks of a context, given its ID and name, can match at all
+    pub fn matches_context(&self, id: &str, name: &str) -> bool {
+        self.filter
+            .context
+            .as_ref()
+            .is_none_or(|wanted| id == wanted || name.eq_ignore_ascii_case(wanted))
+    }
+
+    /// Check whether a chunk's metadata matches
+    pub fn matches(&self, payload: &HashMap<String, Value>) -> bool {
+        let path = payload.get("path").and_then(Value::as_str).map(Path::new);
+
+        if let Some(pattern) = &self.path {
+            let in_path = path.is_some_and(|path| {
+                path.ancestors()
+                    .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                    .any(|ancestor| PatternFilter::matches_pattern(pattern, ancestor))
+            });
+            if !in_path {
+                return false;
+            }
+        }
+
+        if let Some(file_type) = &self.filter.file_type {
+            let file_type_matches = payload
+                .get("file_type")
+                .and_then(Value::as_str)
+                .is_some_and(|actual| actual.eq_ignore_ascii_case(file_type));
+            if !file_type_matches {
+                return false;
+            }
+        }
+
+        if let Some(language) = &self.filter.language {
+            // Code chunks record their language, other files are matched by extension
+            let actual = payload
+                .get("language")
+                .and_then(Value::as_str)
+                .or_else(|| path.and_then(Path::extension).and_then(|ext| ext.to_str()));
+            if !actual.is_some_and(|actual| actual.eq_ignore_ascii_case(language.trim_start_matches('.'))) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn payload(path: &str, file_type: &str, language: Option<&str>) -> HashMap<String, Value> {
+        let mut payload = HashMap::from([
+            ("path".to_string(), json!(path)),
+            ("file_type".to_string(), json!(file_type)),
+        ]);
+        if let Some(language) = language {
+            payload.insert("language".to_string(), json!(language));
+        }
+        payload
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let filter: SearchFilter = "path:src/** type:Markdown lang:.md context:docs".parse().unwrap();
+        assert_eq!(filter, SearchFilter {
+            path: Some("src/**".to_string()),
+            file_type: Some("Markdown".to_string()),
+            language: Some(".md".to_string()),
+            context: Some("docs".to_string()),
+        });
+        assert_eq!(filter.to_string(), "path:src/** type:Markdown lang:.md context:docs");
+        assert_eq!(filter.to_string().parse::<SearchFilter>().unwrap(), filter);
+
+        assert!("".parse::<SearchFilter>().unwrap().is_empty());
+        assert!("src".parse::<SearchFilter>().is_err());
+        assert!("path:".parse::<SearchFilter>().is_err());
+        assert!("owner:me".parse::<SearchFilter>().is_err());
+        assert!("path:[".parse::<SearchFilter>().is_err());
+    }
+
+    #[test]
+    fn test_matches_payload() {
+        let rust = payload("/repo/src/client/mod.rs", "Code", Some("rs"));
+        let readme = payload("/repo/README.md", "Markdown", None);
+        let guide = payload("/repo/src/GUIDE.md", "Markdown", None);
+
+        let matching = |expression: &str| {
+            let filter: SearchFilter = expression.parse().unwrap();
+            let matcher = filter.matcher().unwrap().unwrap();
+            [("rust", &rust), ("readme", &readme), ("guide", &guide)]
+                .into_iter()
+                .filter(|(_, payload)| matcher.matches(payload))
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching("path:src/**"), vec!["rust", "guide"]);
+        assert_eq!(matching("path:src/"), vec!["rust", "guide"]);
+        assert_eq!(matching("path:*.md"), vec!["readme", "guide"]);
+        assert_eq!(matching("type:markdown"), vec!["readme", "guide"]);
+        assert_eq!(matching("lang:RS"), vec!["rust"]);
+        assert_eq!(matching("lang:md path:src"), vec!["guide"]);
+        assert_eq!(matching("type:pdf"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_matches_context() {
+        let matches = |expression: &str| {
+            let filter: SearchFilter = expression.parse().unwrap();
+            filter.matcher().unwrap().unwrap().matches_context("id-1", "docs")
+        };
+
+        assert!(matches("context:id-1"));
+        assert!(matches("context:DOCS"));
+        assert!(matches("type:code"));
+        assert!(!matches("context:other"));
+    }
+}
@@ -34,6 +34,8 @@ pub struct AddContextRequest {
     pub exclude_patterns: Option<Vec<String>>,
     /// Optional embedding type override for this context
     pub embedding_type: Option<EmbeddingType>,
+    /// Whether to re-index the context automatically when its files change
+    pub auto_update: bool,
 }
 
 /// Parameters for indexing operations (internal use)
@@ -56,6 +58,8 @@ pub struct IndexingParams {
     pub exclude_patterns: Option<Vec<String>>,
     /// Optional embedding type override (uses client default if None)
     pub embedding_type: Option<EmbeddingType>,
+    /// Whether to re-index the context automatically when its files change
+    pub auto_update: bool,
 }
 
 use crate::client::context::SemanticContext;
@@ -107,6 +111,19 @@ pub struct KnowledgeContext {
     /// Embedding type used for this context
     #[serde(default)]
     pub embedding_type: EmbeddingType,
+
+    /// Name of the embedding model the context's vectors were computed with. `None` for BM25
+    /// contexts and contexts indexed before the model was recorded.
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+
+    /// Number of dimensions of the context's vectors
+    #[serde(default)]
+    pub embedding_dim: Option<usize>,
+
+    /// Whether the context is re-indexed automatically when files under its source path change
+    #[serde(default)]
+    pub auto_update: bool,
 }
 
 impl KnowledgeContext {
@@ -135,6 +152,9 @@ impl KnowledgeContext {
             persistent,
             item_count,
             embedding_type,
+            embedding_model: None,
+            embedding_dim: None,
+            auto_update: false,
         }
     }
 }
@@ -185,6 +205,89 @@ impl SearchResult {
     pub fn text(&self) -> Option<&str> {
         self.point.payload.get("text").and_then(|v| v.as_str())
     }
+
+    /// Get the path of the file this result was indexed from
+    pub fn path(&self) -> Option<&str> {
+        self.point.payload.get("path").and_then(|v| v.as_str())
+    }
+
+    /// Get the 1-based, inclusive line range of this result within its file
+    pub fn line_range(&self) -> Option<(usize, usize)> {
+        let line = |key: &str| self.point.payload.get(key).and_then(|v| v.as_u64()).map(|v| v as usize);
+        Some((line("start_line")?, line("end_line")?))
+    }
+
+    /// Get the 1-based page of this result, for paginated documents
+    pub fn page(&self) -> Option<usize> {
+        self.point
+            .payload
+            .get("page")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+    }
+
+    /// Get the heading of the document section this result belongs to
+    pub fn section(&self) -> Option<&str> {
+        self.point.payload.get("section").and_then(|v| v.as_str())
+    }
+}
+
+/// Where a search result came from, so that it can be referenced and read back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// ID of the context the result was found in
+    pub context_id: ContextId,
+    /// Name of the context the result was found in
+    pub context_name: String,
+    /// Path of the source file, if the result was indexed from a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// First line of the result within the file (1-based)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    /// Last line of the result within the file (1-based, inclusive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    /// Page of the result, for paginated documents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    /// Heading of the document section the result belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Distance of the result from the query (lower is better)
+    pub score: f32,
+    /// Text of the result
+    pub text: String,
+}
+
+impl Citation {
+    /// Create a citation for a result found in the given context
+    pub fn new(context: &KnowledgeContext, result: &SearchResult) -> Self {
+        let lines = result.line_range();
+        Self {
+            context_id: context.id.clone(),
+            context_name: context.name.clone(),
+            path: result.path().map(str::to_string),
+            start_line: lines.map(|(start, _)| start),
+            end_line: lines.map(|(_, end)| end),
+            page: result.page(),
+            section: result.section().map(str::to_string),
+            score: result.distance,
+            text: result.text().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Format the location of the result as `path:start-end`, `path:line`, `path (page N)` or
+    /// `path`, or `None` if the result wasn't indexed from a file
+    pub fn location(&self) -> Option<String> {
+        let path = self.path.as_deref()?;
+        Some(match (self.start_line, self.end_line, self.page) {
+            (Some(start), Some(end), _) if end > start => format!("{}:{}-{}", path, start, end),
+            (Some(start), _, _) => format!("{}:{}", path, start),
+            (None, _, Some(page)) => format!("{} (page {})", path, page),
+            _ => path.to_string(),
+        })
+    }
 }
 
 /// File type for processing
@@ -198,6 +301,12 @@ pub enum FileType {
     Json,
     /// Source code file (programming languages)
     Code,
+    /// PDF document
+    Pdf,
+    /// Word document (DOCX)
+    Docx,
+    /// HTML page
+    Html,
     /// Unknown file type
     Unknown,
 }
@@ -246,6 +355,13 @@ pub enum OperationType {
         /// Path being indexed
         path: String,
     },
+    /// Re-indexing the files of an existing context that changed since it was last indexed
+    Updating {
+        /// Display name for the operation
+        name: String,
+        /// Path being updated
+        path: String,
+    },
     /// Clearing all contexts
     Clearing,
 }
@@ -255,11 +371,42 @@ impl OperationType {
     pub fn display_name(&self) -> String {
         match self {
             OperationType::Indexing { name, .. } => format!("Indexing '{}'", name),
+            OperationType::Updating { name, .. } => format!("Updating '{}'", name),
             OperationType::Clearing => "Clearing all".to_string(),
         }
     }
 }
 
+/// Summary of the files that changed in a context since it was last indexed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexChanges {
+    /// Files that are new since the last index
+    pub added: usize,
+    /// Files whose content changed since the last index
+    pub modified: usize,
+    /// Files that no longer exist, or no longer match the context's patterns
+    pub removed: usize,
+    /// Files that are unchanged and were not re-indexed
+    pub unchanged: usize,
+}
+
+impl IndexChanges {
+    /// Whether any files need to be re-indexed or removed
+    pub fn has_changes(&self) -> bool {
+        self.added + self.modified + self.removed > 0
+    }
+}
+
+impl std::fmt::Display for IndexChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} modified, {} removed, {} unchanged",
+            self.added, self.modified, self.removed, self.unchanged
+        )
+    }
+}
+
 /// Status information for a single operation (data contract for UI)
 #[derive(Debug, Clone)]
 pub struct OperationStatus {
@@ -283,8 +430,13 @@ pub struct OperationStatus {
     pub is_failed: bool,
     /// Whether the operation is waiting
     pub is_waiting: bool,
+    /// Whether the operation has finished (only update operations are kept around once finished,
+    /// so that their changes can be reported)
+    pub is_completed: bool,
     /// Estimated time to completion
     pub eta: Option<std::time::Duration>,
+    /// Files changed since the context was last indexed, for update operations
+    pub changes: Option<IndexChanges>,
 }
 
 /// Overall status information (data contract for UI)
@@ -304,6 +456,27 @@ pub struct SystemStatus {
     pub waiting_count: usize,
     /// Maximum concurrent operations allowed
     pub max_concurrent: usize,
+    /// Watchers of contexts with auto-update enabled
+    pub watchers: Vec<WatcherStatus>,
+}
+
+/// State of the file watcher of a context with auto-update enabled
+#[derive(Debug, Clone)]
+pub struct WatcherStatus {
+    /// Context ID
+    pub context_id: ContextId,
+    /// Context name
+    pub name: String,
+    /// Watched source path
+    pub path: String,
+    /// Number of changed paths not yet re-indexed, while waiting for files to stop changing
+    pub pending: Option<usize>,
+    /// Why the source path couldn't be watched
+    pub error: Option<String>,
+    /// When watching the source path started
+    pub watching_since: Option<SystemTime>,
+    /// When changes were last re-indexed automatically
+    pub last_updated: Option<SystemTime>,
 }
 
 /// Progress information for operations
@@ -317,6 +490,10 @@ pub struct ProgressInfo {
     pub message: String,
     /// When progress tracking started
     pub progress_started_at: Option<SystemTime>,
+    /// Files changed since the context was last indexed, for update operations
+    pub changes: Option<IndexChanges>,
+    /// When the operation finished, for operations that are kept around once finished
+    pub completed_at: Option<SystemTime>,
 }
 
 impl Default for ProgressInfo {
@@ -333,6 +510,8 @@ impl ProgressInfo {
             total: 0,
             message: "Initializing...".to_string(),
             progress_started_at: None,
+            changes: None,
+            completed_at: None,
         }
     }
 
@@ -390,6 +569,17 @@ pub enum IndexingJob {
         exclude_patterns: Option<Vec<String>>,
         /// Embedding type
         embedding_type: Option<EmbeddingType>,
+        /// Whether to re-index automatically when files change
+        auto_update: bool,
+    },
+    /// Re-index the changed files of an existing context
+    UpdateContext {
+        /// Operation ID
+        id: Uuid,
+        /// Cancellation token
+        cancel: CancellationToken,
+        /// ID of the context to update
+        context_id: ContextId,
     },
     /// Clear all contexts job
     Clear {
@@ -444,3 +634,64 @@ mod progress_tests {
         assert!(progress.calculate_eta().is_none());
     }
 }
+
+#[cfg(test)]
+mod citation_tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn result(payload: serde_json::Value) -> SearchResult {
+        let payload = payload
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        SearchResult::new(
+            DataPoint {
+                id: 0,
+                payload,
+                vector: vec![],
+            },
+            0.25,
+        )
+    }
+
+    #[test]
+    fn test_citation_from_result() {
+        let context = KnowledgeContext::new(
+            "ctx".to_string(),
+            "docs",
+            "",
+            true,
+            None,
+            (vec![], vec![]),
+            1,
+            Default::default(),
+        );
+
+        let citation = Citation::new(
+            &context,
+            &result(json!({"text": "fn main() {}", "path": "/src/main.rs", "start_line": 10, "end_line": 24})),
+        );
+        assert_eq!(citation.context_name, "docs");
+        assert_eq!(citation.text, "fn main() {}");
+        assert_eq!(citation.score, 0.25);
+        assert_eq!(citation.location().as_deref(), Some("/src/main.rs:10-24"));
+
+        let citation = Citation::new(
+            &context,
+            &result(json!({"text": "Intro", "path": "/docs/guide.pdf", "page": 3, "section": "Intro"})),
+        );
+        assert_eq!(citation.section.as_deref(), Some("Intro"));
+        assert_eq!(citation.location().as_deref(), Some("/docs/guide.pdf (page 3)"));
+
+        let citation = Citation::new(&context, &result(json!({"text": "a note"})));
+        assert_eq!(citation.location(), None);
+        assert_eq!(
+            serde_json::to_value(&citation).unwrap(),
+            json!({"context_id": "ctx", "context_name": "docs", "score": 0.25, "text": "a note"})
+        );
+    }
+}
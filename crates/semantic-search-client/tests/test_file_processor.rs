@@ -119,3 +119,53 @@ fn test_process_binary_file() {
     // Clean up
     fs::remove_dir_all(temp_dir).unwrap_or(());
 }
+
+#[test]
+fn test_process_code_file_preserves_structure() {
+    let temp_dir = env::temp_dir().join("semantic_search_test_process_code");
+    fs::create_dir_all(&temp_dir).unwrap();
+    config::init_config(&temp_dir).unwrap();
+
+    let test_file = temp_dir.join("lib.rs");
+    fs::write(
+        &test_file,
+        "fn first() {\n    println!(\"one\");\n}\n\nfn second() {\n    println!(\"two\");\n}\n",
+    )
+    .unwrap();
+
+    let items = semantic_search_client::processing::process_file_with_config(&test_file, Some(4), Some(0)).unwrap();
+    assert_eq!(items.len(), 2);
+
+    // Newlines and indentation are kept, and each chunk records the lines it came from
+    assert_eq!(items[1]["text"], "fn second() {\n    println!(\"two\");\n}");
+    assert_eq!(items[1]["start_line"], 5);
+    assert_eq!(items[1]["end_line"], 7);
+    assert_eq!(items[1]["language"], "rs");
+
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}
+
+#[test]
+fn test_process_html_file_records_sections() {
+    let temp_dir = env::temp_dir().join("semantic_search_test_process_html");
+    fs::create_dir_all(&temp_dir).unwrap();
+    config::init_config(&temp_dir).unwrap();
+
+    let test_file = temp_dir.join("page.html");
+    fs::write(
+        &test_file,
+        "<html><head><script>track()</script></head><body><h1>Install</h1><p>Run the installer.</p><h2>Upgrade</h2><p>Run it again.</p></body></html>",
+    )
+    .unwrap();
+
+    let items = process_file(&test_file).unwrap();
+    assert_eq!(items.len(), 2);
+
+    // Markup and scripts are stripped, and each chunk records the heading it falls under
+    assert_eq!(items[0]["text"], "Install Run the installer.");
+    assert_eq!(items[0]["section"], "Install");
+    assert_eq!(items[1]["section"], "Upgrade");
+    assert_eq!(items[1]["file_type"], "Html");
+
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}